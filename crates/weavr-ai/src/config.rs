@@ -0,0 +1,186 @@
+//! Choosing and configuring a provider.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{AiError, OllamaProvider, OpenAiProvider, SuggestionProvider};
+
+/// Environment variable holding the API key unless configured otherwise.
+const DEFAULT_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Which kind of API a provider speaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ProviderKind {
+    /// An OpenAI-compatible chat completions API.
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// A local Ollama server.
+    #[serde(rename = "ollama")]
+    Ollama,
+}
+
+impl ProviderKind {
+    /// Parses a provider name as used in the config.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "openai" => Some(Self::OpenAi),
+            "ollama" => Some(Self::Ollama),
+            _ => None,
+        }
+    }
+}
+
+/// Settings for AI suggestions, from the `[ai]` config section.
+///
+/// `WEAVR_AI_PROVIDER`, `WEAVR_AI_MODEL`, and `WEAVR_AI_BASE_URL` override
+/// the matching settings. API keys are only read from the environment.
+///
+/// ```toml
+/// [ai]
+/// provider = "ollama"
+/// model = "qwen2.5-coder"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AiConfig {
+    /// Which provider to use: `openai` or `ollama`. Without one, suggestions
+    /// are off.
+    pub provider: Option<ProviderKind>,
+    /// Model to ask; each provider has a default.
+    pub model: Option<String>,
+    /// Base URL of the API, for servers other than the provider's default.
+    pub base_url: Option<String>,
+    /// Environment variable holding the API key (default `OPENAI_API_KEY`).
+    pub api_key_env: Option<String>,
+    /// Seconds to wait for a suggestion.
+    pub timeout_secs: Option<u64>,
+}
+
+impl AiConfig {
+    /// Builds the configured provider, with the environment's overrides, or
+    /// returns `None` if no provider is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::Config` for an unknown provider name, or for
+    /// the hosted `OpenAI` API without an API key.
+    pub fn provider(&self) -> Result<Option<Arc<dyn SuggestionProvider>>, AiError> {
+        self.provider_with(|name| std::env::var(name).ok())
+    }
+
+    /// Builds the provider, reading environment variables through `var`.
+    fn provider_with(
+        &self,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Option<Arc<dyn SuggestionProvider>>, AiError> {
+        let kind = match var("WEAVR_AI_PROVIDER").filter(|v| !v.trim().is_empty()) {
+            Some(name) => Some(
+                ProviderKind::parse(&name)
+                    .ok_or_else(|| AiError::Config(format!("unknown provider `{name}`")))?,
+            ),
+            None => self.provider,
+        };
+        let Some(kind) = kind else {
+            return Ok(None);
+        };
+        let model = var("WEAVR_AI_MODEL").or_else(|| self.model.clone());
+        let base_url = var("WEAVR_AI_BASE_URL").or_else(|| self.base_url.clone());
+
+        let provider: Arc<dyn SuggestionProvider> = match kind {
+            ProviderKind::OpenAi => {
+                let key_env = self.api_key_env.as_deref().unwrap_or(DEFAULT_API_KEY_ENV);
+                let api_key = var(key_env).filter(|key| !key.trim().is_empty());
+                // Other compatible servers, such as local ones, may need no key
+                if api_key.is_none() && base_url.is_none() {
+                    return Err(AiError::Config(format!(
+                        "set {key_env} to use OpenAI, or base_url for another server"
+                    )));
+                }
+                let mut provider = OpenAiProvider::new(
+                    base_url
+                        .as_deref()
+                        .unwrap_or(OpenAiProvider::DEFAULT_BASE_URL),
+                    model.as_deref().unwrap_or(OpenAiProvider::DEFAULT_MODEL),
+                    api_key,
+                );
+                if let Some(secs) = self.timeout_secs {
+                    provider = provider.with_timeout(Duration::from_secs(secs));
+                }
+                Arc::new(provider)
+            }
+            ProviderKind::Ollama => {
+                let mut provider = OllamaProvider::new(
+                    base_url
+                        .as_deref()
+                        .unwrap_or(OllamaProvider::DEFAULT_BASE_URL),
+                    model.as_deref().unwrap_or(OllamaProvider::DEFAULT_MODEL),
+                );
+                if let Some(secs) = self.timeout_secs {
+                    provider = provider.with_timeout(Duration::from_secs(secs));
+                }
+                Arc::new(provider)
+            }
+        };
+        Ok(Some(provider))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect();
+        move |name| map.get(name).cloned()
+    }
+
+    #[test]
+    fn no_provider_means_no_suggestions() {
+        assert!(AiConfig::default()
+            .provider_with(vars(&[]))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn environment_overrides_the_config() {
+        let config: AiConfig =
+            toml::from_str("provider = \"openai\"\nmodel = \"gpt-4o\"\n").unwrap();
+        let provider = config
+            .provider_with(vars(&[("WEAVR_AI_PROVIDER", "ollama")]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(provider.name(), "ollama");
+        assert!(format!("{provider:?}").contains("gpt-4o"));
+    }
+
+    #[test]
+    fn openai_needs_a_key_or_another_server() {
+        let config = AiConfig {
+            provider: Some(ProviderKind::OpenAi),
+            api_key_env: Some("MY_KEY".to_string()),
+            ..AiConfig::default()
+        };
+        let error = config.provider_with(vars(&[])).unwrap_err();
+        assert!(error.to_string().contains("set MY_KEY"));
+        assert!(config
+            .provider_with(vars(&[("MY_KEY", "sk-test")]))
+            .unwrap()
+            .is_some());
+        assert!(config
+            .provider_with(vars(&[("WEAVR_AI_BASE_URL", "http://localhost:8080/v1")]))
+            .unwrap()
+            .is_some());
+        assert!(config
+            .provider_with(vars(&[("WEAVR_AI_PROVIDER", "claude")]))
+            .is_err());
+    }
+}
@@ -0,0 +1,28 @@
+//! Error types for AI suggestions.
+
+use thiserror::Error;
+
+/// Errors that can occur while asking a provider for a suggestion.
+#[derive(Debug, Error)]
+pub enum AiError {
+    /// The provider could not be reached.
+    #[error("could not reach AI provider: {0}")]
+    Http(String),
+
+    /// The provider answered with an error status.
+    #[error("AI provider returned {status}: {message}")]
+    Status {
+        /// HTTP status code.
+        status: u16,
+        /// Error message from the response body.
+        message: String,
+    },
+
+    /// The provider's answer could not be understood.
+    #[error("unexpected AI response: {0}")]
+    Response(String),
+
+    /// The provider is not configured correctly.
+    #[error("AI configuration error: {0}")]
+    Config(String),
+}
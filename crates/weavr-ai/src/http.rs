@@ -0,0 +1,91 @@
+//! Posting JSON to provider APIs.
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::AiError;
+
+/// Posts `body` as JSON to `url`, with `bearer` as the authorization token
+/// if given, and returns the JSON response.
+pub(crate) fn post_json(
+    url: &str,
+    bearer: Option<&str>,
+    body: &Value,
+    timeout: Duration,
+) -> Result<Value, AiError> {
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    let mut request = agent.post(url).set("Content-Type", "application/json");
+    if let Some(token) = bearer {
+        request = request.set("Authorization", &format!("Bearer {token}"));
+    }
+    let response = match request.send_string(&body.to_string()) {
+        Ok(response) => response,
+        Err(ureq::Error::Status(status, response)) => {
+            let text = response.into_string().unwrap_or_default();
+            return Err(AiError::Status {
+                status,
+                message: error_message(&text),
+            });
+        }
+        Err(ureq::Error::Transport(e)) => return Err(AiError::Http(e.to_string())),
+    };
+    let text = response
+        .into_string()
+        .map_err(|e| AiError::Http(e.to_string()))?;
+    serde_json::from_str(&text).map_err(|e| AiError::Response(format!("{e}: {text}")))
+}
+
+/// Returns the message of an error response, which `OpenAI` sends as
+/// `error.message` and Ollama as `error`.
+fn error_message(text: &str) -> String {
+    let value: Option<Value> = serde_json::from_str(text).ok();
+    value
+        .as_ref()
+        .and_then(|v| {
+            v["error"]["message"]
+                .as_str()
+                .or_else(|| v["error"].as_str())
+        })
+        .map_or_else(|| text.trim().to_string(), str::to_string)
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// Serves one request with `status` and the JSON `body`, returning the
+    /// server's URL and a handle that yields the request body received.
+    pub fn serve_once(status: u16, body: &str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let body = body.to_string();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request = vec![0; length];
+            reader.read_exact(&mut request).unwrap();
+            let response = format!(
+                "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+}
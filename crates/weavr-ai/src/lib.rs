@@ -0,0 +1,45 @@
+//! AI suggestion providers for weavr.
+//!
+//! This crate asks a language model how to resolve a conflict hunk:
+//! - [`SuggestionProvider`] is the abstraction the TUI and headless mode use
+//! - [`OpenAiProvider`] talks to any OpenAI-compatible chat completions API
+//! - [`OllamaProvider`] talks to a local Ollama server
+//! - [`AiConfig`] picks and configures a provider from the config file and
+//!   environment variables
+//!
+//! Suggestions are only ever proposals: they become resolutions tagged with
+//! [`ResolutionSource::Ai`](weavr_core::ResolutionSource::Ai) once accepted.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use weavr_ai::{AiConfig, SuggestionRequest};
+//! use weavr_core::MergeSession;
+//!
+//! let content = "<<<<<<< HEAD\nlet x = 1;\n=======\nlet x = 2;\n>>>>>>> b\n";
+//! let session = MergeSession::from_conflicted(content, "src/lib.rs".into())?;
+//! let provider = AiConfig::default().provider()?.expect("no provider configured");
+//!
+//! let request = SuggestionRequest::from_hunk("src/lib.rs".as_ref(), &session.hunks()[0]);
+//! let suggestion = provider.suggest(&request)?;
+//! println!("{}", suggestion.content);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+mod config;
+mod error;
+mod http;
+mod ollama;
+mod openai;
+mod prompt;
+mod provider;
+
+pub use config::{AiConfig, ProviderKind};
+pub use error::AiError;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+pub use prompt::{parse_reply, user_prompt, SYSTEM_PROMPT};
+pub use provider::{Suggestion, SuggestionProvider, SuggestionRequest};
@@ -0,0 +1,104 @@
+//! Provider for a local Ollama server.
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::http::post_json;
+use crate::SYSTEM_PROMPT;
+use crate::{parse_reply, user_prompt, AiError, Suggestion, SuggestionProvider, SuggestionRequest};
+
+/// Suggests resolutions with a model served by Ollama, keeping the code on
+/// the machine.
+#[derive(Debug, Clone)]
+pub struct OllamaProvider {
+    base_url: String,
+    model: String,
+    timeout: Duration,
+}
+
+impl OllamaProvider {
+    /// Where Ollama listens by default.
+    pub const DEFAULT_BASE_URL: &'static str = "http://localhost:11434";
+    /// Model used unless configured otherwise.
+    pub const DEFAULT_MODEL: &'static str = "llama3.1";
+
+    /// Creates a provider for the Ollama server at `base_url`.
+    #[must_use]
+    pub fn new(base_url: &str, model: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            // Local models can be slow to load
+            timeout: Duration::from_secs(120),
+        }
+    }
+
+    /// Sets how long to wait for a suggestion.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl SuggestionProvider for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn suggest(&self, request: &SuggestionRequest) -> Result<Suggestion, AiError> {
+        let body = json!({
+            "model": self.model,
+            "stream": false,
+            "format": "json",
+            "options": { "temperature": 0 },
+            "messages": [
+                { "role": "system", "content": SYSTEM_PROMPT },
+                { "role": "user", "content": user_prompt(request) },
+            ],
+        });
+        let response = post_json(
+            &format!("{}/api/chat", self.base_url),
+            None,
+            &body,
+            self.timeout,
+        )?;
+        let reply = response["message"]["content"]
+            .as_str()
+            .ok_or_else(|| AiError::Response(format!("no message in {response}")))?;
+        parse_reply(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::http::testing::serve_once;
+
+    #[test]
+    fn suggestion_comes_from_the_chat_message() {
+        let (url, server) = serve_once(
+            200,
+            r#"{"message":{"role":"assistant","content":"{\"resolution\":\"merged\",\"explanation\":\"Both.\"}"},"done":true}"#,
+        );
+        let request = SuggestionRequest {
+            path: PathBuf::from("a.py"),
+            left: "a".to_string(),
+            right: "b".to_string(),
+            base: Some("c".to_string()),
+            before: Vec::new(),
+            after: Vec::new(),
+        };
+        let suggestion = OllamaProvider::new(&url, "llama3.1")
+            .suggest(&request)
+            .unwrap();
+        let sent = server.join().unwrap();
+        assert_eq!(suggestion.content, "merged");
+        assert_eq!(suggestion.explanation.as_deref(), Some("Both."));
+        assert!(sent.contains("\"stream\":false"));
+        assert!(sent.contains("Common ancestor"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Provider for OpenAI-compatible chat completions APIs.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::http::post_json;
+use crate::SYSTEM_PROMPT;
+use crate::{parse_reply, user_prompt, AiError, Suggestion, SuggestionProvider, SuggestionRequest};
+
+/// Suggests resolutions with an OpenAI-compatible chat completions API,
+/// whether the hosted one or a local server speaking the same protocol.
+#[derive(Clone)]
+pub struct OpenAiProvider {
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    timeout: Duration,
+}
+
+impl OpenAiProvider {
+    /// The hosted `OpenAI` API.
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.openai.com/v1";
+    /// Model used unless configured otherwise.
+    pub const DEFAULT_MODEL: &'static str = "gpt-4o-mini";
+
+    /// Creates a provider for the API at `base_url`, authenticating with
+    /// `api_key` if given.
+    #[must_use]
+    pub fn new(base_url: &str, model: &str, api_key: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key,
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets how long to wait for a suggestion.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+// Written by hand to keep the API key out of logs
+impl fmt::Debug for OpenAiProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenAiProvider")
+            .field("base_url", &self.base_url)
+            .field("model", &self.model)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl SuggestionProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn suggest(&self, request: &SuggestionRequest) -> Result<Suggestion, AiError> {
+        let body = json!({
+            "model": self.model,
+            "temperature": 0,
+            "messages": [
+                { "role": "system", "content": SYSTEM_PROMPT },
+                { "role": "user", "content": user_prompt(request) },
+            ],
+        });
+        let response = post_json(
+            &format!("{}/chat/completions", self.base_url),
+            self.api_key.as_deref(),
+            &body,
+            self.timeout,
+        )?;
+        let reply = response["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| AiError::Response(format!("no message in {response}")))?;
+        parse_reply(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::http::testing::serve_once;
+
+    fn request() -> SuggestionRequest {
+        SuggestionRequest {
+            path: PathBuf::from("a.rs"),
+            left: "a".to_string(),
+            right: "b".to_string(),
+            base: None,
+            before: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn suggestion_comes_from_the_first_choice() {
+        let (url, server) = serve_once(
+            200,
+            r#"{"choices":[{"message":{"role":"assistant","content":"{\"resolution\":\"ab\",\"confidence\":0.5}"}}]}"#,
+        );
+        let provider = OpenAiProvider::new(&url, "test-model", Some("key".to_string()));
+        let suggestion = provider.suggest(&request()).unwrap();
+        let sent = server.join().unwrap();
+        assert_eq!(suggestion.content, "ab");
+        assert_eq!(suggestion.confidence, Some(0.5));
+        assert!(sent.contains("\"model\":\"test-model\""));
+        assert!(!format!("{provider:?}").contains("key\""));
+    }
+
+    #[test]
+    fn error_status_carries_the_message() {
+        let (url, server) = serve_once(401, r#"{"error":{"message":"Incorrect API key"}}"#);
+        let error = OpenAiProvider::new(&url, "m", None)
+            .suggest(&request())
+            .unwrap_err();
+        server.join().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "AI provider returned 401: Incorrect API key"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! The prompt sent to providers and the parsing of their replies.
+
+use std::fmt::Write;
+
+use serde::Deserialize;
+
+use crate::{AiError, Suggestion, SuggestionRequest};
+
+/// Instructions given to the model with every request.
+pub const SYSTEM_PROMPT: &str = "You resolve git merge conflicts. Given the two sides of a \
+conflict, the common ancestor if known, and the surrounding code, write the lines that should \
+replace the conflict, keeping the intent of both sides where they are compatible. Reply with a \
+single JSON object and nothing else: {\"resolution\": \"<the replacement lines>\", \
+\"confidence\": <0 to 1>, \"explanation\": \"<one sentence>\"}. The resolution must not contain \
+conflict markers or repeat the surrounding lines.";
+
+/// Describes the hunk in `request` for the model.
+#[must_use]
+pub fn user_prompt(request: &SuggestionRequest) -> String {
+    let mut prompt = format!("File: {}\n", request.path.display());
+    push_block(
+        &mut prompt,
+        "Lines before the conflict",
+        &request.before.join("\n"),
+    );
+    push_block(&mut prompt, "Ours", &request.left);
+    if let Some(base) = &request.base {
+        push_block(&mut prompt, "Common ancestor", base);
+    }
+    push_block(&mut prompt, "Theirs", &request.right);
+    push_block(
+        &mut prompt,
+        "Lines after the conflict",
+        &request.after.join("\n"),
+    );
+    prompt
+}
+
+/// Appends a labeled, fenced block of text.
+fn push_block(prompt: &mut String, label: &str, text: &str) {
+    let _ = write!(prompt, "\n{label}:\n```\n{text}\n```\n");
+}
+
+/// The JSON object the model is asked to reply with.
+#[derive(Deserialize)]
+struct Reply {
+    resolution: String,
+    #[serde(default)]
+    confidence: Option<f64>,
+    #[serde(default)]
+    explanation: Option<String>,
+}
+
+/// Parses a model's reply into a suggestion.
+///
+/// Text around the JSON object, such as a code fence, is ignored.
+///
+/// # Errors
+///
+/// Returns `AiError::Response` if the reply holds no such object.
+pub fn parse_reply(text: &str) -> Result<Suggestion, AiError> {
+    let json = match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &text[start..=end],
+        _ => {
+            return Err(AiError::Response(format!(
+                "no JSON object in reply: {text}"
+            )))
+        }
+    };
+    let reply: Reply = serde_json::from_str(json)
+        .map_err(|e| AiError::Response(format!("{e} in reply: {text}")))?;
+    Ok(Suggestion {
+        content: reply.resolution.trim_end_matches('\n').to_string(),
+        confidence: reply.confidence.map(|c| c.clamp(0.0, 1.0)),
+        explanation: reply.explanation.filter(|e| !e.trim().is_empty()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn prompt_includes_sides_base_and_context() {
+        let request = SuggestionRequest {
+            path: PathBuf::from("src/lib.rs"),
+            left: "let x = 1;".to_string(),
+            right: "let x = 2;".to_string(),
+            base: Some("let x = 0;".to_string()),
+            before: vec!["fn main() {".to_string()],
+            after: vec!["}".to_string()],
+        };
+        let prompt = user_prompt(&request);
+        assert!(prompt.starts_with("File: src/lib.rs\n"));
+        assert!(prompt.contains("Lines before the conflict:\n```\nfn main() {\n```"));
+        assert!(prompt.contains("Ours:\n```\nlet x = 1;\n```"));
+        assert!(prompt.contains("Common ancestor:\n```\nlet x = 0;\n```"));
+        assert!(prompt.contains("Theirs:\n```\nlet x = 2;\n```"));
+        assert!(prompt.contains("Lines after the conflict:\n```\n}\n```"));
+    }
+
+    #[test]
+    fn reply_is_parsed_from_fenced_json() {
+        let reply = "```json\n{\"resolution\": \"let x = 2;\\n\", \"confidence\": 1.5, \
+                     \"explanation\": \"Theirs is newer.\"}\n```";
+        let suggestion = parse_reply(reply).unwrap();
+        assert_eq!(suggestion.content, "let x = 2;");
+        assert_eq!(suggestion.confidence, Some(1.0));
+        assert_eq!(suggestion.explanation.as_deref(), Some("Theirs is newer."));
+    }
+
+    #[test]
+    fn reply_without_json_is_an_error() {
+        assert!(matches!(
+            parse_reply("I would keep both."),
+            Err(AiError::Response(_))
+        ));
+        assert!(parse_reply("{\"confidence\": 0.5}").is_err());
+    }
+}
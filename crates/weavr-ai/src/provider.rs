@@ -0,0 +1,135 @@
+//! The provider abstraction and the data passed through it.
+
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+
+use weavr_core::{
+    ConflictHunk, Resolution, ResolutionMetadata, ResolutionSource, ResolutionStrategyKind,
+};
+
+use crate::AiError;
+
+/// Everything a provider is told about a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestionRequest {
+    /// Path of the conflicted file, for the language and naming conventions.
+    pub path: PathBuf,
+    /// Our side of the hunk.
+    pub left: String,
+    /// Their side of the hunk.
+    pub right: String,
+    /// The common ancestor, if the markers include it.
+    pub base: Option<String>,
+    /// Lines before the hunk.
+    pub before: Vec<String>,
+    /// Lines after the hunk.
+    pub after: Vec<String>,
+}
+
+impl SuggestionRequest {
+    /// Builds a request for `hunk` of the file at `path`.
+    ///
+    /// The surrounding lines are the hunk's context, so they follow the
+    /// session's context line setting.
+    #[must_use]
+    pub fn from_hunk(path: &Path, hunk: &ConflictHunk) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            left: hunk.left.text.clone(),
+            right: hunk.right.text.clone(),
+            base: hunk.base.as_ref().map(|base| base.text.clone()),
+            before: hunk.context.before.clone(),
+            after: hunk.context.after.clone(),
+        }
+    }
+}
+
+/// A provider's proposed resolution of a hunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// The lines that replace the conflict.
+    pub content: String,
+    /// How sure the provider is, from 0 to 1, if it said.
+    pub confidence: Option<f64>,
+    /// Why the provider chose this resolution, if it said.
+    pub explanation: Option<String>,
+}
+
+impl Suggestion {
+    /// Describes the confidence and explanation in one line, if either is
+    /// known.
+    #[must_use]
+    pub fn summary(&self) -> Option<String> {
+        let confidence = self
+            .confidence
+            .map(|confidence| format!("{:.0}% confident", confidence * 100.0));
+        match (confidence, &self.explanation) {
+            (Some(confidence), Some(explanation)) => Some(format!("{confidence}: {explanation}")),
+            (Some(confidence), None) => Some(confidence),
+            (None, Some(explanation)) => Some(explanation.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// Turns the suggestion into a resolution from `provider`, keeping the
+    /// confidence and explanation as its note.
+    #[must_use]
+    pub fn into_resolution(self, provider: &str) -> Resolution {
+        let notes = self.summary();
+        Resolution {
+            kind: ResolutionStrategyKind::AiSuggested {
+                provider: provider.to_string(),
+            },
+            content: self.content,
+            metadata: ResolutionMetadata {
+                source: ResolutionSource::Ai,
+                notes,
+                propagated_from: None,
+            },
+        }
+    }
+}
+
+/// A source of suggested resolutions.
+///
+/// Implementations block until the suggestion arrives; callers that must
+/// stay responsive run them on another thread.
+pub trait SuggestionProvider: Debug + Send + Sync {
+    /// Returns the provider's name, recorded on the resolutions it suggests.
+    fn name(&self) -> &str;
+
+    /// Suggests a resolution for the hunk described by `request`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError` if the provider cannot be reached or its answer
+    /// cannot be understood.
+    fn suggest(&self, request: &SuggestionRequest) -> Result<Suggestion, AiError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolution_records_provider_and_confidence() {
+        let suggestion = Suggestion {
+            content: "merged".to_string(),
+            confidence: Some(0.82),
+            explanation: Some("Keeps both imports.".to_string()),
+        };
+        let resolution = suggestion.into_resolution("ollama");
+        assert_eq!(resolution.content, "merged");
+        assert_eq!(resolution.metadata.source, ResolutionSource::Ai);
+        assert_eq!(
+            resolution.kind,
+            ResolutionStrategyKind::AiSuggested {
+                provider: "ollama".to_string()
+            }
+        );
+        assert_eq!(
+            resolution.metadata.notes.as_deref(),
+            Some("82% confident: Keeps both imports.")
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! `weavr apply`: applying a patch, resolving whatever fails in the TUI.
+//!
+//! The patch is first applied with `git apply --3way`, which merges it with
+//! the blobs it was made against and leaves conflict markers where it
+//! conflicts. Patches from elsewhere, such as vendored or backported fixes,
+//! often reference blobs this repository never had; those are applied with
+//! `git apply --reject` instead, and each rejected hunk is written into its
+//! file as a conflict where it most likely belongs. Either way, the files
+//! with conflicts then open in the TUI.
+
+use std::path::{Path, PathBuf};
+
+use weavr_core::{parse_rejected_hunks, place_rejected_hunks, ConflictTree, MergeSession};
+use weavr_git::GitRepo;
+
+use crate::config::Config;
+use crate::error::{exit_codes, CliError};
+use crate::rerere::ResolutionMemory;
+use crate::resume::{ResumeMode, SessionSidecar};
+use crate::tui;
+
+/// Labels of the conflicts made from rejected hunks.
+const REJECT_LABELS: [&str; 3] = ["current", "patch context", "patch"];
+
+/// Applies `patch` and resolves the resulting conflicts, returning the exit
+/// code.
+pub fn run(patch: &Path, config: &Config) -> Result<i32, CliError> {
+    let repo = GitRepo::discover()?;
+    let patch = std::fs::canonicalize(patch).map_err(|_| CliError::FileNotFound(patch.into()))?;
+
+    let files = if repo.apply_three_way(&patch)? {
+        repo.conflicted_files()?
+    } else {
+        println!("Three-way merge unavailable; applying what applies and placing the rest");
+        let mut files = Vec::new();
+        for path in repo.apply_with_rejects(&patch)? {
+            if place_rejects(&repo.root().join(&path))? > 0 {
+                files.push(path);
+            }
+        }
+        files
+    };
+    if files.is_empty() {
+        println!("Patch applied cleanly");
+        return Ok(exit_codes::SUCCESS);
+    }
+
+    let files: Vec<PathBuf> = files.iter().map(|path| repo.root().join(path)).collect();
+    resolve(&files, config)
+}
+
+/// Writes the hunks rejected for `path` into it as conflicts and removes its
+/// `.rej` file, returning the number of conflicts.
+fn place_rejects(path: &Path) -> Result<usize, CliError> {
+    let mut rej = path.as_os_str().to_owned();
+    rej.push(".rej");
+    let rej = PathBuf::from(rej);
+
+    let hunks = parse_rejected_hunks(&std::fs::read_to_string(&rej)?);
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let placed = place_rejected_hunks(&content, &hunks, REJECT_LABELS);
+    let conflicts = MergeSession::from_conflicted(&placed, path.to_path_buf())
+        .map_or(0, |session| session.hunks().len());
+    std::fs::write(path, placed)?;
+    std::fs::remove_file(&rej)?;
+    println!("{}: {conflicts} changes conflict", path.display());
+    Ok(conflicts)
+}
+
+/// Opens each file in the TUI and writes the ones fully resolved.
+fn resolve(files: &[PathBuf], config: &Config) -> Result<i32, CliError> {
+    let validation = config.validation(None)?;
+    let overview = ConflictTree::default();
+    let mut resolved_hunks = Vec::new();
+    let mut any_unresolved = false;
+    let ai = config.suggestion_provider();
+
+    for path in files {
+        let result = tui::process_file(
+            path,
+            None,
+            ResolutionMemory::for_file(path).as_ref(),
+            SessionSidecar::for_file(path, ResumeMode::Ask).as_ref(),
+            None,
+            &resolved_hunks,
+            &config.resolve,
+            &overview,
+            &config.language_for(path),
+            &validation,
+            config.notify.notifier(),
+            ai.as_ref(),
+        )?;
+        resolved_hunks.extend(result.resolved_hunks);
+
+        if let Some(content) = result.content {
+            std::fs::write(path, content)?;
+            println!(
+                "{}: {} hunks resolved ({})",
+                path.display(),
+                result.hunks_resolved,
+                result.breakdown
+            );
+        } else {
+            any_unresolved = true;
+            eprintln!(
+                "{}: exited with {}/{} hunks unresolved",
+                path.display(),
+                result.total_hunks - result.hunks_resolved,
+                result.total_hunks
+            );
+        }
+    }
+
+    Ok(if any_unresolved {
+        exit_codes::UNRESOLVED
+    } else {
+        exit_codes::SUCCESS
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn rejects_become_conflicts_in_place() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, "fn a() {\n    one();\n}\n").unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs.rej"),
+            "diff a/lib.rs b/lib.rs\t(rejected hunks)\n\
+             @@ -1,3 +1,3 @@\n fn a() {\n-    uno();\n+    eins();\n }\n",
+        )
+        .unwrap();
+
+        assert_eq!(place_rejects(&path).unwrap(), 1);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "fn a() {\n<<<<<<< current\n    one();\n||||||| patch context\n    uno();\n\
+             =======\n    eins();\n>>>>>>> patch\n}\n"
+        );
+        assert!(!dir.path().join("lib.rs.rej").exists());
+    }
+}
@@ -0,0 +1,162 @@
+//! `weavr check`: a CI gate for conflicts.
+//!
+//! Fails with [`exit_codes::CONFLICTS`](crate::error::exit_codes::CONFLICTS) when tracked files contain conflict
+//! markers, which usually means a conflict was committed unresolved, or when
+//! merging any of the `--against` revisions into `HEAD` would conflict. The
+//! merges are done in memory, so the check is safe to run in any checkout.
+//! `--format json` prints the [`Report`] that headless runs print with
+//! `--report json`.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use weavr_core::MergeSession;
+use weavr_git::GitRepo;
+
+use crate::cli::ReportFormat;
+use crate::error::CliError;
+use crate::preview::{self, Predicted};
+use crate::report::{self, ConflictKinds, FileEntry, MergeReport, Report};
+
+/// Runs the check and prints the report, returning the exit code.
+pub fn run(against: &[String], format: ReportFormat, paths: &[String]) -> Result<i32, CliError> {
+    let repo = GitRepo::discover()?;
+    let kinds = ConflictKinds::load([Some(repo.root())])?;
+
+    let mut files = Vec::new();
+    for path in repo.files_with_markers(paths)? {
+        let mut entry = kinds.scan(&repo.root().join(&path))?;
+        entry.path = path;
+        files.push(entry);
+    }
+
+    let mut predictions = Vec::new();
+    for branch in against {
+        predictions.push(preview::predict(&repo, branch, "HEAD")?);
+    }
+
+    let report = build(files, against, &predictions);
+    match format {
+        ReportFormat::Text => print!("{}", format_text(&report, &predictions)),
+        ReportFormat::Json => report::print_json(&report)?,
+    }
+    Ok(report.outcome.exit_code())
+}
+
+/// Assembles the report from the conflicted files and each merge's
+/// predicted conflicts.
+fn build(files: Vec<FileEntry>, against: &[String], predictions: &[Vec<Predicted>]) -> Report {
+    let merges = against
+        .iter()
+        .zip(predictions)
+        .map(|(branch, predicted)| MergeReport {
+            branch: branch.clone(),
+            onto: "HEAD".to_string(),
+            conflicts: predicted
+                .iter()
+                .map(|file| predicted_entry(file.path.clone(), file.session.as_ref()))
+                .collect(),
+        })
+        .collect();
+    Report::check(files, merges)
+}
+
+/// Describes a file a merge would leave conflicted.
+fn predicted_entry(path: PathBuf, session: Option<&MergeSession>) -> FileEntry {
+    let hunks = session.map_or(&[][..], MergeSession::hunks);
+    FileEntry::new(
+        path,
+        "predicted",
+        report::hunk_entries(hunks, &HashMap::new()),
+    )
+}
+
+/// Formats the report for people: the files with markers, then each merge
+/// as `weavr preview` shows it, then the verdict.
+fn format_text(report: &Report, predictions: &[Vec<Predicted>]) -> String {
+    let mut out = String::new();
+    if report.files.is_empty() {
+        let _ = writeln!(out, "No conflicts found");
+    } else {
+        let _ = writeln!(out, "Conflicts in {} files:", report.files.len());
+        let width = report
+            .files
+            .iter()
+            .map(|file| file.path.display().to_string().chars().count())
+            .max()
+            .unwrap_or(0);
+        for file in &report.files {
+            let _ = writeln!(
+                out,
+                "{:<width$}  {:>4} hunks  {:>6} lines",
+                file.path.display().to_string(),
+                file.hunks,
+                file.lines
+            );
+        }
+    }
+    for (merge, predicted) in report.merges.iter().zip(predictions) {
+        out.push_str(&preview::format_report(
+            &merge.branch,
+            &merge.onto,
+            predicted,
+        ));
+    }
+    let _ = writeln!(
+        out,
+        "{}",
+        if report.ok {
+            "check passed"
+        } else {
+            "check failed"
+        }
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::exit_codes;
+
+    fn session(content: &str) -> MergeSession {
+        MergeSession::from_conflicted(content, PathBuf::from("a.rs")).unwrap()
+    }
+
+    #[test]
+    fn report_fails_on_markers_or_predicted_conflicts() {
+        let clean = build(Vec::new(), &["main".to_string()], &[Vec::new()]);
+        assert!(clean.ok);
+        assert_eq!(
+            format_text(&clean, &[Vec::new()]),
+            "No conflicts found\nMerging main into HEAD would not conflict\ncheck passed\n"
+        );
+        assert_eq!(clean.outcome.exit_code(), exit_codes::SUCCESS);
+
+        let conflicted = session("<<<<<<< HEAD\na\n=======\nb\n>>>>>>> x\n");
+        let marked = FileEntry::new(
+            PathBuf::from("a.rs"),
+            "markers",
+            report::hunk_entries(conflicted.hunks(), &HashMap::new()),
+        );
+        let report = build(vec![marked], &[], &[]);
+        assert!(!report.ok);
+        assert_eq!(report.outcome.exit_code(), exit_codes::CONFLICTS);
+        let text = format_text(&report, &[]);
+        assert!(text.starts_with("Conflicts in 1 files:\na.rs     1 hunks       2 lines\n"));
+        assert!(text.ends_with("check failed\n"));
+
+        let predicted = vec![Predicted {
+            path: PathBuf::from("a.rs"),
+            session: Some(conflicted),
+        }];
+        let report = build(Vec::new(), &["main".to_string()], &[predicted]);
+        assert!(!report.ok);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["outcome"], "conflicts");
+        assert_eq!(json["merges"][0]["conflicts"][0]["kind"], "predicted");
+        assert_eq!(json["merges"][0]["conflicts"][0]["hunks"], 1);
+        assert_eq!(json["merges"][0]["conflicts"][0]["complexity"], "moderate");
+    }
+}
@@ -2,7 +2,8 @@
 
 use std::path::PathBuf;
 
-use clap::{Parser, ValueEnum};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Parser, Subcommand, ValueEnum};
 
 /// Resolution strategy for headless mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -13,14 +14,247 @@ pub enum Strategy {
     Right,
     /// Accept both sides (combine left then right)
     Both,
+    /// Ask the configured AI provider
+    Ai,
+}
+
+impl Strategy {
+    /// Returns the strategy's name as given on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Left => "left",
+            Strategy::Right => "right",
+            Strategy::Both => "both",
+            Strategy::Ai => "ai",
+        }
+    }
+}
+
+/// Output format of reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ReportFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// A single JSON object
+    Json,
+}
+
+/// How `weavr show` lays out the sides of a conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ShowLayout {
+    /// Sides one after another
+    #[default]
+    Unified,
+    /// Sides in columns
+    SideBySide,
+}
+
+/// When to color output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Subcommands beside the default resolve workflow.
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Commands {
+    /// Create a throwaway repository with realistic conflicts to practice on
+    Playground {
+        /// Directory to create (defaults to a new directory under the system temp dir)
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+    /// Learn the TUI by resolving a scripted set of conflicts with guidance
+    Tutorial,
+    /// Show how files were resolved in past sessions
+    History {
+        /// Only show sessions for this file
+        #[arg(value_name = "FILE")]
+        path: Option<PathBuf>,
+
+        /// Only show sessions with hunks resolved by this source (user, ai, ast, rule, rerere)
+        #[arg(long, value_name = "SOURCE")]
+        source: Option<String>,
+
+        /// Maximum number of sessions to show
+        #[arg(long, value_name = "N", default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show where the conflicts in the current merge are concentrated
+    Stats {
+        /// Roll counts up into a directory tree with a heat bar per row
+        #[arg(long)]
+        tree: bool,
+    },
+    /// Manage the repository's resolution rules in .weavr.toml
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+    /// Re-run the merge with different strategy options, replacing the conflicts in each file
+    Remerge {
+        /// Files to re-merge (defaults to all conflicted files)
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+
+        /// Strategy option passed to git, e.g. ignore-space-change or find-renames=70
+        #[arg(short = 'X', long = "strategy-option", value_name = "OPTION")]
+        options: Vec<String>,
+
+        /// Report how the conflicts would change without writing files
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Replace files even if they were edited since the merge
+        #[arg(long)]
+        force: bool,
+    },
+    /// Review a finished merge without changing anything: a merge commit, or a
+    /// resolution patch written with --export-patch
+    Review {
+        /// Merge commit or resolution patch file
+        #[arg(value_name = "COMMIT|FILE")]
+        target: String,
+    },
+    /// Predict the conflicts of a merge without touching the working tree
+    Preview {
+        /// Branch or commit to merge
+        #[arg(value_name = "BRANCH")]
+        branch: String,
+
+        /// Branch or commit to merge into
+        #[arg(value_name = "ONTO", default_value = "HEAD")]
+        onto: String,
+
+        /// Resolve the predicted conflicts now and save the resolutions to
+        /// PATCH, to apply with --replay once the merge is made
+        #[arg(long, value_name = "PATCH")]
+        resolve: Option<PathBuf>,
+    },
+    /// Apply a patch with a three-way merge and resolve the hunks that fail in the TUI
+    Apply {
+        /// Patch file, as written by git diff or git format-patch
+        #[arg(value_name = "PATCH")]
+        patch: PathBuf,
+    },
+    /// Fail if the tree has conflict markers or merging would conflict, for CI
+    Check {
+        /// Also fail if merging this branch or commit into HEAD would conflict
+        #[arg(long, value_name = "REV")]
+        against: Vec<String>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t)]
+        format: ReportFormat,
+
+        /// Only look for markers in these paths (git pathspecs)
+        #[arg(value_name = "PATHSPEC")]
+        paths: Vec<String>,
+    },
+    /// Print the conflicts of files without entering the TUI
+    Show {
+        /// Files to show (defaults to all conflicted files)
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+
+        /// How to lay out the sides of each conflict
+        #[arg(long, value_enum, default_value_t)]
+        layout: ShowLayout,
+
+        /// Lines of context around each conflict
+        #[arg(long, value_name = "N", default_value_t = 3)]
+        context: usize,
+
+        /// When to color the output
+        #[arg(long, value_enum, default_value_t)]
+        color: ColorChoice,
+
+        /// Write to stdout instead of a pager
+        #[arg(long)]
+        no_pager: bool,
+    },
+    /// Explain from history how the conflicts in a file came about
+    Why {
+        /// Conflicted file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Only explain conflicts overlapping these lines, e.g. 12-20
+        #[arg(value_name = "LINES")]
+        lines: Option<String>,
+    },
+    /// Install git hooks that point out conflicts when a checkout or pull leaves some
+    Hook {
+        #[command(subcommand)]
+        command: HookCommand,
+    },
+    /// Manage the resolutions remembered for recurring conflicts
+    Rerere {
+        #[command(subcommand)]
+        command: RerereCommand,
+    },
+}
+
+/// Subcommands of `weavr rerere`.
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum RerereCommand {
+    /// Forget the resolutions remembered for the conflicts in these files, or
+    /// all of them
+    Clear {
+        /// Conflicted files whose resolutions to forget
+        #[arg(value_name = "FILE")]
+        files: Vec<PathBuf>,
+    },
+}
+
+/// Subcommands of `weavr hook`.
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum HookCommand {
+    /// Install the post-checkout and post-merge hooks
+    Install {
+        /// Replace existing hooks not written by weavr
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the hooks written by weavr
+    Uninstall,
+    /// Summarize the conflicts, if any (run by the installed hooks)
+    #[command(hide = true)]
+    Run {
+        /// Name of the hook running
+        hook: String,
+    },
+}
+
+/// Subcommands of `weavr rules`.
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum RulesCommand {
+    /// Propose rules for the current conflicts and write the accepted ones
+    Init {
+        /// Accept every proposal without asking
+        #[arg(long)]
+        yes: bool,
+    },
 }
 
 /// A terminal-first merge conflict resolver
 #[derive(Parser, Debug)]
 #[command(name = "weavr")]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 #[allow(clippy::struct_excessive_bools)] // CLI flags are naturally boolean
 pub struct Cli {
+    /// Subcommand to run instead of resolving conflicts
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
     /// Files to resolve (defaults to all conflicted files)
     #[arg(value_name = "FILE")]
     pub files: Vec<PathBuf>,
@@ -37,6 +271,17 @@ pub struct Cli {
     #[arg(long, requires = "headless")]
     pub dedupe: bool,
 
+    /// Strategy for hunks the AI provider fails to answer with --strategy=ai
+    #[arg(
+        long,
+        value_name = "STRATEGY",
+        default_value = "left",
+        value_parser = PossibleValuesParser::new(["left", "right", "both"])
+            .map(|name| Strategy::from_str(&name, false).unwrap_or(Strategy::Left)),
+        requires = "headless"
+    )]
+    pub ai_fallback: Strategy,
+
     /// Print result without writing to file
     #[arg(long, requires = "headless")]
     pub dry_run: bool,
@@ -45,10 +290,83 @@ pub struct Cli {
     #[arg(long, requires = "headless")]
     pub fail_on_ambiguous: bool,
 
+    /// Run the `regenerate` and `fallback` commands of rules files. Without
+    /// it, headless mode skips files that need one, and interactive mode
+    /// asks first
+    #[arg(long)]
+    pub allow_commands: bool,
+
+    /// Validation profile for resolved files: basic, strict, or one defined in the
+    /// config. In headless mode a failing check keeps the file from being written;
+    /// interactively, failures are reported after writing
+    #[arg(long, value_name = "PROFILE")]
+    pub validate: Option<String>,
+
     /// List conflicted files and exit
     #[arg(long)]
     pub list: bool,
 
+    /// Report each conflicted file and hunk in FORMAT with the strategy that
+    /// resolved it, exiting with 4 if every conflict was resolved and written and
+    /// 3 if conflicts are left in the tree, as with --dry-run. Use `weavr check`
+    /// to report conflicts without resolving them
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        requires = "headless",
+        conflicts_with = "list"
+    )]
+    pub report: Option<ReportFormat>,
+
+    /// Resolve the conflicts in this repository; repeat to span several repositories
+    #[arg(long = "repo", value_name = "DIR", conflicts_with = "files")]
+    pub repos: Vec<PathBuf>,
+
+    /// Resolve the conflicts in every repository listed in a workspace file
+    #[arg(long, value_name = "PATH", conflicts_with = "files")]
+    pub workspace: Option<PathBuf>,
+
+    /// Write an annotated patch of all resolved hunks to PATH for review
+    #[arg(long, value_name = "PATH")]
+    pub export_patch: Option<PathBuf>,
+
+    /// Write a standalone HTML page showing every hunk and its resolution to PATH,
+    /// for reviewers who don't use weavr
+    #[arg(long, value_name = "PATH")]
+    pub export_html: Option<PathBuf>,
+
+    /// Replay resolutions from an exported patch or a resolved copy of the file
+    #[arg(long, value_name = "PATH")]
+    pub replay: Option<PathBuf>,
+
+    /// Shared resolution store (directory or git ref such as refs/weavr/resolutions)
+    /// used to suggest and record resolutions in interactive mode
+    #[arg(long, value_name = "LOCATION")]
+    pub shared_store: Option<String>,
+
+    /// Neither reuse nor remember resolutions of recurring conflicts
+    #[arg(long)]
+    pub no_rerere: bool,
+
+    /// Resume the progress saved when the TUI was last quit on a file, without asking
+    #[arg(long, conflicts_with_all = ["headless", "report"])]
+    pub resume: bool,
+
+    /// Discard the progress saved when the TUI was last quit on a file, and start over
+    #[arg(long, conflicts_with_all = ["resume", "headless", "report"])]
+    pub discard_session: bool,
+
+    /// Act as a git mergetool: merge LOCAL and REMOTE against BASE and write
+    /// the resolved result to MERGED
+    #[arg(
+        long,
+        num_args = 4,
+        value_names = ["LOCAL", "BASE", "REMOTE", "MERGED"],
+        conflicts_with_all = ["files", "headless", "list", "report"]
+    )]
+    pub mergetool: Option<Vec<PathBuf>>,
+
     /// Configuration file path
     #[arg(long, value_name = "PATH")]
     pub config: Option<PathBuf>,
@@ -67,7 +385,45 @@ mod tests {
         assert!(!cli.dedupe);
         assert!(!cli.dry_run);
         assert!(!cli.fail_on_ambiguous);
+        assert!(!cli.allow_commands);
         assert!(!cli.list);
+        assert!(cli.report.is_none());
+        assert!(cli.export_patch.is_none());
+        assert!(cli.export_html.is_none());
+        assert!(cli.replay.is_none());
+        assert!(!cli.no_rerere);
+        assert!(!cli.resume);
+        assert!(!cli.discard_session);
+        assert!(cli.shared_store.is_none());
+        assert!(cli.repos.is_empty());
+        assert!(cli.workspace.is_none());
+        assert!(cli.command.is_none());
+        assert!(cli.mergetool.is_none());
+    }
+
+    #[test]
+    fn cli_parse_mergetool() {
+        let cli = Cli::parse_from(["weavr", "--mergetool", "l.rs", "b.rs", "r.rs", "m.rs"]);
+        assert_eq!(
+            cli.mergetool,
+            Some(vec![
+                PathBuf::from("l.rs"),
+                PathBuf::from("b.rs"),
+                PathBuf::from("r.rs"),
+                PathBuf::from("m.rs"),
+            ])
+        );
+        assert!(Cli::try_parse_from(["weavr", "--mergetool", "l.rs", "b.rs"]).is_err());
+    }
+
+    #[test]
+    fn cli_parse_repos() {
+        let cli = Cli::parse_from(["weavr", "--repo", "../api", "--repo", "../web", "--list"]);
+        assert_eq!(
+            cli.repos,
+            vec![PathBuf::from("../api"), PathBuf::from("../web")]
+        );
+        assert!(Cli::try_parse_from(["weavr", "--repo", "api", "file.rs"]).is_err());
     }
 
     #[test]
@@ -78,6 +434,23 @@ mod tests {
         assert!(cli.dedupe);
     }
 
+    #[test]
+    fn cli_parse_report() {
+        let cli = Cli::parse_from(["weavr", "--headless", "--report", "json"]);
+        assert_eq!(cli.report, Some(ReportFormat::Json));
+        // Reporting without resolving is `weavr check`
+        assert!(Cli::try_parse_from(["weavr", "--report", "json"]).is_err());
+        assert!(Cli::try_parse_from(["weavr", "--headless", "--report", "xml"]).is_err());
+    }
+
+    #[test]
+    fn cli_parse_resume_flags() {
+        assert!(Cli::parse_from(["weavr", "--resume"]).resume);
+        assert!(Cli::parse_from(["weavr", "--discard-session"]).discard_session);
+        assert!(Cli::try_parse_from(["weavr", "--resume", "--discard-session"]).is_err());
+        assert!(Cli::try_parse_from(["weavr", "--headless", "--resume"]).is_err());
+    }
+
     #[test]
     fn cli_parse_files() {
         let cli = Cli::parse_from(["weavr", "file1.rs", "file2.rs"]);
@@ -90,6 +463,18 @@ mod tests {
         assert!(cli.list);
     }
 
+    #[test]
+    fn cli_parse_export_patch() {
+        let cli = Cli::parse_from(["weavr", "--export-patch", "review.patch"]);
+        assert_eq!(cli.export_patch, Some(PathBuf::from("review.patch")));
+    }
+
+    #[test]
+    fn cli_parse_export_html() {
+        let cli = Cli::parse_from(["weavr", "--export-html", "review.html"]);
+        assert_eq!(cli.export_html, Some(PathBuf::from("review.html")));
+    }
+
     #[test]
     fn cli_parse_dry_run() {
         let cli = Cli::parse_from(["weavr", "--headless", "--dry-run"]);
@@ -129,4 +514,268 @@ mod tests {
         let result = Cli::try_parse_from(["weavr", "--dedupe"]);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn cli_parse_strategy_ai_with_fallback() {
+        let cli = Cli::parse_from(["weavr", "--headless", "--strategy=ai"]);
+        assert_eq!(cli.strategy, Some(Strategy::Ai));
+        assert_eq!(cli.ai_fallback, Strategy::Left);
+
+        let cli = Cli::parse_from(["weavr", "--headless", "--strategy=ai", "--ai-fallback=both"]);
+        assert_eq!(cli.ai_fallback, Strategy::Both);
+        assert!(Cli::try_parse_from(["weavr", "--ai-fallback=right"]).is_err());
+        assert!(Cli::try_parse_from(["weavr", "--headless", "--ai-fallback=ai"]).is_err());
+    }
+
+    #[test]
+    fn cli_parse_replay() {
+        let cli = Cli::parse_from(["weavr", "--replay", "fix.patch", "src/lib.rs"]);
+        assert_eq!(cli.replay, Some(PathBuf::from("fix.patch")));
+        assert_eq!(cli.files, vec![PathBuf::from("src/lib.rs")]);
+    }
+
+    #[test]
+    fn cli_parse_shared_store() {
+        let cli = Cli::parse_from(["weavr", "--shared-store", "refs/weavr/resolutions"]);
+        assert_eq!(cli.shared_store.as_deref(), Some("refs/weavr/resolutions"));
+    }
+
+    #[test]
+    fn cli_parse_playground() {
+        let cli = Cli::parse_from(["weavr", "playground", "/tmp/practice"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Playground {
+                dir: Some(PathBuf::from("/tmp/practice"))
+            })
+        );
+
+        let cli = Cli::parse_from(["weavr", "playground"]);
+        assert_eq!(cli.command, Some(Commands::Playground { dir: None }));
+    }
+
+    #[test]
+    fn cli_subcommand_rejects_resolve_flags() {
+        assert!(Cli::try_parse_from(["weavr", "playground", "--headless"]).is_err());
+    }
+
+    #[test]
+    fn cli_parse_tutorial() {
+        let cli = Cli::parse_from(["weavr", "tutorial"]);
+        assert_eq!(cli.command, Some(Commands::Tutorial));
+    }
+
+    #[test]
+    fn cli_parse_history() {
+        let cli = Cli::parse_from(["weavr", "history", "src/lib.rs", "--source", "ai"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::History {
+                path: Some(PathBuf::from("src/lib.rs")),
+                source: Some("ai".to_string()),
+                limit: 20,
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_stats_tree() {
+        let cli = Cli::parse_from(["weavr", "stats", "--tree"]);
+        assert_eq!(cli.command, Some(Commands::Stats { tree: true }));
+    }
+
+    #[test]
+    fn cli_parse_rules_init() {
+        let cli = Cli::parse_from(["weavr", "rules", "init", "--yes"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Rules {
+                command: RulesCommand::Init { yes: true }
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_remerge_options() {
+        let cli = Cli::parse_from([
+            "weavr",
+            "remerge",
+            "src/a.rs",
+            "-X",
+            "ignore-space-change",
+            "-Xfind-renames=70",
+            "--dry-run",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Remerge {
+                files: vec![PathBuf::from("src/a.rs")],
+                options: vec![
+                    "ignore-space-change".to_string(),
+                    "find-renames=70".to_string()
+                ],
+                dry_run: true,
+                force: false,
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_hook_commands() {
+        let cli = Cli::parse_from(["weavr", "hook", "install", "--force"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Hook {
+                command: HookCommand::Install { force: true }
+            })
+        );
+
+        let cli = Cli::parse_from(["weavr", "hook", "run", "post-merge"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Hook {
+                command: HookCommand::Run {
+                    hook: "post-merge".to_string()
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_rerere_clear() {
+        let cli = Cli::parse_from(["weavr", "rerere", "clear", "src/lib.rs"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Rerere {
+                command: RerereCommand::Clear {
+                    files: vec![PathBuf::from("src/lib.rs")]
+                }
+            })
+        );
+        assert!(Cli::parse_from(["weavr", "--no-rerere"]).no_rerere);
+    }
+
+    #[test]
+    fn cli_parse_review() {
+        let cli = Cli::parse_from(["weavr", "review", "HEAD~1"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Review {
+                target: "HEAD~1".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_preview() {
+        let cli = Cli::parse_from(["weavr", "preview", "feature"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Preview {
+                branch: "feature".to_string(),
+                onto: "HEAD".to_string(),
+                resolve: None,
+            })
+        );
+
+        let cli = Cli::parse_from([
+            "weavr",
+            "preview",
+            "feature",
+            "main",
+            "--resolve",
+            "p.patch",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Preview {
+                branch: "feature".to_string(),
+                onto: "main".to_string(),
+                resolve: Some(PathBuf::from("p.patch")),
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_check() {
+        let cli = Cli::parse_from([
+            "weavr",
+            "check",
+            "--against",
+            "main",
+            "--against",
+            "release",
+            "--format",
+            "json",
+            "src",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Check {
+                against: vec!["main".to_string(), "release".to_string()],
+                format: ReportFormat::Json,
+                paths: vec!["src".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_apply() {
+        let cli = Cli::parse_from(["weavr", "apply", "fix.patch"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Apply {
+                patch: PathBuf::from("fix.patch")
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_show() {
+        let cli = Cli::parse_from(["weavr", "show", "a.rs"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Show {
+                files: vec![PathBuf::from("a.rs")],
+                layout: ShowLayout::Unified,
+                context: 3,
+                color: ColorChoice::Auto,
+                no_pager: false,
+            })
+        );
+
+        let cli = Cli::parse_from([
+            "weavr",
+            "show",
+            "--layout",
+            "side-by-side",
+            "--context",
+            "0",
+            "--color",
+            "never",
+            "--no-pager",
+        ]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Show {
+                files: Vec::new(),
+                layout: ShowLayout::SideBySide,
+                context: 0,
+                color: ColorChoice::Never,
+                no_pager: true,
+            })
+        );
+    }
+
+    #[test]
+    fn cli_parse_why() {
+        let cli = Cli::parse_from(["weavr", "why", "src/lib.rs", "12-20"]);
+        assert_eq!(
+            cli.command,
+            Some(Commands::Why {
+                file: PathBuf::from("src/lib.rs"),
+                lines: Some("12-20".to_string()),
+            })
+        );
+    }
 }
@@ -0,0 +1,565 @@
+//! User configuration loading.
+//!
+//! Configuration follows XDG conventions and is read from
+//! `$XDG_CONFIG_HOME/weavr/config.toml` (falling back to
+//! `~/.config/weavr/config.toml`), or from the path given with `--config`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use weavr_ai::SuggestionProvider;
+use weavr_core::{ImportOrder, LanguageHeuristics, PropagationMode, ValidationProfile};
+use weavr_tui::notify::{Alert, Notifier};
+use weavr_tui::quick::QuickAction;
+
+use crate::error::CliError;
+
+/// Top-level weavr configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Resolution store settings.
+    pub store: StoreConfig,
+    /// Local resolution history settings.
+    pub history: HistoryConfig,
+    /// Interactive resolution settings.
+    pub resolve: ResolveConfig,
+    /// Merge heuristics by language name (`rust`, `python`, ...).
+    pub languages: BTreeMap<String, LanguageConfig>,
+    /// Checks run on resolved files before they are written.
+    pub validation: ValidationConfig,
+    /// Alerts when long operations finish.
+    pub notify: NotifyConfig,
+    /// Provider of AI suggestions.
+    pub ai: weavr_ai::AiConfig,
+}
+
+/// Settings for recording and looking up resolutions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoreConfig {
+    /// Shared resolution store: a directory, or a git ref such as
+    /// `refs/weavr/resolutions`.
+    pub shared: Option<String>,
+}
+
+/// Settings for the local resolution history.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Whether completed sessions are recorded.
+    pub enabled: bool,
+    /// History file (defaults to `$XDG_DATA_HOME/weavr/history.jsonl`).
+    pub path: Option<PathBuf>,
+}
+
+/// How to alert when a long operation finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyMethod {
+    /// No alert.
+    #[default]
+    Off,
+    /// Ring the terminal bell.
+    Bell,
+    /// Send an OSC 9 desktop notification through the terminal.
+    Osc9,
+}
+
+/// Settings for alerts when a headless run, test command, or check command
+/// finishes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// How to alert: `off`, `bell`, or `osc9`.
+    pub method: NotifyMethod,
+    /// Operations shorter than this many seconds do not alert.
+    pub after_secs: u64,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            method: NotifyMethod::Off,
+            after_secs: 10,
+        }
+    }
+}
+
+impl NotifyConfig {
+    /// Returns the notifier for these settings.
+    pub fn notifier(&self) -> Notifier {
+        Notifier {
+            alert: match self.method {
+                NotifyMethod::Off => None,
+                NotifyMethod::Bell => Some(Alert::Bell),
+                NotifyMethod::Osc9 => Some(Alert::Desktop),
+            },
+            after: Duration::from_secs(self.after_secs),
+        }
+    }
+}
+
+/// Settings for interactive resolution.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResolveConfig {
+    /// What to do with hunks identical to one just resolved: `off`,
+    /// `offer` (propose the same resolution), or `apply`.
+    pub propagate: PropagationMode,
+    /// Command that tests the merged result, run from the TUI with `T`
+    /// (e.g. `cargo test -p foo`).
+    pub test_command: Option<String>,
+    /// Command that checks the merged result, run from the TUI with `C`;
+    /// its diagnostics are attached to hunks (e.g.
+    /// `cargo check --message-format=json`).
+    pub check_command: Option<String>,
+    /// External diff tool opened on a hunk with `E`, with `$LOCAL`,
+    /// `$REMOTE`, `$BASE`, and `$MERGED` standing for the hunk's files
+    /// (e.g. `meld $LOCAL $MERGED $REMOTE --output $MERGED`).
+    pub diff_tool: Option<String>,
+    /// Resolution templates inserted with `:snippet <name>`; see
+    /// `weavr_tui::snippet` for the placeholders.
+    pub snippets: BTreeMap<String, String>,
+    /// Quick actions bound to the digit keys, as steps joined with `+`
+    /// (e.g. `1 = "accept left + next unresolved"`); see `weavr_tui::quick`
+    /// for the steps.
+    pub quick_actions: BTreeMap<String, String>,
+    /// Write resolved files on quitting without first confirming the diff
+    /// from the conflicted file to the result.
+    pub skip_final_diff: bool,
+    /// Unchanged lines shown around each hunk in the TUI and kept as its
+    /// context in exports; `+` and `-` change it while resolving. Unset
+    /// shows whole files, with exports keeping 3 lines.
+    pub context_lines: Option<usize>,
+}
+
+impl ResolveConfig {
+    /// Parses the quick actions by digit key.
+    pub fn quick_actions(&self) -> Result<BTreeMap<char, QuickAction>, CliError> {
+        self.quick_actions
+            .iter()
+            .map(|(key, action)| {
+                let [digit @ '0'..='9'] = key.chars().collect::<Vec<_>>()[..] else {
+                    return Err(CliError::Config(format!(
+                        "quick action key '{key}' is not a single digit"
+                    )));
+                };
+                let action = action
+                    .parse()
+                    .map_err(|e| CliError::Config(format!("quick action {key}: {e}")))?;
+                Ok((digit, action))
+            })
+            .collect()
+    }
+}
+
+/// Merge heuristics for one language.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LanguageConfig {
+    /// File extensions of the language; needed only for languages weavr
+    /// does not know.
+    pub extensions: Vec<String>,
+    /// Prefixes of import lines, replacing the built-in ones.
+    pub import_prefixes: Option<Vec<String>>,
+    /// How accepting both sides orders hunks made only of imports: `keep`
+    /// or `sorted`.
+    pub import_order: ImportOrder,
+    /// Keep both sides of conflicts in test files in headless mode,
+    /// whatever the strategy.
+    pub union_tests: bool,
+    /// Line placed between the sides when accepting both, e.g. `// ---`.
+    pub separator: Option<String>,
+    /// Command that formats a resolved file, reading it on stdin and
+    /// writing the result to stdout (e.g. `rustfmt --emit stdout`).
+    pub formatter: Option<String>,
+    /// Command that checks a resolved file's syntax, reading it on stdin
+    /// and failing if it does not parse; run by profiles with `syntax`.
+    pub syntax_check: Option<String>,
+}
+
+/// The settings that apply to one file's language.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanguageSettings {
+    /// Heuristics for accepting both sides.
+    pub heuristics: LanguageHeuristics,
+    /// Formatter run on the resolved file, if configured.
+    pub formatter: Option<String>,
+    /// Syntax check run on the resolved file, if configured.
+    pub syntax_check: Option<String>,
+}
+
+/// Validation settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValidationConfig {
+    /// Profile used when `--validate` is not given (defaults to `basic`).
+    pub profile: Option<String>,
+    /// Project-defined profiles by name.
+    pub profiles: BTreeMap<String, ValidationProfileConfig>,
+}
+
+/// A project-defined validation profile.
+///
+/// Checks left out are taken from the profile it extends.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValidationProfileConfig {
+    /// Built-in profile to start from (defaults to `basic`).
+    pub extends: Option<String>,
+    /// Reject leftover conflict markers.
+    pub markers: Option<bool>,
+    /// Reject resolutions that unbalance brackets.
+    pub balance: Option<bool>,
+    /// Reject declarations repeated by resolutions.
+    pub duplicates: Option<bool>,
+    /// Run the language's `syntax_check` command.
+    pub syntax: Option<bool>,
+    /// Commands that must succeed on each resolved file, which they read on
+    /// stdin; `$WEAVR_FILE` holds its path.
+    pub commands: Vec<String>,
+}
+
+/// A resolved validation profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationSettings {
+    /// Name of the profile.
+    pub name: String,
+    /// Checks run on the merge session.
+    pub checks: ValidationProfile,
+    /// Whether the language's syntax check runs.
+    pub syntax: bool,
+    /// Commands run on the resolved file.
+    pub commands: Vec<String>,
+}
+
+impl ValidationSettings {
+    /// Returns the built-in profile with the given name; `strict` also runs
+    /// syntax checks.
+    fn builtin(name: &str) -> Option<Self> {
+        Some(Self {
+            name: name.to_string(),
+            checks: ValidationProfile::builtin(name)?,
+            syntax: name == "strict",
+            commands: Vec::new(),
+        })
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads configuration from an explicit path or the default location.
+    ///
+    /// A missing file at the default location yields the default
+    /// configuration; a missing explicit path is an error.
+    pub fn load(explicit: Option<&Path>) -> Result<Self, CliError> {
+        let path = match explicit {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.is_file() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| CliError::Config(format!("{}: {e}", path.display())))?;
+        Self::parse(&text).map_err(|e| CliError::Config(format!("{}: {e}", path.display())))
+    }
+
+    /// Returns the AI provider answering `s` in the TUI, if one is
+    /// configured. A misconfigured provider is reported and leaves
+    /// suggestions off.
+    pub fn suggestion_provider(&self) -> Option<Arc<dyn SuggestionProvider>> {
+        self.ai.provider().unwrap_or_else(|e| {
+            eprintln!("weavr: {e}; AI suggestions are off");
+            None
+        })
+    }
+
+    /// Returns the language settings for a file, by its extension.
+    ///
+    /// Configured languages take precedence over the built-in ones, whose
+    /// defaults fill in anything not configured.
+    pub fn language_for(&self, path: &Path) -> LanguageSettings {
+        let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+            return LanguageSettings::default();
+        };
+        let name = self
+            .languages
+            .iter()
+            .find(|(_, language)| language.extensions.iter().any(|e| e == extension))
+            .map(|(name, _)| name.as_str())
+            .or_else(|| LanguageHeuristics::language_for_extension(extension));
+        let Some(name) = name else {
+            return LanguageSettings::default();
+        };
+
+        let mut heuristics = LanguageHeuristics::builtin(name);
+        let Some(config) = self.languages.get(name) else {
+            return LanguageSettings {
+                heuristics,
+                ..LanguageSettings::default()
+            };
+        };
+        if let Some(prefixes) = &config.import_prefixes {
+            heuristics.import_prefixes.clone_from(prefixes);
+        }
+        heuristics.import_order = config.import_order;
+        heuristics.union_tests = config.union_tests;
+        heuristics.separator.clone_from(&config.separator);
+        LanguageSettings {
+            heuristics,
+            formatter: config.formatter.clone(),
+            syntax_check: config.syntax_check.clone(),
+        }
+    }
+
+    /// Returns the validation profile named `name`, or the configured
+    /// default profile.
+    ///
+    /// Project-defined profiles take precedence over built-in ones of the
+    /// same name.
+    pub fn validation(&self, name: Option<&str>) -> Result<ValidationSettings, CliError> {
+        let name = name
+            .or(self.validation.profile.as_deref())
+            .unwrap_or("basic");
+
+        let Some(config) = self.validation.profiles.get(name) else {
+            return ValidationSettings::builtin(name).ok_or_else(|| {
+                let mut known: Vec<&str> = ValidationProfile::BUILTIN.to_vec();
+                known.extend(self.validation.profiles.keys().map(String::as_str));
+                CliError::Config(format!(
+                    "unknown validation profile '{name}' (known: {})",
+                    known.join(", ")
+                ))
+            });
+        };
+
+        let base = config.extends.as_deref().unwrap_or("basic");
+        let mut settings = ValidationSettings::builtin(base).ok_or_else(|| {
+            CliError::Config(format!(
+                "validation profile '{name}' extends unknown profile '{base}'"
+            ))
+        })?;
+        settings.name = name.to_string();
+        settings.checks.markers = config.markers.unwrap_or(settings.checks.markers);
+        settings.checks.balance = config.balance.unwrap_or(settings.checks.balance);
+        settings.checks.duplicates = config.duplicates.unwrap_or(settings.checks.duplicates);
+        settings.syntax = config.syntax.unwrap_or(settings.syntax);
+        settings.commands.clone_from(&config.commands);
+        Ok(settings)
+    }
+
+    /// Parses configuration from TOML text.
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+}
+
+/// Returns the default configuration file path, if a home directory is known.
+fn default_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+/// Returns weavr's configuration directory, `$XDG_CONFIG_HOME/weavr`
+/// (falling back to `~/.config/weavr`), if a home directory is known.
+pub fn config_dir() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("weavr"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_is_default() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parse_shared_store() {
+        let config = Config::parse("[store]\nshared = \"refs/weavr/resolutions\"\n").unwrap();
+        assert_eq!(
+            config.store.shared.as_deref(),
+            Some("refs/weavr/resolutions")
+        );
+    }
+
+    #[test]
+    fn history_is_enabled_by_default() {
+        let config = Config::parse("[history]\npath = \"/tmp/h.jsonl\"\n").unwrap();
+        assert!(config.history.enabled);
+        assert_eq!(config.history.path, Some(PathBuf::from("/tmp/h.jsonl")));
+
+        let config = Config::parse("[history]\nenabled = false\n").unwrap();
+        assert!(!config.history.enabled);
+    }
+
+    #[test]
+    fn parse_propagation_mode() {
+        assert_eq!(Config::default().resolve.propagate, PropagationMode::Offer);
+
+        let config = Config::parse("[resolve]\npropagate = \"apply\"\n").unwrap();
+        assert_eq!(config.resolve.propagate, PropagationMode::Apply);
+        assert!(Config::parse("[resolve]\npropagate = \"always\"\n").is_err());
+    }
+
+    #[test]
+    fn parse_test_command() {
+        assert!(Config::default().resolve.test_command.is_none());
+
+        let config = Config::parse("[resolve]\ntest_command = \"cargo test -p foo\"\n").unwrap();
+        assert_eq!(
+            config.resolve.test_command.as_deref(),
+            Some("cargo test -p foo")
+        );
+
+        let config = Config::parse("[resolve]\ncheck_command = \"make lint\"\n").unwrap();
+        assert_eq!(config.resolve.check_command.as_deref(), Some("make lint"));
+
+        let config = Config::parse("[resolve]\ndiff_tool = \"difft\"\n").unwrap();
+        assert_eq!(config.resolve.diff_tool.as_deref(), Some("difft"));
+    }
+
+    #[test]
+    fn parse_context_lines() {
+        assert!(Config::default().resolve.context_lines.is_none());
+
+        let config = Config::parse("[resolve]\ncontext_lines = 8\n").unwrap();
+        assert_eq!(config.resolve.context_lines, Some(8));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(Config::parse("[store]\nshard = \"/tmp\"\n").is_err());
+    }
+
+    #[test]
+    fn missing_explicit_path_is_error() {
+        let result = Config::load(Some(Path::new("/nonexistent/weavr/config.toml")));
+        assert!(matches!(result, Err(CliError::Config(_))));
+    }
+
+    #[test]
+    fn language_settings_by_extension() {
+        let config = Config::parse(
+            "[languages.rust]\nimport_order = \"sorted\"\nformatter = \"rustfmt --emit stdout\"\n\n\
+             [languages.elixir]\nextensions = [\"ex\", \"exs\"]\nimport_prefixes = [\"alias \"]\n\
+             union_tests = true\n",
+        )
+        .unwrap();
+
+        let rust = config.language_for(Path::new("src/lib.rs"));
+        assert_eq!(rust.heuristics.import_order, ImportOrder::Sorted);
+        assert!(rust
+            .heuristics
+            .import_prefixes
+            .contains(&"use ".to_string()));
+        assert_eq!(rust.formatter.as_deref(), Some("rustfmt --emit stdout"));
+
+        let elixir = config.language_for(Path::new("lib/app.exs"));
+        assert_eq!(
+            elixir.heuristics.import_prefixes,
+            vec!["alias ".to_string()]
+        );
+        assert!(elixir.heuristics.union_tests);
+
+        // Built-in languages work unconfigured; unknown files get nothing
+        let python = config.language_for(Path::new("app.py"));
+        assert_eq!(python.heuristics, LanguageHeuristics::builtin("python"));
+        assert_eq!(
+            config.language_for(Path::new("notes.txt")),
+            LanguageSettings::default()
+        );
+    }
+
+    #[test]
+    fn parse_snippets() {
+        let config = Config::parse(
+            "[resolve.snippets]\nflag = '''\nif flag(\"{{name}}\") {\n    {{theirs}}\n}\n'''\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.resolve.snippets["flag"],
+            "if flag(\"{{name}}\") {\n    {{theirs}}\n}\n"
+        );
+    }
+
+    #[test]
+    fn parse_quick_actions() {
+        let config = Config::parse(
+            "[resolve.quick_actions]\n1 = \"accept left + next unresolved\"\n\
+             3 = \"accept both deduped\"\n",
+        )
+        .unwrap();
+        let actions = config.resolve.quick_actions().unwrap();
+        assert_eq!(actions[&'1'].to_string(), "accept left + next unresolved");
+        assert_eq!(actions[&'3'].steps.len(), 1);
+
+        let config = Config::parse("[resolve.quick_actions]\n12 = \"clear\"\n").unwrap();
+        let Err(CliError::Config(message)) = config.resolve.quick_actions() else {
+            panic!("two-digit key accepted");
+        };
+        assert_eq!(message, "quick action key '12' is not a single digit");
+    }
+
+    #[test]
+    fn parse_notify() {
+        let config = Config::parse("[notify]\nmethod = \"osc9\"\nafter_secs = 30\n").unwrap();
+        let notifier = config.notify.notifier();
+        assert_eq!(notifier.alert, Some(Alert::Desktop));
+        assert_eq!(notifier.after, Duration::from_secs(30));
+        assert_eq!(Config::default().notify.notifier().alert, None);
+        assert!(Config::parse("[notify]\nmethod = \"loud\"\n").is_err());
+    }
+
+    #[test]
+    fn validation_profiles() {
+        let config = Config::parse(
+            "[validation]\nprofile = \"ci\"\n\n\
+             [validation.profiles.ci]\nextends = \"strict\"\nduplicates = false\n\
+             commands = [\"cargo check\"]\n\n\
+             [validation.profiles.docs]\nmarkers = false\n",
+        )
+        .unwrap();
+
+        let ci = config.validation(None).unwrap();
+        assert_eq!(ci.name, "ci");
+        assert!(ci.checks.balance && !ci.checks.duplicates && ci.syntax);
+        assert_eq!(ci.commands, vec!["cargo check".to_string()]);
+
+        let docs = config.validation(Some("docs")).unwrap();
+        assert!(!docs.checks.markers && !docs.checks.balance && !docs.syntax);
+
+        let strict = Config::default().validation(Some("strict")).unwrap();
+        assert_eq!(strict.checks, ValidationProfile::strict());
+        assert_eq!(
+            Config::default().validation(None).unwrap().checks,
+            ValidationProfile::basic()
+        );
+
+        let Err(CliError::Config(message)) = config.validation(Some("paranoid")) else {
+            panic!("unknown profile accepted");
+        };
+        assert_eq!(
+            message,
+            "unknown validation profile 'paranoid' (known: basic, strict, ci, docs)"
+        );
+    }
+}
@@ -1,11 +1,136 @@
 //! Git conflict file discovery.
+//!
+//! Conflicts are usually found in the repository containing the current
+//! directory. With `--repo` or a workspace file, discovery spans several
+//! repositories instead, for meta-repositories and multi-checkout setups
+//! where one change conflicts in several places at once; their files are
+//! grouped and labelled by repository.
 
 use std::path::{Path, PathBuf};
 
+use serde::Deserialize;
 use weavr_git::GitRepo;
 
 use crate::error::CliError;
 
+/// Conflicted files of one repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoGroup {
+    /// Name shown for the repository, or `None` for the only repository of
+    /// a single-repository session.
+    pub label: Option<String>,
+    /// Root of the repository's working tree, for multi-repository sessions.
+    pub root: Option<PathBuf>,
+    /// Files to resolve.
+    pub files: Vec<PathBuf>,
+}
+
+impl RepoGroup {
+    /// Prints a heading naming the repository, if the session spans several.
+    pub fn print_heading(&self) {
+        if let (Some(label), Some(root)) = (&self.label, &self.root) {
+            println!("== {label} ({}) ==", root.display());
+        }
+    }
+
+    /// Returns true if the canonical `path` lies in this group's repository.
+    pub fn contains(&self, path: &Path) -> bool {
+        let Some(root) = &self.root else {
+            return true;
+        };
+        let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.clone());
+        path.starts_with(root)
+    }
+}
+
+/// Workspace file listing the repositories of a multi-repository session:
+///
+/// ```toml
+/// repos = ["api", "web", "../shared"]
+/// ```
+///
+/// Relative paths are relative to the workspace file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Workspace {
+    repos: Vec<PathBuf>,
+}
+
+/// Returns the repositories given with `--repo` and in the workspace file.
+pub fn repositories(repos: &[PathBuf], workspace: Option<&Path>) -> Result<Vec<PathBuf>, CliError> {
+    let mut all = repos.to_vec();
+    if let Some(path) = workspace {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Config(format!("{}: {e}", path.display())))?;
+        let workspace: Workspace = toml::from_str(&text)
+            .map_err(|e| CliError::Config(format!("{}: {e}", path.display())))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        all.extend(workspace.repos.into_iter().map(|repo| dir.join(repo)));
+    }
+    Ok(all)
+}
+
+/// Finds the files to resolve: the given ones, or those conflicted in the
+/// current repository or in each of `repos`.
+pub fn resolve_groups(
+    provided: Vec<PathBuf>,
+    repos: &[PathBuf],
+) -> Result<Vec<RepoGroup>, CliError> {
+    if repos.is_empty() {
+        return Ok(vec![RepoGroup {
+            label: None,
+            root: None,
+            files: resolve_files(provided)?,
+        }]);
+    }
+
+    let mut groups = Vec::new();
+    for repo in discover_repos(repos)? {
+        let files = with_markers(repo.files)?;
+        if !files.is_empty() {
+            groups.push(RepoGroup { files, ..repo });
+        }
+    }
+    if groups.is_empty() {
+        return Err(CliError::NoConflictedFiles);
+    }
+    Ok(groups)
+}
+
+/// Finds the conflicted files in each repository, labelled by the name of
+/// its directory.
+pub fn discover_repos(repos: &[PathBuf]) -> Result<Vec<RepoGroup>, CliError> {
+    let mut groups: Vec<RepoGroup> = Vec::new();
+    for dir in repos {
+        let repo = GitRepo::discover_from(dir)?;
+        let root = repo.root().to_path_buf();
+        if groups.iter().any(|g| g.root.as_ref() == Some(&root)) {
+            continue;
+        }
+        let name = root.file_name().map_or_else(
+            || root.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        // Two checkouts of the same name are told apart by their full paths
+        let label = if groups.iter().any(|g| g.label.as_ref() == Some(&name)) {
+            root.display().to_string()
+        } else {
+            name
+        };
+        let files = repo
+            .conflicted_files()?
+            .into_iter()
+            .map(|file| root.join(file))
+            .collect();
+        groups.push(RepoGroup {
+            label: Some(label),
+            root: Some(root),
+            files,
+        });
+    }
+    Ok(groups)
+}
+
 /// Discovers files with Git merge conflicts in the current repository.
 pub fn discover_conflicted_files() -> Result<Vec<PathBuf>, CliError> {
     let repo = GitRepo::discover()?;
@@ -14,15 +139,21 @@ pub fn discover_conflicted_files() -> Result<Vec<PathBuf>, CliError> {
 }
 
 /// Checks if a file contains conflict markers.
+///
+/// Files that are not valid UTF-8 (e.g. binaries) have no markers.
 pub fn has_conflict_markers(path: &Path) -> Result<bool, CliError> {
-    let content = std::fs::read_to_string(path)?;
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
     Ok(content.contains("<<<<<<<") && content.contains("=======") && content.contains(">>>>>>>"))
 }
 
 /// Filters provided paths to only those with conflicts, or discovers all.
 pub fn resolve_files(provided: Vec<PathBuf>) -> Result<Vec<PathBuf>, CliError> {
     if provided.is_empty() {
-        let files = discover_conflicted_files()?;
+        let files = with_markers(discover_conflicted_files()?)?;
         if files.is_empty() {
             return Err(CliError::NoConflictedFiles);
         }
@@ -45,6 +176,42 @@ pub fn resolve_files(provided: Vec<PathBuf>) -> Result<Vec<PathBuf>, CliError> {
     }
 }
 
+/// Keeps the conflicted files that have markers to resolve.
+fn with_markers(files: Vec<PathBuf>) -> Result<Vec<PathBuf>, CliError> {
+    let mut kept = Vec::new();
+    for path in files {
+        // Binary and delete/modify conflicts leave no markers to resolve
+        if path.exists() && has_conflict_markers(&path)? {
+            kept.push(path);
+        } else {
+            eprintln!(
+                "weavr: skipping {}: no conflict markers (binary or deleted file?)",
+                path.display()
+            );
+        }
+    }
+    Ok(kept)
+}
+
+/// Lists the conflicted files of each repository to stdout, grouped under
+/// its label.
+pub fn list_repo_conflicts(repos: &[PathBuf]) -> Result<(), CliError> {
+    for group in discover_repos(repos)? {
+        let (Some(label), Some(root)) = (&group.label, &group.root) else {
+            continue;
+        };
+        println!("{label}:");
+        if group.files.is_empty() {
+            println!("  No conflicted files found");
+        }
+        for file in &group.files {
+            let relative = file.strip_prefix(root).unwrap_or(file);
+            println!("  {}", relative.display());
+        }
+    }
+    Ok(())
+}
+
 /// Lists conflicted files to stdout.
 pub fn list_conflicted_files() -> Result<(), CliError> {
     let files = discover_conflicted_files()?;
@@ -59,3 +226,66 @@ pub fn list_conflicted_files() -> Result<(), CliError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::{playground, stats};
+
+    #[test]
+    fn workspace_spans_repositories() {
+        let dir = TempDir::new().unwrap();
+        playground::create(Some(dir.path().join("api"))).unwrap();
+        playground::create(Some(dir.path().join("web"))).unwrap();
+        let workspace = dir.path().join("weavr-workspace.toml");
+        std::fs::write(&workspace, "repos = [\"api\", \"web\"]\n").unwrap();
+
+        let repos = repositories(&[dir.path().join("api")], Some(&workspace)).unwrap();
+        let groups = resolve_groups(Vec::new(), &repos).unwrap();
+
+        // The repository given twice is only discovered once
+        let labels: Vec<_> = groups.iter().map(|g| g.label.as_deref()).collect();
+        assert_eq!(labels, vec![Some("api"), Some("web")]);
+        for group in &groups {
+            let root = group.root.as_ref().unwrap();
+            assert!(!group.files.is_empty());
+            assert!(group.files.iter().all(|file| file.starts_with(root)));
+        }
+    }
+
+    #[test]
+    fn overview_names_each_repository() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("a")).unwrap();
+        std::fs::create_dir_all(dir.path().join("b")).unwrap();
+        let conflict = "<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> z\n";
+        std::fs::write(dir.path().join("a/lib.rs"), conflict).unwrap();
+        std::fs::write(dir.path().join("b/lib.rs"), conflict).unwrap();
+
+        let groups: Vec<RepoGroup> = ["a", "b"]
+            .iter()
+            .map(|name| RepoGroup {
+                label: Some(format!("repo-{name}")),
+                root: Some(dir.path().join(name)),
+                files: vec![dir.path().join(name).join("lib.rs")],
+            })
+            .collect();
+        let tree = stats::tree(&groups);
+
+        let names: Vec<&str> = tree.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["repo-a", "repo-b"]);
+        assert_eq!(tree.stats.hunks, 2);
+    }
+
+    #[test]
+    fn unknown_workspace_keys_are_rejected() {
+        let dir = TempDir::new().unwrap();
+        let workspace = dir.path().join("ws.toml");
+        std::fs::write(&workspace, "repositories = [\"a\"]\n").unwrap();
+
+        let result = repositories(&[], Some(&workspace));
+        assert!(matches!(result, Err(CliError::Config(_))));
+    }
+}
@@ -12,6 +12,12 @@ pub mod exit_codes {
     pub const UNRESOLVED: i32 = 1;
     /// Error occurred (parse failure, IO error, etc.).
     pub const ERROR: i32 = 2;
+    /// `weavr check` found conflicts or a merge that would conflict, or a
+    /// headless run with `--report` left conflicts in the tree.
+    pub const CONFLICTS: i32 = 3;
+    /// With `--report`, every conflict was resolved automatically and the
+    /// files written (never with `--dry-run`).
+    pub const AUTO_RESOLVED: i32 = 4;
 }
 
 /// CLI-specific errors.
@@ -44,6 +50,21 @@ pub enum CliError {
     #[error("Completion error: {0}")]
     Completion(#[from] weavr_core::CompletionError),
 
+    #[error("Playground error: {0}")]
+    Playground(String),
+
+    #[error("History error: {0}")]
+    History(String),
+
+    #[error("Config error: {0}")]
+    Config(String),
+
+    #[error("Regeneration error: {0}")]
+    Regenerate(String),
+
+    #[error("AI error: {0}")]
+    Ai(#[from] weavr_ai::AiError),
+
     #[error("Ambiguous hunks remain: {0} hunks could not be auto-resolved")]
     #[allow(dead_code)] // Reserved for --fail-on-ambiguous implementation
     AmbiguousHunks(usize),
@@ -0,0 +1,89 @@
+//! Running a language's formatter on resolved files.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Formats `content` with `command`, which reads the file on stdin and
+/// writes the formatted file to stdout.
+///
+/// A formatter that fails, for instance because the resolution does not
+/// parse, leaves the content as it is; the failure is reported.
+pub fn format(command: Option<&str>, path: &Path, content: String) -> String {
+    let Some(command) = command else {
+        return content;
+    };
+    match run(command, path, &content) {
+        Ok(formatted) => formatted,
+        Err(e) => {
+            eprintln!(
+                "weavr: {}: formatter failed, writing unformatted: {e}",
+                path.display()
+            );
+            content
+        }
+    }
+}
+
+/// Runs `command` through the shell with `content` on stdin and the file's
+/// `path` in `$WEAVR_FILE`, returning its output.
+///
+/// A failure is described by the first line the command wrote to stderr.
+pub fn run(command: &str, path: &Path, content: &str) -> Result<String, String> {
+    let mut child = shell(command)
+        .env("WEAVR_FILE", path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command may exit without reading everything, like `grep -q`
+        match stdin.write_all(content.as_bytes()) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.to_string()),
+            _ => {}
+        }
+    }
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.lines().next().unwrap_or("non-zero exit").to_string());
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+/// Builds a command that runs `command` through the platform shell.
+pub fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatter_output_replaces_content() {
+        let path = Path::new("a.txt");
+        assert_eq!(
+            format(Some("tr a-z A-Z"), path, "abc\n".to_string()),
+            "ABC\n"
+        );
+        assert_eq!(format(None, path, "abc\n".to_string()), "abc\n");
+    }
+
+    #[test]
+    fn failing_formatter_keeps_content() {
+        let path = Path::new("a.txt");
+        assert_eq!(format(Some("exit 3"), path, "abc\n".to_string()), "abc\n");
+    }
+}
@@ -1,9 +1,164 @@
 //! Headless mode implementation.
+//!
+//! Each file is resolved by the first rule in `.weavr.toml` matching it (see
+//! [`crate::rules`]), then by the `--strategy` flag, then by the rules'
+//! `fallback` command. Files none of these cover are skipped, unless no
+//! rules are configured at all: then they take ours, as before rules existed.
+//!
+//! `.weavr.toml` is tracked, so a branch being merged can change its
+//! commands. They only run with `--allow-commands`; without it, files that
+//! need one are skipped.
+//!
+//! With `--strategy ai`, each hunk is resolved by the configured AI provider;
+//! hunks it fails on take the `--ai-fallback` strategy instead.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use weavr_ai::{SuggestionProvider, SuggestionRequest};
+use weavr_core::{ConflictHunk, Resolution, ResolutionBreakdown, ResolutionSource};
 
 use crate::cli::Strategy;
+use crate::config::{LanguageSettings, ValidationSettings};
 use crate::error::CliError;
+use crate::formatter;
+use crate::history::{HistoryEntry, SessionMode};
+use crate::regenerate::Regeneration;
+use crate::replay::{self, ReplaySource};
+use crate::report::{self, HunkEntry};
+use crate::rerere::ResolutionMemory;
+use crate::rules::{FileRules, Rule, RuleStrategy};
+use crate::validate;
+
+/// How headless mode resolves a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Resolve the hunks with a strategy.
+    Resolve(Plan),
+    /// Run a command that regenerates or resolves the file.
+    Command(Regeneration),
+    /// Leave the file conflicted, as it needs a command and commands are
+    /// not allowed.
+    Refused(Regeneration),
+    /// Leave the file conflicted.
+    Skip,
+}
+
+/// A strategy for a file's hunks, and the rule that chose it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    /// Strategy for the hunks.
+    pub strategy: Strategy,
+    /// Whether accept-both drops duplicate lines.
+    pub dedupe: bool,
+    /// Pattern of the rule that chose the strategy, if one did.
+    pub rule: Option<String>,
+    /// Whether a rule or `--strategy` chose the strategy, rather than it
+    /// being the default. Language heuristics never override such a choice.
+    pub explicit: bool,
+}
+
+impl Plan {
+    /// Returns the plan a matching rule calls for.
+    pub fn from_rule(rule: &Rule) -> Self {
+        let (strategy, dedupe) = match rule.strategy {
+            RuleStrategy::Ours => (Strategy::Left, false),
+            RuleStrategy::Theirs => (Strategy::Right, false),
+            RuleStrategy::Both => (Strategy::Both, false),
+            RuleStrategy::BothDeduped => (Strategy::Both, true),
+        };
+        Self {
+            strategy,
+            dedupe,
+            rule: Some(rule.path.clone()),
+            explicit: true,
+        }
+    }
+
+    /// Returns the strategy for the hunks of `path`: the planned one, or
+    /// accept-both for test files when the `language` heuristics keep both
+    /// sides of them and nothing chose the strategy explicitly.
+    pub fn strategy_for(&self, path: &Path, language: &LanguageSettings) -> Strategy {
+        if !self.explicit && language.heuristics.prefers_union(path) {
+            Strategy::Both
+        } else {
+            self.strategy
+        }
+    }
+}
+
+/// Decides how to resolve `path`, given the `--strategy`, `--dedupe` and
+/// `--allow-commands` flags.
+pub fn action_for(
+    path: &Path,
+    strategy: Option<Strategy>,
+    dedupe: bool,
+    allow_commands: bool,
+) -> Result<Action, CliError> {
+    Ok(action(
+        FileRules::load(path)?.as_ref(),
+        strategy,
+        dedupe,
+        allow_commands,
+    ))
+}
+
+/// Decides how to resolve a file with the given rules, refusing commands
+/// unless they are allowed.
+fn action(
+    rules: Option<&FileRules>,
+    strategy: Option<Strategy>,
+    dedupe: bool,
+    allow_commands: bool,
+) -> Action {
+    match planned(rules, strategy, dedupe) {
+        Action::Command(regeneration) if !allow_commands => Action::Refused(regeneration),
+        action => action,
+    }
+}
+
+/// Decides how the rules and flags would resolve a file.
+fn planned(rules: Option<&FileRules>, strategy: Option<Strategy>, dedupe: bool) -> Action {
+    if let Some(file) = rules {
+        if let Some(rule) = file.rule() {
+            return match Regeneration::from_rule(&file.root, rule) {
+                Some(regeneration) => Action::Command(regeneration),
+                None => Action::Resolve(Plan::from_rule(rule)),
+            };
+        }
+    }
+    let configured = rules.is_some_and(|file| !file.rules.rules.is_empty());
+    let fallback = rules.and_then(|file| {
+        let command = file.rules.fallback.as_deref()?;
+        Some(Regeneration::fallback(&file.root, command))
+    });
+    match (strategy, fallback) {
+        (Some(strategy), _) => Action::Resolve(Plan {
+            strategy,
+            dedupe,
+            rule: None,
+            explicit: true,
+        }),
+        (None, Some(fallback)) => Action::Command(fallback),
+        (None, None) if configured => Action::Skip,
+        (None, None) => Action::Resolve(Plan {
+            strategy: Strategy::Left,
+            dedupe,
+            rule: None,
+            explicit: false,
+        }),
+    }
+}
+
+/// The provider answering `--strategy ai`, and the strategy for the hunks
+/// it fails on.
+pub struct AiStrategy<'a> {
+    /// Provider asked for each hunk.
+    pub provider: &'a dyn SuggestionProvider,
+    /// Strategy for hunks the provider fails on.
+    pub fallback: Strategy,
+}
 
 /// Result of headless processing for a single file.
 pub struct HeadlessResult {
@@ -11,18 +166,48 @@ pub struct HeadlessResult {
     pub path: PathBuf,
     /// Number of hunks that were resolved.
     pub hunks_resolved: usize,
+    /// How the hunks were resolved.
+    pub breakdown: ResolutionBreakdown,
     /// The merged output content.
     pub output: String,
+    /// Annotated patch of the resolved hunks, for review.
+    pub patch: String,
+    /// HTML section showing the hunks and their resolutions, for review.
+    pub review: String,
+    /// History record of the session (`None` for files without conflicts).
+    pub history: Option<HistoryEntry>,
+    /// Pattern of the rule that resolved the file, if one did.
+    pub rule: Option<String>,
+    /// Each hunk, and the strategy that resolved it, for `--report`.
+    pub conflicts: Vec<HunkEntry>,
 }
 
 /// Runs headless merge on a single file.
+///
+/// Recorded resolutions from `replay` are applied first, then the ones
+/// remembered in `memory`; the plan's strategy only resolves the hunks they
+/// leave unresolved. The `language` heuristics
+/// shape accept-both, and keep both sides of test files when configured to
+/// and neither a rule nor `--strategy` chose the strategy. Any
+/// failing check of the `validation` profile fails the file. Exports keep
+/// `context_lines` around each hunk when set. The AI strategy asks `ai`.
+#[allow(clippy::too_many_arguments)] // One per source of settings
 pub fn process_file(
     path: &Path,
-    strategy: Strategy,
-    dedupe: bool,
+    plan: &Plan,
+    ai: Option<&AiStrategy<'_>>,
+    replay: Option<&ReplaySource>,
+    memory: Option<&ResolutionMemory>,
+    language: &LanguageSettings,
+    validation: &ValidationSettings,
+    context_lines: Option<usize>,
 ) -> Result<HeadlessResult, CliError> {
+    let started = Instant::now();
     let content = std::fs::read_to_string(path)?;
-    let mut session = weavr_core::MergeSession::from_conflicted(&content, path.to_path_buf())?;
+    let mut session = weavr_core::MergeSession::from_conflicted(content, path.to_path_buf())?;
+    if let Some(count) = context_lines {
+        session.set_context_lines(count);
+    }
 
     let hunks: Vec<_> = session.hunks().to_vec();
 
@@ -31,38 +216,129 @@ pub fn process_file(
         return Ok(HeadlessResult {
             path: path.to_path_buf(),
             hunks_resolved: 0,
-            output: content,
+            breakdown: ResolutionBreakdown::default(),
+            output: session.conflicted().to_string(),
+            patch: String::new(),
+            review: String::new(),
+            history: None,
+            rule: None,
+            conflicts: Vec::new(),
         });
     }
 
-    for hunk in &hunks {
-        let resolution = match strategy {
-            Strategy::Left => weavr_core::Resolution::accept_left(hunk),
-            Strategy::Right => weavr_core::Resolution::accept_right(hunk),
-            Strategy::Both => {
-                let options = weavr_core::AcceptBothOptions {
-                    order: weavr_core::BothOrder::LeftThenRight,
-                    deduplicate: dedupe,
-                    trim_whitespace: false,
-                };
-                weavr_core::Resolution::accept_both(hunk, &options)
-            }
-        };
+    // Strategy that resolved each hunk, for reports
+    let mut resolved_by = HashMap::new();
+
+    if let Some(source) = replay {
+        let report = source.replay(&mut session)?;
+        eprintln!("{}: {}", path.display(), replay::summarize(&report));
+        resolved_by.extend(report.applied.iter().map(|id| (*id, "replay".to_string())));
+    }
+    if let Some(memory) = memory {
+        let recalled = memory.recall(&mut session)?;
+        resolved_by.extend(recalled.iter().map(|id| (*id, "rerere".to_string())));
+        if !recalled.is_empty() {
+            eprintln!(
+                "{}: {} hunks resolved as remembered",
+                path.display(),
+                recalled.len()
+            );
+        }
+    }
+
+    let unresolved: Vec<_> = hunks
+        .iter()
+        .filter(|h| !session.resolutions().contains_key(&h.id))
+        .collect();
 
+    let strategy = plan.strategy_for(path, language);
+    for hunk in unresolved {
+        let (resolution, by) = match (strategy, ai) {
+            (Strategy::Ai, Some(ai)) => suggest(path, hunk, ai, plan.dedupe, language),
+            _ => (resolve(hunk, strategy, plan.dedupe, language), strategy),
+        };
+        resolved_by.insert(hunk.id, by.name().to_string());
         session.set_resolution(hunk.id, resolution)?;
     }
 
+    let review_patch = weavr_core::format_resolution_patch(&session);
+    let review = weavr_core::format_review_html(&session);
+    let breakdown = ResolutionBreakdown::of_hunks(session.hunks());
+    let conflicts = report::hunk_entries(&hunks, &resolved_by);
+    let history = HistoryEntry::from_session(&session, SessionMode::Headless, started.elapsed());
+
     session.apply()?;
-    session.validate()?;
+    session.validate_with(&validation.checks)?;
     let result = session.complete()?;
+    report_warnings(path, &result.warnings);
+
+    let output = formatter::format(language.formatter.as_deref(), path, result.content);
+    let problems = validate::run_commands(validation, language, path, &output);
+    if !problems.is_empty() {
+        return Err(weavr_core::ValidationError::ChecksFailed(problems).into());
+    }
 
     Ok(HeadlessResult {
         path: path.to_path_buf(),
         hunks_resolved: result.summary.resolved_hunks,
-        output: result.content,
+        breakdown,
+        output,
+        patch: review_patch,
+        review,
+        history: Some(history),
+        rule: plan.rule.clone(),
+        conflicts,
     })
 }
 
+/// Resolves `hunk` with a fixed strategy. The AI strategy has no fixed
+/// answer and takes ours.
+fn resolve(
+    hunk: &ConflictHunk,
+    strategy: Strategy,
+    dedupe: bool,
+    language: &LanguageSettings,
+) -> Resolution {
+    let mut resolution = match strategy {
+        Strategy::Left | Strategy::Ai => Resolution::accept_left(hunk),
+        Strategy::Right => Resolution::accept_right(hunk),
+        Strategy::Both => {
+            let options = weavr_core::AcceptBothOptions {
+                order: weavr_core::BothOrder::LeftThenRight,
+                deduplicate: dedupe,
+                trim_whitespace: false,
+            };
+            language.heuristics.accept_both(hunk, &options)
+        }
+    };
+    resolution.metadata.source = ResolutionSource::Rule;
+    resolution
+}
+
+/// Resolves `hunk` with the AI provider's suggestion, or with the fallback
+/// strategy if the provider fails, returning the strategy that answered.
+fn suggest(
+    path: &Path,
+    hunk: &ConflictHunk,
+    ai: &AiStrategy<'_>,
+    dedupe: bool,
+    language: &LanguageSettings,
+) -> (Resolution, Strategy) {
+    let request = SuggestionRequest::from_hunk(path, hunk);
+    match ai.provider.suggest(&request) {
+        Ok(suggestion) => (suggestion.into_resolution(ai.provider.name()), Strategy::Ai),
+        Err(e) => {
+            eprintln!(
+                "weavr: {}: hunk {}: {e}; falling back to {}",
+                path.display(),
+                hunk.id.0 + 1,
+                ai.fallback.name()
+            );
+            (resolve(hunk, ai.fallback, dedupe, language), ai.fallback)
+        }
+    }
+}
+
 /// Writes the result to the file or prints it for dry-run.
 pub fn write_or_print(result: &HeadlessResult, dry_run: bool) -> Result<(), CliError> {
     if dry_run {
@@ -70,11 +346,217 @@ pub fn write_or_print(result: &HeadlessResult, dry_run: bool) -> Result<(), CliE
         print!("{}", result.output);
     } else {
         std::fs::write(&result.path, &result.output)?;
+        let rule = match &result.rule {
+            Some(pattern) => format!(" by rule `{pattern}`"),
+            None => String::new(),
+        };
         println!(
-            "{}: {} hunks resolved",
+            "{}: {} hunks resolved ({}){rule}",
             result.path.display(),
-            result.hunks_resolved
+            result.hunks_resolved,
+            result.breakdown
         );
     }
     Ok(())
 }
+
+/// Prints merge warnings, such as unbalanced resolutions, to stderr.
+pub fn report_warnings(path: &Path, warnings: &[weavr_core::MergeWarning]) {
+    for warning in warnings {
+        match warning.hunk_id {
+            Some(id) => eprintln!(
+                "weavr: {}: hunk {}: {}",
+                path.display(),
+                id.0 + 1,
+                warning.message
+            ),
+            None => eprintln!("weavr: {}: {}", path.display(), warning.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use weavr_ai::{AiError, Suggestion};
+
+    use super::*;
+    use crate::rules::RulesFile;
+
+    /// Merges hunks whose ours starts with `x`, and fails on the rest.
+    #[derive(Debug)]
+    struct Partial;
+
+    impl SuggestionProvider for Partial {
+        fn name(&self) -> &'static str {
+            "partial"
+        }
+
+        fn suggest(&self, request: &SuggestionRequest) -> Result<Suggestion, AiError> {
+            if !request.left.starts_with('x') {
+                return Err(AiError::Http("timed out".to_string()));
+            }
+            Ok(Suggestion {
+                content: format!("{}\n{}", request.left, request.right),
+                confidence: Some(0.75),
+                explanation: None,
+            })
+        }
+    }
+
+    fn rules(text: &str) -> FileRules {
+        FileRules {
+            root: PathBuf::from("/repo"),
+            relative: PathBuf::from("CHANGELOG.md"),
+            rules: toml::from_str(text).unwrap(),
+        }
+    }
+
+    #[test]
+    fn matching_rule_wins_over_the_strategy_flag() {
+        let file = rules("[[rules]]\npath = \"*.md\"\nstrategy = \"both-deduped\"\n");
+        assert_eq!(
+            action(Some(&file), Some(Strategy::Left), false, true),
+            Action::Resolve(Plan {
+                strategy: Strategy::Both,
+                dedupe: true,
+                rule: Some("*.md".to_string()),
+                explicit: true,
+            })
+        );
+    }
+
+    #[test]
+    fn unmatched_files_use_the_flag_then_the_fallback_then_skip() {
+        let file = rules("fallback = \"resolve\"\n\n[[rules]]\npath = \"*.lock\"\n");
+        assert!(matches!(
+            action(Some(&file), Some(Strategy::Right), false, true),
+            Action::Resolve(Plan {
+                strategy: Strategy::Right,
+                rule: None,
+                ..
+            })
+        ));
+        assert_eq!(
+            action(Some(&file), None, false, true),
+            Action::Command(Regeneration::fallback(Path::new("/repo"), "resolve"))
+        );
+
+        let file = rules("[[rules]]\npath = \"*.lock\"\n");
+        assert_eq!(action(Some(&file), None, false, true), Action::Skip);
+    }
+
+    #[test]
+    fn repository_commands_need_to_be_allowed() {
+        let file = rules("fallback = \"resolve\"\n");
+        assert_eq!(
+            action(Some(&file), None, false, false),
+            Action::Refused(Regeneration::fallback(Path::new("/repo"), "resolve"))
+        );
+
+        let file = rules("[[rules]]\npath = \"CHANGELOG.md\"\nregenerate = \"make log\"\n");
+        assert!(matches!(
+            action(Some(&file), None, false, false),
+            Action::Refused(Regeneration { command, .. }) if command == "make log"
+        ));
+        // A matching rule wins over --strategy, so its command is still
+        // refused, while a fallback gives way to the flag
+        assert!(matches!(
+            action(Some(&file), Some(Strategy::Left), false, false),
+            Action::Refused(_)
+        ));
+        assert!(matches!(
+            action(
+                Some(&rules("fallback = \"resolve\"\n")),
+                Some(Strategy::Left),
+                false,
+                false
+            ),
+            Action::Resolve(_)
+        ));
+    }
+
+    #[test]
+    fn without_rules_files_take_ours() {
+        let file = FileRules {
+            root: PathBuf::from("/repo"),
+            relative: PathBuf::from("a.rs"),
+            rules: RulesFile::default(),
+        };
+        for rules in [None, Some(&file)] {
+            assert!(matches!(
+                action(rules, None, false, true),
+                Action::Resolve(Plan {
+                    strategy: Strategy::Left,
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn strategy_flag_wins_over_union_for_tests() {
+        let mut language = LanguageSettings::default();
+        language.heuristics.union_tests = true;
+        let test_file = Path::new("tests/parser_test.rs");
+
+        let Action::Resolve(flagged) = action(None, Some(Strategy::Left), false, true) else {
+            panic!("expected a plan");
+        };
+        assert_eq!(flagged.strategy_for(test_file, &language), Strategy::Left);
+
+        let Action::Resolve(default) = action(None, None, false, true) else {
+            panic!("expected a plan");
+        };
+        assert_eq!(default.strategy_for(test_file, &language), Strategy::Both);
+        assert_eq!(
+            default.strategy_for(Path::new("src/lib.rs"), &language),
+            Strategy::Left
+        );
+    }
+
+    #[test]
+    fn ai_strategy_falls_back_for_hunks_the_provider_fails_on() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(
+            &path,
+            "<<<<<<< HEAD\nx = 1\n=======\nx = 2\n>>>>>>> b\nmid\n\
+             <<<<<<< HEAD\ny = 1\n=======\ny = 2\n>>>>>>> b\n",
+        )
+        .unwrap();
+        let plan = Plan {
+            strategy: Strategy::Ai,
+            dedupe: false,
+            rule: None,
+            explicit: true,
+        };
+        let ai = AiStrategy {
+            provider: &Partial,
+            fallback: Strategy::Right,
+        };
+        let validation = ValidationSettings {
+            name: "none".to_string(),
+            checks: weavr_core::ValidationProfile::default(),
+            syntax: false,
+            commands: Vec::new(),
+        };
+
+        let result = process_file(
+            &path,
+            &plan,
+            Some(&ai),
+            None,
+            None,
+            &LanguageSettings::default(),
+            &validation,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(result.output, "x = 1\nx = 2\nmid\ny = 2\n");
+        assert_eq!(result.breakdown.ai, 1);
+        assert_eq!(result.breakdown.rule, 1);
+        assert!(result.patch.contains("75% confident"));
+    }
+}
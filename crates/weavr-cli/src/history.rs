@@ -0,0 +1,408 @@
+//! Local resolution history.
+//!
+//! Every completed file is appended as one JSON object per line to
+//! `$XDG_DATA_HOME/weavr/history.jsonl` (falling back to
+//! `~/.local/share/weavr/history.jsonl`), and read back by `weavr history`.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use weavr_core::{HunkState, MergeSession, ResolutionBreakdown, ResolutionOrigin};
+
+use crate::config::HistoryConfig;
+use crate::error::CliError;
+use crate::stats;
+
+/// How a session was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionMode {
+    /// Resolved in the TUI.
+    Interactive,
+    /// Resolved by headless mode.
+    Headless,
+}
+
+/// A hunk's resolution as recorded in history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HunkRecord {
+    /// 1-based hunk number within the file.
+    pub hunk: u32,
+    /// Strategy name, e.g. `accept-left`.
+    pub strategy: String,
+    /// Who produced the resolution: `user`, `ai`, `ast`, `rule`, or `rerere`.
+    pub source: String,
+    /// Note attached to the resolution, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// 1-based number of the hunk this resolution was copied from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub propagated_from: Option<u32>,
+    /// How the hunk came to be resolved; missing from older entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<ResolutionOrigin>,
+}
+
+/// A completed session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Completion time, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Absolute path of the resolved file.
+    pub path: PathBuf,
+    /// How the file was resolved.
+    pub mode: SessionMode,
+    /// Time spent on the file, in seconds.
+    pub duration_secs: u64,
+    /// Per-hunk resolutions.
+    pub hunks: Vec<HunkRecord>,
+    /// Whether the result passed the test command before it was written.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub verified: bool,
+}
+
+impl HistoryEntry {
+    /// Captures the resolutions of a session that is about to be completed.
+    pub fn from_session(session: &MergeSession, mode: SessionMode, duration: Duration) -> Self {
+        let path = &session.input().left.path;
+        let hunks = session
+            .hunks()
+            .iter()
+            .filter_map(|hunk| match &hunk.state {
+                HunkState::Resolved(resolution) => Some(HunkRecord {
+                    hunk: hunk.id.0 + 1,
+                    strategy: resolution.kind.name().to_string(),
+                    source: resolution.metadata.source.name().to_string(),
+                    notes: resolution.metadata.notes.clone(),
+                    propagated_from: resolution.metadata.propagated_from.map(|id| id.0 + 1),
+                    origin: Some(ResolutionOrigin::of(resolution)),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            path: std::fs::canonicalize(path).unwrap_or_else(|_| path.clone()),
+            mode,
+            duration_secs: duration.as_secs(),
+            hunks,
+            verified: false,
+        }
+    }
+
+    /// Counts the hunks by how they were resolved, skipping hunks recorded
+    /// before origins were.
+    pub fn breakdown(&self) -> ResolutionBreakdown {
+        let mut breakdown = ResolutionBreakdown::default();
+        for origin in self.hunks.iter().filter_map(|h| h.origin) {
+            breakdown.record(origin);
+        }
+        breakdown
+    }
+
+    /// Returns true if any hunk was produced by the given source.
+    pub fn has_source(&self, source: &str) -> bool {
+        self.hunks.iter().any(|h| h.source == source)
+    }
+}
+
+/// Filters for `weavr history`.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// Only entries whose path ends with this path.
+    pub path: Option<PathBuf>,
+    /// Only entries with at least one hunk from this source.
+    pub source: Option<String>,
+    /// Maximum number of entries to return.
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    /// Returns true if an entry passes every filter.
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        let path_matches = self.path.as_ref().map_or(true, |path| {
+            let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+            entry.path == absolute || entry.path.ends_with(path)
+        });
+        let source_matches = self
+            .source
+            .as_deref()
+            .map_or(true, |source| entry.has_source(source));
+        path_matches && source_matches
+    }
+}
+
+/// Returns the history file location from configuration.
+pub fn history_path(config: &HistoryConfig) -> Option<PathBuf> {
+    if let Some(path) = &config.path {
+        return Some(path.clone());
+    }
+
+    Some(data_dir()?.join("history.jsonl"))
+}
+
+/// Returns weavr's data directory, `$XDG_DATA_HOME/weavr` (falling back to
+/// `~/.local/share/weavr`).
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+    Some(data_home.join("weavr"))
+}
+
+/// Appends entries to the history file, creating it as needed.
+pub fn append(path: &Path, entries: &[HistoryEntry]) -> Result<(), CliError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+
+    for entry in entries {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| CliError::History(format!("cannot encode entry: {e}")))?;
+        writeln!(file, "{line}")?;
+    }
+    Ok(())
+}
+
+/// Loads matching entries, newest first.
+///
+/// Lines that cannot be parsed are skipped so one bad write does not hide
+/// the rest of the history.
+pub fn load(path: &Path, query: &HistoryQuery) -> Result<Vec<HistoryEntry>, CliError> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut entries: Vec<HistoryEntry> = text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .filter(|entry| query.matches(entry))
+        .collect();
+    entries.reverse();
+    if let Some(limit) = query.limit {
+        entries.truncate(limit);
+    }
+    Ok(entries)
+}
+
+/// Counts the hunks of all entries by how they were resolved.
+pub fn overall(entries: &[HistoryEntry]) -> ResolutionBreakdown {
+    let mut overall = ResolutionBreakdown::default();
+    for entry in entries {
+        overall.add(&entry.breakdown());
+    }
+    overall
+}
+
+/// Prints entries as a human-readable list, followed by how their hunks were
+/// resolved overall.
+pub fn print(entries: &[HistoryEntry]) {
+    if entries.is_empty() {
+        println!("No matching history");
+        return;
+    }
+
+    for entry in entries {
+        println!(
+            "{}  {}  {} hunks  {}  ({}{})",
+            format_timestamp(entry.timestamp),
+            entry.path.display(),
+            entry.hunks.len(),
+            format_duration(entry.duration_secs),
+            match entry.mode {
+                SessionMode::Interactive => "interactive",
+                SessionMode::Headless => "headless",
+            },
+            if entry.verified { ", verified" } else { "" }
+        );
+        for hunk in &entry.hunks {
+            let note = hunk
+                .notes
+                .as_deref()
+                .map(|n| format!(" - {n}"))
+                .unwrap_or_default();
+            println!(
+                "    hunk {}: {} by {}{note}",
+                hunk.hunk, hunk.strategy, hunk.source
+            );
+        }
+    }
+
+    let overall = overall(entries);
+    if overall.total() > 0 {
+        println!("Overall: {}", stats::format_breakdown(&overall));
+    }
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD HH:MM` UTC.
+fn format_timestamp(timestamp: u64) -> String {
+    let days = timestamp / 86_400;
+    let seconds = timestamp % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) date.
+///
+/// Howard Hinnant's `civil_from_days` algorithm, restricted to dates after the epoch.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Formats a duration in seconds as e.g. `2m05s`.
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use weavr_core::{Resolution, ResolutionSource};
+
+    use super::*;
+
+    fn entry(path: &str, source: ResolutionSource) -> HistoryEntry {
+        let content = "<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> f";
+        let mut session = MergeSession::from_conflicted(content, PathBuf::from(path)).unwrap();
+        let hunk = session.hunks()[0].clone();
+        let mut resolution = Resolution::accept_left(&hunk);
+        resolution.metadata.source = source;
+        session.set_resolution(hunk.id, resolution).unwrap();
+        HistoryEntry::from_session(&session, SessionMode::Headless, Duration::from_secs(3))
+    }
+
+    #[test]
+    fn entry_records_resolutions() {
+        let entry = entry("/repo/src/lib.rs", ResolutionSource::User);
+        assert_eq!(entry.path, PathBuf::from("/repo/src/lib.rs"));
+        assert_eq!(entry.duration_secs, 3);
+        assert_eq!(
+            entry.hunks,
+            vec![HunkRecord {
+                hunk: 1,
+                strategy: "accept-left".to_string(),
+                source: "user".to_string(),
+                notes: None,
+                propagated_from: None,
+                origin: Some(ResolutionOrigin::Left),
+            }]
+        );
+        assert_eq!(entry.breakdown().left, 1);
+    }
+
+    #[test]
+    fn append_and_load_newest_first() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        append(&path, &[entry("/repo/a.rs", ResolutionSource::User)]).unwrap();
+        append(&path, &[entry("/repo/b.rs", ResolutionSource::Ai)]).unwrap();
+
+        let all = load(&path, &HistoryQuery::default()).unwrap();
+        let paths: Vec<_> = all.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/repo/b.rs"), PathBuf::from("/repo/a.rs")]
+        );
+    }
+
+    #[test]
+    fn load_filters_by_path_source_and_limit() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        append(
+            &path,
+            &[
+                entry("/repo/src/a.rs", ResolutionSource::User),
+                entry("/repo/src/b.rs", ResolutionSource::Ai),
+                entry("/repo/src/a.rs", ResolutionSource::Ai),
+            ],
+        )
+        .unwrap();
+
+        let query = HistoryQuery {
+            path: Some(PathBuf::from("src/a.rs")),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(load(&path, &query).unwrap().len(), 2);
+
+        let query = HistoryQuery {
+            source: Some("ai".to_string()),
+            limit: Some(1),
+            ..HistoryQuery::default()
+        };
+        let entries = load(&path, &query).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, PathBuf::from("/repo/src/a.rs"));
+    }
+
+    #[test]
+    fn load_skips_corrupt_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        append(&path, &[entry("/repo/a.rs", ResolutionSource::User)]).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{not json\n")
+            .unwrap();
+
+        assert_eq!(load(&path, &HistoryQuery::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn missing_history_is_empty() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        assert!(load(&path, &HistoryQuery::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn timestamps_format_as_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00");
+        assert_eq!(format_timestamp(1_760_536_980), "2025-10-15 14:03");
+    }
+
+    #[test]
+    fn durations_format_compactly() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(125), "2m05s");
+        assert_eq!(format_duration(7260), "2h01m");
+    }
+}
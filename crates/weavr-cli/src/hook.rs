@@ -0,0 +1,158 @@
+//! `weavr hook`: git hooks that point out conflicts as soon as they appear.
+//!
+//! Git runs `post-checkout` after `git checkout -m` and `git switch --merge`,
+//! and `post-merge` after a pull whose autostash comes back with conflicts.
+//! The installed hooks call `weavr hook run`, which stays quiet unless there
+//! are conflicted files and then prints where they are and how to resolve
+//! them. Hooks never fail the git command that ran them.
+
+use std::path::{Path, PathBuf};
+
+use weavr_git::GitRepo;
+
+use crate::error::CliError;
+use crate::stats;
+
+/// Hooks installed by `weavr hook install`.
+pub const HOOKS: &[&str] = &["post-checkout", "post-merge"];
+
+/// Line identifying hooks written by weavr.
+const MARKER: &str = "# Installed by weavr hook install";
+
+/// Installs the hooks into the repository in the current directory.
+///
+/// Existing hooks not written by weavr are left alone unless `force` is set.
+pub fn install(force: bool) -> Result<(), CliError> {
+    let repo = GitRepo::discover()?;
+    let dir = repo.hooks_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let weavr = std::env::current_exe()?;
+
+    for hook in HOOKS {
+        let path = dir.join(hook);
+        if path.exists() && !is_ours(&path) && !force {
+            println!(
+                "Skipped {}: it exists; rerun with --force to replace it",
+                path.display()
+            );
+            continue;
+        }
+        std::fs::write(&path, script(hook, &weavr))?;
+        make_executable(&path)?;
+        println!("Installed {}", path.display());
+    }
+    Ok(())
+}
+
+/// Removes the hooks written by weavr.
+pub fn uninstall() -> Result<(), CliError> {
+    let repo = GitRepo::discover()?;
+    let dir = repo.hooks_dir()?;
+
+    let mut removed = 0;
+    for hook in HOOKS {
+        let path = dir.join(hook);
+        if path.exists() && is_ours(&path) {
+            std::fs::remove_file(&path)?;
+            println!("Removed {}", path.display());
+            removed += 1;
+        }
+    }
+    if removed == 0 {
+        println!("No weavr hooks installed");
+    }
+    Ok(())
+}
+
+/// Runs from a hook: summarizes the conflicts, if there are any.
+///
+/// Errors are swallowed; a hook has no business failing a checkout.
+pub fn run(hook: &str) {
+    let Ok(repo) = GitRepo::discover() else {
+        return;
+    };
+    let Ok(files) = repo.conflicted_files() else {
+        return;
+    };
+    if let Some(summary) = summarize(hook, &files) {
+        eprint!("{summary}");
+    }
+}
+
+/// Describes the conflicts in `files` and what to run, or `None` if no file
+/// has conflict markers.
+fn summarize(hook: &str, files: &[PathBuf]) -> Option<String> {
+    let stats = stats::collect(files);
+    if stats.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "weavr ({hook}): conflicts need resolving\n{}\
+         Run `weavr` to resolve them, or `weavr stats --tree` for an overview.\n",
+        stats::format_flat(&stats)
+    ))
+}
+
+/// The hook script: runs `weavr hook run`, preferring the `weavr` on `PATH`
+/// and falling back to the binary that installed it.
+fn script(hook: &str, weavr: &Path) -> String {
+    let fallback = weavr.to_string_lossy().replace('\\', "/").replace('\'', "");
+    format!(
+        "#!/bin/sh\n\
+         {MARKER}; remove with `weavr hook uninstall`\n\
+         weavr=$(command -v weavr || echo '{fallback}')\n\
+         [ -x \"$weavr\" ] && \"$weavr\" hook run {hook}\n\
+         exit 0\n"
+    )
+}
+
+/// Returns true if the hook at `path` was written by weavr.
+fn is_ours(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|text| text.contains(MARKER))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn script_runs_weavr_and_never_fails() {
+        let script = script("post-merge", Path::new("/opt/bin/weavr"));
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains(MARKER));
+        assert!(script.contains("echo '/opt/bin/weavr'"));
+        assert!(script.contains("hook run post-merge"));
+        assert!(script.ends_with("exit 0\n"));
+    }
+
+    #[test]
+    fn summary_lists_conflicted_files() {
+        let dir = TempDir::new().unwrap();
+        let conflicted = dir.path().join("a.rs");
+        let clean = dir.path().join("b.rs");
+        std::fs::write(&conflicted, "<<<<<<< HEAD\na\n=======\nb\n>>>>>>> x\n").unwrap();
+        std::fs::write(&clean, "fn main() {}\n").unwrap();
+
+        let summary = summarize("post-checkout", &[conflicted.clone(), clean.clone()]);
+        let none = summarize("post-checkout", &[clean]);
+
+        let summary = summary.unwrap();
+        assert!(summary.starts_with("weavr (post-checkout): conflicts need resolving\n"));
+        assert!(summary.contains("1 files, 1 hunks"));
+        assert!(summary.contains("Run `weavr`"));
+        assert!(none.is_none());
+    }
+}
@@ -7,51 +7,208 @@
 
 #![forbid(unsafe_code)]
 
+mod apply;
+mod check;
 mod cli;
+mod config;
 mod discovery;
 mod error;
+mod formatter;
 mod headless;
+mod history;
+mod hook;
+mod mergetool;
+mod notes;
+mod playground;
+mod preview;
+mod regenerate;
+mod remerge;
+mod replay;
+mod report;
+mod rerere;
+mod resume;
+mod review;
+mod rules;
+mod rules_wizard;
+mod show;
+mod stats;
+mod store;
 mod tui;
+mod tutorial;
+mod validate;
+mod why;
+
+use std::time::Instant;
 
 use clap::Parser;
 
-use cli::{Cli, Strategy};
+use cli::{Cli, Commands, HookCommand, ReportFormat, RerereCommand, RulesCommand, Strategy};
+use config::{Config, ValidationSettings};
+use discovery::RepoGroup;
 use error::{exit_codes, CliError};
+use headless::{Action, AiStrategy};
+use replay::ReplaySource;
+use report::{ConflictKinds, Report};
+use rerere::ResolutionMemory;
+use resume::{ResumeMode, SessionSidecar};
+use store::SharedStore;
 
 fn run(cli: &Cli) -> Result<i32, CliError> {
+    let config = Config::load(cli.config.as_deref())?;
+
+    if let Some(command) = &cli.command {
+        return run_command(command, &config);
+    }
+
+    if let Some(paths) = &cli.mergetool {
+        return mergetool::run(paths, &config, cli.validate.as_deref(), !cli.no_rerere);
+    }
+
+    // Repositories spanned by the session, if not just the current one
+    let repos = discovery::repositories(&cli.repos, cli.workspace.as_deref())?;
+
     // Mode: List conflicted files
     if cli.list {
-        discovery::list_conflicted_files()?;
+        if repos.is_empty() {
+            discovery::list_conflicted_files()?;
+        } else {
+            discovery::list_repo_conflicts(&repos)?;
+        }
         return Ok(exit_codes::SUCCESS);
     }
 
-    // Resolve which files to process
-    let files = discovery::resolve_files(cli.files.clone())?;
+    // Resolve which files to process, by repository
+    let groups = match (
+        discovery::resolve_groups(cli.files.clone(), &repos),
+        cli.report,
+    ) {
+        (Err(CliError::NoConflictedFiles), Some(format)) => {
+            return Report::resolved(Vec::new(), !cli.dry_run).print(format);
+        }
+        (groups, _) => groups?,
+    };
+
+    // Checks run on each resolved file
+    let validation = config.validation(cli.validate.as_deref())?;
+
+    // Previously recorded resolutions to replay onto each file
+    let replay = cli.replay.as_deref().map(ReplaySource::load).transpose()?;
 
     // Mode: Headless
     if cli.headless {
-        let strategy = cli.strategy.unwrap_or(Strategy::Left);
+        let started = Instant::now();
+        let result = run_headless(cli, &config, &groups, &validation, replay.as_ref());
+        let message = if result.is_ok() {
+            "headless run finished"
+        } else {
+            "headless run failed"
+        };
+        config
+            .notify
+            .notifier()
+            .finished(started.elapsed(), message);
+        return result;
+    }
 
-        for path in &files {
-            let result = headless::process_file(path, strategy, cli.dedupe)?;
-            headless::write_or_print(&result, cli.dry_run)?;
-        }
+    run_interactive(cli, &config, &groups, &validation, replay.as_ref())
+}
 
-        return Ok(exit_codes::SUCCESS);
-    }
+/// Resolves every file in the TUI.
+fn run_interactive(
+    cli: &Cli,
+    config: &Config,
+    groups: &[RepoGroup],
+    validation: &ValidationSettings,
+    replay: Option<&ReplaySource>,
+) -> Result<i32, CliError> {
+    // Annotated patches collected for --export-patch
+    let mut patches = Vec::new();
+
+    // Review page sections collected for --export-html
+    let mut reviews = Vec::new();
+
+    // Completed sessions to record in the local history
+    let mut completed = Vec::new();
 
     // Mode: Interactive (TUI)
+    let store = cli
+        .shared_store
+        .as_deref()
+        .or(config.store.shared.as_deref())
+        .map(SharedStore::open)
+        .transpose()?;
     let mut any_unresolved = false;
 
-    for path in &files {
-        let result = tui::process_file(path)?;
+    // Hunks resolved so far, offered as suggestions for similar hunks
+    let mut resolved_hunks = Vec::new();
+
+    // What to do with progress saved by an earlier run
+    let resume = ResumeMode::from_flags(cli.resume, cli.discard_session);
+
+    // Where the conflicts are, for the overview inside the TUI
+    let overview = stats::tree(groups);
+
+    // Provider of AI suggestions, asked with `s`
+    let ai = config.suggestion_provider();
+
+    for (group, path) in groups
+        .iter()
+        .flat_map(|group| group.files.iter().map(move |path| (group, path)))
+    {
+        // Name the repository before its first file
+        if group.files.first() == Some(path) {
+            group.print_heading();
+        }
+        // Generated files are rebuilt rather than resolved by hand, if the
+        // user agrees to run the command and it succeeds
+        let regeneration = match regenerate::rule_for(path)? {
+            Some(regeneration)
+                if cli.allow_commands || regenerate::confirm(path, &regeneration)? =>
+            {
+                Some(regeneration)
+            }
+            _ => None,
+        };
+        if let Some(regeneration) = regeneration {
+            match regenerate::run(path, &regeneration, history::SessionMode::Interactive) {
+                Ok(regenerated) => {
+                    report_regenerated(path, &regeneration, &regenerated);
+                    completed.push(regenerated.history);
+                    continue;
+                }
+                Err(e) => eprintln!("weavr: {e}; resolve it by hand instead"),
+            }
+        }
+        let memory = (!cli.no_rerere)
+            .then(|| ResolutionMemory::for_file(path))
+            .flatten();
+        let sidecar = SessionSidecar::for_file(path, resume);
+        let result = tui::process_file(
+            path,
+            replay,
+            memory.as_ref(),
+            sidecar.as_ref(),
+            store.as_ref(),
+            &resolved_hunks,
+            &config.resolve,
+            &overview,
+            &config.language_for(path),
+            validation,
+            config.notify.notifier(),
+            ai.as_ref(),
+        )?;
+        patches.push(result.patch.clone());
+        reviews.push(result.review.clone());
+        resolved_hunks.extend(result.resolved_hunks);
 
         if let Some(ref content) = result.content {
             std::fs::write(path, content)?;
+            completed.extend(result.history);
             println!(
-                "{}: {} hunks resolved",
+                "{}: {} hunks resolved ({})",
                 path.display(),
-                result.hunks_resolved
+                result.hunks_resolved,
+                result.breakdown
             );
         } else {
             any_unresolved = true;
@@ -64,6 +221,8 @@ fn run(cli: &Cli) -> Result<i32, CliError> {
         }
     }
 
+    finish(cli, config, groups, &patches, &reviews, &completed)?;
+
     if any_unresolved {
         Ok(exit_codes::UNRESOLVED)
     } else {
@@ -71,6 +230,335 @@ fn run(cli: &Cli) -> Result<i32, CliError> {
     }
 }
 
+/// Resolves every file by its rules or the strategy, without the TUI.
+fn run_headless(
+    cli: &Cli,
+    config: &Config,
+    groups: &[RepoGroup],
+    validation: &ValidationSettings,
+    replay: Option<&ReplaySource>,
+) -> Result<i32, CliError> {
+    let mut patches = Vec::new();
+    let mut reviews = Vec::new();
+    let mut completed = Vec::new();
+    let mut any_skipped = false;
+
+    // Provider answering --strategy ai
+    let provider = match cli.strategy {
+        Some(Strategy::Ai) => Some(config.ai.provider()?.ok_or_else(|| {
+            CliError::Config(
+                "--strategy ai needs a provider: set [ai] provider or WEAVR_AI_PROVIDER"
+                    .to_string(),
+            )
+        })?),
+        _ => None,
+    };
+    let ai = provider.as_deref().map(|provider| AiStrategy {
+        provider,
+        fallback: cli.ai_fallback,
+    });
+
+    // Each file and how it was resolved, for --report
+    let kinds = match cli.report {
+        Some(_) => ConflictKinds::load(groups.iter().map(|group| group.root.as_deref()))?,
+        None => ConflictKinds::default(),
+    };
+    let mut entries = Vec::new();
+
+    // JSON reports keep stdout to themselves
+    let quiet = cli.report == Some(ReportFormat::Json);
+
+    for group in groups {
+        if !quiet {
+            group.print_heading();
+        }
+        for path in &group.files {
+            let plan =
+                match headless::action_for(path, cli.strategy, cli.dedupe, cli.allow_commands)? {
+                    Action::Resolve(plan) => plan,
+                    Action::Command(regeneration) => {
+                        entries.push(kinds.scan(path)?.resolved_by("command"));
+                        completed.extend(regenerate_headless(cli, path, &regeneration, quiet)?);
+                        continue;
+                    }
+                    Action::Refused(regeneration) => {
+                        any_skipped = true;
+                        entries.push(kinds.scan(path)?);
+                        eprintln!(
+                            "{}: skipped, its rules run `{}`; pass --allow-commands to run it",
+                            path.display(),
+                            regeneration.command
+                        );
+                        continue;
+                    }
+                    Action::Skip => {
+                        any_skipped = true;
+                        entries.push(kinds.scan(path)?);
+                        eprintln!(
+                            "{}: skipped, no rule matches and no --strategy given",
+                            path.display()
+                        );
+                        continue;
+                    }
+                };
+            let memory = (!cli.no_rerere)
+                .then(|| ResolutionMemory::for_file(path))
+                .flatten();
+            let result = headless::process_file(
+                path,
+                &plan,
+                ai.as_ref(),
+                replay,
+                memory.as_ref(),
+                &config.language_for(path),
+                validation,
+                config.resolve.context_lines,
+            )?;
+            if !quiet {
+                headless::write_or_print(&result, cli.dry_run)?;
+            } else if !cli.dry_run {
+                std::fs::write(&result.path, &result.output)?;
+            }
+            if !cli.dry_run {
+                completed.extend(result.history);
+            }
+            // Files without hunks were clean already
+            if !result.conflicts.is_empty() {
+                let mut entry =
+                    report::FileEntry::new(result.path, kinds.kind(path), result.conflicts);
+                entry.rule = result.rule;
+                entries.push(entry);
+            }
+            patches.push(result.patch);
+            reviews.push(result.review);
+        }
+    }
+
+    finish(cli, config, groups, &patches, &reviews, &completed)?;
+    if let Some(format) = cli.report {
+        // Modify/delete conflicts have no markers and are left to the user
+        if cli.files.is_empty() {
+            let unmarked = kinds.unmarked(entries.iter().map(|entry| entry.path.as_path()));
+            entries.extend(unmarked);
+        }
+        return Report::resolved(entries, !cli.dry_run).print(format);
+    }
+    if any_skipped && cli.fail_on_ambiguous {
+        Ok(exit_codes::UNRESOLVED)
+    } else {
+        Ok(exit_codes::SUCCESS)
+    }
+}
+
+/// Resolves a file by running a command, or tells which command would for
+/// `--dry-run`. Returns the session to record, if the command ran.
+fn regenerate_headless(
+    cli: &Cli,
+    path: &std::path::Path,
+    regeneration: &regenerate::Regeneration,
+    quiet: bool,
+) -> Result<Option<history::HistoryEntry>, CliError> {
+    if cli.dry_run {
+        if !quiet {
+            println!(
+                "{}: would resolve by {}",
+                path.display(),
+                regeneration.describe()
+            );
+        }
+        return Ok(None);
+    }
+    let regenerated = regenerate::run(path, regeneration, history::SessionMode::Headless)?;
+    if !quiet {
+        report_regenerated(path, regeneration, &regenerated);
+    }
+    Ok(Some(regenerated.history))
+}
+
+/// Reports a file resolved by running a command.
+fn report_regenerated(
+    path: &std::path::Path,
+    regeneration: &regenerate::Regeneration,
+    regenerated: &regenerate::Regenerated,
+) {
+    println!(
+        "{}: {} hunks resolved by {}",
+        path.display(),
+        regenerated.hunks_resolved,
+        regeneration.describe()
+    );
+}
+
+/// Runs a subcommand.
+fn run_command(command: &Commands, config: &Config) -> Result<i32, CliError> {
+    match command {
+        Commands::Playground { dir } => {
+            let dir = playground::create(dir.clone())?;
+            playground::print_summary(&dir);
+        }
+        Commands::Tutorial => {
+            if tutorial::run()? {
+                println!("Tutorial complete. Try `weavr playground` next for more practice.");
+            } else {
+                println!("Tutorial exited early. Run `weavr tutorial` to start again.");
+            }
+        }
+        Commands::History {
+            path,
+            source,
+            limit,
+        } => {
+            let query = history::HistoryQuery {
+                path: path.clone(),
+                source: source.clone(),
+                limit: Some(*limit),
+            };
+            let entries = match history::history_path(&config.history) {
+                Some(file) => history::load(&file, &query)?,
+                None => Vec::new(),
+            };
+            history::print(&entries);
+        }
+        Commands::Stats { tree } => stats::run(*tree)?,
+        Commands::Rules {
+            command: RulesCommand::Init { yes },
+        } => rules_wizard::run(*yes)?,
+        Commands::Remerge {
+            files,
+            options,
+            dry_run,
+            force,
+        } => remerge::run(files, options, *dry_run, *force)?,
+        Commands::Review { target } => review::run(target)?,
+        Commands::Preview {
+            branch,
+            onto,
+            resolve,
+        } => preview::run(branch, onto, resolve.as_deref())?,
+        Commands::Apply { patch } => return apply::run(patch, config),
+        Commands::Check {
+            against,
+            format,
+            paths,
+        } => return check::run(against, *format, paths),
+        Commands::Show {
+            files,
+            layout,
+            context,
+            color,
+            no_pager,
+        } => show::run(files, *layout, *context, *color, *no_pager)?,
+        Commands::Why { file, lines } => why::run(file, lines.as_deref())?,
+        Commands::Hook { command } => match command {
+            HookCommand::Install { force } => hook::install(*force)?,
+            HookCommand::Uninstall => hook::uninstall()?,
+            HookCommand::Run { hook } => hook::run(hook),
+        },
+        Commands::Rerere {
+            command: RerereCommand::Clear { files },
+        } => rerere::clear(files)?,
+    }
+    Ok(exit_codes::SUCCESS)
+}
+
+/// Writes the requested exports, records the completed sessions, and prints
+/// how the hunks were resolved overall.
+fn finish(
+    cli: &Cli,
+    config: &Config,
+    groups: &[RepoGroup],
+    patches: &[String],
+    reviews: &[String],
+    completed: &[history::HistoryEntry],
+) -> Result<(), CliError> {
+    export_patches(cli, patches)?;
+    export_review(cli, reviews)?;
+    record_history(config, completed);
+    record_notes(groups, completed);
+    if cli.report != Some(ReportFormat::Json) {
+        print_overall(groups, completed);
+    }
+    Ok(())
+}
+
+/// Prints how the hunks of the completed files were resolved, when there was
+/// more than one file.
+fn print_overall(groups: &[RepoGroup], completed: &[history::HistoryEntry]) {
+    let files: usize = groups.iter().map(|group| group.files.len()).sum();
+    let overall = history::overall(completed);
+    if files > 1 && overall.total() > 0 {
+        println!("Overall: {}", stats::format_breakdown(&overall));
+    }
+}
+
+/// Appends completed sessions to the local history, if enabled.
+///
+/// Failures are reported but do not fail the run; the merge itself is done.
+fn record_history(config: &Config, completed: &[history::HistoryEntry]) {
+    if !config.history.enabled {
+        return;
+    }
+    let Some(path) = history::history_path(&config.history) else {
+        return;
+    };
+    if let Err(e) = history::append(&path, completed) {
+        eprintln!("weavr: could not record history: {e}");
+    }
+}
+
+/// Adds the notes of completed sessions to each repository's commit message.
+fn record_notes(groups: &[RepoGroup], completed: &[history::HistoryEntry]) {
+    for group in groups {
+        let entries: Vec<history::HistoryEntry> = completed
+            .iter()
+            .filter(|entry| group.contains(&entry.path))
+            .cloned()
+            .collect();
+        notes::record(group.root.as_deref(), &entries);
+    }
+}
+
+/// Writes collected resolution patches to the `--export-patch` path, if set.
+fn export_patches(cli: &Cli, patches: &[String]) -> Result<(), CliError> {
+    let Some(path) = &cli.export_patch else {
+        return Ok(());
+    };
+
+    let combined: Vec<&str> = patches
+        .iter()
+        .map(String::as_str)
+        .filter(|p| !p.is_empty())
+        .collect();
+    std::fs::write(path, combined.join("\n"))?;
+    print_status(
+        cli,
+        &format!("Resolution patch written to {}", path.display()),
+    );
+    Ok(())
+}
+
+/// Writes the review page for `--export-html`, if set.
+fn export_review(cli: &Cli, reviews: &[String]) -> Result<(), CliError> {
+    let Some(path) = &cli.export_html else {
+        return Ok(());
+    };
+
+    let sections: Vec<String> = reviews.iter().filter(|r| !r.is_empty()).cloned().collect();
+    let page = weavr_core::format_review_page("weavr merge review", &sections);
+    std::fs::write(path, page)?;
+    print_status(cli, &format!("Review page written to {}", path.display()));
+    Ok(())
+}
+
+/// Prints a status message, to stderr when stdout is kept for a report.
+fn print_status(cli: &Cli, message: &str) {
+    if cli.report.is_some() {
+        eprintln!("{message}");
+    } else {
+        println!("{message}");
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
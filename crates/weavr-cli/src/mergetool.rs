@@ -0,0 +1,124 @@
+//! Running as a git mergetool.
+//!
+//! Git hands a mergetool the three versions of a file and the path to write
+//! the result to. To use weavr as one:
+//!
+//! ```text
+//! git config merge.tool weavr
+//! git config mergetool.weavr.cmd 'weavr --mergetool "$LOCAL" "$BASE" "$REMOTE" "$MERGED"'
+//! git config mergetool.weavr.trustExitCode true
+//! ```
+//!
+//! The versions are merged three-way, so regions only one side changed are
+//! taken without asking, and the TUI opens on the rest with the base shown
+//! between the sides. The exit code tells git whether the file was resolved.
+
+use std::path::{Path, PathBuf};
+
+use weavr_core::{ConflictTree, FileVersion, MergeInput, MergeSession};
+
+use crate::config::Config;
+use crate::error::{exit_codes, CliError};
+use crate::rerere::ResolutionMemory;
+use crate::tui;
+
+/// Merges the `LOCAL`, `BASE`, `REMOTE` and `MERGED` paths given by git,
+/// writing the result to `MERGED` once every hunk is resolved. Resolved
+/// files are checked against the named validation `profile`, and
+/// resolutions are reused and remembered if `rerere` is set.
+pub fn run(
+    paths: &[PathBuf],
+    config: &Config,
+    profile: Option<&str>,
+    rerere: bool,
+) -> Result<i32, CliError> {
+    let [local, base, remote, merged] = paths else {
+        return Err(CliError::Config(
+            "--mergetool takes LOCAL, BASE, REMOTE and MERGED".to_string(),
+        ));
+    };
+    let validation = config.validation(profile)?;
+    let session = MergeSession::from_three_way(input(local, base, remote, merged)?)?;
+
+    // Every change merged cleanly
+    if session.hunks().is_empty() {
+        std::fs::write(merged, session.complete()?.content)?;
+        println!("{}: merged without conflicts", merged.display());
+        return Ok(exit_codes::SUCCESS);
+    }
+
+    let memory = rerere.then(|| ResolutionMemory::for_file(merged)).flatten();
+    let result = tui::process_session(
+        session,
+        merged,
+        None,
+        memory.as_ref(),
+        None,
+        None,
+        &[],
+        &config.resolve,
+        &ConflictTree::default(),
+        &config.language_for(merged),
+        &validation,
+        config.notify.notifier(),
+        config.suggestion_provider().as_ref(),
+    )?;
+    let Some(content) = result.content else {
+        eprintln!(
+            "{}: exited with {}/{} hunks unresolved",
+            merged.display(),
+            result.total_hunks - result.hunks_resolved,
+            result.total_hunks
+        );
+        return Ok(exit_codes::UNRESOLVED);
+    };
+    std::fs::write(merged, content)?;
+    println!(
+        "{}: {} hunks resolved ({})",
+        merged.display(),
+        result.hunks_resolved,
+        result.breakdown
+    );
+    Ok(exit_codes::SUCCESS)
+}
+
+/// Reads the versions git handed over. A file added on both sides has no
+/// base, which git passes as a missing or empty file.
+fn input(local: &Path, base: &Path, remote: &Path, merged: &Path) -> Result<MergeInput, CliError> {
+    let version = |content: String| FileVersion {
+        path: merged.to_path_buf(),
+        content,
+    };
+    let base = std::fs::read_to_string(base)
+        .ok()
+        .filter(|content| !content.is_empty())
+        .map(version);
+    Ok(MergeInput {
+        left: version(std::fs::read_to_string(local)?),
+        right: version(std::fs::read_to_string(remote)?),
+        base,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn clean_three_way_merge_is_written_without_the_tui() {
+        let dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = ["local", "base", "remote", "merged"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        std::fs::write(&paths[0], "a\nB\nc\nd\n").unwrap();
+        std::fs::write(&paths[1], "a\nb\nc\nd\n").unwrap();
+        std::fs::write(&paths[2], "a\nb\nc\nD\n").unwrap();
+
+        let code = run(&paths, &Config::default(), None, false).unwrap();
+        assert_eq!(code, exit_codes::SUCCESS);
+        assert_eq!(std::fs::read_to_string(&paths[3]).unwrap(), "a\nB\nc\nD\n");
+    }
+}
@@ -0,0 +1,188 @@
+//! Resolution notes for the commit message.
+//!
+//! Notes attached to hunks with `:note` are collected from the completed
+//! sessions and appended to git's prepared commit message (`MERGE_MSG`), so
+//! they show up when the merge is committed. Outside of a merge the block is
+//! printed as a suggestion instead.
+
+use std::path::{Path, PathBuf};
+
+use weavr_git::GitRepo;
+
+use crate::history::HistoryEntry;
+
+/// Heading of the notes block in the commit message.
+const HEADING: &str = "Conflict resolution notes:";
+
+/// A note attached to one hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HunkNote {
+    /// File the hunk belongs to, relative to the repository root if known.
+    pub path: PathBuf,
+    /// 1-based hunk number within the file.
+    pub hunk: u32,
+    /// The note text.
+    pub note: String,
+}
+
+/// Collects the notes from completed sessions, in file and hunk order.
+pub fn collect(completed: &[HistoryEntry], root: Option<&Path>) -> Vec<HunkNote> {
+    let root = root.map(|root| std::fs::canonicalize(root).unwrap_or_else(|_| root.into()));
+
+    completed
+        .iter()
+        .flat_map(|entry| {
+            let path = root
+                .as_deref()
+                .and_then(|root| entry.path.strip_prefix(root).ok())
+                .unwrap_or(&entry.path)
+                .to_path_buf();
+            entry.hunks.iter().filter_map(move |record| {
+                record.notes.as_ref().map(|note| HunkNote {
+                    path: path.clone(),
+                    hunk: record.hunk,
+                    note: note.clone(),
+                })
+            })
+        })
+        .collect()
+}
+
+/// Formats notes as a commit message block, one line per note.
+///
+/// Lines already present in `existing` are left out so that running weavr
+/// again does not repeat them. Returns an empty string if nothing is new.
+pub fn format_block(notes: &[HunkNote], existing: &str) -> String {
+    let lines: Vec<String> = notes
+        .iter()
+        .map(|n| format!("- {} (hunk {}): {}", n.path.display(), n.hunk, n.note))
+        .filter(|line| !existing.lines().any(|l| l == line))
+        .collect();
+
+    if lines.is_empty() {
+        return String::new();
+    }
+    let heading = if existing.lines().any(|l| l == HEADING) {
+        String::new()
+    } else {
+        format!("{HEADING}\n")
+    };
+    format!("{heading}{}\n", lines.join("\n"))
+}
+
+/// Adds the notes of completed sessions to the pending commit message of
+/// the repository at `root`, or of the current repository.
+///
+/// Failures are reported but do not fail the run; the merge itself is done.
+pub fn record(root: Option<&Path>, completed: &[HistoryEntry]) {
+    let repo = match root {
+        Some(root) => GitRepo::discover_from(root).ok(),
+        None => GitRepo::discover().ok(),
+    };
+    let notes = collect(completed, repo.as_ref().map(GitRepo::root));
+    if notes.is_empty() {
+        return;
+    }
+
+    let existing = match repo.as_ref().map(GitRepo::merge_message).transpose() {
+        Ok(message) => message.flatten(),
+        Err(e) => {
+            eprintln!("weavr: could not read the commit message: {e}");
+            None
+        }
+    };
+
+    let Some(existing) = existing else {
+        println!("Suggested commit message notes:\n");
+        print!("{}", format_block(&notes, ""));
+        return;
+    };
+
+    let block = format_block(&notes, &existing);
+    if block.is_empty() {
+        return;
+    }
+    if let Some(repo) = &repo {
+        match repo.append_merge_message(&block) {
+            Ok(_) => println!("Added {} notes to the commit message", notes.len()),
+            Err(e) => eprintln!("weavr: could not update the commit message: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{HunkRecord, SessionMode};
+
+    use super::*;
+
+    fn entry(path: &str, notes: &[(u32, Option<&str>)]) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: 0,
+            path: PathBuf::from(path),
+            mode: SessionMode::Interactive,
+            duration_secs: 0,
+            hunks: notes
+                .iter()
+                .map(|(hunk, note)| HunkRecord {
+                    hunk: *hunk,
+                    strategy: "accept-right".to_string(),
+                    source: "user".to_string(),
+                    notes: note.map(str::to_string),
+                    propagated_from: None,
+                    origin: None,
+                })
+                .collect(),
+            verified: false,
+        }
+    }
+
+    #[test]
+    fn collect_skips_hunks_without_notes() {
+        let completed = [
+            entry("/repo/src/lib.rs", &[(1, None), (2, Some("API change"))]),
+            entry("/repo/README.md", &[(1, None)]),
+        ];
+
+        let notes = collect(&completed, Some(Path::new("/repo")));
+        assert_eq!(
+            notes,
+            vec![HunkNote {
+                path: PathBuf::from("src/lib.rs"),
+                hunk: 2,
+                note: "API change".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn format_block_lists_notes_under_heading() {
+        let notes = collect(
+            &[entry(
+                "a.rs",
+                &[(1, Some("kept theirs")), (3, Some("merged"))],
+            )],
+            None,
+        );
+        assert_eq!(
+            format_block(&notes, ""),
+            "Conflict resolution notes:\n- a.rs (hunk 1): kept theirs\n- a.rs (hunk 3): merged\n"
+        );
+    }
+
+    #[test]
+    fn format_block_skips_lines_already_present() {
+        let notes = collect(
+            &[entry(
+                "a.rs",
+                &[(1, Some("kept theirs")), (3, Some("merged"))],
+            )],
+            None,
+        );
+        let existing =
+            "Merge branch 'x'\n\nConflict resolution notes:\n- a.rs (hunk 1): kept theirs\n";
+
+        assert_eq!(format_block(&notes, existing), "- a.rs (hunk 3): merged\n");
+        assert_eq!(format_block(&notes[..1], existing), "");
+    }
+}
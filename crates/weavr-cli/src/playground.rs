@@ -0,0 +1,240 @@
+//! Conflict playground generator.
+//!
+//! Builds a throwaway repository whose `main` branch is left in the middle of
+//! a merge with a curated set of realistic conflicts, for practicing and for
+//! testing weavr against known scenarios.
+
+use std::path::{Path, PathBuf};
+
+use weavr_git::GitRepo;
+
+use crate::error::CliError;
+
+/// Branch merged into `main` to produce the conflicts.
+const FEATURE_BRANCH: &str = "feature";
+
+/// A change applied to the playground working tree.
+enum Change {
+    /// Write a file, creating parent directories as needed.
+    Write(&'static str, &'static [u8]),
+    /// Delete a file.
+    Delete(&'static str),
+    /// Rename a file.
+    Rename(&'static str, &'static str),
+}
+
+/// A conflict scenario included in the playground.
+pub struct Scenario {
+    /// File that ends up conflicted.
+    pub path: &'static str,
+    /// What kind of conflict it demonstrates.
+    pub description: &'static str,
+}
+
+/// Scenarios in the playground, in the order they are created.
+pub const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        path: "src/main.rs",
+        description: "both sides added imports",
+    },
+    Scenario {
+        path: "Cargo.toml",
+        description: "competing version bumps",
+    },
+    Scenario {
+        path: "src/lib.rs",
+        description: "overlapping edits to the same function",
+    },
+    Scenario {
+        path: "src/legacy.rs",
+        description: "modified here, deleted on the feature branch",
+    },
+    Scenario {
+        path: "assets/logo.bin",
+        description: "binary file changed on both sides",
+    },
+    Scenario {
+        path: "src/helpers.rs",
+        description: "renamed and edited on the feature branch, edited here",
+    },
+];
+
+/// Files shared by both branches.
+const BASE: &[Change] = &[
+    Change::Write(
+        "src/main.rs",
+        b"use std::env;\n\nfn main() {\n    let args: Vec<String> = env::args().collect();\n    println!(\"{args:?}\");\n}\n",
+    ),
+    Change::Write(
+        "Cargo.toml",
+        b"[package]\nname = \"playground\"\nversion = \"1.0.0\"\nedition = \"2021\"\n",
+    ),
+    Change::Write(
+        "src/lib.rs",
+        b"pub fn greet(name: &str) -> String {\n    format!(\"Hello, {name}\")\n}\n\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+    ),
+    Change::Write(
+        "src/legacy.rs",
+        b"pub fn old_api() -> u32 {\n    1\n}\n",
+    ),
+    Change::Write("assets/logo.bin", b"\x89LOGO\x00\x01\x02\x03"),
+    Change::Write(
+        "src/util.rs",
+        b"pub fn clamp(value: i32) -> i32 {\n    value.max(0).min(100)\n}\n\npub fn double(value: i32) -> i32 {\n    value * 2\n}\n",
+    ),
+];
+
+/// Changes committed on `main`.
+const OURS: &[Change] = &[
+    Change::Write(
+        "src/main.rs",
+        b"use std::env;\nuse std::fs;\n\nfn main() {\n    let args: Vec<String> = env::args().collect();\n    println!(\"{args:?}\");\n}\n",
+    ),
+    Change::Write(
+        "Cargo.toml",
+        b"[package]\nname = \"playground\"\nversion = \"1.1.0\"\nedition = \"2021\"\n",
+    ),
+    Change::Write(
+        "src/lib.rs",
+        b"pub fn greet(name: &str) -> String {\n    format!(\"Hello, {name}!\")\n}\n\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+    ),
+    Change::Write(
+        "src/legacy.rs",
+        b"pub fn old_api() -> u32 {\n    2\n}\n",
+    ),
+    Change::Write("assets/logo.bin", b"\x89LOGO\x00\x01\x02\x04"),
+    Change::Write(
+        "src/util.rs",
+        b"pub fn clamp(value: i32) -> i32 {\n    value.clamp(0, 100)\n}\n\npub fn double(value: i32) -> i32 {\n    value * 2\n}\n",
+    ),
+];
+
+/// Changes committed on the feature branch.
+const THEIRS: &[Change] = &[
+    Change::Write(
+        "src/main.rs",
+        b"use std::env;\nuse std::io;\n\nfn main() {\n    let args: Vec<String> = env::args().collect();\n    println!(\"{args:?}\");\n}\n",
+    ),
+    Change::Write(
+        "Cargo.toml",
+        b"[package]\nname = \"playground\"\nversion = \"2.0.0\"\nedition = \"2021\"\n",
+    ),
+    Change::Write(
+        "src/lib.rs",
+        b"pub fn greet(name: &str) -> String {\n    format!(\"Hi, {name}\")\n}\n\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+    ),
+    Change::Delete("src/legacy.rs"),
+    Change::Write("assets/logo.bin", b"\x89LOGO\x00\x01\x02\x05"),
+    Change::Rename("src/util.rs", "src/helpers.rs"),
+    Change::Write(
+        "src/helpers.rs",
+        b"pub fn clamp(value: i32) -> i32 {\n    value.max(0).min(255)\n}\n\npub fn double(value: i32) -> i32 {\n    value * 2\n}\n",
+    ),
+];
+
+/// Creates a playground repository and leaves it mid-merge.
+///
+/// Returns the directory the repository was created in.
+pub fn create(dir: Option<PathBuf>) -> Result<PathBuf, CliError> {
+    let dir = dir.unwrap_or_else(default_dir);
+    if dir.exists() && std::fs::read_dir(&dir)?.next().is_some() {
+        return Err(CliError::Playground(format!(
+            "{} already exists and is not empty",
+            dir.display()
+        )));
+    }
+    std::fs::create_dir_all(&dir)?;
+
+    let repo = GitRepo::init(&dir)?;
+    repo.set_config("user.name", "weavr playground")?;
+    repo.set_config("user.email", "playground@weavr.invalid")?;
+
+    apply(&dir, BASE)?;
+    repo.commit_all("Add playground project")?;
+    repo.create_branch(FEATURE_BRANCH)?;
+
+    apply(&dir, OURS)?;
+    repo.commit_all("Polish on main")?;
+
+    repo.checkout(FEATURE_BRANCH)?;
+    apply(&dir, THEIRS)?;
+    repo.commit_all("Rework on feature")?;
+
+    repo.checkout("main")?;
+    if repo.merge(FEATURE_BRANCH, &[])? {
+        return Err(CliError::Playground(
+            "merge unexpectedly completed without conflicts".to_string(),
+        ));
+    }
+
+    Ok(dir)
+}
+
+/// Prints what was created and how to start.
+pub fn print_summary(dir: &Path) {
+    println!("Playground created in {}", dir.display());
+    println!("Merging '{FEATURE_BRANCH}' into 'main' left these conflicts:");
+    for scenario in SCENARIOS {
+        println!("  {:<18} {}", scenario.path, scenario.description);
+    }
+    println!();
+    println!("Start with: cd {} && weavr", dir.display());
+}
+
+/// Returns a fresh directory under the system temp dir.
+fn default_dir() -> PathBuf {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    std::env::temp_dir().join(format!("weavr-playground-{stamp}-{}", std::process::id()))
+}
+
+/// Applies a list of changes to the working tree.
+fn apply(dir: &Path, changes: &[Change]) -> Result<(), CliError> {
+    for change in changes {
+        match change {
+            Change::Write(path, content) => {
+                let path = dir.join(path);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, content)?;
+            }
+            Change::Delete(path) => std::fs::remove_file(dir.join(path))?,
+            Change::Rename(from, to) => std::fs::rename(dir.join(from), dir.join(to))?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn playground_has_every_scenario_conflicted() {
+        let dir = TempDir::new().unwrap();
+        let created = create(Some(dir.path().to_path_buf())).unwrap();
+        let repo = GitRepo::discover_from(&created).unwrap();
+
+        let mut conflicted = repo.conflicted_files().unwrap();
+        conflicted.sort();
+        let mut expected: Vec<PathBuf> = SCENARIOS.iter().map(|s| PathBuf::from(s.path)).collect();
+        expected.sort();
+        assert_eq!(conflicted, expected);
+    }
+
+    #[test]
+    fn playground_refuses_non_empty_dir() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("keep.txt"), "x").unwrap();
+
+        assert!(matches!(
+            create(Some(dir.path().to_path_buf())),
+            Err(CliError::Playground(_))
+        ));
+        assert!(dir.path().join("keep.txt").exists());
+    }
+}
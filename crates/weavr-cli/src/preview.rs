@@ -0,0 +1,174 @@
+//! `weavr preview`: predicting the conflicts of a merge before making it.
+//!
+//! The merge is done in memory by git, so the working tree, the index, and
+//! any operation in progress are left alone. Each file that would conflict is
+//! listed with its hunks and an estimate of how hard they are to resolve.
+//! With `--resolve`, the predicted conflicts open in the TUI and the
+//! resolutions are saved as a resolution patch, ready for `--replay` once
+//! the merge is actually made.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use weavr_core::{Complexity, ConflictStats, MergeSession};
+use weavr_git::GitRepo;
+use weavr_tui::App;
+
+use crate::error::CliError;
+
+/// A file the merge would conflict in.
+pub struct Predicted {
+    /// Path relative to the repository root.
+    pub path: PathBuf,
+    /// The file as the merge would leave it, or `None` if the merge deletes
+    /// it (a modify/delete conflict).
+    pub session: Option<MergeSession>,
+}
+
+/// Previews merging `branch` into `onto`, then resolves the predicted
+/// conflicts into `resolve` if given.
+pub fn run(branch: &str, onto: &str, resolve: Option<&Path>) -> Result<(), CliError> {
+    let repo = GitRepo::discover()?;
+    let predicted = predict(&repo, branch, onto)?;
+    print!("{}", format_report(branch, onto, &predicted));
+
+    if let Some(patch) = resolve {
+        let sessions: Vec<MergeSession> = predicted
+            .into_iter()
+            .filter_map(|file| file.session)
+            .filter(|session| !session.hunks().is_empty())
+            .collect();
+        if !sessions.is_empty() {
+            pre_resolve(sessions, patch)?;
+        }
+    }
+    Ok(())
+}
+
+/// Merges in memory and parses each conflicted file.
+pub fn predict(repo: &GitRepo, branch: &str, onto: &str) -> Result<Vec<Predicted>, CliError> {
+    repo.preview_merge(onto, branch)?
+        .into_iter()
+        .map(|(path, content)| {
+            let session = content
+                .map(|content| MergeSession::from_conflicted(content, path.clone()))
+                .transpose()?;
+            Ok(Predicted { path, session })
+        })
+        .collect()
+}
+
+/// Opens each session in the TUI and writes what was resolved to `patch`.
+fn pre_resolve(sessions: Vec<MergeSession>, patch: &Path) -> Result<(), CliError> {
+    let mut patches = Vec::new();
+    for session in sessions {
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_status_message("Preview: resolutions are saved to a patch, not to the file");
+        weavr_tui::run(&mut app)?;
+        if let Some(session) = app.take_session() {
+            let text = weavr_core::format_resolution_patch(&session);
+            if !text.is_empty() {
+                patches.push(text);
+            }
+        }
+    }
+
+    if patches.is_empty() {
+        println!("Nothing was resolved; no patch written");
+        return Ok(());
+    }
+    std::fs::write(patch, patches.join("\n"))?;
+    println!(
+        "Resolutions written to {}; after merging, apply them with `weavr --replay {}`",
+        patch.display(),
+        patch.display()
+    );
+    Ok(())
+}
+
+/// Formats one line per conflicted file with its complexity, followed by
+/// the totals.
+pub fn format_report(branch: &str, onto: &str, predicted: &[Predicted]) -> String {
+    if predicted.is_empty() {
+        return format!("Merging {branch} into {onto} would not conflict\n");
+    }
+
+    let width = predicted
+        .iter()
+        .map(|file| file.path.display().to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = format!(
+        "Merging {branch} into {onto} would conflict in {} files:\n",
+        predicted.len()
+    );
+    let mut total = ConflictStats::default();
+    let mut counts = [0usize; 3];
+    for file in predicted {
+        let path = file.path.display().to_string();
+        let hunks = file.session.as_ref().map_or(&[][..], MergeSession::hunks);
+        let Some(complexity) = Complexity::of_file(hunks) else {
+            let _ = writeln!(
+                out,
+                "{path:<width$}  deleted on one side, changed on the other"
+            );
+            continue;
+        };
+        let stats = ConflictStats::of_file(hunks);
+        let _ = writeln!(
+            out,
+            "{path:<width$}  {:>4} hunks  {:>6} lines  {}",
+            stats.hunks,
+            stats.lines,
+            complexity.name()
+        );
+        total.hunks += stats.hunks;
+        total.lines += stats.lines;
+        for hunk in hunks {
+            counts[Complexity::of_hunk(hunk) as usize] += 1;
+        }
+    }
+    let _ = writeln!(
+        out,
+        "{} hunks ({} trivial, {} moderate, {} complex), {} conflicting lines",
+        total.hunks, counts[0], counts[1], counts[2], total.lines
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn predicted(path: &str, content: Option<&str>) -> Predicted {
+        Predicted {
+            path: PathBuf::from(path),
+            session: content.map(|content| {
+                MergeSession::from_conflicted(content, PathBuf::from(path)).unwrap()
+            }),
+        }
+    }
+
+    #[test]
+    fn report_lists_files_with_complexity() {
+        let files = [
+            predicted(
+                "src/lib.rs",
+                Some("<<<<<<< HEAD\na\n=======\nb\n>>>>>>> x\nc\n<<<<<<< HEAD\nd \n=======\nd\n>>>>>>> x\n"),
+            ),
+            predicted("old.rs", None),
+        ];
+        assert_eq!(
+            format_report("feature", "HEAD", &files),
+            "Merging feature into HEAD would conflict in 2 files:\n\
+             src/lib.rs     2 hunks       4 lines  moderate\n\
+             old.rs      deleted on one side, changed on the other\n\
+             2 hunks (1 trivial, 1 moderate, 0 complex), 4 conflicting lines\n"
+        );
+        assert_eq!(
+            format_report("feature", "main", &[]),
+            "Merging feature into main would not conflict\n"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! Resolving generated files by regenerating them.
+//!
+//! A rule with a `regenerate` command covers files that are built from other
+//! sources, such as protobuf output, snapshots, or lockfiles:
+//!
+//! ```toml
+//! [[rules]]
+//! path = "Cargo.lock"
+//! regenerate = "cargo generate-lockfile"
+//! ```
+//!
+//! Every hunk of a matching file is resolved with the rule's strategy, the
+//! result is written, and the command is run from the repository root with
+//! the file's path in `$WEAVR_FILE`. The file only counts as resolved if the
+//! command succeeds and leaves no conflict markers behind; otherwise the
+//! conflicted file is put back.
+//!
+//! The rules file's `fallback` command runs the same way on files no rule
+//! matches, but on the conflicted file as it is, to resolve it.
+//!
+//! Rules come with the repository, and a branch being merged can change
+//! them, so commands only run with `--allow-commands`: without it,
+//! interactive mode asks first and headless mode skips the file.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Instant;
+
+use weavr_core::{AcceptBothOptions, MergeSession, Resolution, ResolutionSource};
+
+use crate::discovery;
+use crate::error::CliError;
+use crate::formatter;
+use crate::history::{HistoryEntry, SessionMode};
+use crate::rules::{FileRules, Rule, RuleStrategy};
+
+/// A regeneration rule matching a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regeneration {
+    /// Root of the repository the command runs in.
+    pub root: PathBuf,
+    /// Side the file is resolved to before regenerating; without one, the
+    /// command resolves the conflicted file itself.
+    pub strategy: Option<RuleStrategy>,
+    /// Command that regenerates the file.
+    pub command: String,
+}
+
+/// A file resolved by regenerating it.
+#[derive(Debug)]
+pub struct Regenerated {
+    /// Number of hunks the file had.
+    pub hunks_resolved: usize,
+    /// History record of the session.
+    pub history: HistoryEntry,
+}
+
+/// Returns the regeneration rule in the repository's or the user's rules
+/// file that matches `path`, if the first rule matching it regenerates.
+pub fn rule_for(path: &Path) -> Result<Option<Regeneration>, CliError> {
+    let Some(file) = FileRules::load(path)? else {
+        return Ok(None);
+    };
+    Ok(file
+        .rule()
+        .and_then(|rule| Regeneration::from_rule(&file.root, rule)))
+}
+
+impl Regeneration {
+    /// Returns the regeneration `rule` asks for, if any.
+    pub fn from_rule(root: &Path, rule: &Rule) -> Option<Self> {
+        let Rule {
+            strategy,
+            regenerate,
+            ..
+        } = rule;
+        regenerate.clone().map(|command| Self {
+            root: root.to_path_buf(),
+            strategy: Some(*strategy),
+            command,
+        })
+    }
+
+    /// Describes how the file is resolved, for reports.
+    pub fn describe(&self) -> String {
+        match self.strategy {
+            Some(_) => format!("regenerating with `{}`", self.command),
+            None => format!("running fallback `{}`", self.command),
+        }
+    }
+
+    /// Returns a run of the fallback `command` on a conflicted file.
+    pub fn fallback(root: &Path, command: &str) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            strategy: None,
+            command: command.to_string(),
+        }
+    }
+}
+
+/// Asks on the terminal whether to run the command regenerating `path`,
+/// no being the default. Without a terminal it is not run.
+pub fn confirm(path: &Path, regeneration: &Regeneration) -> io::Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    ask(
+        &format!(
+            "{}: run `{}` from the rules file? [y/N] ",
+            path.display(),
+            regeneration.command
+        ),
+        &mut io::stdin().lock(),
+        &mut io::stderr(),
+    )
+}
+
+/// Asks a yes/no question, no being the default.
+fn ask<R: BufRead, W: Write>(question: &str, input: &mut R, output: &mut W) -> io::Result<bool> {
+    write!(output, "{question}")?;
+    output.flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Resolves `path` with the rule's strategy, if any, and runs its command.
+///
+/// If the command fails or leaves conflict markers, the conflicted file is
+/// restored and the failure returned.
+pub fn run(
+    path: &Path,
+    regeneration: &Regeneration,
+    mode: SessionMode,
+) -> Result<Regenerated, CliError> {
+    let started = Instant::now();
+    let content = std::fs::read_to_string(path)?;
+    let mut session = MergeSession::from_conflicted(&content, path.to_path_buf())?;
+
+    let hunks = session.hunks().to_vec();
+    if let Some(strategy) = regeneration.strategy {
+        for hunk in &hunks {
+            let mut resolution = match strategy {
+                RuleStrategy::Ours => Resolution::accept_left(hunk),
+                RuleStrategy::Theirs => Resolution::accept_right(hunk),
+                RuleStrategy::Both => Resolution::accept_both(hunk, &AcceptBothOptions::default()),
+                RuleStrategy::BothDeduped => Resolution::accept_both(
+                    hunk,
+                    &AcceptBothOptions {
+                        deduplicate: true,
+                        ..AcceptBothOptions::default()
+                    },
+                ),
+            };
+            resolution.metadata.source = ResolutionSource::Rule;
+            session.set_resolution(hunk.id, resolution)?;
+        }
+    }
+    let history = HistoryEntry::from_session(&session, mode, started.elapsed());
+    if regeneration.strategy.is_some() {
+        session.apply()?;
+        session.validate()?;
+        std::fs::write(path, session.complete()?.content)?;
+    }
+
+    let failure = match execute(path, regeneration) {
+        Ok(()) => match discovery::has_conflict_markers(path) {
+            Ok(false) => None,
+            Ok(true) => Some("the regenerated file still has conflict markers".to_string()),
+            Err(e) => Some(e.to_string()),
+        },
+        Err(e) => Some(e),
+    };
+    if let Some(reason) = failure {
+        std::fs::write(path, &content)?;
+        return Err(CliError::Regenerate(format!(
+            "{}: `{}` failed: {reason}",
+            path.display(),
+            regeneration.command
+        )));
+    }
+    Ok(Regenerated {
+        hunks_resolved: hunks.len(),
+        history,
+    })
+}
+
+/// Runs the regeneration command, describing a failure by the last line it
+/// wrote to stderr.
+fn execute(path: &Path, regeneration: &Regeneration) -> Result<(), String> {
+    // The command runs from the root, so a relative path would not resolve
+    let path = std::fs::canonicalize(path).map_err(|e| e.to_string())?;
+    let output = formatter::shell(&regeneration.command)
+        .current_dir(&regeneration.root)
+        .env("WEAVR_FILE", path)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(stderr
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map_or_else(|| output.status.to_string(), str::to_string))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const CONFLICT: &str = "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> b\n";
+
+    fn regeneration(dir: &Path, command: &str) -> Regeneration {
+        Regeneration {
+            root: dir.to_path_buf(),
+            strategy: Some(RuleStrategy::Theirs),
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn command_runs_on_the_resolved_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("out.txt");
+        std::fs::write(&path, CONFLICT).unwrap();
+
+        let result = run(
+            &path,
+            &regeneration(
+                dir.path(),
+                "sed 's/theirs/regenerated/' \"$WEAVR_FILE\" > tmp && mv tmp out.txt",
+            ),
+            SessionMode::Headless,
+        )
+        .unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(result.hunks_resolved, 1);
+        assert_eq!(written, "regenerated\n");
+    }
+
+    #[test]
+    fn fallback_command_runs_on_the_conflicted_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("out.txt");
+        std::fs::write(&path, CONFLICT).unwrap();
+
+        let result = run(
+            &path,
+            &Regeneration::fallback(
+                dir.path(),
+                "sed -n 2p \"$WEAVR_FILE\" > tmp && mv tmp out.txt",
+            ),
+            SessionMode::Headless,
+        )
+        .unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(result.hunks_resolved, 1);
+        assert_eq!(written, "ours\n");
+    }
+
+    #[test]
+    fn failing_command_restores_the_conflict() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("out.txt");
+        std::fs::write(&path, CONFLICT).unwrap();
+
+        let failed = run(
+            &path,
+            &regeneration(dir.path(), "echo 'protoc: not found' >&2; exit 127"),
+            SessionMode::Headless,
+        );
+        let kept = run(
+            &path,
+            &regeneration(
+                dir.path(),
+                "printf '<<<<<<< a\\nx\\n=======\\ny\\n>>>>>>> b\\n' > out.txt",
+            ),
+            SessionMode::Headless,
+        );
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(failed
+            .unwrap_err()
+            .to_string()
+            .contains("protoc: not found"));
+        assert!(kept.unwrap_err().to_string().contains("conflict markers"));
+        assert_eq!(written, CONFLICT);
+    }
+
+    #[test]
+    fn commands_only_run_when_the_answer_is_yes() {
+        let answer = |input: &str| ask("run? ", &mut input.as_bytes(), &mut Vec::new()).unwrap();
+        assert!(answer("y\n"));
+        assert!(answer("Yes\n"));
+        assert!(!answer("\n"));
+        assert!(!answer("n\n"));
+        assert!(!answer(""));
+    }
+}
@@ -0,0 +1,224 @@
+//! Re-running the merge with different strategy options.
+//!
+//! Whitespace changes or a missed rename can turn a trivial merge into a pile
+//! of conflicts. Replaying the operation with options such as
+//! `-X ignore-space-change` often makes them disappear, and is worth trying
+//! before resolving anything by hand.
+//!
+//! Files are only replaced while they are as the operation left them, so
+//! hunks resolved or text edited by hand are never thrown away; `--force`
+//! replaces them anyway.
+
+use std::path::{Path, PathBuf};
+
+use weavr_core::MergeSession;
+use weavr_git::GitRepo;
+
+use crate::discovery;
+use crate::error::CliError;
+
+/// Re-merges `files` (or every conflicted file) and replaces their content
+/// with the new result.
+pub fn run(
+    files: &[PathBuf],
+    options: &[String],
+    dry_run: bool,
+    force: bool,
+) -> Result<(), CliError> {
+    let repo = GitRepo::discover()?;
+    let files = discovery::resolve_files(files.to_vec())?;
+    let changed = remerge(&repo, &files, options, dry_run, force)?;
+
+    if dry_run {
+        println!("{changed} of {} files would change", files.len());
+    } else {
+        println!("{changed} of {} files changed", files.len());
+    }
+    Ok(())
+}
+
+/// Re-merges `files` in `repo`, returning how many changed. Files edited
+/// since the operation are left alone unless `force` is set.
+fn remerge(
+    repo: &GitRepo,
+    files: &[PathBuf],
+    options: &[String],
+    dry_run: bool,
+    force: bool,
+) -> Result<usize, CliError> {
+    let relative: Vec<PathBuf> = files
+        .iter()
+        .map(|path| relative_to(repo.root(), path))
+        .collect::<Result<_, _>>()?;
+
+    let options: Vec<&str> = options.iter().map(String::as_str).collect();
+    let remerged = repo.remerge(&relative, &options)?;
+    // What the operation itself left in each file, to tell hand edits apart
+    let original = if force {
+        Vec::new()
+    } else {
+        repo.remerge(&relative, &[])?
+    };
+
+    let mut changed = 0;
+    for (index, (path, (_, content))) in files.iter().zip(remerged).enumerate() {
+        let current = std::fs::read_to_string(path)?;
+        let Some(content) = content else {
+            println!("{}: removed by the merge, left as is", path.display());
+            continue;
+        };
+        if without_labels(&content) == without_labels(&current) {
+            println!("{}: unchanged", path.display());
+            continue;
+        }
+        let original = original
+            .get(index)
+            .and_then(|(_, original)| original.as_deref());
+        if let Some(reason) = original.and_then(|original| edited(&current, original, path)) {
+            println!(
+                "{}: {reason}, left as is (--force replaces it)",
+                path.display()
+            );
+            continue;
+        }
+
+        changed += 1;
+        println!(
+            "{}: {}",
+            path.display(),
+            describe(hunk_count(&current, path), hunk_count(&content, path))
+        );
+        if !dry_run {
+            std::fs::write(path, content)?;
+        }
+    }
+    Ok(changed)
+}
+
+/// Tells why `current` is no longer the `original` content the operation
+/// left, if it isn't.
+fn edited(current: &str, original: &str, path: &Path) -> Option<&'static str> {
+    if hunk_count(current, path) == 0 {
+        Some("no conflicts left")
+    } else if without_labels(current) != without_labels(original) {
+        Some("edited since the merge")
+    } else {
+        None
+    }
+}
+
+/// Describes how the number of hunks in a file changed.
+fn describe(before: usize, after: usize) -> String {
+    if after == 0 {
+        format!("{before} hunks -> merged cleanly (stage it with git add)")
+    } else {
+        format!("{before} hunks -> {after} hunks")
+    }
+}
+
+/// Strips the labels from conflict markers, which name the same commits
+/// differently when the merge is replayed.
+fn without_labels(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| {
+            ["<<<<<<<", "|||||||", ">>>>>>>"]
+                .into_iter()
+                .find(|marker| line.starts_with(marker))
+                .unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Counts the conflict hunks in `content`; unparseable content counts as none.
+fn hunk_count(content: &str, path: &Path) -> usize {
+    MergeSession::from_conflicted(content, path.to_path_buf()).map_or(0, |s| s.hunks().len())
+}
+
+/// Returns `path` relative to the repository root.
+fn relative_to(root: &Path, path: &Path) -> Result<PathBuf, CliError> {
+    let absolute = std::fs::canonicalize(path)?;
+    let root = std::fs::canonicalize(root)?;
+    absolute
+        .strip_prefix(&root)
+        .map(Path::to_path_buf)
+        .map_err(|_| CliError::FileNotFound(path.to_path_buf()))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::playground;
+
+    #[test]
+    fn describes_hunk_changes() {
+        assert_eq!(describe(3, 1), "3 hunks -> 1 hunks");
+        assert_eq!(
+            describe(2, 0),
+            "2 hunks -> merged cleanly (stage it with git add)"
+        );
+    }
+
+    #[test]
+    fn marker_labels_are_ignored() {
+        assert_eq!(
+            without_labels("<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> feature\n"),
+            without_labels("<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> 1a2b3c4\n")
+        );
+        assert_ne!(
+            without_labels("<<<<<<< a\nx\n"),
+            without_labels("<<<<<<< a\ny\n")
+        );
+    }
+
+    #[test]
+    fn counts_hunks_in_content() {
+        let path = Path::new("f.txt");
+        assert_eq!(hunk_count("<<<<<<< a\nx\n=======\ny\n>>>>>>> b\n", path), 1);
+        assert_eq!(hunk_count("plain\n", path), 0);
+    }
+
+    #[test]
+    fn hand_edited_files_are_kept_unless_forced() {
+        let dir = TempDir::new().unwrap();
+        let root = playground::create(Some(dir.path().to_path_buf())).unwrap();
+        let repo = GitRepo::discover_from(&root).unwrap();
+        let (edited, resolved) = (root.join("src/lib.rs"), root.join("Cargo.toml"));
+
+        // One file with text edited next to its conflicts, one resolved
+        let conflicted = std::fs::read_to_string(&edited).unwrap();
+        let hand_edited = format!("// reviewed\n{conflicted}");
+        std::fs::write(&edited, &hand_edited).unwrap();
+        let by_hand = "[package]\nname = \"playground\"\n";
+        std::fs::write(&resolved, by_hand).unwrap();
+
+        let files = [edited.clone(), resolved.clone()];
+        assert_eq!(remerge(&repo, &files, &[], false, false).unwrap(), 0);
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), hand_edited);
+        assert_eq!(std::fs::read_to_string(&resolved).unwrap(), by_hand);
+
+        assert_eq!(remerge(&repo, &files, &[], false, true).unwrap(), 2);
+        assert_eq!(
+            without_labels(&std::fs::read_to_string(&edited).unwrap()),
+            without_labels(&conflicted)
+        );
+    }
+
+    #[test]
+    fn conflicts_left_alone_are_not_edits() {
+        let original = "a\n<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> feature\n";
+        let path = Path::new("f.txt");
+        assert_eq!(
+            edited(&original.replace("feature", "1a2b3c4"), original, path),
+            None
+        );
+        assert_eq!(
+            edited(&original.replace('a', "b"), original, path),
+            Some("edited since the merge")
+        );
+        assert_eq!(edited("a\nx\n", original, path), Some("no conflicts left"));
+    }
+}
@@ -0,0 +1,138 @@
+//! Replaying previously recorded resolutions onto a new session.
+
+use std::path::Path;
+
+use weavr_core::{
+    MergeSession, RecordedResolution, ReplayReport, ResolutionPatchFile, ResolutionSource,
+};
+
+use crate::error::CliError;
+
+/// First line written by `weavr_core::format_resolution_patch`.
+const PATCH_HEADER: &str = "# weavr resolution patch";
+
+/// Resolutions loaded from a `--replay` file.
+pub enum ReplaySource {
+    /// An annotated patch exported with `--export-patch`.
+    Patch(Vec<ResolutionPatchFile>),
+    /// A resolved copy of the conflicted file.
+    Resolved(String),
+}
+
+impl ReplaySource {
+    /// Loads a replay file, detecting whether it is a resolution patch or a resolved copy.
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let text = std::fs::read_to_string(path)?;
+        if text.starts_with(PATCH_HEADER) {
+            Ok(Self::Patch(weavr_core::parse_resolution_patch(&text)?))
+        } else {
+            Ok(Self::Resolved(text))
+        }
+    }
+
+    /// Returns the recorded resolutions that apply to a session's file.
+    ///
+    /// Patch sections are matched by path; a patch for a single file applies
+    /// to any file, so it can be replayed after a rename.
+    pub fn recordings_for(&self, session: &MergeSession) -> Vec<RecordedResolution> {
+        match self {
+            Self::Patch(files) => {
+                let path = &session.input().left.path;
+                files
+                    .iter()
+                    .find(|f| path.ends_with(&f.path) || f.path.ends_with(path))
+                    .or(if files.len() == 1 {
+                        files.first()
+                    } else {
+                        None
+                    })
+                    .map(|f| f.resolutions.clone())
+                    .unwrap_or_default()
+            }
+            Self::Resolved(text) => session.extract_resolutions(text),
+        }
+    }
+
+    /// Replays matching resolutions onto the session, marking them as reused.
+    pub fn replay(&self, session: &mut MergeSession) -> Result<ReplayReport, CliError> {
+        let mut recorded = self.recordings_for(session);
+        for entry in &mut recorded {
+            entry.resolution.metadata.source = ResolutionSource::Rerere;
+        }
+        Ok(session.replay(&recorded)?)
+    }
+}
+
+/// Formats a one-line summary of a replay, listing hunks that did not apply.
+pub fn summarize(report: &ReplayReport) -> String {
+    let total = report.applied.len() + report.unmatched.len();
+    let summary = format!("replayed {}/{total} hunks", report.applied.len());
+    if report.unmatched.is_empty() {
+        return summary;
+    }
+
+    let unmatched: Vec<String> = report
+        .unmatched
+        .iter()
+        .map(|id| (id.0 + 1).to_string())
+        .collect();
+    format!("{summary} (no match for hunk {})", unmatched.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use weavr_core::HunkId;
+
+    use super::*;
+
+    const CONFLICT: &str = "a\n<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> f\nz";
+
+    fn session(path: &str) -> MergeSession {
+        MergeSession::from_conflicted(CONFLICT, PathBuf::from(path)).unwrap()
+    }
+
+    #[test]
+    fn patch_sections_match_by_path() {
+        let mut resolved = session("src/lib.rs");
+        let hunk = resolved.hunks()[0].clone();
+        resolved
+            .set_resolution(hunk.id, weavr_core::Resolution::accept_right(&hunk))
+            .unwrap();
+        let mut files =
+            weavr_core::parse_resolution_patch(&weavr_core::format_resolution_patch(&resolved))
+                .unwrap();
+        files.push(ResolutionPatchFile {
+            path: PathBuf::from("other.rs"),
+            resolutions: Vec::new(),
+        });
+        let source = ReplaySource::Patch(files);
+
+        assert_eq!(source.recordings_for(&session("src/lib.rs")).len(), 1);
+        assert!(source.recordings_for(&session("other.rs")).is_empty());
+        assert!(source.recordings_for(&session("missing.rs")).is_empty());
+    }
+
+    #[test]
+    fn resolved_copy_replays() {
+        let source = ReplaySource::Resolved("a\nr\nz\n".to_string());
+        let mut target = session("f.rs");
+
+        let report = source.replay(&mut target).unwrap();
+        assert_eq!(report.applied, vec![HunkId(0)]);
+        assert!(target.is_fully_resolved());
+    }
+
+    #[test]
+    fn summarize_lists_unmatched_hunks() {
+        let report = ReplayReport {
+            applied: vec![HunkId(0)],
+            unmatched: vec![HunkId(1), HunkId(3)],
+        };
+        assert_eq!(
+            summarize(&report),
+            "replayed 1/3 hunks (no match for hunk 2, 4)"
+        );
+    }
+}
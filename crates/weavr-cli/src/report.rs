@@ -0,0 +1,488 @@
+//! Conflict reports for pipelines, shared by `weavr check` and headless
+//! runs with `--report`.
+//!
+//! `weavr check` reports the conflicts in the tree without resolving
+//! anything; a headless run with `--report` also tells which strategy
+//! resolved each hunk. Both sum up the outcome in the same exit codes:
+//! [`exit_codes::SUCCESS`] when nothing conflicts,
+//! [`exit_codes::CONFLICTS`] when conflicts are left in the tree, and
+//! [`exit_codes::AUTO_RESOLVED`] when a run resolved every conflict
+//! without anyone's help and wrote the files. A `--dry-run` writes
+//! nothing, so it exits as `weavr check` would on the unchanged tree; its
+//! outcome tells whether every conflict could have been resolved. Both
+//! print the same JSON object:
+//!
+//! ```json
+//! {
+//!   "ok": true,
+//!   "outcome": "all-auto-resolved",
+//!   "files": [{
+//!     "path": "src/lib.rs",
+//!     "kind": "both-modified",
+//!     "hunks": 1,
+//!     "lines": 6,
+//!     "complexity": "moderate",
+//!     "rule": "src/*.rs",
+//!     "conflicts": [{
+//!       "lines": [12, 20], "ours": 2, "theirs": 3, "base": 1, "overlap": 1,
+//!       "complexity": "moderate", "resolved_by": "right"
+//!     }]
+//!   }],
+//!   "merges": []
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use weavr_core::{Complexity, ConflictHunk, HunkId, MergeSession};
+use weavr_git::{ConflictType, GitRepo};
+
+use crate::cli::ReportFormat;
+use crate::error::{exit_codes, CliError};
+use crate::why;
+
+/// What was conflicted, and how it was resolved.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Whether no conflicts are left in the files and no merge checked
+    /// would conflict.
+    pub ok: bool,
+    /// The outcome the exit code reflects.
+    pub outcome: Outcome,
+    /// Each conflicted file.
+    pub files: Vec<FileEntry>,
+    /// Predicted outcome of each merge checked by `weavr check --against`.
+    pub merges: Vec<MergeReport>,
+}
+
+/// How a run ended, as far as pipelines are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    /// Nothing was conflicted.
+    NoConflicts,
+    /// Conflicts are left in the tree, or a merge checked would conflict.
+    Conflicts,
+    /// Every conflict was resolved automatically and the files written.
+    AllAutoResolved,
+    /// Every conflict could be resolved automatically, but a dry run left
+    /// them in the tree.
+    AllAutoResolvable,
+}
+
+impl Outcome {
+    /// Returns the exit code for the outcome.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::NoConflicts => exit_codes::SUCCESS,
+            Outcome::Conflicts | Outcome::AllAutoResolvable => exit_codes::CONFLICTS,
+            Outcome::AllAutoResolved => exit_codes::AUTO_RESOLVED,
+        }
+    }
+
+    /// Returns true if the outcome leaves no conflicts in the tree.
+    pub fn is_ok(self) -> bool {
+        matches!(self, Outcome::NoConflicts | Outcome::AllAutoResolved)
+    }
+
+    /// Describes the outcome in a few words.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Outcome::NoConflicts => "no conflicts",
+            Outcome::Conflicts => "conflicts remain",
+            Outcome::AllAutoResolved => "all conflicts auto-resolved",
+            Outcome::AllAutoResolvable => "all conflicts can be auto-resolved; nothing written",
+        }
+    }
+}
+
+/// A conflicted file.
+#[derive(Debug, Serialize)]
+pub struct FileEntry {
+    /// Path of the file, as the rest of the run prints it.
+    pub path: PathBuf,
+    /// How git sees the conflict, such as `both-modified`; `markers` for
+    /// files git does not list as conflicted, and `predicted` for conflicts
+    /// of a merge not made yet.
+    pub kind: &'static str,
+    /// Number of conflict hunks.
+    pub hunks: usize,
+    /// Number of conflicting lines across all sides.
+    pub lines: usize,
+    /// Estimated effort, or `None` if the file has no hunks (malformed
+    /// markers, or a modify/delete conflict).
+    pub complexity: Option<Complexity>,
+    /// Pattern of the rule that chose how to resolve the file, if one did.
+    pub rule: Option<String>,
+    /// Each hunk, in file order.
+    pub conflicts: Vec<HunkEntry>,
+}
+
+impl FileEntry {
+    /// Describes the file at `path` with the given hunks.
+    pub fn new(path: PathBuf, kind: &'static str, conflicts: Vec<HunkEntry>) -> Self {
+        Self {
+            path,
+            kind,
+            hunks: conflicts.len(),
+            lines: conflicts
+                .iter()
+                .map(|hunk| hunk.ours + hunk.theirs + hunk.base.unwrap_or(0))
+                .sum(),
+            complexity: conflicts.iter().map(|hunk| hunk.complexity).max(),
+            rule: None,
+            conflicts,
+        }
+    }
+
+    /// Marks every hunk as resolved by `strategy`.
+    pub fn resolved_by(mut self, strategy: &str) -> Self {
+        for hunk in &mut self.conflicts {
+            hunk.resolved_by = Some(strategy.to_string());
+        }
+        self
+    }
+}
+
+/// Conflicts a merge would produce.
+#[derive(Debug, Serialize)]
+pub struct MergeReport {
+    /// Branch or commit being merged.
+    pub branch: String,
+    /// Branch or commit merged into.
+    pub onto: String,
+    /// Files that would conflict.
+    pub conflicts: Vec<FileEntry>,
+}
+
+/// A conflict hunk.
+#[derive(Debug, Serialize)]
+pub struct HunkEntry {
+    /// 1-based lines of the hunk's start and end markers.
+    pub lines: (usize, usize),
+    /// Lines on our side.
+    pub ours: usize,
+    /// Lines on their side.
+    pub theirs: usize,
+    /// Lines of the base, when the markers carry it.
+    pub base: Option<usize>,
+    /// Lines both sides changed: the base's, or the shorter side's when the
+    /// markers carry no base.
+    pub overlap: usize,
+    /// Estimated effort.
+    pub complexity: Complexity,
+    /// Strategy that resolved the hunk, such as `right`, `rerere` or
+    /// `command`, or `None` if it is left unresolved.
+    pub resolved_by: Option<String>,
+}
+
+impl HunkEntry {
+    /// Describes `hunk`, resolved by `resolved_by`.
+    pub fn of(hunk: &ConflictHunk, resolved_by: Option<String>) -> Self {
+        let ours = hunk.left.text.lines().count();
+        let theirs = hunk.right.text.lines().count();
+        let base = hunk.base.as_ref().map(|base| base.text.lines().count());
+        Self {
+            lines: why::span(hunk),
+            ours,
+            theirs,
+            base,
+            overlap: base.unwrap_or_else(|| ours.min(theirs)),
+            complexity: Complexity::of_hunk(hunk),
+            resolved_by,
+        }
+    }
+}
+
+/// Describes the hunks of a session, with the strategy that resolved each
+/// one.
+pub fn hunk_entries(
+    hunks: &[ConflictHunk],
+    resolved_by: &HashMap<HunkId, String>,
+) -> Vec<HunkEntry> {
+    hunks
+        .iter()
+        .map(|hunk| HunkEntry::of(hunk, resolved_by.get(&hunk.id).cloned()))
+        .collect()
+}
+
+impl Report {
+    /// Sums up a check of the files in the tree and of the merges into it.
+    pub fn check(files: Vec<FileEntry>, merges: Vec<MergeReport>) -> Self {
+        let outcome = if files.is_empty() && merges.iter().all(|merge| merge.conflicts.is_empty()) {
+            Outcome::NoConflicts
+        } else {
+            Outcome::Conflicts
+        };
+        Self {
+            ok: outcome.is_ok(),
+            outcome,
+            files,
+            merges,
+        }
+    }
+
+    /// Sums up the files of a headless run, which wrote the resolved files
+    /// unless `written` is false (a dry run).
+    pub fn resolved(files: Vec<FileEntry>, written: bool) -> Self {
+        let outcome = if files.is_empty() {
+            Outcome::NoConflicts
+        } else if files.iter().all(|file| {
+            file.hunks > 0 && file.conflicts.iter().all(|hunk| hunk.resolved_by.is_some())
+        }) {
+            if written {
+                Outcome::AllAutoResolved
+            } else {
+                Outcome::AllAutoResolvable
+            }
+        } else {
+            Outcome::Conflicts
+        };
+        Self {
+            ok: outcome.is_ok(),
+            outcome,
+            files,
+            merges: Vec::new(),
+        }
+    }
+
+    /// Prints the report in `format`, returning the exit code.
+    pub fn print(&self, format: ReportFormat) -> Result<i32, CliError> {
+        match format {
+            ReportFormat::Text => print!("{}", format_text(self)),
+            ReportFormat::Json => print_json(self)?,
+        }
+        Ok(self.outcome.exit_code())
+    }
+}
+
+/// Prints the report as a single JSON object.
+pub fn print_json(report: &Report) -> Result<(), CliError> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(report).map_err(std::io::Error::other)?
+    );
+    Ok(())
+}
+
+/// How git sees each conflicted file of the repositories a run spans.
+#[derive(Debug, Default)]
+pub struct ConflictKinds {
+    /// Each conflicted file: its path as printed, its canonical path, and
+    /// its kind.
+    entries: Vec<(PathBuf, PathBuf, ConflictType)>,
+}
+
+impl ConflictKinds {
+    /// Asks git about the repository at each root, `None` being the current
+    /// one. Files outside any repository are simply not listed.
+    pub fn load<'a>(roots: impl IntoIterator<Item = Option<&'a Path>>) -> Result<Self, CliError> {
+        let mut entries = Vec::new();
+        for root in roots {
+            let repo = match root {
+                Some(root) => GitRepo::discover_from(root)?,
+                None => match GitRepo::discover() {
+                    Ok(repo) => repo,
+                    Err(_) => continue,
+                },
+            };
+            for entry in repo.conflicted_entries()? {
+                let absolute = repo.root().join(&entry.path);
+                let shown = if root.is_some() {
+                    absolute.clone()
+                } else {
+                    entry.path
+                };
+                entries.push((shown, canonical(&absolute), entry.conflict_type));
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    /// Returns how git sees the conflict in `path`.
+    pub fn kind(&self, path: &Path) -> &'static str {
+        let path = canonical(path);
+        self.entries
+            .iter()
+            .find(|(_, canonical, _)| *canonical == path)
+            .map_or("markers", |(_, _, kind)| kind_name(*kind))
+    }
+
+    /// Describes `path`, resolved by nothing yet.
+    pub fn scan(&self, path: &Path) -> Result<FileEntry, CliError> {
+        let hunks = match std::fs::read_to_string(path) {
+            Ok(content) => MergeSession::from_conflicted(content, path.to_path_buf())
+                .map(|session| session.hunks().to_vec())
+                .unwrap_or_default(),
+            // Deleted and binary files have no hunks
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::InvalidData
+                ) =>
+            {
+                Vec::new()
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(FileEntry::new(
+            path.to_path_buf(),
+            self.kind(path),
+            hunk_entries(&hunks, &HashMap::new()),
+        ))
+    }
+
+    /// Describes the conflicted files git lists that are not `scanned`,
+    /// such as modify/delete conflicts without markers.
+    pub fn unmarked<'a>(&self, scanned: impl IntoIterator<Item = &'a Path>) -> Vec<FileEntry> {
+        let scanned: Vec<PathBuf> = scanned.into_iter().map(canonical).collect();
+        self.entries
+            .iter()
+            .filter(|(_, canonical, _)| !scanned.contains(canonical))
+            .map(|(shown, _, kind)| FileEntry::new(shown.clone(), kind_name(*kind), Vec::new()))
+            .collect()
+    }
+}
+
+/// Returns the name of a kind of conflict.
+fn kind_name(kind: ConflictType) -> &'static str {
+    match kind {
+        ConflictType::BothModified => "both-modified",
+        ConflictType::BothAdded => "both-added",
+        ConflictType::BothDeleted => "both-deleted",
+        ConflictType::AddedByUsDeletedByThem => "deleted-by-them",
+        ConflictType::AddedByThemDeletedByUs => "deleted-by-us",
+    }
+}
+
+/// Returns the canonical form of `path`, or `path` itself if it does not
+/// exist.
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Formats the report for people: each file and its hunks, then the outcome.
+fn format_text(report: &Report) -> String {
+    let mut out = String::new();
+    for file in &report.files {
+        let rule = match &file.rule {
+            Some(pattern) => format!(" by rule `{pattern}`"),
+            None => String::new(),
+        };
+        let _ = writeln!(
+            out,
+            "{}: {}, {} hunks{rule}",
+            file.path.display(),
+            file.kind,
+            file.hunks
+        );
+        for hunk in &file.conflicts {
+            let base = hunk
+                .base
+                .map_or(String::new(), |base| format!(", {base} base"));
+            let resolved = match &hunk.resolved_by {
+                Some(strategy) => format!("resolved by {strategy}"),
+                None => "unresolved".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "  lines {}-{}: {} ours, {} theirs{base}, {} overlapping; {resolved}",
+                hunk.lines.0, hunk.lines.1, hunk.ours, hunk.theirs, hunk.overlap
+            );
+        }
+    }
+    let _ = writeln!(out, "{}", report.outcome.describe());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunks(content: &str) -> Vec<ConflictHunk> {
+        MergeSession::from_conflicted(content, PathBuf::from("a.rs"))
+            .unwrap()
+            .hunks()
+            .to_vec()
+    }
+
+    fn file(conflicts: Vec<HunkEntry>) -> FileEntry {
+        FileEntry::new(PathBuf::from("a.rs"), "both-modified", conflicts)
+    }
+
+    #[test]
+    fn hunk_entry_measures_the_sides() {
+        let three_way =
+            hunks("x\n<<<<<<< HEAD\na\nb\n||||||| base\no\n=======\nc\n>>>>>>> topic\ny\n");
+        let entry = HunkEntry::of(&three_way[0], Some("right".to_string()));
+        assert_eq!(entry.lines, (2, 9));
+        assert_eq!((entry.ours, entry.theirs, entry.base), (2, 1, Some(1)));
+        assert_eq!(entry.overlap, 1);
+
+        let two_way = hunks("<<<<<<< HEAD\na\nb\n=======\nc\n>>>>>>> topic\n");
+        let entry = HunkEntry::of(&two_way[0], None);
+        assert_eq!(entry.lines, (1, 6));
+        assert_eq!((entry.base, entry.overlap), (None, 1));
+    }
+
+    #[test]
+    fn outcome_tells_remaining_from_auto_resolved_conflicts() {
+        assert_eq!(
+            Report::resolved(Vec::new(), true).outcome,
+            Outcome::NoConflicts
+        );
+
+        let two = hunks(
+            "<<<<<<< HEAD\na\n=======\nb\n>>>>>>> x\nm\n<<<<<<< HEAD\nc\n=======\nd\n>>>>>>> x\n",
+        );
+        let resolved: HashMap<HunkId, String> = [(two[0].id, "left".to_string())].into();
+        let report = Report::resolved(vec![file(hunk_entries(&two, &resolved))], true);
+        assert_eq!(report.outcome, Outcome::Conflicts);
+        assert_eq!(report.outcome.exit_code(), exit_codes::CONFLICTS);
+        assert!(!report.ok);
+
+        let resolved: HashMap<HunkId, String> = two
+            .iter()
+            .map(|hunk| (hunk.id, "rerere".to_string()))
+            .collect();
+        let report = Report::resolved(vec![file(hunk_entries(&two, &resolved))], true);
+        assert_eq!(report.outcome, Outcome::AllAutoResolved);
+        assert_eq!(report.outcome.exit_code(), exit_codes::AUTO_RESOLVED);
+        assert!(report.ok);
+
+        // A dry run leaves the conflicts in the tree
+        let report = Report::resolved(vec![file(hunk_entries(&two, &resolved))], false);
+        assert_eq!(report.outcome, Outcome::AllAutoResolvable);
+        assert_eq!(report.outcome.exit_code(), exit_codes::CONFLICTS);
+        assert!(!report.ok);
+
+        // A modify/delete conflict has no hunks to resolve automatically
+        let report = Report::resolved(vec![file(Vec::new())], true);
+        assert_eq!(report.outcome, Outcome::Conflicts);
+    }
+
+    #[test]
+    fn report_formats_files_and_hunks() {
+        let one = hunks("<<<<<<< HEAD\na\n=======\nb\nc\n>>>>>>> x\n");
+        let mut entry = file(hunk_entries(&one, &HashMap::new()));
+        entry.rule = Some("*.rs".to_string());
+        let report = Report::resolved(vec![entry], true);
+        assert_eq!(
+            format_text(&report),
+            "a.rs: both-modified, 1 hunks by rule `*.rs`\n  \
+             lines 1-6: 1 ours, 2 theirs, 1 overlapping; unresolved\nconflicts remain\n"
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["ok"], false);
+        assert_eq!(json["outcome"], "conflicts");
+        assert_eq!(json["files"][0]["kind"], "both-modified");
+        assert_eq!(json["files"][0]["lines"], 3);
+        assert_eq!(json["files"][0]["complexity"], "moderate");
+        assert_eq!(json["files"][0]["conflicts"][0]["lines"][1], 6);
+        assert!(json["files"][0]["conflicts"][0]["resolved_by"].is_null());
+    }
+}
@@ -0,0 +1,243 @@
+//! Resolution memory for recurring conflicts.
+//!
+//! Resolutions chosen in the TUI are remembered under the conflict's
+//! [`rerere_key`](weavr_core::rerere_key), one JSON file per key, in
+//! `.git/weavr/rerere/` of the file's repository (or
+//! `$XDG_DATA_HOME/weavr/rerere/` outside one). Later sessions, interactive
+//! or headless, resolve matching hunks with them before anything else, and
+//! mark them as recalled so they can be reviewed. Each entry keeps the
+//! conflict it resolved, and is only recalled for that conflict. `--no-rerere` turns this
+//! off, and `weavr rerere clear` forgets.
+
+use std::path::{Path, PathBuf};
+
+use weavr_core::{HunkId, MergeSession, RememberedResolution};
+use weavr_git::GitRepo;
+
+use crate::error::CliError;
+use crate::{history, tui};
+
+/// A directory of remembered resolutions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolutionMemory {
+    dir: PathBuf,
+}
+
+impl ResolutionMemory {
+    /// Opens the memory kept in `dir`, created on first write.
+    pub fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Opens the memory for `path`: its repository's, or the user's outside
+    /// a repository.
+    pub fn for_file(path: &Path) -> Option<Self> {
+        match tui::locate(path) {
+            Some((repo, _)) => Self::for_repo(&repo),
+            None => Self::user(),
+        }
+    }
+
+    /// Opens the memory kept in a repository's git directory.
+    fn for_repo(repo: &GitRepo) -> Option<Self> {
+        repo.git_path("weavr/rerere").ok().map(Self::at)
+    }
+
+    /// Opens the memory kept in the user's data directory.
+    fn user() -> Option<Self> {
+        history::data_dir().map(|dir| Self::at(dir.join("rerere")))
+    }
+
+    /// Returns the resolution remembered under `key`, if any. Entries that
+    /// cannot be read are treated as missing.
+    pub fn lookup(&self, key: &str) -> Option<RememberedResolution> {
+        let text = std::fs::read_to_string(self.entry(key)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Resolves the session's unresolved hunks that have a remembered
+    /// resolution, returning the hunks resolved.
+    pub fn recall(&self, session: &mut MergeSession) -> Result<Vec<HunkId>, CliError> {
+        Ok(session.recall(|hunk| self.lookup(&hunk.rerere_key())?.resolution_for(hunk))?)
+    }
+
+    /// Remembers the session's resolutions, returning how many were new or
+    /// changed.
+    pub fn remember(&self, session: &MergeSession) -> Result<usize, CliError> {
+        let mut changed = 0;
+        for (key, remembered) in session.remembered() {
+            if self.lookup(&key).as_ref() == Some(&remembered) {
+                continue;
+            }
+            std::fs::create_dir_all(&self.dir)?;
+            let text = serde_json::to_string_pretty(&remembered)
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            std::fs::write(self.entry(&key), text)?;
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// Forgets the resolutions remembered for the session's conflicts,
+    /// returning how many were removed.
+    pub fn forget(&self, session: &MergeSession) -> Result<usize, CliError> {
+        let mut removed = 0;
+        for hunk in session.hunks() {
+            match std::fs::remove_file(self.entry(&hunk.rerere_key())) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Forgets every remembered resolution, returning how many there were.
+    pub fn clear(&self) -> Result<usize, CliError> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                std::fs::remove_file(path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Returns the file a key's resolution is kept in.
+    fn entry(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+/// Runs `weavr rerere clear`: forgets the resolutions remembered for the
+/// conflicts in `files`, or everything remembered in the current repository
+/// if no files are given.
+pub fn clear(files: &[PathBuf]) -> Result<(), CliError> {
+    if files.is_empty() {
+        let memory = match GitRepo::discover() {
+            Ok(repo) => ResolutionMemory::for_repo(&repo),
+            Err(_) => ResolutionMemory::user(),
+        };
+        let memory =
+            memory.ok_or_else(|| CliError::Config("no place to keep resolutions".to_string()))?;
+        let removed = memory.clear()?;
+        println!("Forgot {removed} remembered resolutions");
+        return Ok(());
+    }
+    for path in files {
+        let content = std::fs::read_to_string(path)?;
+        let session = MergeSession::from_conflicted(content, path.clone())?;
+        let removed = match ResolutionMemory::for_file(path) {
+            Some(memory) => memory.forget(&session)?,
+            None => 0,
+        };
+        println!(
+            "{}: forgot {removed} remembered resolutions",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use weavr_core::{HunkState, Resolution, ResolutionSource, ResolutionStrategyKind};
+
+    use super::*;
+
+    const CONFLICT: &str = "a\n<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> f\nz\n";
+
+    fn memory() -> (TempDir, ResolutionMemory) {
+        let dir = TempDir::new().unwrap();
+        let memory = ResolutionMemory::at(dir.path().join("rerere"));
+        (dir, memory)
+    }
+
+    fn session(content: &str) -> MergeSession {
+        MergeSession::from_conflicted(content, PathBuf::from("src/lib.rs")).unwrap()
+    }
+
+    #[test]
+    fn remembered_resolutions_resolve_the_same_conflict_later() {
+        let (_dir, memory) = memory();
+        let mut resolved = session(CONFLICT);
+        resolved
+            .set_resolution(HunkId(0), Resolution::manual("l\nr".to_string()))
+            .unwrap();
+        assert_eq!(memory.remember(&resolved).unwrap(), 1);
+        assert_eq!(memory.remember(&resolved).unwrap(), 0);
+
+        // The same conflict from the other direction, as in a rebase
+        let mut fresh = session("a\n<<<<<<< HEAD\nr\n=======\nl\n>>>>>>> f\nz\n");
+        assert_eq!(memory.recall(&mut fresh).unwrap(), vec![HunkId(0)]);
+        let HunkState::Resolved(resolution) = &fresh.hunks()[0].state else {
+            panic!("expected a recalled resolution");
+        };
+        assert_eq!(resolution.content, "l\nr");
+        assert_eq!(resolution.metadata.source, ResolutionSource::Rerere);
+    }
+
+    #[test]
+    fn forgotten_resolutions_are_not_recalled() {
+        let (_dir, memory) = memory();
+        let mut resolved = session(CONFLICT);
+        let hunk = resolved.hunks()[0].clone();
+        resolved
+            .set_resolution(hunk.id, Resolution::accept_right(&hunk))
+            .unwrap();
+        memory.remember(&resolved).unwrap();
+
+        assert_eq!(memory.forget(&session(CONFLICT)).unwrap(), 1);
+        assert!(memory.recall(&mut session(CONFLICT)).unwrap().is_empty());
+
+        memory.remember(&resolved).unwrap();
+        assert_eq!(memory.clear().unwrap(), 1);
+        assert_eq!(memory.clear().unwrap(), 0);
+    }
+
+    #[test]
+    fn recalled_sides_follow_the_swap() {
+        let (_dir, memory) = memory();
+        let mut resolved = session(CONFLICT);
+        let hunk = resolved.hunks()[0].clone();
+        resolved
+            .set_resolution(hunk.id, Resolution::accept_left(&hunk))
+            .unwrap();
+        memory.remember(&resolved).unwrap();
+
+        let mut swapped = session("a\n<<<<<<< HEAD\nr\n=======\nl\n>>>>>>> f\nz\n");
+        memory.recall(&mut swapped).unwrap();
+        let resolution = &swapped.resolutions()[&HunkId(0)];
+        assert_eq!(resolution.kind, ResolutionStrategyKind::AcceptRight);
+        assert_eq!(resolution.content, "l");
+    }
+
+    #[test]
+    fn entries_for_another_conflict_are_not_recalled() {
+        let (_dir, memory) = memory();
+        let mut resolved = session(CONFLICT);
+        resolved
+            .set_resolution(HunkId(0), Resolution::manual("l\nr".to_string()))
+            .unwrap();
+        let (_, remembered) = resolved.remembered().remove(0);
+
+        // Filed under the key of a different conflict, as a collision would
+        let mut other = session("a\n<<<<<<< HEAD\nl\n=======\nq\n>>>>>>> f\nz\n");
+        std::fs::create_dir_all(&memory.dir).unwrap();
+        std::fs::write(
+            memory.entry(&other.hunks()[0].rerere_key()),
+            serde_json::to_string(&remembered).unwrap(),
+        )
+        .unwrap();
+
+        assert!(memory.recall(&mut other).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+//! Saved progress on files left partly resolved.
+//!
+//! Quitting the TUI before every hunk is resolved saves the resolutions
+//! made so far, manual edits included, as a JSON sidecar per file in
+//! `.git/weavr/sessions/` of the file's repository (or
+//! `$XDG_DATA_HOME/weavr/sessions/` outside one). The next run on the file
+//! offers to resume them; `--resume` does so without asking and
+//! `--discard-session` starts over. Progress saved before the file's
+//! conflicts changed, say after `git checkout --conflict`, is discarded
+//! rather than applied. Resolving the file removes its sidecar.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use weavr_core::{content_hash, HunkId, MergeSession, RestoreError, SavedSession};
+
+use crate::error::CliError;
+use crate::{history, tui};
+
+/// What to do with a file's saved progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeMode {
+    /// Ask whether to resume, when there is someone to ask.
+    Ask,
+    /// Resume without asking.
+    Resume,
+    /// Discard the progress and start over.
+    Discard,
+}
+
+impl ResumeMode {
+    /// Returns the mode the `--resume` and `--discard-session` flags ask for.
+    pub fn from_flags(resume: bool, discard: bool) -> Self {
+        match (resume, discard) {
+            (true, _) => ResumeMode::Resume,
+            (_, true) => ResumeMode::Discard,
+            _ => ResumeMode::Ask,
+        }
+    }
+}
+
+/// The saved progress on one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSidecar {
+    file: PathBuf,
+    mode: ResumeMode,
+}
+
+impl SessionSidecar {
+    /// Opens the sidecar kept at `file`.
+    pub fn at(file: PathBuf, mode: ResumeMode) -> Self {
+        Self { file, mode }
+    }
+
+    /// Opens the sidecar for `path`: in its repository, named after its path
+    /// there, or in the user's data directory outside a repository.
+    pub fn for_file(path: &Path, mode: ResumeMode) -> Option<Self> {
+        let (dir, name) = match tui::locate(path) {
+            Some((repo, relative)) => (repo.git_path("weavr/sessions").ok()?, relative),
+            None => (
+                history::data_dir()?.join("sessions"),
+                std::fs::canonicalize(path).ok()?,
+            ),
+        };
+        let key = content_hash(&name.to_string_lossy());
+        Some(Self::at(dir.join(format!("{key}.json")), mode))
+    }
+
+    /// Reads the saved progress, if any. A sidecar that cannot be read is
+    /// treated as missing.
+    pub fn load(&self) -> Option<SavedSession> {
+        let text = std::fs::read_to_string(&self.file).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Saves the session's resolutions, or removes the sidecar if there are
+    /// none.
+    pub fn save(&self, session: &MergeSession) -> Result<(), CliError> {
+        let saved = session.save();
+        if saved.hunks.is_empty() {
+            return self.discard();
+        }
+        if let Some(dir) = self.file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string_pretty(&saved).map_err(io::Error::other)?;
+        std::fs::write(&self.file, text)?;
+        Ok(())
+    }
+
+    /// Removes the sidecar.
+    pub fn discard(&self) -> Result<(), CliError> {
+        match std::fs::remove_file(&self.file) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Restores the saved progress onto `session` as the mode calls for,
+    /// asking on the terminal in [`ResumeMode::Ask`], and returns the hunks
+    /// restored. Stale progress is discarded.
+    pub fn restore(&self, session: &mut MergeSession) -> Result<Vec<HunkId>, CliError> {
+        let Some(saved) = self.load() else {
+            return Ok(Vec::new());
+        };
+        let path = session.input().left.path.display().to_string();
+        if !saved.matches(session) {
+            eprintln!("weavr: {path}: discarding saved session, the conflicts changed since");
+            self.discard()?;
+            return Ok(Vec::new());
+        }
+        let resume = match self.mode {
+            ResumeMode::Resume => true,
+            ResumeMode::Discard => false,
+            // Without a terminal there is no one to ask; keep it for later
+            ResumeMode::Ask if !io::stdin().is_terminal() => return Ok(Vec::new()),
+            ResumeMode::Ask => ask(
+                &format!(
+                    "{path}: resume the saved session ({} of {} hunks resolved)? [Y/n] ",
+                    saved.hunks.len(),
+                    saved.total_hunks
+                ),
+                &mut io::stdin().lock(),
+                &mut io::stderr(),
+            )?,
+        };
+        if !resume {
+            self.discard()?;
+            return Ok(Vec::new());
+        }
+        match session.restore(&saved) {
+            Ok(restored) => Ok(restored),
+            Err(RestoreError::Stale) => Ok(Vec::new()),
+            Err(RestoreError::Resolution(e)) => Err(e.into()),
+        }
+    }
+}
+
+/// Asks a yes/no question, yes being the default. End of input means no.
+fn ask<R: BufRead, W: Write>(question: &str, input: &mut R, output: &mut W) -> io::Result<bool> {
+    write!(output, "{question}")?;
+    output.flush()?;
+    let mut answer = String::new();
+    if input.read_line(&mut answer)? == 0 {
+        return Ok(false);
+    }
+    Ok(matches!(
+        answer.trim().to_lowercase().as_str(),
+        "" | "y" | "yes"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use weavr_core::Resolution;
+
+    use super::*;
+
+    const CONFLICT: &str =
+        "<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> f\nm\n<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> f\n";
+
+    fn sidecar(mode: ResumeMode) -> (TempDir, SessionSidecar) {
+        let dir = TempDir::new().unwrap();
+        let sidecar = SessionSidecar::at(dir.path().join("lib.rs.json"), mode);
+        (dir, sidecar)
+    }
+
+    fn session(content: &str) -> MergeSession {
+        MergeSession::from_conflicted(content, PathBuf::from("src/lib.rs")).unwrap()
+    }
+
+    #[test]
+    fn saved_progress_is_resumed_then_removed() {
+        let (_dir, sidecar) = sidecar(ResumeMode::Resume);
+        let mut partial = session(CONFLICT);
+        partial
+            .set_resolution(HunkId(1), Resolution::manual("x and y\n".to_string()))
+            .unwrap();
+        sidecar.save(&partial).unwrap();
+
+        let mut fresh = session(CONFLICT);
+        assert_eq!(sidecar.restore(&mut fresh).unwrap(), vec![HunkId(1)]);
+        assert_eq!(fresh.resolutions()[&HunkId(1)].content, "x and y\n");
+
+        // Saving a session without resolutions leaves nothing behind
+        sidecar.save(&session(CONFLICT)).unwrap();
+        assert!(sidecar.load().is_none());
+    }
+
+    #[test]
+    fn stale_or_discarded_progress_is_not_applied() {
+        let (_dir, sidecar) = sidecar(ResumeMode::Resume);
+        let mut partial = session(CONFLICT);
+        partial
+            .set_resolution(HunkId(0), Resolution::manual("l\n".to_string()))
+            .unwrap();
+        sidecar.save(&partial).unwrap();
+        let mut changed = session(&CONFLICT.replace("\ny\n", "\nz\n"));
+        assert!(sidecar.restore(&mut changed).unwrap().is_empty());
+        assert!(changed.resolutions().is_empty());
+        assert!(sidecar.load().is_none());
+
+        let discarding = SessionSidecar::at(sidecar.file.clone(), ResumeMode::Discard);
+        discarding.save(&partial).unwrap();
+        assert!(discarding
+            .restore(&mut session(CONFLICT))
+            .unwrap()
+            .is_empty());
+        assert!(discarding.load().is_none());
+    }
+
+    #[test]
+    fn ask_defaults_to_yes() {
+        let mut output = Vec::new();
+        assert!(ask("resume? ", &mut "\n".as_bytes(), &mut output).unwrap());
+        assert!(!ask("resume? ", &mut "n\n".as_bytes(), &mut output).unwrap());
+        assert!(!ask("resume? ", &mut "".as_bytes(), &mut output).unwrap());
+        assert_eq!(String::from_utf8(output).unwrap(), "resume? ".repeat(3));
+    }
+}
@@ -0,0 +1,220 @@
+//! `weavr review`: read-only review of a finished merge.
+//!
+//! For a merge commit, each file that differs from both parents is merged
+//! again from its parents and their common ancestor, which brings back the
+//! conflicts, and the committed file shows how each one was resolved. For
+//! a resolution patch written with `--export-patch`, the recorded conflicts
+//! are shown with the resolutions recorded for them. Either way the TUI
+//! opens read-only, so nothing can be changed by accident.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use weavr_core::{
+    parse_resolution_patch, recover_resolutions, MergeSession, RecordedResolution,
+    ResolutionPatchFile,
+};
+use weavr_git::GitRepo;
+use weavr_tui::App;
+
+use crate::error::CliError;
+use crate::replay;
+
+/// Opens `target`, a merge commit or a resolution patch file, for review.
+pub fn run(target: &str) -> Result<(), CliError> {
+    let path = Path::new(target);
+    let sessions = if path.is_file() {
+        from_patch(path)?
+    } else {
+        from_commit(&GitRepo::discover()?, target)?
+    };
+    if sessions.is_empty() {
+        println!("Nothing to review: {target} has no resolved conflicts");
+        return Ok(());
+    }
+
+    for session in sessions {
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_read_only(true);
+        weavr_tui::run(&mut app)?;
+    }
+    Ok(())
+}
+
+/// Rebuilds the conflicts of merge commit `rev` with the resolutions it
+/// committed.
+///
+/// Files that merged cleanly are skipped; so are conflicts whose
+/// surroundings were edited too, as where they were resolved is unclear.
+pub fn from_commit(repo: &GitRepo, rev: &str) -> Result<Vec<MergeSession>, CliError> {
+    let parents = repo.parents(rev)?;
+    let [ours, theirs] = parents.as_slice() else {
+        return Err(CliError::Config(format!(
+            "{rev} is not a merge of two parents (it has {})",
+            parents.len()
+        )));
+    };
+    let base = repo.merge_base(ours, theirs)?;
+
+    // Files the merge changed relative to both parents combined them
+    let from_ours: BTreeSet<PathBuf> = repo.changed_paths(ours, rev)?.into_iter().collect();
+    let from_theirs: BTreeSet<PathBuf> = repo.changed_paths(theirs, rev)?.into_iter().collect();
+
+    let labels = [short(ours), "base".to_string(), short(theirs)];
+    let labels = [labels[0].as_str(), labels[1].as_str(), labels[2].as_str()];
+    let mut sessions = Vec::new();
+    for path in from_ours.intersection(&from_theirs) {
+        let Some(merged) = repo.file_at(rev, path)? else {
+            continue;
+        };
+        // Either parent or the base may have the file under another name
+        let read = |from: &str| -> Result<String, CliError> {
+            Ok(match repo.path_before(from, rev, path)? {
+                Some(before) => repo.file_at(from, &before)?.unwrap_or_default(),
+                None => String::new(),
+            })
+        };
+        let base_content = match &base {
+            Some(base) => read(base)?,
+            None => String::new(),
+        };
+        let conflicted = repo.merge_file(&read(ours)?, &base_content, &read(theirs)?, labels)?;
+        let mut session = MergeSession::from_conflicted(conflicted, path.clone())?;
+        if session.hunks().is_empty() {
+            continue;
+        }
+        for (id, resolution) in recover_resolutions(&session, &merged) {
+            session.set_resolution(id, resolution)?;
+        }
+        sessions.push(session);
+    }
+    Ok(sessions)
+}
+
+/// Rebuilds the conflicts recorded in a resolution patch with their
+/// resolutions.
+fn from_patch(path: &Path) -> Result<Vec<MergeSession>, CliError> {
+    let text = std::fs::read_to_string(path)?;
+    let files = parse_resolution_patch(&text)?;
+
+    let mut sessions = Vec::new();
+    for ResolutionPatchFile { path, resolutions } in files {
+        if resolutions.is_empty() {
+            continue;
+        }
+        let mut session = MergeSession::from_conflicted(conflicts(&resolutions), path.clone())?;
+        let report = session.replay(&resolutions)?;
+        if !report.unmatched.is_empty() {
+            eprintln!("{}: {}", path.display(), replay::summarize(&report));
+        }
+        sessions.push(session);
+    }
+    Ok(sessions)
+}
+
+/// Writes recorded conflicts out as a conflicted file, separated by `...`
+/// lines.
+fn conflicts(recorded: &[RecordedResolution]) -> String {
+    let section = |text: &str| {
+        if text.is_empty() {
+            String::new()
+        } else {
+            format!("{text}\n")
+        }
+    };
+    let mut out = String::new();
+    for (i, conflict) in recorded.iter().enumerate() {
+        if i > 0 {
+            out.push_str("...\n");
+        }
+        let _ = write!(out, "<<<<<<< ours\n{}", section(&conflict.left));
+        if let Some(base) = &conflict.base {
+            let _ = write!(out, "||||||| base\n{}", section(base));
+        }
+        let _ = write!(out, "=======\n{}>>>>>>> theirs\n", section(&conflict.right));
+    }
+    out
+}
+
+/// Abbreviates a commit ID for conflict labels.
+fn short(id: &str) -> String {
+    id.chars().take(10).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use weavr_core::{HunkState, Resolution, ResolutionStrategyKind};
+
+    use super::*;
+
+    #[test]
+    fn recorded_conflicts_are_rebuilt_with_their_resolutions() {
+        let content = "a\n<<<<<<< HEAD\nleft\n||||||| base\norig\n=======\nright\n>>>>>>> x\nb\n\
+                       <<<<<<< HEAD\n=======\nnew\n>>>>>>> x\n";
+        let mut session = MergeSession::from_conflicted(content, PathBuf::from("f.rs")).unwrap();
+        let hunks = session.hunks().to_vec();
+        session
+            .set_resolution(hunks[0].id, Resolution::accept_right(&hunks[0]))
+            .unwrap();
+        session
+            .set_resolution(hunks[1].id, Resolution::manual("mine".to_string()))
+            .unwrap();
+
+        let dir = TempDir::new().unwrap();
+        let patch = dir.path().join("review.patch");
+        std::fs::write(&patch, weavr_core::format_resolution_patch(&session)).unwrap();
+
+        let sessions = from_patch(&patch).unwrap();
+        assert_eq!(sessions.len(), 1);
+        let reviewed = &sessions[0];
+        assert_eq!(reviewed.hunks().len(), 2);
+        assert_eq!(reviewed.hunks()[0].base.as_ref().unwrap().text, "orig");
+        let kinds: Vec<_> = reviewed
+            .hunks()
+            .iter()
+            .map(|hunk| match &hunk.state {
+                HunkState::Resolved(resolution) => resolution.kind.clone(),
+                _ => panic!("hunk {} not resolved", hunk.id.0),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ResolutionStrategyKind::AcceptRight,
+                ResolutionStrategyKind::Manual
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_commit_conflicts_follow_renames() {
+        let dir = TempDir::new().unwrap();
+        let repo = GitRepo::init(dir.path()).unwrap();
+        repo.set_config("user.email", "test@test.com").unwrap();
+        repo.set_config("user.name", "Test").unwrap();
+        let lines = "one\ntwo\nthree\nfour\nfive\nsix\n";
+        std::fs::write(dir.path().join("old.txt"), lines).unwrap();
+        repo.commit_all("Base").unwrap();
+        repo.create_branch("feature").unwrap();
+        std::fs::write(dir.path().join("old.txt"), lines.replace("three", "ours")).unwrap();
+        repo.commit_all("Ours").unwrap();
+        repo.checkout("feature").unwrap();
+        std::fs::remove_file(dir.path().join("old.txt")).unwrap();
+        std::fs::write(dir.path().join("new.txt"), lines.replace("three", "theirs")).unwrap();
+        repo.commit_all("Rename").unwrap();
+        repo.checkout("-").unwrap();
+        assert!(!repo.merge("feature", &[]).unwrap());
+        std::fs::write(dir.path().join("new.txt"), lines.replace("three", "both")).unwrap();
+        repo.commit_all("Merge").unwrap();
+
+        let sessions = from_commit(&repo, "HEAD").unwrap();
+        assert_eq!(sessions.len(), 1);
+        let hunk = &sessions[0].hunks()[0];
+        assert_eq!(hunk.left.text, "ours");
+        assert_eq!(hunk.base.as_ref().unwrap().text, "three");
+        assert_eq!(hunk.right.text, "theirs");
+    }
+}
@@ -0,0 +1,337 @@
+//! Repository resolution rules.
+//!
+//! Rules live in `.weavr.toml` at the repository root and map path globs to
+//! the strategy to use for matching files:
+//!
+//! ```toml
+//! [[rules]]
+//! path = "package-lock.json"
+//! strategy = "theirs"
+//!
+//! [[rules]]
+//! path = "CHANGELOG.md"
+//! strategy = "both-deduped"
+//!
+//! [[rules]]
+//! path = "*.pb.go"
+//! regenerate = "make proto"
+//!
+//! # Run on conflicted files no rule matches
+//! fallback = "./scripts/resolve.sh"
+//! ```
+//!
+//! A rule with `regenerate` resolves matching files by running the command,
+//! and so does `fallback` for the files no rule matches; see
+//! [`crate::regenerate`].
+//!
+//! `$XDG_CONFIG_HOME/weavr/rules.toml` (falling back to
+//! `~/.config/weavr/rules.toml`) declares rules for every repository, in
+//! the same format. They are checked after the repository's own, whose
+//! `fallback` also wins.
+//!
+//! A pattern without a `/` matches the file name in any directory; one with a
+//! `/` matches the whole path from the repository root. `*` and `?` stay
+//! within one path segment and `**` spans any number of segments.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::error::CliError;
+use crate::tui;
+
+/// Name of the repository rules file.
+pub const RULES_FILE: &str = ".weavr.toml";
+
+/// Name of the user's rules file in weavr's configuration directory.
+pub const USER_RULES_FILE: &str = "rules.toml";
+
+/// How files matching a rule are resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleStrategy {
+    /// Take our side.
+    #[serde(alias = "left")]
+    Ours,
+    /// Take their side.
+    #[default]
+    #[serde(alias = "right")]
+    Theirs,
+    /// Take both sides, ours first.
+    Both,
+    /// Take both sides, ours first, dropping their lines that ours has.
+    #[serde(rename = "both-deduped")]
+    BothDeduped,
+}
+
+impl fmt::Display for RuleStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RuleStrategy::Ours => "ours",
+            RuleStrategy::Theirs => "theirs",
+            RuleStrategy::Both => "both",
+            RuleStrategy::BothDeduped => "both-deduped",
+        })
+    }
+}
+
+/// A path pattern and the strategy for files matching it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Glob matched against the path relative to the repository root.
+    pub path: String,
+    /// Strategy for matching files; with `regenerate`, the side the command
+    /// starts from. Defaults to theirs.
+    #[serde(default)]
+    pub strategy: RuleStrategy,
+    /// Command that regenerates matching files after they are resolved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regenerate: Option<String>,
+}
+
+impl Rule {
+    /// Returns true if the rule applies to `path`, relative to the repository
+    /// root.
+    pub fn matches(&self, path: &Path) -> bool {
+        glob_matches(&self.path, &path.to_string_lossy().replace('\\', "/"))
+    }
+}
+
+/// The rules declared in a repository's `.weavr.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RulesFile {
+    /// Rules in declaration order; the first match wins.
+    pub rules: Vec<Rule>,
+    /// Command that resolves files no rule matches.
+    pub fallback: Option<String>,
+}
+
+impl RulesFile {
+    /// Loads the repository's rules followed by those in the user's rules
+    /// file at `user`, if any.
+    pub fn load_layered(root: &Path, user: Option<&Path>) -> Result<Self, CliError> {
+        let mut rules = Self::load(root)?;
+        if let Some(user) = user {
+            let user = Self::load_file(user)?;
+            rules.rules.extend(user.rules);
+            rules.fallback = rules.fallback.or(user.fallback);
+        }
+        Ok(rules)
+    }
+
+    /// Loads the rules file from the repository root, or no rules if there is
+    /// none.
+    pub fn load(root: &Path) -> Result<Self, CliError> {
+        Self::load_file(&root.join(RULES_FILE))
+    }
+
+    /// Loads the rules file at `path`, or no rules if there is none.
+    fn load_file(path: &Path) -> Result<Self, CliError> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Config(format!("{}: {e}", path.display())))?;
+        toml::from_str(&text).map_err(|e| CliError::Config(format!("{}: {e}", path.display())))
+    }
+
+    /// Returns the first rule matching `path`, relative to the repository root.
+    pub fn rule_for(&self, path: &Path) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.matches(path))
+    }
+
+    /// Writes the rules to the repository's rules file.
+    ///
+    /// Other settings in an existing file are kept; its `rules` are replaced.
+    /// Returns the path written.
+    pub fn save(&self, root: &Path) -> Result<PathBuf, CliError> {
+        let path = root.join(RULES_FILE);
+        let mut table = if path.is_file() {
+            let text = std::fs::read_to_string(&path)?;
+            toml::from_str::<toml::Table>(&text)
+                .map_err(|e| CliError::Config(format!("{}: {e}", path.display())))?
+        } else {
+            toml::Table::new()
+        };
+
+        let rules = toml::Value::try_from(&self.rules)
+            .map_err(|e| CliError::Config(format!("{}: {e}", path.display())))?;
+        table.insert("rules".to_string(), rules);
+        let text = toml::to_string(&table)
+            .map_err(|e| CliError::Config(format!("{}: {e}", path.display())))?;
+        std::fs::write(&path, text)?;
+        Ok(path)
+    }
+}
+
+/// The rules that apply to a file.
+#[derive(Debug, Clone)]
+pub struct FileRules {
+    /// Root of the file's repository.
+    pub root: PathBuf,
+    /// Path of the file relative to the root.
+    pub relative: PathBuf,
+    /// The repository's and the user's rules.
+    pub rules: RulesFile,
+}
+
+impl FileRules {
+    /// Loads the rules for `path`, or `None` outside a repository.
+    pub fn load(path: &Path) -> Result<Option<Self>, CliError> {
+        let Some((repo, relative)) = tui::locate(path) else {
+            return Ok(None);
+        };
+        let user = config::config_dir().map(|dir| dir.join(USER_RULES_FILE));
+        let rules = RulesFile::load_layered(repo.root(), user.as_deref())?;
+        Ok(Some(Self {
+            root: repo.root().to_path_buf(),
+            relative,
+            rules,
+        }))
+    }
+
+    /// Returns the first rule matching the file.
+    pub fn rule(&self) -> Option<&Rule> {
+        self.rules.rule_for(&self.relative)
+    }
+}
+
+/// Matches a slash-separated path against a rule pattern.
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    let path = path.trim_start_matches("./");
+    if pattern.contains('/') {
+        let pattern: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+        let path: Vec<&str> = path.split('/').collect();
+        match_segments(&pattern, &path)
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        match_segment(pattern.as_bytes(), name.as_bytes())
+    }
+}
+
+/// Matches path segments, where a `**` pattern segment spans any number of
+/// path segments.
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, path_rest)| {
+            match_segment(first.as_bytes(), segment.as_bytes()) && match_segments(rest, path_rest)
+        }),
+    }
+}
+
+/// Matches one segment against a pattern with `*` and `?` wildcards.
+fn match_segment(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|skip| match_segment(rest, &text[skip..])),
+        Some((b'?', rest)) => !text.is_empty() && match_segment(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && match_segment(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn patterns_without_slash_match_file_names() {
+        assert!(glob_matches("Cargo.lock", "Cargo.lock"));
+        assert!(glob_matches("Cargo.lock", "crates/foo/Cargo.lock"));
+        assert!(glob_matches("*.snap", "src/__snapshots__/a.test.ts.snap"));
+        assert!(glob_matches("*.generated.?s", "api/client.generated.ts"));
+        assert!(!glob_matches("*.snap", "src/a.snapshot"));
+    }
+
+    #[test]
+    fn patterns_with_slash_match_from_the_root() {
+        assert!(glob_matches("docs/*.md", "docs/intro.md"));
+        assert!(!glob_matches("docs/*.md", "docs/guide/intro.md"));
+        assert!(glob_matches("docs/**/*.md", "docs/intro.md"));
+        assert!(glob_matches("docs/**/*.md", "docs/guide/intro.md"));
+        assert!(glob_matches(
+            "**/__snapshots__/**",
+            "a/b/__snapshots__/x.snap"
+        ));
+        assert!(!glob_matches("docs/*.md", "other/docs/intro.md"));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules: RulesFile = toml::from_str(
+            "[[rules]]\npath = \"CHANGELOG.md\"\nstrategy = \"both\"\n\n\
+             [[rules]]\npath = \"*.md\"\nstrategy = \"right\"\n",
+        )
+        .unwrap();
+        let rule = |path: &str| rules.rule_for(Path::new(path)).map(|r| r.strategy);
+        assert_eq!(rule("CHANGELOG.md"), Some(RuleStrategy::Both));
+        assert_eq!(rule("README.md"), Some(RuleStrategy::Theirs));
+        assert_eq!(rule("src/lib.rs"), None);
+    }
+
+    #[test]
+    fn regenerate_rules_need_no_strategy() {
+        let rules: RulesFile =
+            toml::from_str("[[rules]]\npath = \"*.pb.go\"\nregenerate = \"make proto\"\n").unwrap();
+        let rule = rules.rule_for(Path::new("api/v1/user.pb.go")).unwrap();
+        assert_eq!(rule.strategy, RuleStrategy::Theirs);
+        assert_eq!(rule.regenerate.as_deref(), Some("make proto"));
+    }
+
+    #[test]
+    fn user_rules_come_after_repository_rules() {
+        let (repo, config) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let user = config.path().join(USER_RULES_FILE);
+        std::fs::write(
+            repo.path().join(RULES_FILE),
+            "[[rules]]\npath = \"CHANGELOG.md\"\nstrategy = \"both-deduped\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &user,
+            "fallback = \"resolve\"\n\n[[rules]]\npath = \"*.md\"\nstrategy = \"ours\"\n",
+        )
+        .unwrap();
+
+        let rules = RulesFile::load_layered(repo.path(), Some(&user)).unwrap();
+        let only_repo = RulesFile::load_layered(repo.path(), None).unwrap();
+        let rule = |path: &str| rules.rule_for(Path::new(path)).map(|r| r.strategy);
+        assert_eq!(rule("CHANGELOG.md"), Some(RuleStrategy::BothDeduped));
+        assert_eq!(rule("README.md"), Some(RuleStrategy::Ours));
+        assert_eq!(rules.fallback.as_deref(), Some("resolve"));
+        assert_eq!(only_repo.rules.len(), 1);
+        assert!(only_repo.fallback.is_none());
+    }
+
+    #[test]
+    fn save_keeps_other_settings() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(RULES_FILE),
+            "[resolve]\ntest_command = \"make test\"\n\n[[rules]]\npath = \"a\"\nstrategy = \"ours\"\n",
+        )
+        .unwrap();
+
+        let rules = RulesFile {
+            rules: vec![Rule {
+                path: "yarn.lock".to_string(),
+                strategy: RuleStrategy::Theirs,
+                regenerate: None,
+            }],
+            fallback: None,
+        };
+        rules.save(dir.path()).unwrap();
+
+        assert_eq!(RulesFile::load(dir.path()).unwrap(), rules);
+        let text = std::fs::read_to_string(dir.path().join(RULES_FILE)).unwrap();
+        assert!(text.contains("test_command = \"make test\""));
+        assert!(text.contains("[[rules]]"));
+    }
+}
@@ -0,0 +1,317 @@
+//! `weavr rules init`: proposes rules for the current conflicts.
+//!
+//! Lockfiles, snapshots, and generated files tend to conflict together and
+//! are rarely worth resolving hunk by hunk. The wizard groups the conflicted
+//! files it recognizes, proposes a rule for each group, and lets the user
+//! accept, adjust, or skip each one before writing `.weavr.toml`.
+
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use weavr_git::GitRepo;
+
+use crate::error::CliError;
+use crate::rules::{glob_matches, Rule, RuleStrategy, RulesFile, RULES_FILE};
+
+/// A kind of file usually resolved wholesale.
+struct FileKind {
+    /// Singular and plural names shown to the user.
+    names: (&'static str, &'static str),
+    /// Patterns recognizing the kind; each becomes its own proposal.
+    patterns: &'static [&'static str],
+    /// Strategy proposed for the kind.
+    strategy: RuleStrategy,
+}
+
+/// Kinds recognized by the wizard, in the order they are proposed.
+const KINDS: &[FileKind] = &[
+    FileKind {
+        names: ("lockfile", "lockfiles"),
+        patterns: &[
+            "Cargo.lock",
+            "package-lock.json",
+            "yarn.lock",
+            "pnpm-lock.yaml",
+            "Gemfile.lock",
+            "poetry.lock",
+            "Pipfile.lock",
+            "composer.lock",
+            "go.sum",
+            "flake.lock",
+        ],
+        strategy: RuleStrategy::Theirs,
+    },
+    FileKind {
+        names: ("snapshot", "snapshots"),
+        patterns: &["*.snap", "**/__snapshots__/**"],
+        strategy: RuleStrategy::Theirs,
+    },
+    FileKind {
+        names: ("generated file", "generated files"),
+        patterns: &[
+            "*.generated.*",
+            "*.pb.go",
+            "*_pb2.py",
+            "*.g.dart",
+            "*.min.js",
+            "*.min.css",
+        ],
+        strategy: RuleStrategy::Theirs,
+    },
+    FileKind {
+        names: ("changelog", "changelogs"),
+        patterns: &["CHANGELOG.md", "CHANGES.md", "HISTORY.md"],
+        strategy: RuleStrategy::Both,
+    },
+];
+
+/// Number of example files listed with a proposal.
+const EXAMPLES: usize = 3;
+
+/// A rule proposed for a group of conflicted files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal {
+    /// Singular and plural names of the kind of file.
+    pub names: (&'static str, &'static str),
+    /// The proposed rule.
+    pub rule: Rule,
+    /// Conflicted files the rule would cover.
+    pub files: Vec<PathBuf>,
+}
+
+impl Proposal {
+    /// Describes the proposal, e.g. `12 lockfiles (Cargo.lock, ...) -> theirs`.
+    fn describe(&self) -> String {
+        let name = if self.files.len() == 1 {
+            self.names.0
+        } else {
+            self.names.1
+        };
+        let mut examples: Vec<String> = self
+            .files
+            .iter()
+            .take(EXAMPLES)
+            .map(|f| f.display().to_string())
+            .collect();
+        if self.files.len() > EXAMPLES {
+            examples.push(format!("+{} more", self.files.len() - EXAMPLES));
+        }
+        format!(
+            "{} {name} matching {} ({}) -> {}",
+            self.files.len(),
+            self.rule.path,
+            examples.join(", "),
+            self.rule.strategy
+        )
+    }
+}
+
+/// Runs the wizard for the repository in the current directory.
+pub fn run(accept_all: bool) -> Result<(), CliError> {
+    let repo = GitRepo::discover()?;
+    let files = repo.conflicted_files()?;
+    let mut rules = RulesFile::load(repo.root())?;
+
+    let proposals = propose(&files, &rules);
+    if proposals.is_empty() {
+        println!("No rules to propose for the current conflicts");
+        return Ok(());
+    }
+
+    let accepted = if accept_all {
+        for proposal in &proposals {
+            println!("{}", proposal.describe());
+        }
+        proposals.into_iter().map(|p| p.rule).collect()
+    } else {
+        review(proposals, &mut io::stdin().lock(), &mut io::stdout())?
+    };
+
+    if accepted.is_empty() {
+        println!("No rules written");
+        return Ok(());
+    }
+    let count = accepted.len();
+    rules.rules.extend(accepted);
+    rules.save(repo.root())?;
+    println!("Wrote {count} rules to {RULES_FILE}");
+    Ok(())
+}
+
+/// Groups conflicted files by the first pattern recognizing them.
+///
+/// Files already covered by an existing rule are left out.
+pub fn propose(files: &[PathBuf], existing: &RulesFile) -> Vec<Proposal> {
+    let mut proposals: Vec<Proposal> = Vec::new();
+    for kind in KINDS {
+        for pattern in kind.patterns {
+            let matching: Vec<PathBuf> = files
+                .iter()
+                .filter(|f| existing.rule_for(f).is_none())
+                .filter(|f| glob_matches(pattern, &slashed(f)))
+                .filter(|f| !proposals.iter().any(|p| p.files.contains(f)))
+                .cloned()
+                .collect();
+            if !matching.is_empty() {
+                proposals.push(Proposal {
+                    names: kind.names,
+                    rule: Rule {
+                        path: (*pattern).to_string(),
+                        strategy: kind.strategy,
+                        regenerate: None,
+                    },
+                    files: matching,
+                });
+            }
+        }
+    }
+    proposals
+}
+
+/// Asks about each proposal and returns the accepted rules.
+///
+/// Besides accepting or skipping, the strategy and pattern can be changed
+/// before accepting. End of input skips the remaining proposals.
+pub fn review<R: BufRead, W: Write>(
+    proposals: Vec<Proposal>,
+    input: &mut R,
+    output: &mut W,
+) -> io::Result<Vec<Rule>> {
+    let mut accepted = Vec::new();
+    'proposals: for mut proposal in proposals {
+        loop {
+            writeln!(output, "{}", proposal.describe())?;
+            write!(
+                output,
+                "  [y] accept  [n] skip  [o/t/b/d] use ours/theirs/both/both-deduped  [p] edit pattern: "
+            )?;
+            output.flush()?;
+
+            let Some(answer) = read_line(input)? else {
+                break 'proposals;
+            };
+            match answer.as_str() {
+                "" | "y" => {
+                    accepted.push(proposal.rule);
+                    break;
+                }
+                "n" => break,
+                "o" => proposal.rule.strategy = RuleStrategy::Ours,
+                "t" => proposal.rule.strategy = RuleStrategy::Theirs,
+                "b" => proposal.rule.strategy = RuleStrategy::Both,
+                "d" => proposal.rule.strategy = RuleStrategy::BothDeduped,
+                "p" => {
+                    write!(output, "  Pattern [{}]: ", proposal.rule.path)?;
+                    output.flush()?;
+                    match read_line(input)? {
+                        Some(pattern) if !pattern.is_empty() => proposal.rule.path = pattern,
+                        Some(_) => {}
+                        None => break 'proposals,
+                    }
+                }
+                _ => writeln!(output, "  Unknown answer: {answer}")?,
+            }
+        }
+    }
+    Ok(accepted)
+}
+
+/// Reads one trimmed line, or `None` at end of input.
+fn read_line<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// Displays a path with forward slashes for pattern matching.
+fn slashed(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn proposes_rules_for_recognized_files() {
+        let conflicted = files(&[
+            "web/package-lock.json",
+            "api/package-lock.json",
+            "Cargo.lock",
+            "src/__snapshots__/app.test.ts.snap",
+            "CHANGELOG.md",
+            "src/main.rs",
+        ]);
+        let proposals = propose(&conflicted, &RulesFile::default());
+        let summary: Vec<(&str, usize, RuleStrategy)> = proposals
+            .iter()
+            .map(|p| (p.rule.path.as_str(), p.files.len(), p.rule.strategy))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Cargo.lock", 1, RuleStrategy::Theirs),
+                ("package-lock.json", 2, RuleStrategy::Theirs),
+                ("*.snap", 1, RuleStrategy::Theirs),
+                ("CHANGELOG.md", 1, RuleStrategy::Both),
+            ]
+        );
+        assert_eq!(
+            proposals[1].describe(),
+            "2 lockfiles matching package-lock.json (web/package-lock.json, api/package-lock.json) -> theirs"
+        );
+    }
+
+    #[test]
+    fn files_covered_by_existing_rules_are_skipped() {
+        let existing = RulesFile {
+            rules: vec![Rule {
+                path: "*.lock".to_string(),
+                strategy: RuleStrategy::Ours,
+                regenerate: None,
+            }],
+            fallback: None,
+        };
+        assert!(propose(&files(&["Cargo.lock"]), &existing).is_empty());
+    }
+
+    #[test]
+    fn review_accepts_edits_and_skips() {
+        let proposals = propose(
+            &files(&["Cargo.lock", "a.snap", "CHANGELOG.md"]),
+            &RulesFile::default(),
+        );
+        let mut input = "\nb\np\n**/*.snap\ny\nn\n".as_bytes();
+        let mut output = Vec::new();
+        let rules = review(proposals, &mut input, &mut output).unwrap();
+
+        assert_eq!(
+            rules,
+            vec![
+                Rule {
+                    path: "Cargo.lock".to_string(),
+                    strategy: RuleStrategy::Theirs,
+                    regenerate: None,
+                },
+                Rule {
+                    path: "**/*.snap".to_string(),
+                    strategy: RuleStrategy::Both,
+                    regenerate: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn review_stops_at_end_of_input() {
+        let proposals = propose(&files(&["Cargo.lock"]), &RulesFile::default());
+        let rules = review(proposals, &mut "".as_bytes(), &mut Vec::new()).unwrap();
+        assert!(rules.is_empty());
+    }
+}
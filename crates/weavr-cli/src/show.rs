@@ -0,0 +1,375 @@
+//! `weavr show`: printing a file's conflicts without entering the TUI.
+//!
+//! Each conflict is printed with the labels from its markers, the lines
+//! around it, and its sides either one after another or in columns. Output
+//! is colored and paged when stdout is a terminal, so conflicts can be
+//! inspected quickly over SSH or piped into scripts.
+
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use weavr_core::{ConflictHunk, MergeSession};
+
+use crate::cli::{ColorChoice, ShowLayout};
+use crate::discovery;
+use crate::error::CliError;
+use crate::formatter;
+
+/// Width used for side-by-side output when `COLUMNS` is not set.
+const DEFAULT_WIDTH: usize = 120;
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const BOLD: &str = "\x1b[1m";
+const LEFT: &str = "\x1b[32m";
+const BASE: &str = "\x1b[33m";
+const RIGHT: &str = "\x1b[34m";
+
+/// How conflicts are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowOptions {
+    /// Whether the sides are printed in columns.
+    pub layout: ShowLayout,
+    /// Lines of context shown around each conflict.
+    pub context: usize,
+    /// Whether to color the output with ANSI escapes.
+    pub color: bool,
+    /// Total width of side-by-side output.
+    pub width: usize,
+}
+
+/// A conflict with the labels and context read from its file.
+struct Shown<'a> {
+    /// 1-based line of the start marker.
+    line: usize,
+    /// Labels of the ours, base, and theirs markers.
+    labels: [Option<&'a str>; 3],
+    before: &'a [&'a str],
+    after: &'a [&'a str],
+    left: Vec<&'a str>,
+    base: Option<Vec<&'a str>>,
+    right: Vec<&'a str>,
+}
+
+/// Prints the conflicts of `files`, or of every conflicted file.
+pub fn run(
+    files: &[PathBuf],
+    layout: ShowLayout,
+    context: usize,
+    color: ColorChoice,
+    no_pager: bool,
+) -> Result<(), CliError> {
+    let files = discovery::resolve_files(files.to_vec())?;
+    let terminal = std::io::stdout().is_terminal();
+    let options = ShowOptions {
+        layout,
+        context,
+        color: match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => terminal && std::env::var_os("NO_COLOR").is_none(),
+        },
+        width: std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(DEFAULT_WIDTH),
+    };
+
+    let mut out = String::new();
+    for path in &files {
+        let text = std::fs::read_to_string(path)?;
+        out.push_str(&render(path, &text, &options)?);
+    }
+
+    if terminal && !no_pager {
+        page(&out)
+    } else {
+        print!("{out}");
+        Ok(())
+    }
+}
+
+/// Renders the conflicts of one file.
+pub fn render(path: &Path, content: &str, options: &ShowOptions) -> Result<String, CliError> {
+    let session = MergeSession::from_conflicted(content, path.to_path_buf())?;
+    let lines: Vec<&str> = content.lines().collect();
+    let hunks = session.hunks();
+    let conflicts = locate(hunks, &lines, options.context);
+
+    let mut out = String::new();
+    let noun = if conflicts.len() == 1 {
+        "conflict"
+    } else {
+        "conflicts"
+    };
+    let heading = format!("{}: {} {noun}", path.display(), conflicts.len());
+    let _ = writeln!(out, "{}", paint(options, BOLD, &heading));
+    for (i, conflict) in conflicts.iter().enumerate() {
+        let title = format!(
+            "@@ conflict {} of {}, line {} @@",
+            i + 1,
+            conflicts.len(),
+            conflict.line
+        );
+        let _ = writeln!(out, "\n{}", paint(options, DIM, &title));
+        for line in conflict.before {
+            let _ = writeln!(out, "  {line}");
+        }
+        match options.layout {
+            ShowLayout::Unified => unified(&mut out, conflict, options),
+            ShowLayout::SideBySide => side_by_side(&mut out, conflict, options),
+        }
+        for line in conflict.after {
+            let _ = writeln!(out, "  {line}");
+        }
+    }
+    Ok(out)
+}
+
+/// Finds each hunk's markers in `lines` to read their labels and context.
+fn locate<'a>(hunks: &'a [ConflictHunk], lines: &'a [&'a str], context: usize) -> Vec<Shown<'a>> {
+    let count = |text: &str| text.lines().count();
+    let mut previous_end = 0;
+    let mut conflicts = Vec::with_capacity(hunks.len());
+    for (i, hunk) in hunks.iter().enumerate() {
+        // 0-based indices of the marker lines
+        let start = hunk.context.start_line_left.saturating_sub(2);
+        let base = hunk
+            .base
+            .as_ref()
+            .map(|_| start + 1 + count(&hunk.left.text));
+        let end = hunk.context.start_line_right.saturating_sub(1) + count(&hunk.right.text);
+        let next_start = hunks.get(i + 1).map_or(lines.len(), |next| {
+            next.context.start_line_left.saturating_sub(2)
+        });
+
+        let label = |index: usize| {
+            lines
+                .get(index)
+                .and_then(|line| line.get(7..))
+                .map(str::trim)
+                .filter(|label| !label.is_empty())
+        };
+        let after_end = (end + 1 + context).min(next_start).min(lines.len());
+        conflicts.push(Shown {
+            line: start + 1,
+            labels: [label(start), base.and_then(label), label(end)],
+            before: &lines[start.saturating_sub(context).max(previous_end)..start],
+            after: &lines[(end + 1).min(after_end)..after_end],
+            left: hunk.left.text.lines().collect(),
+            base: hunk.base.as_ref().map(|b| b.text.lines().collect()),
+            right: hunk.right.text.lines().collect(),
+        });
+        previous_end = end + 1;
+    }
+    conflicts
+}
+
+/// The sides of a conflict with their names, labels, and colors.
+fn sides<'a>(conflict: &'a Shown<'a>) -> Vec<(String, &'static str, &'a [&'a str])> {
+    let name = |side: &str, label: Option<&str>| match label {
+        Some(label) => format!("{side}: {label}"),
+        None => side.to_string(),
+    };
+    let mut sides = vec![(name("ours", conflict.labels[0]), LEFT, &conflict.left[..])];
+    if let Some(base) = &conflict.base {
+        sides.push((name("base", conflict.labels[1]), BASE, &base[..]));
+    }
+    sides.push((
+        name("theirs", conflict.labels[2]),
+        RIGHT,
+        &conflict.right[..],
+    ));
+    sides
+}
+
+/// Prints the sides one after another.
+fn unified(out: &mut String, conflict: &Shown, options: &ShowOptions) {
+    for (i, (name, color, lines)) in sides(conflict).into_iter().enumerate() {
+        let corner = if i == 0 { '┌' } else { '├' };
+        let _ = writeln!(
+            out,
+            "{}",
+            paint(options, color, &format!("{corner} {name}"))
+        );
+        for line in lines {
+            let _ = writeln!(out, "{}", paint(options, color, &format!("│ {line}")));
+        }
+    }
+    let _ = writeln!(out, "└");
+}
+
+/// Prints the sides in columns, truncating lines that do not fit.
+fn side_by_side(out: &mut String, conflict: &Shown, options: &ShowOptions) {
+    let sides = sides(conflict);
+    let gutters = 3 * (sides.len() - 1);
+    let column = (options.width.saturating_sub(gutters + 2) / sides.len()).max(8);
+    let rows = sides
+        .iter()
+        .map(|(_, _, lines)| lines.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut row = |cells: Vec<(String, &str)>| {
+        let last = cells.len() - 1;
+        let mut line = String::from("  ");
+        for (i, (cell, color)) in cells.into_iter().enumerate() {
+            let cell = fit(&cell, column);
+            line.push_str(&paint(options, color, &cell));
+            if i < last {
+                line.push_str(" │ ");
+            }
+        }
+        let _ = writeln!(out, "{}", line.trim_end());
+    };
+    row(sides
+        .iter()
+        .map(|(name, color, _)| (name.clone(), *color))
+        .collect());
+    row(sides
+        .iter()
+        .map(|(_, color, _)| ("─".repeat(column), *color))
+        .collect());
+    for i in 0..rows {
+        row(sides
+            .iter()
+            .map(|(_, color, lines)| (lines.get(i).copied().unwrap_or("").to_string(), *color))
+            .collect());
+    }
+}
+
+/// Pads or truncates `text` to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let text = text.replace('\t', "    ");
+    let length = text.chars().count();
+    if length > width {
+        let mut cut: String = text.chars().take(width - 1).collect();
+        cut.push('…');
+        cut
+    } else {
+        format!("{text}{}", " ".repeat(width - length))
+    }
+}
+
+/// Wraps `text` in a color escape if coloring is on.
+fn paint(options: &ShowOptions, color: &str, text: &str) -> String {
+    if options.color {
+        format!("{color}{text}{RESET}")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Shows `text` through `$PAGER`, or `less`, falling back to stdout if the
+/// pager cannot be started.
+fn page(text: &str) -> Result<(), CliError> {
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+    let mut command = formatter::shell(&pager);
+    if std::env::var_os("LESS").is_none() {
+        // Keep colors, and exit at once when everything fits on screen
+        command.env("LESS", "FRX");
+    }
+    let Ok(mut child) = command.stdin(Stdio::piped()).spawn() else {
+        print!("{text}");
+        return Ok(());
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFLICT: &str = "\
+fn main() {
+    setup();
+<<<<<<< HEAD
+    run(1);
+||||||| base
+    run(0);
+=======
+    run(2);
+    log();
+>>>>>>> feature
+    finish();
+}
+";
+
+    fn options(layout: ShowLayout) -> ShowOptions {
+        ShowOptions {
+            layout,
+            context: 1,
+            color: false,
+            width: 48,
+        }
+    }
+
+    #[test]
+    fn unified_shows_labels_and_context() {
+        let out = render(
+            Path::new("main.rs"),
+            CONFLICT,
+            &options(ShowLayout::Unified),
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "\
+main.rs: 1 conflict
+
+@@ conflict 1 of 1, line 3 @@
+      setup();
+┌ ours: HEAD
+│     run(1);
+├ base: base
+│     run(0);
+├ theirs: feature
+│     run(2);
+│     log();
+└
+      finish();
+"
+        );
+    }
+
+    #[test]
+    fn side_by_side_puts_sides_in_columns() {
+        let out = render(
+            Path::new("main.rs"),
+            CONFLICT,
+            &options(ShowLayout::SideBySide),
+        )
+        .unwrap();
+        let rows: Vec<&str> = out.lines().skip(4).take(4).collect();
+        assert_eq!(
+            rows,
+            [
+                "  ours: HEAD    │ base: base    │ theirs: feat…",
+                "  ───────────── │ ───────────── │ ─────────────",
+                "      run(1);   │     run(0);   │     run(2);",
+                "                │               │     log();",
+            ]
+        );
+    }
+
+    #[test]
+    fn color_wraps_sides_in_escapes() {
+        let options = ShowOptions {
+            color: true,
+            ..options(ShowLayout::Unified)
+        };
+        let out = render(Path::new("main.rs"), CONFLICT, &options).unwrap();
+        assert!(out.contains(&format!("{LEFT}┌ ours: HEAD{RESET}")));
+        assert!(out.contains(&format!("{RIGHT}│     log();{RESET}")));
+    }
+}
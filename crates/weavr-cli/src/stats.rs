@@ -0,0 +1,232 @@
+//! Conflict statistics for the files in the current merge.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use weavr_core::{ConflictStats, ConflictTree, MergeSession, ResolutionBreakdown};
+
+use crate::discovery::{self, RepoGroup};
+use crate::error::CliError;
+
+/// Width of the heat bar drawn next to each tree row.
+const BAR_WIDTH: usize = 20;
+
+/// Collects statistics for the given conflicted files.
+///
+/// Files that cannot be read or parsed are skipped; they have nothing to
+/// resolve here.
+pub fn collect(files: &[PathBuf]) -> Vec<(PathBuf, ConflictStats)> {
+    files
+        .iter()
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(path).ok()?;
+            let session = MergeSession::from_conflicted(content, path.clone()).ok()?;
+            (!session.hunks().is_empty())
+                .then(|| (path.clone(), ConflictStats::of_file(session.hunks())))
+        })
+        .collect()
+}
+
+/// Builds the conflict tree for a session's files. When the session spans
+/// several repositories, each one is a top-level directory named by its
+/// label.
+pub fn tree(groups: &[RepoGroup]) -> ConflictTree {
+    let stats: Vec<(PathBuf, ConflictStats)> = groups
+        .iter()
+        .flat_map(|group| {
+            collect(&group.files).into_iter().map(move |(path, stats)| {
+                match (&group.label, &group.root) {
+                    (Some(label), Some(root)) => {
+                        let relative = path.strip_prefix(root).unwrap_or(&path);
+                        (Path::new(label).join(relative), stats)
+                    }
+                    _ => (path, stats),
+                }
+            })
+        })
+        .collect();
+    ConflictTree::build(stats.iter().map(|(path, stats)| (path.as_path(), *stats)))
+}
+
+/// Prints statistics for every conflicted file, optionally as a tree.
+pub fn run(as_tree: bool) -> Result<(), CliError> {
+    let files = discovery::discover_conflicted_files()?;
+    let stats = collect(&files);
+    if stats.is_empty() {
+        println!("No conflicted files found");
+        return Ok(());
+    }
+
+    if as_tree {
+        let tree = ConflictTree::build(stats.iter().map(|(path, stats)| (path.as_path(), *stats)));
+        print!("{}", format_tree(&tree));
+    } else {
+        print!("{}", format_flat(&stats));
+    }
+    Ok(())
+}
+
+/// Formats one line per file, busiest first, followed by the totals.
+pub fn format_flat(stats: &[(PathBuf, ConflictStats)]) -> String {
+    let mut sorted: Vec<&(PathBuf, ConflictStats)> = stats.iter().collect();
+    sorted.sort_by(|(a_path, a), (b_path, b)| b.lines.cmp(&a.lines).then(a_path.cmp(b_path)));
+
+    let width = sorted
+        .iter()
+        .map(|(path, _)| display(path).chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for (path, file) in sorted {
+        out.push_str(&format_row(&display(path), width, file));
+    }
+
+    let total = stats
+        .iter()
+        .fold(ConflictStats::default(), |mut acc, (_, s)| {
+            acc.files += s.files;
+            acc.hunks += s.hunks;
+            acc.lines += s.lines;
+            acc
+        });
+    let _ = writeln!(
+        out,
+        "{} files, {} hunks, {} conflicting lines",
+        total.files, total.hunks, total.lines
+    );
+    out
+}
+
+/// Formats the tree with per-directory totals and a heat bar scaled to the
+/// busiest row.
+fn format_tree(tree: &ConflictTree) -> String {
+    let rows = tree.rows();
+    let labels: Vec<String> = rows
+        .iter()
+        .map(|row| format!("{}{}", "  ".repeat(row.depth), row.name))
+        .collect();
+    let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let max_lines = rows.iter().map(|r| r.stats.lines).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for (row, label) in rows.iter().zip(&labels) {
+        let files = if row.is_dir {
+            format!("{:>4} files", row.stats.files)
+        } else {
+            " ".repeat(10)
+        };
+        let _ = writeln!(
+            out,
+            "{label:<width$}  {files}  {:>4} hunks  {:>6} lines  {}",
+            row.stats.hunks,
+            row.stats.lines,
+            heat_bar(row.stats.lines, max_lines)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "{} files, {} hunks, {} conflicting lines",
+        tree.stats.files, tree.stats.hunks, tree.stats.lines
+    );
+    out
+}
+
+/// Formats how the hunks were resolved, followed by how many of them were
+/// automated, e.g. `3 left, 1 rule (1 of 4 automated)`.
+pub fn format_breakdown(breakdown: &ResolutionBreakdown) -> String {
+    format!(
+        "{breakdown} ({} of {} automated)",
+        breakdown.automated(),
+        breakdown.total()
+    )
+}
+
+/// Formats a single file line of the flat listing.
+fn format_row(path: &str, width: usize, stats: &ConflictStats) -> String {
+    format!(
+        "{path:<width$}  {:>4} hunks  {:>6} lines\n",
+        stats.hunks, stats.lines
+    )
+}
+
+/// Returns a bar proportional to `value / max`, at least one cell when
+/// `value` is non-zero.
+fn heat_bar(value: usize, max: usize) -> String {
+    if max == 0 || value == 0 {
+        return String::new();
+    }
+    let cells = (value * BAR_WIDTH).div_ceil(max);
+    "█".repeat(cells)
+}
+
+/// Displays a path with forward slashes on every platform.
+fn display(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(hunks: usize, lines: usize) -> ConflictStats {
+        ConflictStats {
+            files: 1,
+            hunks,
+            lines,
+        }
+    }
+
+    #[test]
+    fn flat_listing_puts_busiest_files_first() {
+        let out = format_flat(&[
+            (PathBuf::from("a.rs"), stats(1, 4)),
+            (PathBuf::from("src/b.rs"), stats(3, 40)),
+        ]);
+        assert_eq!(
+            out,
+            "src/b.rs     3 hunks      40 lines\n\
+             a.rs         1 hunks       4 lines\n\
+             2 files, 4 hunks, 44 conflicting lines\n"
+        );
+    }
+
+    #[test]
+    fn tree_shows_directory_totals_and_heat() {
+        let files = [
+            (PathBuf::from("src/b.rs"), stats(3, 40)),
+            (PathBuf::from("src/c.rs"), stats(1, 20)),
+            (PathBuf::from("a.rs"), stats(1, 6)),
+        ];
+        let tree = ConflictTree::build(files.iter().map(|(p, s)| (p.as_path(), *s)));
+        let out = format_tree(&tree);
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert!(lines[0].starts_with("a.rs  "));
+        assert!(lines[0].ends_with(&"█".repeat(2)));
+        assert!(lines[1].starts_with("src/  "));
+        assert!(lines[1].contains("   2 files     4 hunks      60 lines"));
+        assert!(lines[1].ends_with(&"█".repeat(BAR_WIDTH)));
+        assert!(lines[2].starts_with("  b.rs"));
+        assert_eq!(lines[4], "3 files, 5 hunks, 66 conflicting lines");
+    }
+
+    #[test]
+    fn breakdown_counts_automated_hunks() {
+        let breakdown = ResolutionBreakdown {
+            left: 3,
+            rule: 1,
+            ..ResolutionBreakdown::default()
+        };
+        assert_eq!(
+            format_breakdown(&breakdown),
+            "3 left, 1 rule (1 of 4 automated)"
+        );
+    }
+
+    #[test]
+    fn heat_bar_scales_to_maximum() {
+        assert_eq!(heat_bar(0, 10), "");
+        assert_eq!(heat_bar(1, 1000), "█");
+        assert_eq!(heat_bar(5, 10), "█".repeat(BAR_WIDTH / 2));
+    }
+}
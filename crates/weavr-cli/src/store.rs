@@ -0,0 +1,222 @@
+//! Team-shared resolution store.
+//!
+//! The store maps conflict preimages (see [`weavr_core::preimage_key`]) to the
+//! resolutions people chose for them. Each entry is a resolution patch named
+//! `<key>.patch`, kept either in a plain directory (e.g. on a network share)
+//! or on a git ref that is pushed and fetched like any other ref.
+
+use std::path::{Path, PathBuf};
+
+use weavr_core::{HunkState, MergeSession, RecordedResolution};
+use weavr_git::GitRepo;
+
+use crate::error::CliError;
+
+/// Where shared resolutions are kept.
+pub enum SharedStore {
+    /// A directory of entry files.
+    Directory(PathBuf),
+    /// A git ref whose tree holds the entry files.
+    GitRef {
+        /// Repository holding the ref.
+        repo: GitRepo,
+        /// Full ref name, e.g. `refs/weavr/resolutions`.
+        refname: String,
+    },
+}
+
+impl SharedStore {
+    /// Opens a store from a location string.
+    ///
+    /// Locations starting with `refs/` name a git ref in the current
+    /// repository; anything else is a directory, created on first write.
+    pub fn open(location: &str) -> Result<Self, CliError> {
+        if location.starts_with("refs/") {
+            Ok(Self::GitRef {
+                repo: GitRepo::discover()?,
+                refname: location.to_string(),
+            })
+        } else {
+            Ok(Self::Directory(PathBuf::from(location)))
+        }
+    }
+
+    /// Returns the resolutions recorded for a preimage key, newest first.
+    pub fn lookup(&self, key: &str) -> Result<Vec<RecordedResolution>, CliError> {
+        let Some(text) = self.read_entry(key)? else {
+            return Ok(Vec::new());
+        };
+
+        Ok(weavr_core::parse_resolution_patch(&text)?
+            .into_iter()
+            .flat_map(|file| file.resolutions)
+            .collect())
+    }
+
+    /// Records a resolution, returning false if the same content was already recorded.
+    pub fn record(&self, path: &Path, entry: RecordedResolution) -> Result<bool, CliError> {
+        let key = entry.key();
+        let mut recorded = self.lookup(&key)?;
+        if recorded
+            .iter()
+            .any(|r| r.resolution.content == entry.resolution.content)
+        {
+            return Ok(false);
+        }
+
+        recorded.insert(0, entry);
+        let text = weavr_core::format_recorded_resolutions(path, &recorded);
+        self.write_entry(&key, &text)?;
+        Ok(true)
+    }
+
+    /// Proposes recorded resolutions for every unresolved hunk in a session.
+    ///
+    /// Returns the number of hunks that received suggestions.
+    pub fn suggest(&self, session: &mut MergeSession) -> Result<usize, CliError> {
+        let mut suggested = 0;
+
+        for hunk in session.hunks().to_vec() {
+            if matches!(hunk.state, HunkState::Resolved(_)) {
+                continue;
+            }
+
+            let proposals: Vec<_> = self
+                .lookup(&hunk.preimage_key())?
+                .into_iter()
+                .filter(|r| r.matches(&hunk))
+                .map(|r| r.resolution)
+                .collect();
+
+            if !proposals.is_empty() {
+                session.propose(hunk.id, proposals)?;
+                suggested += 1;
+            }
+        }
+
+        Ok(suggested)
+    }
+
+    /// Records every resolved hunk of a session, returning how many were new.
+    pub fn record_session(&self, session: &MergeSession) -> Result<usize, CliError> {
+        let path = &session.input().left.path;
+        let mut added = 0;
+
+        for hunk in session.hunks() {
+            if let HunkState::Resolved(resolution) = &hunk.state {
+                let entry = RecordedResolution::from_hunk(hunk, resolution.clone());
+                if self.record(path, entry)? {
+                    added += 1;
+                }
+            }
+        }
+
+        Ok(added)
+    }
+
+    /// Reads the entry file for a key, if present.
+    fn read_entry(&self, key: &str) -> Result<Option<String>, CliError> {
+        let name = entry_name(key);
+        match self {
+            Self::Directory(dir) => match std::fs::read_to_string(dir.join(name)) {
+                Ok(text) => Ok(Some(text)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            Self::GitRef { repo, refname } => Ok(repo.read_ref_file(refname, &name)?),
+        }
+    }
+
+    /// Writes the entry file for a key.
+    fn write_entry(&self, key: &str, text: &str) -> Result<(), CliError> {
+        let name = entry_name(key);
+        match self {
+            Self::Directory(dir) => {
+                std::fs::create_dir_all(dir)?;
+                std::fs::write(dir.join(name), text)?;
+            }
+            Self::GitRef { repo, refname } => {
+                repo.write_ref_file(refname, &name, text, &format!("weavr: record {key}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the file name of a store entry.
+fn entry_name(key: &str) -> String {
+    format!("{key}.patch")
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use weavr_core::{HunkId, Resolution};
+
+    use super::*;
+
+    const CONFLICT: &str = "a\n<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> f\nz";
+
+    fn temp_store() -> (TempDir, SharedStore) {
+        let dir = TempDir::new().unwrap();
+        let store = SharedStore::Directory(dir.path().join("store"));
+        (dir, store)
+    }
+
+    fn session() -> MergeSession {
+        MergeSession::from_conflicted(CONFLICT, PathBuf::from("src/lib.rs")).unwrap()
+    }
+
+    #[test]
+    fn recorded_resolutions_are_suggested() {
+        let (_dir, store) = temp_store();
+        let mut resolved = session();
+        resolved
+            .set_resolution(HunkId(0), Resolution::manual("l\nr".to_string()))
+            .unwrap();
+        assert_eq!(store.record_session(&resolved).unwrap(), 1);
+
+        let mut fresh = session();
+        assert_eq!(store.suggest(&mut fresh).unwrap(), 1);
+        assert!(matches!(
+            &fresh.hunks()[0].state,
+            HunkState::Proposed(p) if p[0].content == "l\nr"
+        ));
+        // Suggestions are never applied on their own
+        assert!(fresh.resolutions().is_empty());
+    }
+
+    #[test]
+    fn recording_same_content_twice_is_skipped() {
+        let (_dir, store) = temp_store();
+        let mut resolved = session();
+        let hunk = resolved.hunks()[0].clone();
+        resolved
+            .set_resolution(hunk.id, Resolution::accept_left(&hunk))
+            .unwrap();
+
+        assert_eq!(store.record_session(&resolved).unwrap(), 1);
+        assert_eq!(store.record_session(&resolved).unwrap(), 0);
+
+        resolved
+            .set_resolution(hunk.id, Resolution::accept_right(&hunk))
+            .unwrap();
+        assert_eq!(store.record_session(&resolved).unwrap(), 1);
+
+        // Newest resolution is suggested first
+        let recorded = store.lookup(&hunk.preimage_key()).unwrap();
+        let contents: Vec<_> = recorded
+            .iter()
+            .map(|r| r.resolution.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["r", "l"]);
+    }
+
+    #[test]
+    fn empty_store_suggests_nothing() {
+        let (_dir, store) = temp_store();
+        let mut fresh = session();
+        assert_eq!(store.suggest(&mut fresh).unwrap(), 0);
+        assert!(matches!(fresh.hunks()[0].state, HunkState::Unresolved));
+    }
+}
@@ -1,11 +1,32 @@
 //! TUI mode implementation.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 
-use weavr_core::MergeSession;
+use weavr_ai::SuggestionProvider;
+use weavr_core::{
+    ConflictHunk, ConflictStyle, ConflictTree, HunkState, MergeSession, ResolutionBreakdown,
+    ValidationProfile, SIMILARITY_THRESHOLD,
+};
+use weavr_git::GitRepo;
+use weavr_tui::notify::Notifier;
+use weavr_tui::revision::AlternateRevision;
 use weavr_tui::App;
 
+use crate::config::{LanguageSettings, ResolveConfig, ValidationSettings};
 use crate::error::CliError;
+use crate::formatter;
+use crate::headless;
+use crate::history::{HistoryEntry, SessionMode};
+use crate::replay::{self, ReplaySource};
+use crate::rerere::ResolutionMemory;
+use crate::resume::SessionSidecar;
+use crate::store::SharedStore;
+use crate::validate;
+
+/// Number of incoming commits offered by the revision picker.
+const ALTERNATE_REVISIONS: usize = 10;
 
 /// Result of TUI processing for a single file.
 pub struct TuiResult {
@@ -15,61 +36,340 @@ pub struct TuiResult {
     pub hunks_resolved: usize,
     /// Total number of hunks in the file.
     pub total_hunks: usize,
+    /// How the resolved hunks were resolved.
+    pub breakdown: ResolutionBreakdown,
+    /// Annotated patch of the resolved hunks, for review.
+    pub patch: String,
+    /// HTML section showing the hunks and their resolutions, for review.
+    pub review: String,
+    /// History record, set when the file was fully resolved.
+    pub history: Option<HistoryEntry>,
+    /// Hunks resolved in this file, for suggesting resolutions in later files.
+    pub resolved_hunks: Vec<ConflictHunk>,
 }
 
 /// Runs the TUI for a single file.
 ///
 /// Returns the resolution result after the user quits the TUI.
-pub fn process_file(path: &Path) -> Result<TuiResult, CliError> {
+/// Progress saved in the `sidecar` is resumed, and resolutions from
+/// `replay` and those remembered in `memory` are applied before the TUI
+/// opens, and the shared `store` and hunks similar to ones
+/// resolved `earlier` in this run suggest resolutions for the rest. `resolve` controls how a resolution
+/// carries over to identical hunks and which commands test and check the
+/// result. `overview` covers every file in the merge and backs the heatmap,
+/// and `language` shapes accept-both and formats the result. Once the file
+/// is fully resolved, its resolutions are recorded in the store and the
+/// memory, and the `validation` profile's checks are reported; only leftover markers, if
+/// checked, keep the file from being written. The `notifier` alerts when a
+/// long test or check run finishes, and the `ai` provider answers `s`.
+#[allow(clippy::too_many_arguments)] // One per source of settings
+pub fn process_file(
+    path: &Path,
+    replay: Option<&ReplaySource>,
+    memory: Option<&ResolutionMemory>,
+    sidecar: Option<&SessionSidecar>,
+    store: Option<&SharedStore>,
+    earlier: &[ConflictHunk],
+    resolve: &ResolveConfig,
+    overview: &ConflictTree,
+    language: &LanguageSettings,
+    validation: &ValidationSettings,
+    notifier: Notifier,
+    ai: Option<&Arc<dyn SuggestionProvider>>,
+) -> Result<TuiResult, CliError> {
     let content = std::fs::read_to_string(path)?;
-    let session = MergeSession::from_conflicted(&content, path.to_path_buf())?;
+    let session = MergeSession::from_conflicted(content, path.to_path_buf())?;
+    process_session(
+        session, path, replay, memory, sidecar, store, earlier, resolve, overview, language,
+        validation, notifier, ai,
+    )
+}
+
+/// Resumes saved progress, applies replayed and remembered resolutions and
+/// proposes resolutions from the store and from hunks resolved `earlier`,
+/// returning a message telling the user about them.
+fn prefill(
+    session: &mut MergeSession,
+    replay: Option<&ReplaySource>,
+    memory: Option<&ResolutionMemory>,
+    sidecar: Option<&SessionSidecar>,
+    store: Option<&SharedStore>,
+    earlier: &[ConflictHunk],
+) -> Result<Option<String>, CliError> {
+    let resumed = sidecar
+        .map(|sidecar| sidecar.restore(session))
+        .transpose()?
+        .map_or(0, |hunks| hunks.len());
+    let replay_summary = replay
+        .map(|source| source.replay(session))
+        .transpose()?
+        .map(|report| replay::summarize(&report));
+    let recalled = memory
+        .map(|memory| memory.recall(session))
+        .transpose()?
+        .map_or(0, |hunks| hunks.len());
+    let suggested = store
+        .map(|store| store.suggest(session))
+        .transpose()?
+        .unwrap_or(0);
+    let similar = session.propose_from_similar(earlier, SIMILARITY_THRESHOLD)?;
 
+    Ok(if let Some(summary) = replay_summary {
+        Some(format!("Replay: {summary}"))
+    } else if resumed > 0 {
+        Some(format!("Resumed saved session: {resumed} hunks resolved"))
+    } else if recalled > 0 {
+        Some(format!(
+            "{recalled} hunks resolved as remembered - review them, x clears"
+        ))
+    } else if suggested > 0 {
+        Some(format!(
+            "{suggested} hunks have shared suggestions - press a to accept"
+        ))
+    } else if similar > 0 {
+        Some(format!(
+            "{similar} hunks resemble ones resolved earlier - press a to accept"
+        ))
+    } else {
+        None
+    })
+}
+
+/// Runs the TUI for a session already built for `path`.
+///
+/// Takes the same settings as [`process_file`].
+#[allow(clippy::too_many_arguments)] // One per source of settings
+pub fn process_session(
+    mut session: MergeSession,
+    path: &Path,
+    replay: Option<&ReplaySource>,
+    memory: Option<&ResolutionMemory>,
+    sidecar: Option<&SessionSidecar>,
+    store: Option<&SharedStore>,
+    earlier: &[ConflictHunk],
+    resolve: &ResolveConfig,
+    overview: &ConflictTree,
+    language: &LanguageSettings,
+    validation: &ValidationSettings,
+    notifier: Notifier,
+    ai: Option<&Arc<dyn SuggestionProvider>>,
+) -> Result<TuiResult, CliError> {
     // Handle files without conflicts (already clean)
     if session.hunks().is_empty() {
         return Ok(TuiResult {
-            content: Some(content),
+            content: Some(session.conflicted().to_string()),
             hunks_resolved: 0,
             total_hunks: 0,
+            breakdown: ResolutionBreakdown::default(),
+            patch: String::new(),
+            review: String::new(),
+            history: None,
+            resolved_hunks: Vec::new(),
         });
     }
 
     let total_hunks = session.hunks().len();
 
     // Create and configure App
+    let message = prefill(&mut session, replay, memory, sidecar, store, earlier)?;
+    let quick_actions = resolve.quick_actions()?;
     let mut app = App::new();
     app.set_session(session);
+    configure(&mut app, path, resolve, overview, language);
+    app.set_quick_actions(quick_actions);
+    app.set_notifier(notifier);
+    if let Some(provider) = ai {
+        app.set_suggestion_provider(Arc::clone(provider));
+    }
+    if let Some(message) = message {
+        app.set_status_message(&message);
+    }
 
     // Run TUI event loop
+    let started = Instant::now();
     weavr_tui::run(&mut app)?;
 
     // Extract session and check resolution state
+    let verified = app.is_verified();
     let session = app
         .take_session()
         .ok_or_else(|| std::io::Error::other("merge session unexpectedly missing after TUI run"))?;
-    let resolved_count = session
+    let breakdown = ResolutionBreakdown::of_hunks(session.hunks());
+    let review_patch = weavr_core::format_resolution_patch(&session);
+    let review = weavr_core::format_review_html(&session);
+    let resolved_hunks: Vec<ConflictHunk> = session
         .hunks()
         .iter()
-        .filter(|h| matches!(h.state, weavr_core::HunkState::Resolved(_)))
-        .count();
+        .filter(|h| matches!(h.state, HunkState::Resolved(_)))
+        .cloned()
+        .collect();
 
     if session.is_fully_resolved() {
+        record(&session, store, memory, sidecar);
+
+        let mut history =
+            HistoryEntry::from_session(&session, SessionMode::Interactive, started.elapsed());
+        history.verified = verified;
+
         // Complete the lifecycle to get the merged content
         let mut session = session;
+        // Balance and duplicate problems are reported as warnings below
         session.apply()?;
-        session.validate()?;
+        session.validate_with(&ValidationProfile {
+            balance: false,
+            duplicates: false,
+            ..validation.checks
+        })?;
         let result = session.complete()?;
+        let content = formatter::format(language.formatter.as_deref(), path, result.content);
+        let mut warnings = result.warnings;
+        warnings.extend(validate::run_commands(validation, language, path, &content));
+        headless::report_warnings(path, &warnings);
 
         Ok(TuiResult {
-            content: Some(result.content),
+            content: Some(content),
             hunks_resolved: result.summary.resolved_hunks,
             total_hunks,
+            breakdown,
+            patch: review_patch,
+            review,
+            history: Some(history),
+            resolved_hunks,
         })
     } else {
-        // User quit without resolving all hunks
+        // User quit without resolving all hunks; keep the progress for next time
+        if let Some(sidecar) = sidecar {
+            match sidecar.save(&session) {
+                Ok(()) if breakdown.total() > 0 => {
+                    eprintln!(
+                        "{}: progress saved, run weavr again to resume",
+                        path.display()
+                    );
+                }
+                Ok(()) => {}
+                Err(e) => eprintln!("weavr: could not save session: {e}"),
+            }
+        }
         Ok(TuiResult {
             content: None,
-            hunks_resolved: resolved_count,
+            hunks_resolved: breakdown.total(),
             total_hunks,
+            breakdown,
+            patch: review_patch,
+            review,
+            history: None,
+            resolved_hunks,
         })
     }
 }
+
+/// Records the resolutions of a fully resolved session in the store and
+/// the memory, and removes its saved progress. Failures are reported but
+/// must not lose the merge itself.
+fn record(
+    session: &MergeSession,
+    store: Option<&SharedStore>,
+    memory: Option<&ResolutionMemory>,
+    sidecar: Option<&SessionSidecar>,
+) {
+    if let Some(store) = store {
+        if let Err(e) = store.record_session(session) {
+            eprintln!("weavr: could not record resolutions in shared store: {e}");
+        }
+    }
+    if let Some(memory) = memory {
+        if let Err(e) = memory.remember(session) {
+            eprintln!("weavr: could not remember resolutions: {e}");
+        }
+    }
+    if let Some(sidecar) = sidecar {
+        if let Err(e) = sidecar.discard() {
+            eprintln!("weavr: could not remove saved session: {e}");
+        }
+    }
+}
+
+/// Applies the settings and repository context for `path` to the app.
+fn configure(
+    app: &mut App,
+    path: &Path,
+    resolve: &ResolveConfig,
+    overview: &ConflictTree,
+    language: &LanguageSettings,
+) {
+    app.set_propagation(resolve.propagate);
+    if let Some(command) = &resolve.test_command {
+        app.set_test_command(command.clone());
+    }
+    if let Some(command) = &resolve.check_command {
+        app.set_check_command(command.clone());
+    }
+    if let Some(command) = &resolve.diff_tool {
+        app.set_diff_tool(command.clone());
+    }
+    app.set_overview(overview.clone());
+    if let Some(head) = pre_merge_version(path) {
+        app.set_head_version(head);
+    }
+    app.set_alternate_revisions(alternate_revisions(path));
+    app.set_snippets(resolve.snippets.clone());
+    app.set_confirm_write(!resolve.skip_final_diff);
+    app.set_context_lines(resolve.context_lines);
+    app.set_language_heuristics(language.heuristics.clone());
+    if let Some(style) = configured_conflict_style(path) {
+        // zdiff3 markers look like diff3, so only git's setting tells them apart
+        if style == ConflictStyle::Zdiff3 && app.conflict_style() == ConflictStyle::Diff3 {
+            app.set_conflict_style(style);
+        }
+    }
+}
+
+/// Reads the `merge.conflictStyle` git is configured with for `path`.
+fn configured_conflict_style(path: &Path) -> Option<ConflictStyle> {
+    let (repo, _) = locate(path)?;
+    let value = repo.config("merge.conflictStyle").ok().flatten()?;
+    ConflictStyle::parse(&value)
+}
+
+/// Reads our side's version of `path` from before the merge, if available.
+fn pre_merge_version(path: &Path) -> Option<String> {
+    let (repo, relative) = locate(path)?;
+    repo.pre_merge_version(&relative).ok().flatten()
+}
+
+/// Reads earlier versions of `path` from the incoming branch, newest first,
+/// for taking a hunk's right side from another commit.
+fn alternate_revisions(path: &Path) -> Vec<AlternateRevision> {
+    let Some((repo, relative)) = locate(path) else {
+        return Vec::new();
+    };
+    let Some(head) = repo.current_operation().incoming_head() else {
+        return Vec::new();
+    };
+    // The incoming side may have the file under another name
+    let Ok(Some(relative)) = repo.path_before(head, "HEAD", &relative) else {
+        return Vec::new();
+    };
+    let Ok(entries) = repo.path_log(head, &relative, ALTERNATE_REVISIONS) else {
+        return Vec::new();
+    };
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let content = repo.file_at(&entry.id, &relative).ok().flatten()?;
+            Some(AlternateRevision {
+                label: format!("{} {} ({})", entry.short_id, entry.summary, entry.date),
+                content,
+            })
+        })
+        .collect()
+}
+
+/// Finds the repository containing `path` and the path relative to its root.
+pub fn locate(path: &Path) -> Option<(GitRepo, PathBuf)> {
+    let absolute = std::fs::canonicalize(path).ok()?;
+    let repo = GitRepo::discover_from(absolute.parent()?).ok()?;
+    let root = std::fs::canonicalize(repo.root()).ok()?;
+    let relative = absolute.strip_prefix(root).ok()?.to_path_buf();
+    Some((repo, relative))
+}
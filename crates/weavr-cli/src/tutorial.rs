@@ -0,0 +1,187 @@
+//! Interactive tutorial.
+//!
+//! Opens the TUI on a scripted conflict file with a guide that walks through
+//! navigation, resolving, undoing, and the accept-both options. Nothing is
+//! written to disk.
+
+use std::path::PathBuf;
+
+use weavr_core::{BothOrder, HunkState, MergeSession, ResolutionStrategyKind};
+use weavr_tui::guide::{Guide, GuideStep};
+use weavr_tui::input::Dialog;
+use weavr_tui::App;
+
+use crate::error::CliError;
+
+/// The scripted file resolved during the tutorial.
+const TUTORIAL_FILE: &str = "\
+use std::collections::HashMap;
+<<<<<<< ours
+use std::fs;
+=======
+use std::io;
+>>>>>>> theirs
+
+<<<<<<< ours
+const VERSION: &str = \"1.4.0\";
+=======
+const VERSION: &str = \"1.3.1\";
+>>>>>>> theirs
+
+fn setup() {
+<<<<<<< ours
+    init_logging();
+=======
+    load_config();
+>>>>>>> theirs
+}";
+
+/// Returns the resolution kind of a hunk, if it is resolved.
+fn resolved_kind(app: &App, index: usize) -> Option<&ResolutionStrategyKind> {
+    match &app.session()?.hunks().get(index)?.state {
+        HunkState::Resolved(resolution) => Some(&resolution.kind),
+        _ => None,
+    }
+}
+
+/// Builds the tutorial steps, in order.
+fn steps() -> Vec<GuideStep> {
+    vec![
+        GuideStep::new(
+            "Welcome! Ours is on the left, theirs on the right, and the merged result below. \
+             Press j to move to the next conflict.",
+            |app| app.current_hunk_index() == 1,
+        ),
+        GuideStep::new("Press k to go back to the first conflict.", |app| {
+            app.current_hunk_index() == 0
+        }),
+        GuideStep::new(
+            "Each side added an import and we want both. Press b to accept both.",
+            |app| {
+                matches!(
+                    resolved_kind(app, 0),
+                    Some(ResolutionStrategyKind::AcceptBoth(_))
+                )
+            },
+        ),
+        GuideStep::new("Every action can be undone. Press u to undo it.", |app| {
+            resolved_kind(app, 0).is_none()
+        }),
+        GuideStep::new(
+            "On second thought only theirs is needed. Press t to accept theirs.",
+            |app| {
+                matches!(
+                    resolved_kind(app, 0),
+                    Some(ResolutionStrategyKind::AcceptRight)
+                )
+            },
+        ),
+        GuideStep::new("Press n to jump to the next unresolved conflict.", |app| {
+            app.current_hunk_index() == 1
+        }),
+        GuideStep::new(
+            "Our version bump is newer. Press o to accept ours.",
+            |app| {
+                matches!(
+                    resolved_kind(app, 1),
+                    Some(ResolutionStrategyKind::AcceptLeft)
+                )
+            },
+        ),
+        GuideStep::new(
+            "Press n to reach the last conflict, then B to open the accept-both options.",
+            |app| {
+                app.current_hunk_index() == 2
+                    && matches!(app.active_dialog(), Some(Dialog::AcceptBothOptions(_)))
+            },
+        ),
+        GuideStep::new(
+            "Config must load before logging starts. Press R for right first, then Enter.",
+            |app| {
+                matches!(
+                    resolved_kind(app, 2),
+                    Some(ResolutionStrategyKind::AcceptBoth(options))
+                        if options.order == BothOrder::RightThenLeft
+                )
+            },
+        ),
+        GuideStep::new(
+            "Press ? to see every key binding. Close it with Esc when you are done.",
+            |app| matches!(app.active_dialog(), Some(Dialog::Help)),
+        ),
+    ]
+}
+
+/// Runs the tutorial and reports whether every step was completed.
+pub fn run() -> Result<bool, CliError> {
+    let session = MergeSession::from_conflicted(TUTORIAL_FILE, PathBuf::from("tutorial.rs"))?;
+
+    let mut app = App::new();
+    app.set_session(session);
+    app.set_guide(Guide::new(steps()));
+
+    weavr_tui::run(&mut app)?;
+
+    Ok(app.guide().is_some_and(Guide::is_complete))
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use weavr_tui::event::handle_event;
+
+    use super::*;
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x1b' => KeyCode::Esc,
+                c => KeyCode::Char(c),
+            };
+            let modifiers = if c.is_ascii_uppercase() {
+                KeyModifiers::SHIFT
+            } else {
+                KeyModifiers::NONE
+            };
+            handle_event(app, &Event::Key(KeyEvent::new(code, modifiers)));
+        }
+    }
+
+    fn tutorial_app() -> App {
+        let session =
+            MergeSession::from_conflicted(TUTORIAL_FILE, PathBuf::from("tutorial.rs")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_guide(Guide::new(steps()));
+        app
+    }
+
+    #[test]
+    fn tutorial_file_has_three_conflicts() {
+        let session =
+            MergeSession::from_conflicted(TUTORIAL_FILE, PathBuf::from("tutorial.rs")).unwrap();
+        assert_eq!(session.hunks().len(), 3);
+    }
+
+    #[test]
+    fn scripted_keys_complete_every_step() {
+        let mut app = tutorial_app();
+        press(&mut app, "jkbutnonBR\n?");
+
+        let guide = app.guide().unwrap();
+        assert!(guide.is_complete(), "stuck at {:?}", guide.progress());
+        assert!(app.session().unwrap().is_fully_resolved());
+    }
+
+    #[test]
+    fn wrong_action_does_not_advance() {
+        let mut app = tutorial_app();
+        press(&mut app, "jk");
+        assert_eq!(app.guide().unwrap().progress().0, 2);
+
+        // Step 3 asks for accept-both; accepting ours is not accepted
+        press(&mut app, "o");
+        assert_eq!(app.guide().unwrap().progress().0, 2);
+    }
+}
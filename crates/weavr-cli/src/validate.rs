@@ -0,0 +1,81 @@
+//! Running a validation profile's commands on resolved files.
+
+use std::path::Path;
+
+use weavr_core::MergeWarning;
+
+use crate::config::{LanguageSettings, ValidationSettings};
+use crate::formatter;
+
+/// Runs the syntax check and commands of `validation` on a resolved file.
+///
+/// Returns a problem for each command that fails.
+pub fn run_commands(
+    validation: &ValidationSettings,
+    language: &LanguageSettings,
+    path: &Path,
+    content: &str,
+) -> Vec<MergeWarning> {
+    let syntax = language
+        .syntax_check
+        .as_deref()
+        .filter(|_| validation.syntax)
+        .map(|command| ("syntax check".to_string(), command));
+    let commands = validation
+        .commands
+        .iter()
+        .map(|command| (format!("`{command}`"), command.as_str()));
+
+    syntax
+        .into_iter()
+        .chain(commands)
+        .filter_map(|(name, command)| {
+            let error = formatter::run(command, path, content).err()?;
+            Some(MergeWarning {
+                message: format!("{name} failed: {error}"),
+                hunk_id: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use weavr_core::ValidationProfile;
+
+    use super::*;
+
+    #[test]
+    fn failing_commands_are_problems() {
+        let validation = ValidationSettings {
+            name: "ci".to_string(),
+            checks: ValidationProfile::basic(),
+            syntax: true,
+            commands: vec![
+                "grep -q main".to_string(),
+                "test \"$WEAVR_FILE\" = a.rs".to_string(),
+            ],
+        };
+        let language = LanguageSettings {
+            syntax_check: Some("echo 'unexpected }' >&2; exit 1".to_string()),
+            ..LanguageSettings::default()
+        };
+        let path = Path::new("a.rs");
+
+        let problems = run_commands(&validation, &language, path, "fn other() {}\n");
+        let messages: Vec<&str> = problems.iter().map(|p| p.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "syntax check failed: unexpected }",
+                "`grep -q main` failed: non-zero exit"
+            ]
+        );
+
+        let validation = ValidationSettings {
+            syntax: false,
+            ..validation
+        };
+        assert!(run_commands(&validation, &language, path, "fn main() {}\n").is_empty());
+    }
+}
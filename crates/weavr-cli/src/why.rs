@@ -0,0 +1,325 @@
+//! `weavr why`: explaining from history how a conflict came about.
+//!
+//! For each conflict in the requested lines, the commit the two sides
+//! diverged from is found, and each side's text is located in the file at
+//! its tip so that `git log -L` can list the commits that changed exactly
+//! those lines since, following the file if a side renamed it. The result
+//! is printed as a short narrative.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use weavr_core::{ConflictHunk, MergeSession};
+use weavr_git::{GitOperation, GitRepo, LogEntry};
+
+use crate::error::CliError;
+use crate::tui;
+
+/// Commits listed per side before the rest are summarized.
+const MAX_COMMITS: usize = 10;
+
+/// The commits on one side of a conflict.
+pub struct SideHistory {
+    /// How the side is named, e.g. `ours (HEAD)`.
+    pub name: String,
+    /// Commits that changed the side's lines, newest first.
+    pub commits: Vec<LogEntry>,
+    /// Whether the side's lines could not be found, so the commits are those
+    /// that changed anywhere in the file.
+    pub whole_file: bool,
+}
+
+/// Explains the conflicts of `file`, or those overlapping `lines`.
+pub fn run(file: &Path, lines: Option<&str>) -> Result<(), CliError> {
+    let range = lines.map(parse_range).transpose()?;
+    if !file.exists() {
+        return Err(CliError::FileNotFound(file.to_path_buf()));
+    }
+    let content = std::fs::read_to_string(file)?;
+    let session = MergeSession::from_conflicted(content, file.to_path_buf())?;
+
+    let (repo, relative) = tui::locate(file).ok_or_else(|| {
+        CliError::Config(format!("{} is not in a git repository", file.display()))
+    })?;
+    let operation = repo.current_operation();
+    let Some(theirs) = operation.incoming_head() else {
+        return Err(CliError::Config(
+            "no merge, rebase, cherry-pick, or revert is in progress".to_string(),
+        ));
+    };
+    let base = divergence(&repo, operation, theirs)?;
+    let base_commit = base
+        .as_deref()
+        .map(|id| repo.commit(id))
+        .transpose()?
+        .flatten();
+    // Either side may have the file under another name
+    let ours_path = repo
+        .path_before("HEAD", theirs, &relative)?
+        .unwrap_or_else(|| relative.clone());
+    let theirs_path = repo
+        .path_before(theirs, "HEAD", &relative)?
+        .unwrap_or_else(|| relative.clone());
+    let ours_text = repo.file_at("HEAD", &ours_path)?.unwrap_or_default();
+    let theirs_text = repo.file_at(theirs, &theirs_path)?.unwrap_or_default();
+
+    let hunks = session.hunks();
+    let selected: Vec<(usize, &ConflictHunk)> = hunks
+        .iter()
+        .enumerate()
+        .filter(|(_, hunk)| {
+            let (start, end) = span(hunk);
+            range.map_or(true, |(from, to)| start <= to && from <= end)
+        })
+        .collect();
+    if selected.is_empty() {
+        println!("{}: no conflicts in those lines", file.display());
+        return Ok(());
+    }
+
+    for (index, hunk) in selected {
+        let side = |name: String, tip: &str, path: &Path, text: &str, side_text: &str| {
+            let lines = find_lines(text, side_text);
+            let range = match &base {
+                Some(base) => format!("{base}..{tip}"),
+                None => tip.to_string(),
+            };
+            repo.line_log(&range, path, lines)
+                .map(|commits| SideHistory {
+                    name,
+                    commits,
+                    whole_file: lines.is_none(),
+                })
+        };
+        let sides = [
+            side(
+                "ours (HEAD)".to_string(),
+                "HEAD",
+                &ours_path,
+                &ours_text,
+                &hunk.left.text,
+            )?,
+            side(
+                format!("theirs ({theirs})"),
+                theirs,
+                &theirs_path,
+                &theirs_text,
+                &hunk.right.text,
+            )?,
+        ];
+        print!(
+            "{}",
+            narrate(
+                file,
+                (index, hunks.len()),
+                span(hunk),
+                base_commit.as_ref(),
+                &sides
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Returns the commit the incoming change is compared against: the merge
+/// base for a merge, or the parent of the commit being applied otherwise.
+fn divergence(
+    repo: &GitRepo,
+    operation: GitOperation,
+    theirs: &str,
+) -> Result<Option<String>, CliError> {
+    Ok(match operation {
+        GitOperation::Merge => repo.merge_base("HEAD", theirs)?,
+        _ => repo.parents(theirs)?.into_iter().next(),
+    })
+}
+
+/// Parses a 1-based line range such as `12-20` or `12`.
+pub fn parse_range(text: &str) -> Result<(usize, usize), CliError> {
+    let invalid = || CliError::Config(format!("invalid line range '{text}' (expected N or N-M)"));
+    let (start, end) = text.split_once('-').unwrap_or((text, text));
+    let start: usize = start.trim().parse().map_err(|_| invalid())?;
+    let end: usize = end.trim().parse().map_err(|_| invalid())?;
+    if start == 0 || end < start {
+        return Err(invalid());
+    }
+    Ok((start, end))
+}
+
+/// Returns the 1-based lines of a conflict's start and end markers.
+pub fn span(hunk: &ConflictHunk) -> (usize, usize) {
+    let start = hunk.context.start_line_left.saturating_sub(1);
+    let end = hunk.context.start_line_right + hunk.right.text.lines().count();
+    (start, end)
+}
+
+/// Finds the 1-based inclusive lines of `needle` in `haystack`, or `None`
+/// if it is empty or does not appear line for line.
+pub fn find_lines(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    let needle: Vec<&str> = needle.lines().collect();
+    if needle.is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = haystack.lines().collect();
+    lines
+        .windows(needle.len())
+        .position(|window| window == needle.as_slice())
+        .map(|start| (start + 1, start + needle.len()))
+}
+
+/// Tells how one conflict came about.
+pub fn narrate(
+    path: &Path,
+    (index, total): (usize, usize),
+    (start, end): (usize, usize),
+    base: Option<&LogEntry>,
+    sides: &[SideHistory; 2],
+) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{}: conflict {} of {total} (lines {start}-{end})",
+        path.display(),
+        index + 1
+    );
+    match base {
+        Some(base) => {
+            let _ = writeln!(
+                out,
+                "  The sides diverged at {} \"{}\" ({}, {}).",
+                base.short_id, base.summary, base.author, base.date
+            );
+        }
+        None => {
+            let _ = writeln!(out, "  The sides share no history.");
+        }
+    }
+
+    for side in sides {
+        let what = if side.whole_file {
+            "the file"
+        } else {
+            "these lines"
+        };
+        if side.commits.is_empty() {
+            let _ = writeln!(
+                out,
+                "  {} did not change {what} since.",
+                capitalize(&side.name)
+            );
+            continue;
+        }
+        let noun = if side.commits.len() == 1 {
+            "commit"
+        } else {
+            "commits"
+        };
+        let _ = writeln!(
+            out,
+            "  {} changed {what} in {} {noun}:",
+            capitalize(&side.name),
+            side.commits.len()
+        );
+        for commit in side.commits.iter().take(MAX_COMMITS) {
+            let _ = writeln!(
+                out,
+                "    {} {} ({}, {})",
+                commit.short_id, commit.summary, commit.author, commit.date
+            );
+        }
+        if side.commits.len() > MAX_COMMITS {
+            let _ = writeln!(out, "    ... and {} more", side.commits.len() - MAX_COMMITS);
+        }
+    }
+
+    let summary = match (sides[0].commits.first(), sides[1].commits.first()) {
+        (Some(ours), Some(theirs)) => format!(
+            "Both sides changed the same lines, most recently {} by {} and {} by {}, so git could not choose between them.",
+            ours.date, ours.author, theirs.date, theirs.author
+        ),
+        (Some(_), None) | (None, Some(_)) => {
+            "Only one side changed these lines; the conflict comes from edits next to them on the other side.".to_string()
+        }
+        (None, None) => "Neither side changed these lines; the conflict comes from edits around them.".to_string(),
+    };
+    let _ = writeln!(out, "  {summary}\n");
+    out
+}
+
+/// Uppercases the first letter of `text`.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(short_id: &str, author: &str, date: &str, summary: &str) -> LogEntry {
+        LogEntry {
+            id: format!("{short_id}0000"),
+            short_id: short_id.to_string(),
+            author: author.to_string(),
+            date: date.to_string(),
+            summary: summary.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_line_ranges() {
+        assert_eq!(parse_range("12").unwrap(), (12, 12));
+        assert_eq!(parse_range("12-20").unwrap(), (12, 20));
+        assert!(parse_range("20-12").is_err());
+        assert!(parse_range("0").is_err());
+        assert!(parse_range("a-b").is_err());
+    }
+
+    #[test]
+    fn find_side_lines() {
+        let text = "a\nb\nc\nb\n";
+        assert_eq!(find_lines(text, "b\nc"), Some((2, 3)));
+        assert_eq!(find_lines(text, "c\nd"), None);
+        assert_eq!(find_lines(text, ""), None);
+    }
+
+    #[test]
+    fn narrative_names_commits_on_each_side() {
+        let base = commit("abc1234", "Ada", "3 weeks ago", "Initial");
+        let sides = [
+            SideHistory {
+                name: "ours (HEAD)".to_string(),
+                commits: vec![commit("def5678", "Bob", "2 days ago", "Rename parse")],
+                whole_file: false,
+            },
+            SideHistory {
+                name: "theirs (MERGE_HEAD)".to_string(),
+                commits: vec![commit("9876fed", "Cy", "5 days ago", "Add option")],
+                whole_file: true,
+            },
+        ];
+        let out = narrate(
+            Path::new("src/lib.rs"),
+            (0, 2),
+            (12, 20),
+            Some(&base),
+            &sides,
+        );
+        assert_eq!(
+            out,
+            "\
+src/lib.rs: conflict 1 of 2 (lines 12-20)
+  The sides diverged at abc1234 \"Initial\" (Ada, 3 weeks ago).
+  Ours (HEAD) changed these lines in 1 commit:
+    def5678 Rename parse (Bob, 2 days ago)
+  Theirs (MERGE_HEAD) changed the file in 1 commit:
+    9876fed Add option (Cy, 5 days ago)
+  Both sides changed the same lines, most recently 2 days ago by Bob and 5 days ago by Cy, so git could not choose between them.
+
+"
+        );
+    }
+}
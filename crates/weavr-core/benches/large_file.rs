@@ -0,0 +1,105 @@
+//! Timings and memory for a large conflicted file: a generated SQL dump of
+//! about 300 MB with 4,000 hunks, and a smaller one with 20,000.
+//!
+//! Memory is the peak resident size a step adds on top of what was already
+//! resident, read from `/proc/self/status`, so it is only shown on Linux.
+//! Opening a session takes the dump by value, so it should add about as
+//! much as parsing does rather than another copy of the file; applying
+//! builds the merged output, so it adds about the size of the dump.
+//!
+//! Run with `cargo bench -p weavr-core --bench large_file`. The sizes can be
+//! changed with `HUNKS` and `ROWS` (clean lines between hunks).
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use weavr_core::{parse_conflict_markers, MergeSession, Resolution};
+
+/// Generates a dump with `hunks` conflicts, `rows` clean lines before each.
+fn dump(hunks: usize, rows: usize) -> String {
+    let mut content = String::new();
+    for hunk in 0..hunks {
+        for row in 0..rows {
+            writeln!(
+                content,
+                "INSERT INTO events VALUES ({hunk}, {row}, 'generated row payload');"
+            )
+            .unwrap();
+        }
+        writeln!(
+            content,
+            "<<<<<<< HEAD\nUPDATE seq SET n = {hunk};\n=======\nUPDATE seq SET n = {};\n>>>>>>> topic",
+            hunk + 1
+        )
+        .unwrap();
+    }
+    content
+}
+
+/// Reads a `/proc/self/status` field such as `VmRSS`, in kB.
+fn status_kb(field: &str) -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with(field))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Resets the peak resident size so `VmHWM` covers only what follows.
+fn reset_peak() -> bool {
+    fs::write("/proc/self/clear_refs", "5").is_ok()
+}
+
+/// Runs `f` and prints how long it took and how much memory it added.
+fn time<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let before = status_kb("VmRSS").filter(|_| reset_peak());
+    let start = Instant::now();
+    let value = f();
+    let elapsed = start.elapsed();
+    let memory = match (before, status_kb("VmHWM")) {
+        (Some(before), Some(peak)) => format!("{:>8} kB", peak.saturating_sub(before)),
+        _ => format!("{:>11}", "n/a"),
+    };
+    println!(
+        "  {name:<24} {:>10.1} ms {memory}",
+        elapsed.as_secs_f64() * 1000.0
+    );
+    value
+}
+
+fn run(hunks: usize, rows: usize) {
+    let content = dump(hunks, rows);
+    println!(
+        "{hunks} hunks, {rows} rows between, {} MB",
+        content.len() / 1_000_000
+    );
+    println!("  {:<24} {:>13} {:>11}", "step", "time", "peak added");
+
+    time("parse", || parse_conflict_markers(&content).unwrap());
+    let mut session = time("open session", || {
+        MergeSession::from_conflicted(content, PathBuf::from("dump.sql")).unwrap()
+    });
+    time("preview line ranges", || session.preview_line_ranges());
+    let resolutions: Vec<_> = session
+        .hunks()
+        .iter()
+        .map(|hunk| (hunk.id, Resolution::accept_right(hunk)))
+        .collect();
+    time("resolve every hunk", || {
+        for (id, resolution) in resolutions {
+            session.set_resolution(id, resolution).unwrap();
+        }
+    });
+    time("apply", || session.apply().unwrap());
+}
+
+fn main() {
+    let var = |name: &str| env::var(name).ok().and_then(|value| value.parse().ok());
+    if let (Some(hunks), Some(rows)) = (var("HUNKS"), var("ROWS")) {
+        run(hunks, rows);
+    } else {
+        run(4_000, 1_250);
+        run(20_000, 10);
+    }
+}
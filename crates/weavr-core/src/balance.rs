@@ -0,0 +1,365 @@
+//! Bracket and string balance checks for resolutions.
+//!
+//! A resolution that keeps one side's opening line and the other side's
+//! body, or accepts both sides of a changed function header, leaves a block
+//! unclosed or closes one twice. Counting brackets in the resolution and in
+//! each side catches most of these: a correct resolution nearly always has
+//! the same balance as at least one side.
+//!
+//! Comments and strings are skipped using the comment and quote syntax of
+//! the file's language, recognized by extension. Files in unknown languages
+//! are not checked.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use std::path::Path;
+
+use crate::hunk::ConflictHunk;
+
+/// Comment and string syntax of a language, as far as balance checks need.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syntax {
+    /// Line comment introducers, e.g. `//` or `#`.
+    pub line_comments: &'static [&'static str],
+    /// Block comment delimiters, e.g. `/*` and `*/`.
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// Characters delimiting strings.
+    pub quotes: &'static [u8],
+    /// Whether `'` starts a character literal (`'x'`, `'\n'`) rather than a
+    /// string; a `'` that is not one (a Rust lifetime) is ignored.
+    pub char_literals: bool,
+}
+
+const C_LIKE: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"",
+    char_literals: true,
+};
+
+const SCRIPT: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"'`",
+    char_literals: false,
+};
+
+const HASH: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: b"\"'",
+    char_literals: false,
+};
+
+const CONFIG: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: b"\"",
+    char_literals: false,
+};
+
+const JSON: Syntax = Syntax {
+    line_comments: &[],
+    block_comment: None,
+    quotes: b"\"",
+    char_literals: false,
+};
+
+const DASH: Syntax = Syntax {
+    line_comments: &["--"],
+    block_comment: None,
+    quotes: b"\"'",
+    char_literals: false,
+};
+
+const CSS: Syntax = Syntax {
+    line_comments: &[],
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"'",
+    char_literals: false,
+};
+
+impl Syntax {
+    /// Returns the syntax for a file, by extension.
+    #[must_use]
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        let syntax = match extension {
+            "rs" | "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "cs" | "java" | "kt" | "kts"
+            | "go" | "scala" | "swift" | "dart" => C_LIKE,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "php" => SCRIPT,
+            "py" | "pyi" | "rb" | "sh" | "bash" | "zsh" | "pl" | "r" => HASH,
+            "toml" | "yaml" | "yml" | "nix" => CONFIG,
+            "json" => JSON,
+            "lua" | "sql" | "hs" => DASH,
+            "css" | "scss" | "less" => CSS,
+            _ => return None,
+        };
+        Some(syntax)
+    }
+}
+
+/// Net bracket counts of a piece of text: openings minus closings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Balance {
+    /// Net `(` count.
+    pub parens: i32,
+    /// Net `[` count.
+    pub brackets: i32,
+    /// Net `{` count.
+    pub braces: i32,
+    /// Whether the text ends inside a string or block comment.
+    pub unterminated: bool,
+}
+
+/// Where the scanner is.
+#[derive(Clone, Copy)]
+enum State {
+    Code,
+    LineComment,
+    BlockComment,
+    Str(u8),
+}
+
+impl Balance {
+    /// Scans `text`, skipping comments and strings.
+    #[must_use]
+    pub fn of(text: &str, syntax: &Syntax) -> Self {
+        let bytes = text.as_bytes();
+        let mut balance = Self::default();
+        let mut state = State::Code;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let rest = &bytes[i..];
+            let b = bytes[i];
+            match state {
+                State::Code => {
+                    if let Some((open, _)) = syntax
+                        .block_comment
+                        .filter(|(o, _)| rest.starts_with(o.as_bytes()))
+                    {
+                        state = State::BlockComment;
+                        i += open.len();
+                        continue;
+                    }
+                    if syntax
+                        .line_comments
+                        .iter()
+                        .any(|c| rest.starts_with(c.as_bytes()))
+                    {
+                        state = State::LineComment;
+                    } else if b == b'\'' && syntax.char_literals {
+                        i += char_literal_len(rest);
+                        continue;
+                    } else if syntax.quotes.contains(&b) {
+                        state = State::Str(b);
+                    } else {
+                        balance.count(b);
+                    }
+                }
+                State::LineComment => {
+                    if b == b'\n' {
+                        state = State::Code;
+                    }
+                }
+                State::BlockComment => {
+                    if let Some((_, close)) = syntax
+                        .block_comment
+                        .filter(|(_, c)| rest.starts_with(c.as_bytes()))
+                    {
+                        state = State::Code;
+                        i += close.len();
+                        continue;
+                    }
+                }
+                State::Str(quote) => {
+                    if b == b'\\' {
+                        i += 2;
+                        continue;
+                    }
+                    if b == quote {
+                        state = State::Code;
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        balance.unterminated = matches!(state, State::BlockComment | State::Str(_));
+        balance
+    }
+
+    fn count(&mut self, b: u8) {
+        match b {
+            b'(' => self.parens += 1,
+            b')' => self.parens -= 1,
+            b'[' => self.brackets += 1,
+            b']' => self.brackets -= 1,
+            b'{' => self.braces += 1,
+            b'}' => self.braces -= 1,
+            _ => {}
+        }
+    }
+
+    /// Describes how this balance differs from `expected`, or `None` if it
+    /// does not.
+    fn describe_difference(&self, expected: &Self) -> Option<String> {
+        let mut parts = Vec::new();
+        for (ours, theirs, open, close) in [
+            (self.braces, expected.braces, '{', '}'),
+            (self.parens, expected.parens, '(', ')'),
+            (self.brackets, expected.brackets, '[', ']'),
+        ] {
+            let diff = ours - theirs;
+            if diff > 0 {
+                parts.push(format!("{diff} unclosed '{open}'"));
+            } else if diff < 0 {
+                parts.push(format!("{} extra '{close}'", -diff));
+            }
+        }
+        if self.unterminated && !expected.unterminated {
+            parts.push("unterminated string or comment".to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    fn distance(&self, other: &Self) -> u32 {
+        self.parens.abs_diff(other.parens)
+            + self.brackets.abs_diff(other.brackets)
+            + self.braces.abs_diff(other.braces)
+            + u32::from(self.unterminated != other.unterminated)
+    }
+}
+
+/// Length of a character literal at the start of `bytes`, or 1 if the `'`
+/// does not start one.
+fn char_literal_len(bytes: &[u8]) -> usize {
+    // An escape: '\n', '\'', '\u{1F600}'
+    if bytes.get(1) == Some(&b'\\') {
+        return bytes
+            .iter()
+            .enumerate()
+            .skip(3)
+            .take(10)
+            .take_while(|(_, &b)| b != b'\n')
+            .find(|(_, &b)| b == b'\'')
+            .map_or(1, |(end, _)| end + 1);
+    }
+    // A single character, possibly several bytes long
+    let window = &bytes[1..bytes.len().min(6)];
+    let text = match std::str::from_utf8(window) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&window[..e.valid_up_to()]).unwrap_or_default(),
+    };
+    let mut chars = text.char_indices();
+    match (chars.next(), chars.next()) {
+        (Some((_, c)), Some((end, '\''))) if c != '\'' && c != '\n' => end + 2,
+        _ => 1,
+    }
+}
+
+/// Checks a resolution of `hunk` against the balance of its sides.
+///
+/// Returns a description of the imbalance if the resolution's balance
+/// matches neither side (nor the base), comparing against the closest one.
+#[must_use]
+pub fn check_balance(hunk: &ConflictHunk, content: &str, syntax: &Syntax) -> Option<String> {
+    let resolved = Balance::of(content, syntax);
+    let mut sides = vec![
+        ("ours", Balance::of(&hunk.left.text, syntax)),
+        ("theirs", Balance::of(&hunk.right.text, syntax)),
+    ];
+    if let Some(base) = &hunk.base {
+        sides.push(("base", Balance::of(&base.text, syntax)));
+    }
+    if sides.iter().any(|(_, side)| *side == resolved) {
+        return None;
+    }
+
+    let (name, closest) = sides
+        .iter()
+        .min_by_key(|(_, side)| resolved.distance(side))?;
+    resolved
+        .describe_difference(closest)
+        .map(|difference| format!("{difference} compared to {name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hunk::{HunkContent, HunkContext, HunkId, HunkState};
+
+    fn hunk(left: &str, right: &str) -> ConflictHunk {
+        ConflictHunk {
+            id: HunkId(0),
+            left: HunkContent {
+                text: left.to_string(),
+            },
+            right: HunkContent {
+                text: right.to_string(),
+            },
+            base: None,
+            context: HunkContext::default(),
+            state: HunkState::default(),
+        }
+    }
+
+    fn rust() -> Syntax {
+        Syntax::for_path(Path::new("a.rs")).unwrap()
+    }
+
+    #[test]
+    fn skips_comments_strings_and_char_literals() {
+        let text = "fn a<'a>(x: &'a str) {\n    // }\n    let s = \"}\\\"\";\n    let c = '{';\n    let e = '\\'';\n    /* ) */\n";
+        assert_eq!(
+            Balance::of(text, &rust()),
+            Balance {
+                braces: 1,
+                ..Balance::default()
+            }
+        );
+
+        let python = Syntax::for_path(Path::new("a.py")).unwrap();
+        assert_eq!(
+            Balance::of("x = f('(', \"[\")  # ]\n", &python),
+            Balance::default()
+        );
+        assert!(Balance::of("s = \"open\n", &python).unterminated);
+    }
+
+    #[test]
+    fn accepting_both_headers_is_flagged() {
+        let hunk = hunk("fn run(a: u32) {", "fn run(a: u64) {");
+        assert_eq!(check_balance(&hunk, "fn run(a: u64) {", &rust()), None);
+        assert_eq!(
+            check_balance(&hunk, "fn run(a: u32) {\nfn run(a: u64) {", &rust()),
+            Some("1 unclosed '{' compared to ours".to_string())
+        );
+    }
+
+    #[test]
+    fn truncated_block_is_flagged() {
+        let hunk = hunk("if a {\n    b();\n}", "if a {\n    c();\n}");
+        assert_eq!(
+            check_balance(&hunk, "if a {\n    c();", &rust()),
+            Some("1 unclosed '{' compared to ours".to_string())
+        );
+        assert_eq!(
+            check_balance(&hunk, "    c();\n}", &rust()),
+            Some("1 extra '}' compared to ours".to_string())
+        );
+    }
+
+    #[test]
+    fn multibyte_text_is_scanned() {
+        let text = "let c = '\u{e9}'; let s = \"\\\u{e9}{\"; let d = 'é'; // ü\n";
+        assert_eq!(Balance::of(text, &rust()), Balance::default());
+    }
+
+    #[test]
+    fn unknown_languages_are_not_checked() {
+        assert_eq!(Syntax::for_path(Path::new("README.md")), None);
+        assert_eq!(Syntax::for_path(Path::new("Makefile")), None);
+    }
+}
@@ -0,0 +1,653 @@
+//! Duplicate declaration detection.
+//!
+//! Accepting both sides of a conflict where each side added the same
+//! function, the same dependency, or the same config key yields a file that
+//! declares it twice. Such a file often still looks fine in review but fails
+//! to compile or silently shadows one entry. This module lists the
+//! declarations of a file and reports names that the merge declares more
+//! often than either side did.
+//!
+//! Declarations are recognized line by line: definition keywords (`fn`,
+//! `def`, `class`, ...) in code, key paths in JSON, YAML, and TOML, and
+//! package names in `requirements.txt` and `go.mod`. The heuristics favor
+//! missing a duplicate over reporting one that is not there.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::Syntax;
+
+/// What a declaration declares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeclarationKind {
+    /// A function, type, or other definition in code.
+    Definition,
+    /// A key in a JSON, YAML, or TOML document.
+    Key,
+    /// A package in a dependency list.
+    Dependency,
+}
+
+impl fmt::Display for DeclarationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DeclarationKind::Definition => "definition",
+            DeclarationKind::Key => "key",
+            DeclarationKind::Dependency => "dependency",
+        })
+    }
+}
+
+/// A declaration found in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    /// What is declared.
+    pub kind: DeclarationKind,
+    /// The declared name: the signature line for definitions, the dotted
+    /// key path for keys, the package name for dependencies.
+    pub name: String,
+    /// 0-based line of the declaration.
+    pub line: usize,
+}
+
+/// A name declared more often in a merge than in either side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    /// What is declared.
+    pub kind: DeclarationKind,
+    /// The declared name.
+    pub name: String,
+    /// 0-based line of the first declaration.
+    pub first_line: usize,
+    /// 0-based line of the repeated declaration.
+    pub line: usize,
+}
+
+impl fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "duplicate {} `{}` (first on line {})",
+            self.kind,
+            self.name,
+            self.first_line + 1
+        )
+    }
+}
+
+/// Lists the declarations in `text`, choosing the format by `path`.
+///
+/// Files in unrecognized formats have no declarations.
+#[must_use]
+pub fn declarations(text: &str, path: &Path) -> Vec<Declaration> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+
+    if name == "go.mod" {
+        return go_requires(text);
+    }
+    if name.starts_with("requirements") && extension == "txt" {
+        return requirements(text);
+    }
+    match extension {
+        "json" => json_keys(text),
+        "yaml" | "yml" => yaml_keys(text),
+        "toml" => toml_keys(text),
+        _ if Syntax::for_path(path).is_some() => definitions(text),
+        _ => Vec::new(),
+    }
+}
+
+/// Finds names that `merged` declares more often than any of `sides` does.
+///
+/// Names declared several times on purpose, like overloads or methods of
+/// the same name in different types, are already repeated in the sides and
+/// are not reported. Each extra declaration is reported once, in document
+/// order.
+#[must_use]
+pub fn find_duplicates(merged: &str, sides: &[&str], path: &Path) -> Vec<Duplicate> {
+    let allowed: HashMap<(DeclarationKind, String), usize> = sides
+        .iter()
+        .map(|side| count(&declarations(side, path)))
+        .fold(HashMap::new(), |mut allowed, counts| {
+            for (key, n) in counts {
+                let entry = allowed.entry(key).or_insert(0);
+                *entry = (*entry).max(n);
+            }
+            allowed
+        });
+
+    let mut seen: HashMap<(DeclarationKind, String), (usize, usize)> = HashMap::new();
+    let mut duplicates = Vec::new();
+    for declaration in declarations(merged, path) {
+        let key = (declaration.kind, declaration.name);
+        let limit = allowed.get(&key).copied().unwrap_or(1).max(1);
+        let (first_line, n) = seen.entry(key.clone()).or_insert((declaration.line, 0));
+        *n += 1;
+        if *n > limit {
+            duplicates.push(Duplicate {
+                kind: key.0,
+                name: key.1,
+                first_line: *first_line,
+                line: declaration.line,
+            });
+        }
+    }
+    duplicates
+}
+
+fn count(declarations: &[Declaration]) -> HashMap<(DeclarationKind, String), usize> {
+    let mut counts = HashMap::new();
+    for declaration in declarations {
+        *counts
+            .entry((declaration.kind, declaration.name.clone()))
+            .or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Keywords introducing a definition.
+const DEFINITION_KEYWORDS: &[&str] = &[
+    "fn",
+    "def",
+    "function",
+    "func",
+    "class",
+    "struct",
+    "enum",
+    "trait",
+    "interface",
+    "mod",
+    "module",
+];
+
+/// Modifiers that may precede a definition.
+const MODIFIERS: &[&str] = &[
+    "pub",
+    "pub(crate)",
+    "pub(super)",
+    "export",
+    "default",
+    "async",
+    "unsafe",
+    "const",
+    "static",
+    "public",
+    "private",
+    "protected",
+    "internal",
+    "abstract",
+    "final",
+    "override",
+    "virtual",
+    "sealed",
+    "open",
+    "suspend",
+];
+
+/// Definition lines in code, named by their signature.
+///
+/// A line counts when it starts, after modifiers, with a definition keyword,
+/// or when it has an access modifier and a parameter list (Java and C#
+/// methods). The signature is the line up to its opening brace or colon.
+fn definitions(text: &str) -> Vec<Declaration> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line, raw)| {
+            let trimmed = raw.trim();
+            let mut rest = trimmed;
+            let mut modified = false;
+            while let Some(word) = rest.split_whitespace().next() {
+                if !MODIFIERS.contains(&word) {
+                    break;
+                }
+                modified = true;
+                rest = rest[word.len()..].trim_start();
+            }
+            let keyword = rest.split_whitespace().next()?;
+            let is_definition = (DEFINITION_KEYWORDS.contains(&keyword)
+                && rest.len() > keyword.len())
+                || (modified && rest.contains('(') && !rest.ends_with(';'));
+            if !is_definition {
+                return None;
+            }
+            let signature = trimmed.trim_end_matches(['{', ':']).trim_end().to_string();
+            Some(Declaration {
+                kind: DeclarationKind::Definition,
+                name: signature,
+                line,
+            })
+        })
+        .collect()
+}
+
+/// Joins a parent key path and a key.
+fn key_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+/// Removes matching quotes around a key.
+fn unquote(key: &str) -> &str {
+    let key = key.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = key.strip_prefix(quote).and_then(|k| k.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    key
+}
+
+/// Key paths in a TOML document.
+///
+/// Each `[[array]]` table starts a fresh scope, so its entries can repeat
+/// keys. Multi-line strings are skipped.
+fn toml_keys(text: &str) -> Vec<Declaration> {
+    let mut keys = Vec::new();
+    let mut table = String::new();
+    let mut array_tables: HashMap<String, usize> = HashMap::new();
+    let mut in_multiline: Option<&str> = None;
+
+    for (line, raw) in text.lines().enumerate() {
+        let trimmed = raw.trim();
+        if let Some(delimiter) = in_multiline {
+            if trimmed.contains(delimiter) {
+                in_multiline = None;
+            }
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = trimmed
+            .strip_prefix("[[")
+            .and_then(|t| t.split("]]").next())
+        {
+            let name = name.trim().to_string();
+            let index = array_tables.entry(name.clone()).or_insert(0);
+            table = format!("{name}[{index}]");
+            *index += 1;
+        } else if let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.split(']').next()) {
+            table = name.trim().to_string();
+            keys.push(Declaration {
+                kind: DeclarationKind::Key,
+                name: format!("[{table}]"),
+                line,
+            });
+        } else if let Some((key, value)) = trimmed.split_once('=') {
+            keys.push(Declaration {
+                kind: DeclarationKind::Key,
+                name: key_path(&table, unquote(key)),
+                line,
+            });
+            let value = value.trim();
+            for delimiter in ["\"\"\"", "'''"] {
+                if value.starts_with(delimiter) && value.matches(delimiter).count() == 1 {
+                    in_multiline = Some(delimiter);
+                }
+            }
+        }
+    }
+    keys
+}
+
+/// Key paths in a YAML document.
+///
+/// Nesting follows indentation; each sequence item and each `---` document
+/// starts a fresh scope, the latter numbered like `(1).key`. Block scalars
+/// (`|`, `>`) are skipped.
+fn yaml_keys(text: &str) -> Vec<Declaration> {
+    let mut keys = Vec::new();
+    // (indent, path) of the enclosing mappings
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut items: HashMap<String, usize> = HashMap::new();
+    let mut document = 0;
+    let mut block_scalar: Option<usize> = None;
+
+    for (line, raw) in text.lines().enumerate() {
+        let trimmed = raw.trim();
+        let mut indent = raw.len() - raw.trim_start().len();
+        if let Some(scalar_indent) = block_scalar {
+            if trimmed.is_empty() || indent > scalar_indent {
+                continue;
+            }
+            block_scalar = None;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed == "---" {
+            document += 1;
+            stack.clear();
+            continue;
+        }
+
+        stack.retain(|(i, _)| *i < indent);
+        let mut parent = match stack.last() {
+            Some((_, path)) => path.clone(),
+            None if document > 0 => format!("({document})"),
+            None => String::new(),
+        };
+
+        let mut content = trimmed;
+        if let Some(rest) = content
+            .strip_prefix('-')
+            .filter(|r| r.is_empty() || r.starts_with(' '))
+        {
+            let index = items.entry(parent.clone()).or_insert(0);
+            parent = format!("{parent}[{index}]");
+            *index += 1;
+            stack.push((indent, parent.clone()));
+            indent += 1 + rest.len() - rest.trim_start().len();
+            content = rest.trim_start();
+        }
+
+        let Some((key, value)) = content
+            .split_once(": ")
+            .or_else(|| content.strip_suffix(':').map(|k| (k, "")))
+        else {
+            continue;
+        };
+        if key.starts_with(['{', '[', '&', '*', '!']) || key.contains('#') {
+            continue;
+        }
+        let path = key_path(&parent, unquote(key));
+        keys.push(Declaration {
+            kind: DeclarationKind::Key,
+            name: path.clone(),
+            line,
+        });
+        if value.trim_start().starts_with(['|', '>']) {
+            block_scalar = Some(indent);
+        }
+        stack.push((indent, path));
+    }
+    keys
+}
+
+/// A JSON container being scanned.
+enum Container {
+    /// An object: its path and the key awaiting a value.
+    Object { path: String, key: Option<String> },
+    /// An array: its path and the index of the current element.
+    Array { path: String, index: usize },
+}
+
+/// Key paths in a JSON document.
+fn json_keys(text: &str) -> Vec<Declaration> {
+    let bytes = text.as_bytes();
+    let mut keys = Vec::new();
+    let mut stack: Vec<Container> = Vec::new();
+    let mut line = 0;
+    let mut i = 0;
+
+    // Path of a value starting at the current position
+    let value_path = |stack: &[Container]| match stack.last() {
+        Some(Container::Object {
+            path,
+            key: Some(key),
+        }) => key_path(path, key),
+        Some(Container::Array { path, index }) => format!("{path}[{index}]"),
+        Some(Container::Object { path, key: None }) => path.clone(),
+        None => String::new(),
+    };
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\n' => line += 1,
+            b'{' => {
+                let path = value_path(&stack);
+                stack.push(Container::Object { path, key: None });
+            }
+            b'[' => {
+                let path = value_path(&stack);
+                stack.push(Container::Array { path, index: 0 });
+            }
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b',' => match stack.last_mut() {
+                Some(Container::Object { key, .. }) => *key = None,
+                Some(Container::Array { index, .. }) => *index += 1,
+                None => {}
+            },
+            b'"' => {
+                let start = i + 1;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                let string = text.get(start..i.min(bytes.len())).unwrap_or_default();
+                let is_key = bytes[(i + 1).min(bytes.len())..]
+                    .iter()
+                    .find(|b| !b.is_ascii_whitespace())
+                    == Some(&b':');
+                if let Some(Container::Object { path, key }) = stack.last_mut() {
+                    if is_key && key.is_none() {
+                        keys.push(Declaration {
+                            kind: DeclarationKind::Key,
+                            name: key_path(path, string),
+                            line,
+                        });
+                        *key = Some(string.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    keys
+}
+
+/// Package names in a pip requirements file, normalized.
+fn requirements(text: &str) -> Vec<Declaration> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line, raw)| {
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with(['#', '-']) {
+                return None;
+            }
+            let name: String = trimmed
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+                .map(|c| {
+                    if c == '_' {
+                        '-'
+                    } else {
+                        c.to_ascii_lowercase()
+                    }
+                })
+                .collect();
+            (!name.is_empty()).then_some(Declaration {
+                kind: DeclarationKind::Dependency,
+                name,
+                line,
+            })
+        })
+        .collect()
+}
+
+/// Module paths required in a `go.mod`.
+fn go_requires(text: &str) -> Vec<Declaration> {
+    let mut requires = Vec::new();
+    let mut in_block = false;
+    for (line, raw) in text.lines().enumerate() {
+        let trimmed = raw.trim();
+        let module = if in_block {
+            if trimmed == ")" {
+                in_block = false;
+                continue;
+            }
+            trimmed
+        } else if trimmed == "require (" {
+            in_block = true;
+            continue;
+        } else if let Some(rest) = trimmed.strip_prefix("require ") {
+            rest
+        } else {
+            continue;
+        };
+        if let Some(module) = module
+            .split_whitespace()
+            .next()
+            .filter(|m| !m.starts_with("//"))
+        {
+            requires.push(Declaration {
+                kind: DeclarationKind::Dependency,
+                name: module.to_string(),
+                line,
+            });
+        }
+    }
+    requires
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(text: &str, path: &str) -> Vec<String> {
+        declarations(text, Path::new(path))
+            .into_iter()
+            .map(|d| d.name)
+            .collect()
+    }
+
+    #[test]
+    fn code_definitions_are_signatures() {
+        let text = "pub async fn run(a: u32) -> u32 {\n    let f = 1;\n}\nclass Foo:\n    public void bar(int a) {\n    foo(1);\n";
+        assert_eq!(
+            names(text, "a.rs"),
+            vec![
+                "pub async fn run(a: u32) -> u32",
+                "class Foo",
+                "public void bar(int a)"
+            ]
+        );
+        assert!(names(text, "notes.md").is_empty());
+    }
+
+    #[test]
+    fn toml_keys_follow_tables() {
+        let text = "[package]\nname = \"a\"\n\n[dependencies]\nserde = \"1\"\n\"tokio\" = { version = \"1\" }\n\n[[bin]]\nname = \"x\"\n[[bin]]\nname = \"y\"\ndoc = \"\"\"\nkey = 1\n\"\"\"\n";
+        assert_eq!(
+            names(text, "Cargo.toml"),
+            vec![
+                "[package]",
+                "package.name",
+                "[dependencies]",
+                "dependencies.serde",
+                "dependencies.tokio",
+                "bin[0].name",
+                "bin[1].name",
+                "bin[1].doc",
+            ]
+        );
+    }
+
+    #[test]
+    fn yaml_keys_follow_indentation_and_items() {
+        let text = "jobs:\n  test:\n    steps:\n      - name: a\n        run: |\n          x: 1\n      - name: b\n  lint: {}\n---\njobs: 1\n";
+        assert_eq!(
+            names(text, "ci.yml"),
+            vec![
+                "jobs",
+                "jobs.test",
+                "jobs.test.steps",
+                "jobs.test.steps[0].name",
+                "jobs.test.steps[0].run",
+                "jobs.test.steps[1].name",
+                "jobs.lint",
+                "(1).jobs",
+            ]
+        );
+    }
+
+    #[test]
+    fn json_keys_follow_nesting() {
+        let text = "{\n  \"name\": \"a\",\n  \"deps\": {\"x\": \"1\", \"y\": [\"z:\"]},\n  \"list\": [{\"k\": 1}, {\"k\": 2}]\n}\n";
+        let keys = declarations(text, Path::new("package.json"));
+        let named: Vec<(&str, usize)> = keys.iter().map(|d| (d.name.as_str(), d.line)).collect();
+        assert_eq!(
+            named,
+            vec![
+                ("name", 1),
+                ("deps", 2),
+                ("deps.x", 2),
+                ("deps.y", 2),
+                ("list", 3),
+                ("list[0].k", 3),
+                ("list[1].k", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn dependency_lists() {
+        assert_eq!(
+            names(
+                "# pinned\nRequests==2.0\nflask_login>=1\n-r base.txt\n",
+                "requirements.txt"
+            ),
+            vec!["requests", "flask-login"]
+        );
+        assert_eq!(
+            names(
+                "module m\n\nrequire a.io/x v1\nrequire (\n\tb.io/y v2\n\tc.io/z v3 // indirect\n)\n",
+                "go.mod"
+            ),
+            vec!["a.io/x", "b.io/y", "c.io/z"]
+        );
+    }
+
+    #[test]
+    fn only_new_repetitions_are_duplicates() {
+        let ours = "impl A {\n    fn new() {}\n}\nimpl B {\n    fn new() {}\n}\nfn a() {}\n";
+        let theirs = "impl A {\n    fn new() {}\n}\nimpl B {\n    fn new() {}\n}\nfn b() {}\n";
+        let merged =
+            "impl A {\n    fn new() {}\n}\nimpl B {\n    fn new() {}\n}\nfn a() {}\nfn a() {}\n";
+        let duplicates = find_duplicates(merged, &[ours, theirs], Path::new("lib.rs"));
+        assert_eq!(
+            duplicates,
+            vec![Duplicate {
+                kind: DeclarationKind::Definition,
+                name: "fn a() {}".to_string(),
+                first_line: 6,
+                line: 7,
+            }]
+        );
+        assert_eq!(
+            duplicates[0].to_string(),
+            "duplicate definition `fn a() {}` (first on line 7)"
+        );
+    }
+
+    #[test]
+    fn duplicate_dependency_keys_are_found() {
+        let ours = "[dependencies]\nserde = \"1\"\n";
+        let theirs = "[dependencies]\nserde = \"1.0.200\"\n";
+        let merged = "[dependencies]\nserde = \"1\"\nserde = \"1.0.200\"\n";
+        let duplicates = find_duplicates(merged, &[ours, theirs], Path::new("Cargo.toml"));
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].name, "dependencies.serde");
+        assert_eq!(duplicates[0].line, 2);
+    }
+}
@@ -4,7 +4,7 @@
 
 use thiserror::Error;
 
-use crate::{HunkId, MergeState};
+use crate::{HunkId, MergeState, MergeWarning};
 
 /// Error parsing conflict markers.
 #[derive(Debug, Clone, Error, PartialEq, Eq)]
@@ -40,6 +40,21 @@ pub enum ValidationError {
     /// Syntax error in output.
     #[error("syntax error: {0}")]
     SyntaxError(String),
+    /// Checks of the validation profile failed.
+    #[error("{}", describe_problems(.0))]
+    ChecksFailed(Vec<MergeWarning>),
+}
+
+/// Describes failed validation checks, naming the hunk of each.
+fn describe_problems(problems: &[MergeWarning]) -> String {
+    let messages: Vec<String> = problems
+        .iter()
+        .map(|problem| match problem.hunk_id {
+            Some(id) => format!("hunk {}: {}", id.0 + 1, problem.message),
+            None => problem.message.clone(),
+        })
+        .collect();
+    format!("validation failed: {}", messages.join("; "))
 }
 
 /// Error applying resolutions to generate output.
@@ -67,6 +82,17 @@ pub enum CompletionError {
     LifecycleError(LifecycleError),
 }
 
+/// Error restoring a saved session.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum RestoreError {
+    /// The conflicts changed since the session was saved.
+    #[error("the conflicts changed since the session was saved")]
+    Stale,
+    /// A saved resolution could not be applied.
+    #[error(transparent)]
+    Resolution(#[from] ResolutionError),
+}
+
 /// Error for invalid lifecycle transitions.
 #[derive(Debug, Clone, Error, PartialEq, Eq)]
 pub enum LifecycleError {
@@ -113,6 +139,21 @@ mod tests {
     fn validation_error_display() {
         let err = ValidationError::MarkersRemain(3);
         assert_eq!(err.to_string(), "conflict markers remain: 3 markers");
+
+        let err = ValidationError::ChecksFailed(vec![
+            MergeWarning {
+                message: "1 extra '}' compared to ours".to_string(),
+                hunk_id: Some(HunkId(1)),
+            },
+            MergeWarning {
+                message: "check failed".to_string(),
+                hunk_id: None,
+            },
+        ]);
+        assert_eq!(
+            err.to_string(),
+            "validation failed: hunk 2: 1 extra '}' compared to ours; check failed"
+        );
     }
 
     #[test]
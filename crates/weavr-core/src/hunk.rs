@@ -61,6 +61,56 @@ pub struct ConflictHunk {
     pub state: HunkState,
 }
 
+impl ConflictHunk {
+    /// Returns a stable identifier for this hunk's content.
+    ///
+    /// See [`preimage_key`](crate::preimage_key).
+    #[must_use]
+    pub fn preimage_key(&self) -> String {
+        crate::preimage_key(
+            &self.left.text,
+            self.base.as_ref().map(|b| b.text.as_str()),
+            &self.right.text,
+        )
+    }
+
+    /// Finds the region this hunk covers in another version of the file.
+    ///
+    /// The region is the text between the hunk's `before` and `after`
+    /// context lines. When the context occurs more than once, the occurrence
+    /// closest to the hunk's own position is used. Returns `None` if the
+    /// context cannot be found.
+    #[must_use]
+    pub fn locate_in(&self, content: &str) -> Option<String> {
+        let lines: Vec<&str> = content.lines().collect();
+        let before = &self.context.before;
+        let after = &self.context.after;
+        let matches_at = |context: &[String], at: usize| {
+            lines
+                .get(at..at + context.len())
+                .is_some_and(|window| window.iter().zip(context).all(|(a, b)| *a == b))
+        };
+
+        // Candidate region starts: just past each occurrence of `before`
+        let mut starts: Vec<usize> = (0..=lines.len().saturating_sub(before.len()))
+            .filter(|&at| matches_at(before, at))
+            .map(|at| at + before.len())
+            .collect();
+        let expected = self.context.start_line_left.saturating_sub(1);
+        starts.sort_by_key(|&start| start.abs_diff(expected));
+
+        starts.into_iter().find_map(|start| {
+            let end = if after.is_empty() {
+                lines.len()
+            } else {
+                (start..=lines.len().saturating_sub(after.len()))
+                    .find(|&at| matches_at(after, at))?
+            };
+            Some(lines[start..end].join("\n"))
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,6 +131,24 @@ mod tests {
         assert!(!set.contains(&HunkId(3)));
     }
 
+    #[test]
+    fn locate_in_finds_region_between_context() {
+        let content = "fn a() {}\n<<<<<<< HEAD\nx = 1\n=======\nx = 2\n>>>>>>> b\nfn z() {}\n";
+        let parsed = crate::parse_conflict_markers(content).unwrap();
+        let hunk = &parsed.hunks[0];
+
+        assert_eq!(
+            hunk.locate_in("fn a() {}\nx = 3\ny = 3\nfn z() {}\n")
+                .as_deref(),
+            Some("x = 3\ny = 3")
+        );
+        assert_eq!(
+            hunk.locate_in("fn a() {}\nfn z() {}\n").as_deref(),
+            Some("")
+        );
+        assert_eq!(hunk.locate_in("fn b() {}\n"), None);
+    }
+
     #[test]
     fn hunk_state_default() {
         assert_eq!(HunkState::default(), HunkState::Unresolved);
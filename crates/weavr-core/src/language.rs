@@ -0,0 +1,303 @@
+//! Per-language merge heuristics.
+//!
+//! Accepting both sides is the usual answer for conflicting imports, test
+//! cases, and list entries, but what makes a good combined result depends on
+//! the language and the project: imports may be kept sorted, test files
+//! usually want both branches' tests, and a separator comment can make the
+//! seam visible. [`LanguageHeuristics`] holds these knobs; callers look them
+//! up by file extension.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::hunk::ConflictHunk;
+use crate::resolution::{AcceptBothOptions, BothOrder, Resolution};
+
+/// Built-in languages: name, file extensions, and import line prefixes.
+const BUILTIN: &[(&str, &[&str], &[&str])] = &[
+    ("rust", &["rs"], &["use ", "pub use ", "extern crate "]),
+    ("python", &["py", "pyi"], &["import ", "from "]),
+    ("javascript", &["js", "jsx", "mjs", "cjs"], &["import "]),
+    ("typescript", &["ts", "tsx", "mts", "cts"], &["import "]),
+    ("go", &["go"], &["import "]),
+    ("java", &["java"], &["import "]),
+    ("kotlin", &["kt", "kts"], &["import "]),
+    ("csharp", &["cs"], &["using "]),
+];
+
+/// How combined import lines are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportOrder {
+    /// Keep the order produced by accepting both sides.
+    #[default]
+    Keep,
+    /// Sort the combined imports and drop duplicates.
+    Sorted,
+}
+
+/// Merge heuristics for one language.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageHeuristics {
+    /// Prefixes identifying import lines, e.g. `use ` or `from `.
+    pub import_prefixes: Vec<String>,
+    /// How hunks made only of imports are combined.
+    pub import_order: ImportOrder,
+    /// Keep both sides of conflicts in test files instead of picking one,
+    /// since both branches' tests are usually wanted.
+    pub union_tests: bool,
+    /// Line placed between the two sides when accepting both, e.g. `// ---`.
+    pub separator: Option<String>,
+}
+
+impl LanguageHeuristics {
+    /// Returns the name of the built-in language for a file extension.
+    #[must_use]
+    pub fn language_for_extension(extension: &str) -> Option<&'static str> {
+        BUILTIN
+            .iter()
+            .find(|(_, extensions, _)| extensions.contains(&extension))
+            .map(|(name, _, _)| *name)
+    }
+
+    /// Returns the default heuristics for a language.
+    ///
+    /// Built-in languages know their import prefixes; everything else is off
+    /// until configured, so the defaults behave like plain accept-both.
+    #[must_use]
+    pub fn builtin(language: &str) -> Self {
+        let import_prefixes = BUILTIN
+            .iter()
+            .find(|(name, _, _)| *name == language)
+            .map(|(_, _, prefixes)| prefixes.iter().map(|p| (*p).to_string()).collect())
+            .unwrap_or_default();
+        Self {
+            import_prefixes,
+            ..Self::default()
+        }
+    }
+
+    /// Returns true if conflicts in the file at `path` should keep both
+    /// sides rather than one.
+    #[must_use]
+    pub fn prefers_union(&self, path: &Path) -> bool {
+        self.union_tests && is_test_path(path)
+    }
+
+    /// Accepts both sides of `hunk`, applying these heuristics on top of
+    /// `options`.
+    ///
+    /// Hunks made only of imports are deduplicated and sorted when
+    /// `import_order` is [`ImportOrder::Sorted`]; otherwise the separator,
+    /// if any, goes between the two sides.
+    #[must_use]
+    pub fn accept_both(&self, hunk: &ConflictHunk, options: &AcceptBothOptions) -> Resolution {
+        let mut options = options.clone();
+
+        if self.import_order == ImportOrder::Sorted
+            && self.is_import_block(&hunk.left.text)
+            && self.is_import_block(&hunk.right.text)
+        {
+            options.deduplicate = true;
+            let mut resolution = Resolution::accept_both(hunk, &options);
+            resolution.content = sort_lines(&resolution.content);
+            return resolution;
+        }
+
+        let mut resolution = Resolution::accept_both(hunk, &options);
+        if let Some(separator) = &self.separator {
+            if !hunk.left.text.is_empty() && !hunk.right.text.is_empty() {
+                let first = match options.order {
+                    BothOrder::LeftThenRight => &hunk.left.text,
+                    BothOrder::RightThenLeft => &hunk.right.text,
+                };
+                let first_lines = first.lines().count();
+                let mut lines: Vec<&str> = resolution.content.lines().collect();
+                // Deduplication may have dropped every line of the second side
+                if lines.len() > first_lines {
+                    lines.insert(first_lines, separator);
+                    let trailing = resolution.content.ends_with('\n');
+                    resolution.content = lines.join("\n");
+                    if trailing {
+                        resolution.content.push('\n');
+                    }
+                }
+            }
+        }
+        resolution
+    }
+
+    /// Returns true if every non-blank line of `text` is an import.
+    fn is_import_block(&self, text: &str) -> bool {
+        let mut lines = text.lines().map(str::trim_start).filter(|l| !l.is_empty());
+        !self.import_prefixes.is_empty()
+            && lines.clone().next().is_some()
+            && lines.all(|line| {
+                self.import_prefixes
+                    .iter()
+                    .any(|p| line.starts_with(p.as_str()))
+            })
+    }
+}
+
+/// Returns true if `path` looks like a test file: it lives in a `test`,
+/// `tests`, `spec`, or `__tests__` directory, or its name marks it as a test
+/// (`foo_test.go`, `test_foo.py`, `foo.spec.ts`, `FooTest.java`).
+#[must_use]
+pub fn is_test_path(path: &Path) -> bool {
+    let in_test_dir = path.parent().is_some_and(|parent| {
+        parent.components().any(|c| {
+            matches!(
+                c.as_os_str().to_str(),
+                Some("test" | "tests" | "spec" | "__tests__")
+            )
+        })
+    });
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    in_test_dir
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || Path::new(stem)
+            .extension()
+            .is_some_and(|ext| ext == "test" || ext == "spec")
+        || stem.ends_with("Test")
+        || stem.ends_with("Tests")
+}
+
+/// Sorts lines, dropping duplicates, and keeps a trailing newline.
+fn sort_lines(content: &str) -> String {
+    let mut lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines.sort_unstable();
+    lines.dedup();
+    let mut sorted = lines.join("\n");
+    if content.ends_with('\n') {
+        sorted.push('\n');
+    }
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hunk::{HunkContent, HunkContext, HunkId, HunkState};
+
+    fn hunk(left: &str, right: &str) -> ConflictHunk {
+        ConflictHunk {
+            id: HunkId(0),
+            left: HunkContent {
+                text: left.to_string(),
+            },
+            right: HunkContent {
+                text: right.to_string(),
+            },
+            base: None,
+            context: HunkContext::default(),
+            state: HunkState::default(),
+        }
+    }
+
+    #[test]
+    fn builtin_languages_by_extension() {
+        assert_eq!(
+            LanguageHeuristics::language_for_extension("rs"),
+            Some("rust")
+        );
+        assert_eq!(
+            LanguageHeuristics::language_for_extension("tsx"),
+            Some("typescript")
+        );
+        assert_eq!(LanguageHeuristics::language_for_extension("txt"), None);
+        assert!(LanguageHeuristics::builtin("python")
+            .import_prefixes
+            .contains(&"from ".to_string()));
+        assert_eq!(
+            LanguageHeuristics::builtin("cobol"),
+            LanguageHeuristics::default()
+        );
+    }
+
+    #[test]
+    fn defaults_match_plain_accept_both() {
+        let hunk = hunk("use b;\nuse a;", "use c;");
+        let options = AcceptBothOptions::default();
+        assert_eq!(
+            LanguageHeuristics::builtin("rust").accept_both(&hunk, &options),
+            Resolution::accept_both(&hunk, &options)
+        );
+    }
+
+    #[test]
+    fn sorted_imports_are_deduplicated() {
+        let heuristics = LanguageHeuristics {
+            import_order: ImportOrder::Sorted,
+            ..LanguageHeuristics::builtin("rust")
+        };
+        let imports = hunk(
+            "use std::io;\nuse crate::b;\n",
+            "use crate::a;\nuse std::io;\n",
+        );
+        let resolution = heuristics.accept_both(&imports, &AcceptBothOptions::default());
+        assert_eq!(
+            resolution.content,
+            "use crate::a;\nuse crate::b;\nuse std::io;\n"
+        );
+
+        // Anything besides imports keeps its order
+        let code = hunk("use std::io;\nfn a() {}", "use crate::a;");
+        let resolution = heuristics.accept_both(&code, &AcceptBothOptions::default());
+        assert_eq!(resolution.content, "use std::io;\nfn a() {}\nuse crate::a;");
+    }
+
+    #[test]
+    fn separator_goes_between_sides() {
+        let heuristics = LanguageHeuristics {
+            separator: Some("# ---".to_string()),
+            ..LanguageHeuristics::default()
+        };
+        let options = AcceptBothOptions {
+            order: BothOrder::RightThenLeft,
+            ..AcceptBothOptions::default()
+        };
+        let resolution = heuristics.accept_both(&hunk("a\nb\n", "c\n"), &options);
+        assert_eq!(resolution.content, "c\n# ---\na\nb\n");
+
+        // Nothing to separate when one side is empty
+        let resolution = heuristics.accept_both(&hunk("a", ""), &options);
+        assert_eq!(resolution.content, "a");
+    }
+
+    #[test]
+    fn union_is_preferred_for_test_files() {
+        let heuristics = LanguageHeuristics {
+            union_tests: true,
+            ..LanguageHeuristics::default()
+        };
+        assert!(heuristics.prefers_union(Path::new("tests/cli.rs")));
+        assert!(!heuristics.prefers_union(Path::new("src/cli.rs")));
+        assert!(!LanguageHeuristics::default().prefers_union(Path::new("tests/cli.rs")));
+    }
+
+    #[test]
+    fn recognizes_test_paths() {
+        for path in [
+            "tests/a.rs",
+            "pkg/foo_test.go",
+            "test_foo.py",
+            "src/app.spec.ts",
+            "web/__tests__/x.js",
+            "src/FooTest.java",
+        ] {
+            assert!(is_test_path(Path::new(path)), "{path}");
+        }
+        for path in ["src/lib.rs", "src/testing.rs", "contest.py"] {
+            assert!(!is_test_path(Path::new(path)), "{path}");
+        }
+    }
+}
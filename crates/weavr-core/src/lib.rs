@@ -20,18 +20,46 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+mod balance;
+mod duplicates;
 mod error;
 mod hunk;
 mod input;
+mod language;
+mod line_ending;
+mod merge;
 mod parser;
+mod patch;
+mod recover;
+mod reject;
+mod rerere;
 mod resolution;
 mod result;
+mod review;
+mod saved;
 mod session;
+mod similarity;
+mod stats;
+mod validation;
 
+pub use balance::*;
+pub use duplicates::*;
 pub use error::*;
 pub use hunk::*;
 pub use input::*;
+pub use language::*;
+pub use line_ending::*;
+pub use merge::*;
 pub use parser::*;
+pub use patch::*;
+pub use recover::*;
+pub use reject::*;
+pub use rerere::*;
 pub use resolution::*;
 pub use result::*;
+pub use review::*;
+pub use saved::*;
 pub use session::*;
+pub use similarity::*;
+pub use stats::*;
+pub use validation::*;
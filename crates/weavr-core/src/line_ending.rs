@@ -0,0 +1,78 @@
+//! Line ending detection and restoration.
+//!
+//! Hunks and resolutions always use `\n` internally. A file checked out with
+//! `\r\n` line endings, as is common on Windows, gets them back when the
+//! merged output is generated, so resolving conflicts does not rewrite every
+//! line of the file.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use serde::{Deserialize, Serialize};
+
+/// The line ending used by a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    /// Detects the line ending most lines of `text` use.
+    ///
+    /// Text without line breaks, or with as many `\n` as `\r\n` endings,
+    /// uses `\n`.
+    #[must_use]
+    pub fn detect(text: &str) -> Self {
+        if !text.contains('\r') {
+            return Self::Lf;
+        }
+        let breaks = text.matches('\n').count();
+        let crlf = text.matches("\r\n").count();
+        if crlf * 2 > breaks {
+            Self::CrLf
+        } else {
+            Self::Lf
+        }
+    }
+
+    /// Returns the line ending's characters.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+
+    /// Rewrites every line break in `text` to this line ending.
+    #[must_use]
+    pub fn apply(self, text: &str) -> String {
+        let normalized = text.replace("\r\n", "\n");
+        match self {
+            Self::Lf => normalized,
+            Self::CrLf => normalized.replace('\n', "\r\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_the_dominant_ending() {
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\nb\r\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("no breaks"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn apply_normalizes_mixed_endings() {
+        assert_eq!(LineEnding::CrLf.apply("a\nb\r\nc"), "a\r\nb\r\nc");
+        assert_eq!(LineEnding::Lf.apply("a\r\nb\n"), "a\nb\n");
+        assert_eq!(LineEnding::CrLf.as_str(), "\r\n");
+    }
+}
@@ -0,0 +1,171 @@
+//! Three-way merging of file versions.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+use crate::MergeInput;
+
+/// Marker opening a conflict, labeled as our side.
+const START_MARKER: &str = "<<<<<<< ours";
+/// Marker opening a conflict's base section.
+const BASE_MARKER: &str = "||||||| base";
+/// Marker closing a conflict, labeled as their side.
+const END_MARKER: &str = ">>>>>>> theirs";
+
+/// Merges the versions of a file, marking each region both sides changed
+/// differently with diff3-style conflict markers.
+///
+/// A region changed by one side only takes that side's change, and one
+/// changed identically by both takes it once. Without a base, the sides
+/// are compared directly and every region where they differ conflicts.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use weavr_core::{merge_with_markers, FileVersion, MergeInput};
+///
+/// let version = |content: &str| FileVersion {
+///     path: PathBuf::from("f.txt"),
+///     content: content.to_string(),
+/// };
+/// let input = MergeInput {
+///     left: version("a\nB\nc\nd\n"),
+///     right: version("a\nb\nc\nD\n"),
+///     base: Some(version("a\nb\nc\nd\n")),
+/// };
+/// assert_eq!(merge_with_markers(&input), "a\nB\nc\nD\n");
+/// ```
+#[must_use]
+pub fn merge_with_markers(input: &MergeInput) -> String {
+    let left: Vec<&str> = input.left.content.lines().collect();
+    let right: Vec<&str> = input.right.content.lines().collect();
+    let base: Vec<&str> = match &input.base {
+        Some(base) => base.content.lines().collect(),
+        None => left.clone(),
+    };
+    let two_way = input.base.is_none();
+
+    let left_matches = matches(&base, &left);
+    let right_matches = matches(&base, &right);
+
+    let mut out: Vec<&str> = Vec::new();
+    let (mut base_at, mut left_at, mut right_at) = (0, 0, 0);
+    loop {
+        // The next base line both sides kept, where the merge is in sync
+        let sync = (base_at..base.len())
+            .find_map(|line| Some((line, left_matches[line]?, right_matches[line]?)));
+        let (base_end, left_end, right_end) = sync.unwrap_or((base.len(), left.len(), right.len()));
+        push_chunk(
+            &mut out,
+            &base[base_at..base_end],
+            &left[left_at..left_end],
+            &right[right_at..right_end],
+            two_way,
+        );
+        if sync.is_none() {
+            break;
+        }
+        out.push(base[base_end]);
+        (base_at, left_at, right_at) = (base_end + 1, left_end + 1, right_end + 1);
+    }
+
+    let mut merged = out.join("\n");
+    if !merged.is_empty() && input.left.content.ends_with('\n') {
+        merged.push('\n');
+    }
+    merged
+}
+
+/// Maps each line of `base` to the line of `side` it is kept as, if any.
+fn matches(base: &[&str], side: &[&str]) -> Vec<Option<usize>> {
+    let mut kept = vec![None; base.len()];
+    for op in capture_diff_slices(Algorithm::Myers, base, side) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            for n in 0..len {
+                kept[old_index + n] = Some(new_index + n);
+            }
+        }
+    }
+    kept
+}
+
+/// Pushes the merge of a region between sync points.
+fn push_chunk<'a>(
+    out: &mut Vec<&'a str>,
+    base: &[&'a str],
+    left: &[&'a str],
+    right: &[&'a str],
+    two_way: bool,
+) {
+    if left == right {
+        out.extend_from_slice(left);
+    } else if left == base && !two_way {
+        out.extend_from_slice(right);
+    } else if right == base && !two_way {
+        out.extend_from_slice(left);
+    } else {
+        out.push(START_MARKER);
+        out.extend_from_slice(left);
+        if !two_way {
+            out.push(BASE_MARKER);
+            out.extend_from_slice(base);
+        }
+        out.push("=======");
+        out.extend_from_slice(right);
+        out.push(END_MARKER);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::FileVersion;
+
+    fn input(base: Option<&str>, left: &str, right: &str) -> MergeInput {
+        let version = |content: &str| FileVersion {
+            path: PathBuf::from("f.txt"),
+            content: content.to_string(),
+        };
+        MergeInput {
+            left: version(left),
+            right: version(right),
+            base: base.map(version),
+        }
+    }
+
+    #[test]
+    fn changes_on_one_side_merge_cleanly() {
+        let merged = merge_with_markers(&input(Some("a\nb\nc\n"), "a\nb\nc\nd\n", "z\na\nb\nc\n"));
+        assert_eq!(merged, "z\na\nb\nc\nd\n");
+    }
+
+    #[test]
+    fn identical_changes_are_taken_once() {
+        let merged = merge_with_markers(&input(Some("a\nb\n"), "a\nB\n", "a\nB\n"));
+        assert_eq!(merged, "a\nB\n");
+    }
+
+    #[test]
+    fn overlapping_changes_conflict_with_base() {
+        let merged = merge_with_markers(&input(Some("a\nb\nc\n"), "a\nleft\nc\n", "a\nright\nc\n"));
+        assert_eq!(
+            merged,
+            "a\n<<<<<<< ours\nleft\n||||||| base\nb\n=======\nright\n>>>>>>> theirs\nc\n"
+        );
+    }
+
+    #[test]
+    fn without_base_differences_conflict() {
+        let merged = merge_with_markers(&input(None, "a\nb\nc", "a\nx\nc"));
+        assert_eq!(merged, "a\n<<<<<<< ours\nb\n=======\nx\n>>>>>>> theirs\nc");
+    }
+}
@@ -2,22 +2,85 @@
 //!
 //! All types in this module are **stable** and covered by semantic versioning.
 
+use std::borrow::Cow;
+use std::ops::Range;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{ConflictHunk, HunkContent, HunkContext, HunkId, HunkState, ParseError};
 
-/// Default number of context lines before and after a conflict.
-const DEFAULT_CONTEXT_LINES: usize = 3;
+/// Default number of context lines kept before and after a conflict.
+///
+/// Use [`MergeSession::set_context_lines`](crate::MergeSession::set_context_lines)
+/// to keep a different number.
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
 
 /// A segment of a file - either clean text or a conflict.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Segment {
-    /// Non-conflicting text (preserved exactly).
-    Clean(String),
+    /// Non-conflicting text (preserved exactly), as the byte range of its
+    /// lines in the parsed content. Read it with [`clean_text`].
+    Clean(Range<usize>),
     /// A conflict hunk (index into `ParsedConflict::hunks`).
     Conflict(usize),
 }
 
+/// Style of the conflict markers in a file, as set by git's
+/// `merge.conflictStyle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStyle {
+    /// Ours and theirs only.
+    #[default]
+    Merge,
+    /// Ours, the base, and theirs.
+    Diff3,
+    /// Like diff3, with lines common to both sides moved out of the conflict.
+    Zdiff3,
+}
+
+impl ConflictStyle {
+    /// Detects the style from parsed hunks: diff3 if any hunk has a base.
+    ///
+    /// zdiff3 writes the same markers as diff3, so it is detected as diff3;
+    /// use [`ConflictStyle::parse`] on the configured style to tell them apart.
+    #[must_use]
+    pub fn detect(hunks: &[ConflictHunk]) -> Self {
+        if hunks.iter().any(|hunk| hunk.base.is_some()) {
+            Self::Diff3
+        } else {
+            Self::Merge
+        }
+    }
+
+    /// Parses a `merge.conflictStyle` value.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "merge" => Some(Self::Merge),
+            "diff3" => Some(Self::Diff3),
+            "zdiff3" => Some(Self::Zdiff3),
+            _ => None,
+        }
+    }
+
+    /// Returns the style's name as git spells it.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::Diff3 => "diff3",
+            Self::Zdiff3 => "zdiff3",
+        }
+    }
+
+    /// Returns true if hunks in this style carry a base.
+    #[must_use]
+    pub fn has_base(self) -> bool {
+        self != Self::Merge
+    }
+}
+
 /// Result of parsing a conflicted file.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ParsedConflict {
@@ -76,9 +139,93 @@ fn detect_marker(line: &str) -> Option<Marker> {
     }
 }
 
+/// Iterates over the lines of `content` with the byte offset of each.
+///
+/// Lines end as in [`str::lines`]: at `\n` or `\r\n`, which is left out.
+fn lines_with_offsets(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    content.split_inclusive('\n').scan(0, |offset, piece| {
+        let start = *offset;
+        *offset += piece.len();
+        let line = piece
+            .strip_suffix('\n')
+            .map_or(piece, |line| line.strip_suffix('\r').unwrap_or(line));
+        Some((start, line))
+    })
+}
+
+/// Extends `span`, the byte range of a run of lines, to take in `line`,
+/// which starts at `offset`.
+fn extend(span: &mut Option<Range<usize>>, offset: usize, line: &str) {
+    let end = offset + line.len();
+    match span {
+        Some(range) => range.end = end,
+        None => *span = Some(offset..end),
+    }
+}
+
+/// Copies the lines in `span` out of `content`, joined with `\n`.
+fn join_lines(content: &str, span: Option<Range<usize>>) -> String {
+    span.map_or_else(String::new, |range| {
+        clean_text(content, &range).into_owned()
+    })
+}
+
+/// Returns the lines in `range` of `content`, joined with `\n`, as a
+/// [`Segment::Clean`] holds them.
+///
+/// Borrows from `content` unless the lines end in `\r\n`.
+#[must_use]
+pub fn clean_text<'a>(content: &'a str, range: &Range<usize>) -> Cow<'a, str> {
+    let text = &content[range.clone()];
+    if !text.contains('\r') {
+        return Cow::Borrowed(text);
+    }
+    // Drop the \r of \r\n line endings, as str::lines does
+    let mut joined = String::with_capacity(text.len());
+    let mut pieces = text.split('\n').peekable();
+    while let Some(piece) = pieces.next() {
+        if pieces.peek().is_some() {
+            joined.push_str(piece.strip_suffix('\r').unwrap_or(piece));
+            joined.push('\n');
+        } else {
+            joined.push_str(piece);
+        }
+    }
+    Cow::Owned(joined)
+}
+
+/// Fills in the after context of `previous`, the conflict before the clean
+/// `text`, and returns the before context of the conflict after it.
+///
+/// `text` is split on `\n` like [`MergeSession::set_context_lines`](crate::MergeSession::set_context_lines)
+/// does: it never holds the newline that ends its last line, so a trailing
+/// empty piece is a blank line.
+fn context_lines(text: &str, previous: Option<&mut ConflictHunk>) -> Vec<String> {
+    if let Some(hunk) = previous {
+        hunk.context.after = text
+            .split('\n')
+            .take(DEFAULT_CONTEXT_LINES)
+            .map(str::to_string)
+            .collect();
+    }
+    let mut before: Vec<String> = text
+        .rsplit('\n')
+        .take(DEFAULT_CONTEXT_LINES)
+        .map(str::to_string)
+        .collect();
+    before.reverse();
+    before
+}
+
 /// Parses conflict markers from file content.
 ///
-/// Supports both standard 2-way conflicts and diff3 3-way conflicts.
+/// Supports standard 2-way conflicts and 3-way conflicts in the diff3 and
+/// zdiff3 styles, whose base section may be empty.
+///
+/// The content is read in a single pass that tracks each section as a byte
+/// range. Conflict sides are copied out; clean regions stay byte ranges
+/// into `content`, so memory grows with the conflicted regions rather than
+/// the file, and time with the file however many hunks it has.
 ///
 /// # Arguments
 ///
@@ -109,31 +256,33 @@ fn detect_marker(line: &str) -> Option<Marker> {
 /// ```
 #[allow(clippy::too_many_lines)]
 pub fn parse_conflict_markers(content: &str) -> Result<ParsedConflict, ParseError> {
-    let lines: Vec<&str> = content.lines().collect();
     let mut state = ParserState::Clean;
     let mut segments: Vec<Segment> = Vec::new();
     let mut hunks: Vec<ConflictHunk> = Vec::new();
 
-    let mut clean_buffer: Vec<String> = Vec::new();
-    let mut left_buffer: Vec<String> = Vec::new();
-    let mut base_buffer: Option<Vec<String>> = None;
-    let mut right_buffer: Vec<String> = Vec::new();
+    // Byte ranges of the lines in each section
+    let mut clean: Option<Range<usize>> = None;
+    let mut left: Option<Range<usize>> = None;
+    let mut base: Option<Option<Range<usize>>> = None;
+    let mut right: Option<Range<usize>> = None;
+
+    let mut before: Vec<String> = Vec::new();
 
     let mut hunk_start_line: usize = 0;
     let mut left_content_start: usize = 0;
     let mut right_content_start: usize = 0;
     let mut hunk_id_counter: u32 = 0;
 
-    for (line_num, line) in lines.iter().enumerate() {
+    for (line_num, (offset, line)) in lines_with_offsets(content).enumerate() {
         let one_indexed = line_num + 1;
 
         match (detect_marker(line), state) {
             // Start marker in clean state - begin new conflict
             (Some(Marker::Start), ParserState::Clean) => {
-                // Flush clean buffer to segments
-                if !clean_buffer.is_empty() {
-                    segments.push(Segment::Clean(clean_buffer.join("\n")));
-                    clean_buffer.clear();
+                // Flush clean lines to segments, taking context from both ends
+                if let Some(range) = clean.take() {
+                    before = context_lines(&clean_text(content, &range), hunks.last_mut());
+                    segments.push(Segment::Clean(range));
                 }
                 hunk_start_line = one_indexed;
                 left_content_start = one_indexed + 1;
@@ -149,7 +298,7 @@ pub fn parse_conflict_markers(content: &str) -> Result<ParsedConflict, ParseErro
 
             // Base marker after left - enter diff3 base section
             (Some(Marker::Base), ParserState::InLeft) => {
-                base_buffer = Some(Vec::new());
+                base = Some(None);
                 state = ParserState::InBase;
             }
 
@@ -187,32 +336,20 @@ pub fn parse_conflict_markers(content: &str) -> Result<ParsedConflict, ParseErro
 
             // End marker after right - complete the hunk
             (Some(Marker::End), ParserState::InRight) => {
-                // Extract context lines
-                let context_start = if hunk_start_line > DEFAULT_CONTEXT_LINES {
-                    hunk_start_line - DEFAULT_CONTEXT_LINES - 1
-                } else {
-                    0
-                };
-                let before: Vec<String> = lines[context_start..hunk_start_line - 1]
-                    .iter()
-                    .map(|s| (*s).to_string())
-                    .collect();
-
-                // Build the hunk
                 let hunk = ConflictHunk {
                     id: HunkId(hunk_id_counter),
                     left: HunkContent {
-                        text: left_buffer.join("\n"),
+                        text: join_lines(content, left.take()),
                     },
                     right: HunkContent {
-                        text: right_buffer.join("\n"),
+                        text: join_lines(content, right.take()),
                     },
-                    base: base_buffer
-                        .take()
-                        .map(|b| HunkContent { text: b.join("\n") }),
+                    base: base.take().map(|span| HunkContent {
+                        text: join_lines(content, span),
+                    }),
                     context: HunkContext {
-                        before,
-                        after: Vec::new(), // Will be filled after parsing completes
+                        before: std::mem::take(&mut before),
+                        after: Vec::new(), // Filled from the clean lines that follow
                         start_line_left: left_content_start,
                         start_line_right: right_content_start,
                     },
@@ -224,8 +361,6 @@ pub fn parse_conflict_markers(content: &str) -> Result<ParsedConflict, ParseErro
                 segments.push(Segment::Conflict(hunk_index));
 
                 hunk_id_counter += 1;
-                left_buffer.clear();
-                right_buffer.clear();
                 state = ParserState::Clean;
             }
 
@@ -236,24 +371,20 @@ pub fn parse_conflict_markers(content: &str) -> Result<ParsedConflict, ParseErro
                 )));
             }
 
-            // Regular line - add to appropriate buffer
+            // Regular line - add to the current section
             (None, ParserState::Clean) => {
-                clean_buffer.push((*line).to_string());
+                extend(&mut clean, offset, line);
             }
 
-            (None, ParserState::InLeft) => {
-                left_buffer.push((*line).to_string());
-            }
+            (None, ParserState::InLeft) => extend(&mut left, offset, line),
 
             (None, ParserState::InBase) => {
-                if let Some(ref mut buf) = base_buffer {
-                    buf.push((*line).to_string());
+                if let Some(span) = base.as_mut() {
+                    extend(span, offset, line);
                 }
             }
 
-            (None, ParserState::InRight) => {
-                right_buffer.push((*line).to_string());
-            }
+            (None, ParserState::InRight) => extend(&mut right, offset, line),
         }
     }
 
@@ -265,62 +396,14 @@ pub fn parse_conflict_markers(content: &str) -> Result<ParsedConflict, ParseErro
     }
 
     // Flush remaining clean content
-    if !clean_buffer.is_empty() {
-        segments.push(Segment::Clean(clean_buffer.join("\n")));
+    if let Some(range) = clean {
+        context_lines(&clean_text(content, &range), hunks.last_mut());
+        segments.push(Segment::Clean(range));
     }
 
-    // Fill in 'after' context for all hunks
-    fill_after_context(&mut hunks, &lines);
-
     Ok(ParsedConflict { hunks, segments })
 }
 
-/// Fills in the 'after' context for all hunks by scanning forward from each hunk's position.
-fn fill_after_context(hunks: &mut [ConflictHunk], lines: &[&str]) {
-    let hunk_count = hunks.len();
-
-    // First pass: collect the start lines of each hunk for boundary checking
-    let hunk_starts: Vec<usize> = hunks
-        .iter()
-        .map(|h| h.context.start_line_left.saturating_sub(1)) // <<<<<<< marker line
-        .collect();
-
-    // Second pass: fill in after context
-    for hunk_index in 0..hunk_count {
-        let hunk = &hunks[hunk_index];
-
-        // Find where this hunk ends by looking at its right content start line
-        // and counting forward through the right content
-        let right_start = hunk.context.start_line_right;
-        let right_line_count = if hunk.right.text.is_empty() {
-            0
-        } else {
-            hunk.right.text.lines().count()
-        };
-        let end_marker_line = right_start + right_line_count; // Line with >>>>>>>
-
-        // Collect up to DEFAULT_CONTEXT_LINES after the end marker
-        let after_start = end_marker_line;
-        let after_end = (after_start + DEFAULT_CONTEXT_LINES).min(lines.len());
-
-        // Check if next segment is another conflict
-        let next_conflict_start = if hunk_index + 1 < hunk_count {
-            hunk_starts[hunk_index + 1]
-        } else {
-            usize::MAX
-        };
-
-        let actual_end = after_end.min(next_conflict_start);
-
-        if after_start < actual_end && after_start < lines.len() {
-            hunks[hunk_index].context.after = lines[after_start..actual_end]
-                .iter()
-                .map(|s| (*s).to_string())
-                .collect();
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,6 +445,32 @@ after";
         assert_eq!(result.hunks[0].base.as_ref().unwrap().text, "base content");
     }
 
+    #[test]
+    fn parse_zdiff3_conflict_with_empty_base() {
+        // As written by git with merge.conflictStyle=zdiff3
+        let content = "a\nX\nc\n<<<<<<< HEAD\nZ\n||||||| f0eb6f2\n=======\nY\n>>>>>>> other\nd\n";
+
+        let result = parse_conflict_markers(content).unwrap();
+        assert_eq!(result.hunks.len(), 1);
+        assert_eq!(result.hunks[0].left.text, "Z");
+        assert_eq!(result.hunks[0].base.as_ref().unwrap().text, "");
+        assert_eq!(result.hunks[0].right.text, "Y");
+        assert_eq!(ConflictStyle::detect(&result.hunks), ConflictStyle::Diff3);
+    }
+
+    #[test]
+    fn conflict_style_detection_and_names() {
+        let merge = parse_conflict_markers("<<<<<<< a\nx\n=======\ny\n>>>>>>> b\n").unwrap();
+        assert_eq!(ConflictStyle::detect(&merge.hunks), ConflictStyle::Merge);
+        assert_eq!(
+            ConflictStyle::parse(" zdiff3\n"),
+            Some(ConflictStyle::Zdiff3)
+        );
+        assert_eq!(ConflictStyle::parse("other"), None);
+        assert_eq!(ConflictStyle::Diff3.name(), "diff3");
+        assert!(!ConflictStyle::Merge.has_base());
+    }
+
     #[test]
     fn parse_multiple_hunks() {
         let content = r"// header
@@ -393,8 +502,11 @@ second right
         let result = parse_conflict_markers(content).unwrap();
         assert!(result.hunks.is_empty());
         assert_eq!(result.segments.len(), 1);
-        if let Segment::Clean(text) = &result.segments[0] {
-            assert_eq!(text, "just normal content\nno conflicts here");
+        if let Segment::Clean(range) = &result.segments[0] {
+            assert_eq!(
+                clean_text(content, range),
+                "just normal content\nno conflicts here"
+            );
         } else {
             panic!("Expected Clean segment");
         }
@@ -516,6 +628,69 @@ line 8";
         assert_eq!(result.hunks[0].context.after[2], "line 7");
     }
 
+    #[test]
+    fn context_stops_at_neighboring_conflicts() {
+        let content = r"<<<<<<< HEAD
+a
+=======
+b
+>>>>>>> feature
+middle
+<<<<<<< HEAD
+c
+=======
+d
+>>>>>>> feature";
+
+        let result = parse_conflict_markers(content).unwrap();
+        assert_eq!(result.hunks[0].context.after, vec!["middle"]);
+        assert_eq!(result.hunks[1].context.before, vec!["middle"]);
+    }
+
+    #[test]
+    fn after_context_skips_blank_right_side() {
+        let content = "<<<<<<< HEAD\na\n=======\n\n>>>>>>> feature\nafter\n";
+
+        let result = parse_conflict_markers(content).unwrap();
+        assert_eq!(result.hunks[0].right.text, "");
+        assert_eq!(result.hunks[0].context.after, vec!["after"]);
+    }
+
+    #[test]
+    fn context_keeps_blank_lines_next_to_markers() {
+        let content = "x\na\n\n<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> f\n\ny\n";
+
+        let result = parse_conflict_markers(content).unwrap();
+        assert_eq!(result.hunks[0].context.before, vec!["x", "a", ""]);
+        assert_eq!(result.hunks[0].context.after, vec!["", "y"]);
+    }
+
+    #[test]
+    fn crlf_line_endings_are_dropped_like_lines() {
+        let content =
+            "one\r\ntwo\r\n<<<<<<< HEAD\r\na\r\nb\r\n=======\r\nc\r\n>>>>>>> x\r\nthree\r";
+
+        let result = parse_conflict_markers(content).unwrap();
+        assert_eq!(result.hunks[0].left.text, "a\nb");
+        assert_eq!(result.hunks[0].context.before, vec!["one", "two"]);
+        let texts: Vec<_> = result
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Clean(range) => Some(clean_text(content, range)),
+                Segment::Conflict(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                Some("one\ntwo".into()),
+                None,
+                Some(content.lines().last().unwrap().into()),
+            ]
+        );
+    }
+
     #[test]
     fn line_numbers_are_one_indexed() {
         let content = r"line 1
@@ -676,11 +851,15 @@ after";
 
         let result = parse_conflict_markers(content).unwrap();
         assert_eq!(result.segments.len(), 5);
-        assert!(matches!(&result.segments[0], Segment::Clean(s) if s == "before"));
+        assert!(
+            matches!(&result.segments[0], Segment::Clean(r) if &content[r.clone()] == "before")
+        );
         assert!(matches!(&result.segments[1], Segment::Conflict(0)));
-        assert!(matches!(&result.segments[2], Segment::Clean(s) if s == "middle"));
+        assert!(
+            matches!(&result.segments[2], Segment::Clean(r) if &content[r.clone()] == "middle")
+        );
         assert!(matches!(&result.segments[3], Segment::Conflict(1)));
-        assert!(matches!(&result.segments[4], Segment::Clean(s) if s == "after"));
+        assert!(matches!(&result.segments[4], Segment::Clean(r) if &content[r.clone()] == "after"));
     }
 
     #[test]
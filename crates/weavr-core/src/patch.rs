@@ -0,0 +1,959 @@
+//! Reviewable resolution patches.
+//!
+//! A resolution patch is a unified diff from the conflicted file to the
+//! resolved file, containing a diff hunk per resolved conflict, or per run of
+//! conflicts whose context lines touch. Each diff hunk header is annotated
+//! with how its conflicts were resolved, so reviewers can audit merge
+//! decisions separately from the merge commit's full diff.
+//!
+//! Patches can be parsed back into [`RecordedResolution`]s and replayed onto
+//! a fresh session with [`MergeSession::replay`].
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    parse_conflict_markers, AcceptBothOptions, BothOrder, ConflictHunk, HunkState, MergeSession,
+    ParseError, Resolution, ResolutionMetadata, ResolutionSource, ResolutionStrategyKind,
+};
+
+/// A resolution recorded against a conflict's content, detached from any session.
+///
+/// The left, base, and right texts form the preimage used to find the
+/// matching hunk when the resolution is replayed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResolution {
+    /// Left side content of the conflict.
+    pub left: String,
+    /// Base content of the conflict, if known.
+    pub base: Option<String>,
+    /// Right side content of the conflict.
+    pub right: String,
+    /// The resolution that was chosen.
+    pub resolution: Resolution,
+}
+
+impl RecordedResolution {
+    /// Records a resolution against a hunk's current content.
+    #[must_use]
+    pub fn from_hunk(hunk: &ConflictHunk, resolution: Resolution) -> Self {
+        Self {
+            left: hunk.left.text.clone(),
+            base: hunk.base.as_ref().map(|b| b.text.clone()),
+            right: hunk.right.text.clone(),
+            resolution,
+        }
+    }
+
+    /// Returns the preimage key of the recorded conflict.
+    ///
+    /// See [`preimage_key`].
+    #[must_use]
+    pub fn key(&self) -> String {
+        preimage_key(&self.left, self.base.as_deref(), &self.right)
+    }
+
+    /// Returns true if this recording was made against the given hunk's content.
+    ///
+    /// Base content is only compared when both sides have it.
+    #[must_use]
+    pub fn matches(&self, hunk: &ConflictHunk) -> bool {
+        let base_matches = match (&self.base, &hunk.base) {
+            (Some(recorded), Some(current)) => *recorded == current.text,
+            _ => true,
+        };
+        self.left == hunk.left.text && self.right == hunk.right.text && base_matches
+    }
+}
+
+/// Returns a stable identifier for a conflict's content.
+///
+/// The key is a 64-bit FNV-1a hash of the left, base, and right texts,
+/// rendered as 16 hex digits. It does not depend on the platform or Rust
+/// version, so keys can be shared between machines.
+///
+/// # Examples
+///
+/// ```
+/// use weavr_core::preimage_key;
+///
+/// let key = preimage_key("left", None, "right");
+/// assert_eq!(key.len(), 16);
+/// assert_eq!(key, preimage_key("left", None, "right"));
+/// assert_ne!(key, preimage_key("right", None, "left"));
+/// ```
+#[must_use]
+pub fn preimage_key(left: &str, base: Option<&str>, right: &str) -> String {
+    // Separators keep ("ab", "c") and ("a", "bc") apart; the base marker
+    // distinguishes a missing base from an empty one
+    fnv1a(&[
+        left.as_bytes(),
+        &[0],
+        base.map_or(&[1][..], str::as_bytes),
+        &[0],
+        right.as_bytes(),
+    ])
+}
+
+/// Returns the 64-bit FNV-1a hash of the concatenated `parts`, as 16 hex
+/// digits.
+pub(crate) fn fnv1a(parts: &[&[u8]]) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = parts
+        .iter()
+        .flat_map(|part| part.iter())
+        .fold(OFFSET_BASIS, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        });
+    format!("{hash:016x}")
+}
+
+/// Resolutions recorded for a single file in a resolution patch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolutionPatchFile {
+    /// Path of the file, as written in the patch.
+    pub path: PathBuf,
+    /// Recorded resolutions in file order.
+    pub resolutions: Vec<RecordedResolution>,
+}
+
+/// Renders the resolved hunks of a session as an annotated unified diff.
+///
+/// The old side of each diff hunk is the original conflict block (markers
+/// included) and the new side is the chosen resolution, both surrounded by
+/// the hunk's context lines. Conflicts whose context lines overlap or touch
+/// share one diff hunk, whose header describes each in turn, so the patch
+/// applies with `git apply` and `patch`. Unresolved hunks are omitted.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use weavr_core::{format_resolution_patch, MergeSession, Resolution};
+///
+/// let content = "before\n<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> feature\nafter";
+/// let mut session = MergeSession::from_conflicted(content, PathBuf::from("f.txt")).unwrap();
+/// let hunk = session.hunks()[0].clone();
+/// session.set_resolution(hunk.id, Resolution::accept_right(&hunk)).unwrap();
+///
+/// let patch = format_resolution_patch(&session);
+/// assert!(patch.contains("@@ -1,7 +1,3 @@ hunk 1: accept-right"));
+/// assert!(patch.contains("\n+right\n"));
+/// ```
+#[must_use]
+pub fn format_resolution_patch(session: &MergeSession) -> String {
+    let path = session.input().left.path.display().to_string();
+    let original: Vec<&str> = session.conflicted().lines().collect();
+    let hunks = session.hunks();
+
+    let resolved: Vec<(&ConflictHunk, &Resolution)> = hunks
+        .iter()
+        .filter_map(|hunk| match &hunk.state {
+            HunkState::Resolved(resolution) => Some((hunk, resolution)),
+            _ => None,
+        })
+        .collect();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# weavr resolution patch for {path}");
+    let _ = writeln!(
+        out,
+        "# {} of {} hunks resolved",
+        resolved.len(),
+        hunks.len()
+    );
+
+    if resolved.is_empty() {
+        return out;
+    }
+
+    let _ = writeln!(out, "--- a/{path}");
+    let _ = writeln!(out, "+++ b/{path}");
+
+    // Line totals of the groups so far, to translate old line numbers into new ones
+    let mut added: usize = 0;
+    let mut removed: usize = 0;
+
+    for group in group_touching(&resolved, &original) {
+        let first = &group[0];
+        let last = &group[group.len() - 1];
+        let before = &first.hunk.context.before;
+        let after = &last.hunk.context.after;
+
+        let mut body = Vec::new();
+        body.extend(before.iter().map(|line| format!(" {line}")));
+        let mut old_count = before.len() + after.len();
+        let mut new_count = old_count;
+        for (i, block) in group.iter().enumerate() {
+            if i > 0 {
+                // Unchanged lines between two conflicts of the group
+                let shared = between(&group[i - 1], block);
+                old_count += shared.len();
+                new_count += shared.len();
+                body.extend(shared.into_iter().map(|line| format!(" {line}")));
+            }
+            body.extend(block.lines.iter().map(|line| format!("-{line}")));
+            let resolution_lines = text_lines(&block.resolution.content);
+            old_count += block.lines.len();
+            new_count += resolution_lines.len();
+            body.extend(resolution_lines.into_iter().map(|line| format!("+{line}")));
+        }
+        body.extend(after.iter().map(|line| format!(" {line}")));
+
+        let old_start = first.start + 1 - before.len();
+        // An empty new side is numbered by the line before it
+        let new_start = old_start + added - removed - usize::from(new_count == 0);
+        added += new_count;
+        removed += old_count;
+
+        let descriptions: Vec<String> = group
+            .iter()
+            .map(|block| describe(block.hunk.id.0 as usize + 1, block.resolution))
+            .collect();
+        let _ = writeln!(
+            out,
+            "@@ -{old_start},{old_count} +{new_start},{new_count} @@ {}",
+            descriptions.join("; ")
+        );
+        for line in body {
+            let _ = writeln!(out, "{line}");
+        }
+    }
+
+    out
+}
+
+/// A resolved conflict block of the original file.
+struct ResolvedBlock<'a> {
+    hunk: &'a ConflictHunk,
+    resolution: &'a Resolution,
+    /// The block's lines, markers included.
+    lines: Vec<String>,
+    /// 0-based line of its `<<<<<<<` marker.
+    start: usize,
+}
+
+impl ResolvedBlock<'_> {
+    /// Returns the 0-based line after the block.
+    fn end(&self) -> usize {
+        self.start + self.lines.len()
+    }
+}
+
+/// Groups resolved conflicts whose context lines overlap or touch, so each
+/// group becomes a single diff hunk: patch tools reject overlapping hunks.
+fn group_touching<'a>(
+    resolved: &[(&'a ConflictHunk, &'a Resolution)],
+    original: &[&str],
+) -> Vec<Vec<ResolvedBlock<'a>>> {
+    let mut groups: Vec<Vec<ResolvedBlock<'a>>> = Vec::new();
+    for &(hunk, resolution) in resolved {
+        let block = ResolvedBlock {
+            hunk,
+            resolution,
+            lines: conflict_block(hunk, original),
+            // start_line_left is the first line after the <<<<<<< marker
+            start: hunk.context.start_line_left.saturating_sub(2),
+        };
+        match groups.last_mut().and_then(|group| group.last()) {
+            Some(previous)
+                if block.start - hunk.context.before.len()
+                    <= previous.end() + previous.hunk.context.after.len() =>
+            {
+                if let Some(group) = groups.last_mut() {
+                    group.push(block);
+                }
+            }
+            _ => groups.push(vec![block]),
+        }
+    }
+    groups
+}
+
+/// Returns the unchanged lines between two conflicts of a group, taken from
+/// the context lines that cover them.
+fn between<'a>(previous: &'a ResolvedBlock<'_>, next: &'a ResolvedBlock<'_>) -> Vec<&'a str> {
+    let count = next.start - previous.end();
+    let after = &previous.hunk.context.after;
+    let before = &next.hunk.context.before;
+    let from_after = count.min(after.len());
+    after[..from_after]
+        .iter()
+        .chain(&before[before.len() - (count - from_after)..])
+        .map(String::as_str)
+        .collect()
+}
+
+/// Renders recorded resolutions as a resolution patch without context lines.
+///
+/// The output can be read back with [`parse_resolution_patch`], so recorded
+/// resolutions can be stored and exchanged as ordinary patch files.
+#[must_use]
+pub fn format_recorded_resolutions(path: &Path, recorded: &[RecordedResolution]) -> String {
+    let path = path.display().to_string();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# weavr resolution patch for {path}");
+    let _ = writeln!(out, "# {} recorded resolutions", recorded.len());
+
+    if recorded.is_empty() {
+        return out;
+    }
+
+    let _ = writeln!(out, "--- a/{path}");
+    let _ = writeln!(out, "+++ b/{path}");
+
+    for (i, entry) in recorded.iter().enumerate() {
+        let conflict_lines = marker_block(&entry.left, entry.base.as_deref(), &entry.right);
+        let resolution_lines = text_lines(&entry.resolution.content);
+
+        let _ = writeln!(
+            out,
+            "@@ -1,{} +{},{} @@ {}",
+            conflict_lines.len(),
+            usize::from(!resolution_lines.is_empty()),
+            resolution_lines.len(),
+            describe(i + 1, &entry.resolution)
+        );
+        for line in &conflict_lines {
+            let _ = writeln!(out, "-{line}");
+        }
+        for line in &resolution_lines {
+            let _ = writeln!(out, "+{line}");
+        }
+    }
+
+    out
+}
+
+/// Returns the lines of a hunk's conflict block, markers included.
+///
+/// Lines are taken verbatim from the original content when available so
+/// marker labels are preserved; otherwise the block is rebuilt from the hunk.
+fn conflict_block(hunk: &ConflictHunk, original: &[&str]) -> Vec<String> {
+    let marker_index = hunk.context.start_line_left.checked_sub(2);
+    if let Some(start) = marker_index.filter(|&i| {
+        original
+            .get(i)
+            .is_some_and(|line| line.starts_with("<<<<<<<"))
+    }) {
+        if let Some(len) = original[start..]
+            .iter()
+            .position(|line| line.starts_with(">>>>>>>"))
+        {
+            return original[start..=start + len]
+                .iter()
+                .map(|line| (*line).to_string())
+                .collect();
+        }
+    }
+
+    marker_block(
+        &hunk.left.text,
+        hunk.base.as_ref().map(|b| b.text.as_str()),
+        &hunk.right.text,
+    )
+}
+
+/// Builds a conflict block with generic marker labels from side texts.
+pub(crate) fn marker_block(left: &str, base: Option<&str>, right: &str) -> Vec<String> {
+    let mut lines = vec!["<<<<<<< ours".to_string()];
+    lines.extend(text_lines(left));
+    if let Some(base) = base {
+        lines.push("||||||| base".to_string());
+        lines.extend(text_lines(base));
+    }
+    lines.push("=======".to_string());
+    lines.extend(text_lines(right));
+    lines.push(">>>>>>> theirs".to_string());
+    lines
+}
+
+/// Splits hunk side text into lines, treating empty text as no lines.
+fn text_lines(text: &str) -> Vec<String> {
+    if text.is_empty() {
+        Vec::new()
+    } else {
+        text.split('\n').map(str::to_string).collect()
+    }
+}
+
+/// Builds the annotation shown after a diff hunk header.
+fn describe(number: usize, resolution: &Resolution) -> String {
+    let mut text = format!(
+        "hunk {number}: {} by {}",
+        describe_strategy(&resolution.kind),
+        resolution.metadata.source.name()
+    );
+
+    if let Some(notes) = &resolution.metadata.notes {
+        let single_line = notes.split_whitespace().collect::<Vec<_>>().join(" ");
+        let _ = write!(text, "; note: {single_line}");
+    }
+
+    text
+}
+
+/// Names a strategy along with its options, e.g. `accept-both (left first,
+/// deduplicated)`.
+pub(crate) fn describe_strategy(kind: &ResolutionStrategyKind) -> String {
+    let mut text = kind.name().to_string();
+
+    match kind {
+        ResolutionStrategyKind::AcceptBoth(options) => {
+            let mut details = vec![match options.order {
+                BothOrder::LeftThenRight => "left first",
+                BothOrder::RightThenLeft => "right first",
+            }];
+            if options.deduplicate {
+                details.push("deduplicated");
+            }
+            if options.trim_whitespace {
+                details.push("whitespace-insensitive");
+            }
+            let _ = write!(text, " ({})", details.join(", "));
+        }
+        ResolutionStrategyKind::AstMerged { language } => {
+            let _ = write!(text, " ({language})");
+        }
+        ResolutionStrategyKind::AiSuggested { provider } => {
+            let _ = write!(text, " ({provider})");
+        }
+        _ => {}
+    }
+
+    text
+}
+
+/// Parses a resolution patch produced by [`format_resolution_patch`].
+///
+/// Patches for several files may be concatenated; one
+/// [`ResolutionPatchFile`] is returned per `--- a/<path>` section.
+///
+/// # Errors
+///
+/// Returns `ParseError::MalformedContent` if a diff hunk appears outside a
+/// file section, has an unrecognized annotation, or does not contain exactly
+/// one conflict block.
+pub fn parse_resolution_patch(text: &str) -> Result<Vec<ResolutionPatchFile>, ParseError> {
+    let mut files: Vec<ResolutionPatchFile> = Vec::new();
+    let mut pending: Option<PendingHunk> = None;
+
+    for (line_num, line) in text.lines().enumerate() {
+        let one_indexed = line_num + 1;
+
+        if let Some(path) = line.strip_prefix("--- a/") {
+            finish_hunk(&mut files, pending.take())?;
+            files.push(ResolutionPatchFile {
+                path: PathBuf::from(path),
+                resolutions: Vec::new(),
+            });
+        } else if line.starts_with("@@ ") {
+            finish_hunk(&mut files, pending.take())?;
+            if files.is_empty() {
+                return Err(ParseError::MalformedContent(format!(
+                    "diff hunk outside a file section at line {one_indexed}"
+                )));
+            }
+            pending = Some(PendingHunk::new(line, one_indexed)?);
+        } else if let Some(hunk) = pending.as_mut() {
+            if let Some(rest) = line.strip_prefix('-') {
+                hunk.remove(rest);
+            } else if let Some(rest) = line.strip_prefix('+') {
+                hunk.add(rest);
+            } else if !line.starts_with(' ') {
+                // Anything else ends the hunk body
+                finish_hunk(&mut files, pending.take())?;
+            }
+        }
+    }
+    finish_hunk(&mut files, pending)?;
+
+    Ok(files)
+}
+
+/// A diff hunk being collected while parsing a resolution patch.
+struct PendingHunk<'a> {
+    /// Line of the `@@` header, for error messages.
+    header_line: usize,
+    /// Resolution kind and metadata of each conflict, from the header
+    /// annotation.
+    annotations: Vec<(ResolutionStrategyKind, ResolutionMetadata)>,
+    /// Removed lines (a conflict block) and added lines (its resolution)
+    /// of each conflict.
+    blocks: Vec<(Vec<&'a str>, Vec<&'a str>)>,
+}
+
+impl<'a> PendingHunk<'a> {
+    /// Starts a hunk from its `@@ -a,b +c,d @@ hunk N: ...` header, which
+    /// describes each conflict of the hunk in turn.
+    fn new(header: &'a str, header_line: usize) -> Result<Self, ParseError> {
+        let malformed = || {
+            ParseError::MalformedContent(format!(
+                "unrecognized hunk annotation at line {header_line}"
+            ))
+        };
+
+        let annotation = header
+            .splitn(3, "@@")
+            .nth(2)
+            .map(str::trim)
+            .filter(|rest| rest.starts_with("hunk "))
+            .ok_or_else(malformed)?;
+        let annotations = split_descriptions(annotation)
+            .into_iter()
+            .map(|description| {
+                description
+                    .split_once(": ")
+                    .and_then(|(_, description)| parse_description(description))
+                    .ok_or_else(malformed)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            header_line,
+            annotations,
+            blocks: Vec::new(),
+        })
+    }
+
+    /// Adds a removed line, starting a new conflict after a finished one.
+    fn remove(&mut self, line: &'a str) {
+        let finished = self.blocks.last().map_or(true, |(conflict, _)| {
+            conflict
+                .last()
+                .is_some_and(|line| line.starts_with(">>>>>>>"))
+        });
+        if finished {
+            self.blocks.push((Vec::new(), Vec::new()));
+        }
+        if let Some((conflict, _)) = self.blocks.last_mut() {
+            conflict.push(line);
+        }
+    }
+
+    /// Adds an added line to the resolution of the last conflict.
+    fn add(&mut self, line: &'a str) {
+        if let Some((_, resolution)) = self.blocks.last_mut() {
+            resolution.push(line);
+        }
+    }
+}
+
+/// Splits a header annotation into the `hunk N: ...` description of each
+/// conflict.
+fn split_descriptions(annotation: &str) -> Vec<&str> {
+    let mut descriptions = Vec::new();
+    let mut start = 0;
+    for (at, _) in annotation.match_indices("; hunk ") {
+        let rest = &annotation[at + "; hunk ".len()..];
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 && rest[digits..].starts_with(": ") {
+            descriptions.push(&annotation[start..at]);
+            start = at + "; ".len();
+        }
+    }
+    descriptions.push(&annotation[start..]);
+    descriptions
+}
+
+/// Parses a description such as `accept-both (left first) by user; note:
+/// ...` into a resolution kind and metadata.
+fn parse_description(annotation: &str) -> Option<(ResolutionStrategyKind, ResolutionMetadata)> {
+    let (description, notes) = match annotation.split_once("; note: ") {
+        Some((description, note)) => (description, Some(note.to_string())),
+        None => (annotation, None),
+    };
+    let (kind_text, source_name) = description.rsplit_once(" by ")?;
+    let source = match source_name {
+        "user" => ResolutionSource::User,
+        "ai" => ResolutionSource::Ai,
+        "ast" => ResolutionSource::Ast,
+        "rule" => ResolutionSource::Rule,
+        "rerere" => ResolutionSource::Rerere,
+        _ => return None,
+    };
+
+    let (name, details) = match kind_text.split_once(" (") {
+        Some((name, rest)) => (name, rest.strip_suffix(')').unwrap_or(rest)),
+        None => (kind_text, ""),
+    };
+    let kind = match name {
+        "accept-left" => ResolutionStrategyKind::AcceptLeft,
+        "accept-right" => ResolutionStrategyKind::AcceptRight,
+        "accept-base" => ResolutionStrategyKind::AcceptBase,
+        "accept-both" => ResolutionStrategyKind::AcceptBoth(AcceptBothOptions {
+            order: if details.contains("right first") {
+                BothOrder::RightThenLeft
+            } else {
+                BothOrder::LeftThenRight
+            },
+            deduplicate: details.contains("deduplicated"),
+            trim_whitespace: details.contains("whitespace-insensitive"),
+        }),
+        "manual" => ResolutionStrategyKind::Manual,
+        "ast-merged" => ResolutionStrategyKind::AstMerged {
+            language: details.to_string(),
+        },
+        "ai-suggested" => ResolutionStrategyKind::AiSuggested {
+            provider: details.to_string(),
+        },
+        _ => return None,
+    };
+
+    Some((
+        kind,
+        ResolutionMetadata {
+            source,
+            notes,
+            propagated_from: None,
+        },
+    ))
+}
+
+/// Converts a collected hunk into recorded resolutions on the last file.
+fn finish_hunk(
+    files: &mut [ResolutionPatchFile],
+    pending: Option<PendingHunk<'_>>,
+) -> Result<(), ParseError> {
+    let Some(hunk) = pending else {
+        return Ok(());
+    };
+
+    if hunk.blocks.len() != hunk.annotations.len() {
+        return Err(ParseError::MalformedContent(format!(
+            "expected {} conflict blocks in hunk at line {}",
+            hunk.annotations.len(),
+            hunk.header_line
+        )));
+    }
+    for ((conflict, resolution), (kind, metadata)) in hunk.blocks.into_iter().zip(hunk.annotations)
+    {
+        let parsed = parse_conflict_markers(&conflict.join("\n"))?;
+        let [conflict] = parsed.hunks.as_slice() else {
+            return Err(ParseError::MalformedContent(format!(
+                "expected one conflict block per resolution in hunk at line {}",
+                hunk.header_line
+            )));
+        };
+
+        if let Some(file) = files.last_mut() {
+            let resolution = Resolution {
+                kind,
+                content: resolution.join("\n"),
+                metadata,
+            };
+            file.resolutions
+                .push(RecordedResolution::from_hunk(conflict, resolution));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(content: &str) -> MergeSession {
+        MergeSession::from_conflicted(content, PathBuf::from("src/lib.rs")).unwrap()
+    }
+
+    #[test]
+    fn patch_without_resolutions_has_only_header() {
+        let session = session("a\n<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> feature\nb");
+        let patch = format_resolution_patch(&session);
+        assert_eq!(
+            patch,
+            "# weavr resolution patch for src/lib.rs\n# 0 of 1 hunks resolved\n"
+        );
+    }
+
+    #[test]
+    fn patch_contains_conflict_and_resolution() {
+        let mut session = session("a\n<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> feature\nb");
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(hunk.id, Resolution::accept_left(&hunk))
+            .unwrap();
+
+        let patch = format_resolution_patch(&session);
+        let expected = "\
+# weavr resolution patch for src/lib.rs
+# 1 of 1 hunks resolved
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,7 +1,3 @@ hunk 1: accept-left by user
+ a
+-<<<<<<< HEAD
+-l
+-=======
+-r
+->>>>>>> feature
++l
+ b
+";
+        assert_eq!(patch, expected);
+    }
+
+    #[test]
+    fn patch_merges_conflicts_with_touching_context() {
+        let content = "\
+<<<<<<< HEAD
+one
+=======
+uno
+>>>>>>> feature
+middle
+<<<<<<< HEAD
+two
+=======
+dos
+>>>>>>> feature";
+        let mut session = session(content);
+        let first = session.hunks()[0].clone();
+        let second = session.hunks()[1].clone();
+        session
+            .set_resolution(first.id, Resolution::accept_right(&first))
+            .unwrap();
+        session
+            .set_resolution(second.id, Resolution::accept_left(&second))
+            .unwrap();
+
+        // Both conflicts share "middle" as context, so they form one hunk
+        let patch = format_resolution_patch(&session);
+        let expected = "\
+# weavr resolution patch for src/lib.rs
+# 2 of 2 hunks resolved
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,11 +1,3 @@ hunk 1: accept-right by user; hunk 2: accept-left by user
+-<<<<<<< HEAD
+-one
+-=======
+-uno
+->>>>>>> feature
++uno
+ middle
+-<<<<<<< HEAD
+-two
+-=======
+-dos
+->>>>>>> feature
++two
+";
+        assert_eq!(patch, expected);
+
+        let files = parse_resolution_patch(&patch).unwrap();
+        let recorded = &files[0].resolutions;
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].resolution.content, "uno");
+        assert_eq!(
+            recorded[1].resolution.kind,
+            ResolutionStrategyKind::AcceptLeft
+        );
+        assert_eq!(recorded[1].left, "two");
+    }
+
+    #[test]
+    fn patch_skips_unresolved_and_offsets_line_numbers() {
+        let lines = |from: usize, to: usize| -> String {
+            (from..=to).map(|i| i.to_string() + "\n").collect()
+        };
+        let block = |side: &str| format!("<<<<<<< HEAD\n{side}\n=======\nother\n>>>>>>> x\n");
+        let content = format!(
+            "{}{}{}{}{}",
+            block("a"),
+            lines(1, 8),
+            block("b"),
+            lines(9, 16),
+            block("c")
+        );
+        let mut session = session(&content);
+        let first = session.hunks()[0].clone();
+        let last = session.hunks()[2].clone();
+        session
+            .set_resolution(first.id, Resolution::manual(String::new()))
+            .unwrap();
+        session
+            .set_resolution(last.id, Resolution::accept_left(&last))
+            .unwrap();
+
+        let patch = format_resolution_patch(&session);
+        // An empty resolution adds no lines
+        assert!(patch.contains("@@ -1,8 +1,3 @@ hunk 1: manual by user\n-<<<<<<< HEAD\n"));
+        assert!(patch.contains("->>>>>>> x\n 1\n"));
+        // The last block starts on old line 27; the first shrank by five
+        assert!(patch.contains("@@ -24,8 +19,4 @@ hunk 3: accept-left by user"));
+        assert!(patch.ends_with("->>>>>>> x\n+c\n"));
+    }
+
+    #[test]
+    fn patch_describes_accept_both_options_and_notes() {
+        let mut session = session("<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> feature");
+        let hunk = session.hunks()[0].clone();
+        let options = AcceptBothOptions {
+            order: BothOrder::RightThenLeft,
+            deduplicate: true,
+            trim_whitespace: false,
+        };
+        let mut resolution = Resolution::accept_both(&hunk, &options);
+        resolution.metadata.notes = Some("kept both\nimports".to_string());
+        session.set_resolution(hunk.id, resolution).unwrap();
+
+        let patch = format_resolution_patch(&session);
+        assert!(patch.contains(
+            "hunk 1: accept-both (right first, deduplicated) by user; note: kept both imports"
+        ));
+    }
+
+    #[test]
+    fn parse_roundtrips_formatted_patch() {
+        let content = "a\n<<<<<<< HEAD\nl\n||||||| base\nb\n=======\nr\n>>>>>>> f\nz\n<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> f";
+        let mut session = session(content);
+        let first = session.hunks()[0].clone();
+        let second = session.hunks()[1].clone();
+        let options = AcceptBothOptions {
+            order: BothOrder::RightThenLeft,
+            deduplicate: true,
+            trim_whitespace: true,
+        };
+        let mut both = Resolution::accept_both(&first, &options);
+        both.metadata.notes = Some("order matters".to_string());
+        session.set_resolution(first.id, both.clone()).unwrap();
+        let manual = Resolution::manual("x\n\ny".to_string());
+        session.set_resolution(second.id, manual.clone()).unwrap();
+
+        let files = parse_resolution_patch(&format_resolution_patch(&session)).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, PathBuf::from("src/lib.rs"));
+        assert_eq!(
+            files[0].resolutions,
+            vec![
+                RecordedResolution {
+                    left: "l".to_string(),
+                    base: Some("b".to_string()),
+                    right: "r".to_string(),
+                    resolution: both,
+                },
+                RecordedResolution {
+                    left: "x".to_string(),
+                    base: None,
+                    right: "y".to_string(),
+                    resolution: manual,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_roundtrips_empty_resolution() {
+        let mut session = session("<<<<<<< HEAD\nl\n=======\nr\n>>>>>>> f");
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(hunk.id, Resolution::manual(String::new()))
+            .unwrap();
+
+        let files = parse_resolution_patch(&format_resolution_patch(&session)).unwrap();
+        assert_eq!(files[0].resolutions[0].resolution.content, "");
+    }
+
+    #[test]
+    fn recorded_resolutions_roundtrip() {
+        let recorded = vec![
+            RecordedResolution {
+                left: "l".to_string(),
+                base: Some(String::new()),
+                right: "r1\nr2".to_string(),
+                resolution: Resolution::manual("merged".to_string()),
+            },
+            RecordedResolution {
+                left: String::new(),
+                base: None,
+                right: "r".to_string(),
+                resolution: Resolution::manual(String::new()),
+            },
+        ];
+
+        let text = format_recorded_resolutions(Path::new("a/b.rs"), &recorded);
+        let files = parse_resolution_patch(&text).unwrap();
+        assert_eq!(files[0].path, PathBuf::from("a/b.rs"));
+        assert_eq!(files[0].resolutions, recorded);
+    }
+
+    #[test]
+    fn preimage_key_distinguishes_missing_base() {
+        assert_ne!(
+            preimage_key("a", None, "b"),
+            preimage_key("a", Some(""), "b")
+        );
+        assert_ne!(preimage_key("ab", None, "c"), preimage_key("a", None, "bc"));
+        // Pinned so stored keys stay valid across releases
+        assert_eq!(preimage_key("", None, ""), "d949ac186c0c4c8e");
+    }
+
+    #[test]
+    fn parse_rejects_unknown_annotation() {
+        let patch = "--- a/f\n+++ b/f\n@@ -1,5 +1,1 @@ hunk 1: guess by user\n-<<<<<<< a\n-=======\n->>>>>>> b\n+x\n";
+        assert!(matches!(
+            parse_resolution_patch(patch),
+            Err(ParseError::MalformedContent(msg)) if msg.contains("line 3")
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_hunk_outside_file() {
+        let patch = "@@ -1,5 +1,1 @@ hunk 1: manual by user\n";
+        assert!(parse_resolution_patch(patch).is_err());
+    }
+
+    #[test]
+    fn recorded_resolution_ignores_missing_base() {
+        let session = session("<<<<<<< HEAD\nl\n||||||| base\nb\n=======\nr\n>>>>>>> f");
+        let hunk = &session.hunks()[0];
+        let mut recorded = RecordedResolution {
+            left: "l".to_string(),
+            base: None,
+            right: "r".to_string(),
+            resolution: Resolution::manual(String::new()),
+        };
+        assert!(recorded.matches(hunk));
+        recorded.base = Some("other".to_string());
+        assert!(!recorded.matches(hunk));
+    }
+
+    #[test]
+    fn patch_rebuilds_markers_without_original_content() {
+        let hunk = ConflictHunk {
+            id: crate::HunkId(0),
+            left: crate::HunkContent {
+                text: "l".to_string(),
+            },
+            right: crate::HunkContent {
+                text: String::new(),
+            },
+            base: Some(crate::HunkContent {
+                text: "b".to_string(),
+            }),
+            context: crate::HunkContext::default(),
+            state: HunkState::Unresolved,
+        };
+        assert_eq!(
+            conflict_block(&hunk, &[]),
+            vec![
+                "<<<<<<< ours",
+                "l",
+                "||||||| base",
+                "b",
+                "=======",
+                ">>>>>>> theirs"
+            ]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Recovering resolutions from a finished merge.
+//!
+//! Given the conflicts a merge produced and the file that was eventually
+//! committed, the text between the conflicts' surrounding clean lines is what
+//! each conflict was resolved to. This lets a merge made with any tool be
+//! reviewed hunk by hunk after the fact.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use crate::{
+    AcceptBothOptions, BothOrder, ConflictHunk, HunkId, MergeSession, Resolution,
+    ResolutionMetadata, ResolutionStrategyKind, Segment,
+};
+
+/// Finds how each hunk of `session` was resolved in `merged`.
+///
+/// Clean text between conflicts is located in `merged` in order, and the
+/// lines in between become the resolution of the conflict there. Hunks that
+/// cannot be located, because the clean text around them was edited too or
+/// two conflicts are adjacent, are left out.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use weavr_core::{recover_resolutions, MergeSession, ResolutionStrategyKind};
+///
+/// let content = "a\n<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> feature\nb\n";
+/// let session = MergeSession::from_conflicted(content, PathBuf::from("f.txt")).unwrap();
+///
+/// let recovered = recover_resolutions(&session, "a\nright\nb\n");
+/// assert_eq!(recovered[0].1.kind, ResolutionStrategyKind::AcceptRight);
+/// ```
+#[must_use]
+pub fn recover_resolutions(session: &MergeSession, merged: &str) -> Vec<(HunkId, Resolution)> {
+    let merged = merged.strip_suffix('\n').unwrap_or(merged);
+    let lines: Vec<&str> = merged.split('\n').collect();
+    let hunks = session.hunks();
+
+    let mut recovered = Vec::new();
+    let mut cursor = 0;
+    // Hunk whose end is found by locating the next clean segment
+    let mut pending: Option<usize> = None;
+
+    for segment in session.segments() {
+        match segment {
+            Segment::Conflict(index) => {
+                if pending.is_some() {
+                    // Adjacent conflicts: nothing tells where one ends
+                    return recovered;
+                }
+                pending = Some(*index);
+            }
+            Segment::Clean(range) => {
+                let text = session.clean_text(range);
+                let clean: Vec<&str> = text.split('\n').collect();
+                let Some(start) = find(&lines, &clean, cursor) else {
+                    return recovered;
+                };
+                if let Some(index) = pending.take() {
+                    let content = lines[cursor..start].join("\n");
+                    recovered.push((hunks[index].id, infer_resolution(&hunks[index], content)));
+                } else if start != cursor {
+                    // Lines added outside any conflict; they are not ours to
+                    // attribute, so stop rather than guess
+                    return recovered;
+                }
+                cursor = start + clean.len();
+            }
+        }
+    }
+
+    if let Some(index) = pending {
+        let content = lines[cursor.min(lines.len())..].join("\n");
+        recovered.push((hunks[index].id, infer_resolution(&hunks[index], content)));
+    }
+    recovered
+}
+
+/// Describes `content` as a resolution of `hunk`: one side, the base, both
+/// sides, or a manual edit.
+#[must_use]
+pub fn infer_resolution(hunk: &ConflictHunk, content: String) -> Resolution {
+    let left = &hunk.left.text;
+    let right = &hunk.right.text;
+    let kind = if content == *left {
+        ResolutionStrategyKind::AcceptLeft
+    } else if content == *right {
+        ResolutionStrategyKind::AcceptRight
+    } else if hunk.base.as_ref().is_some_and(|base| content == base.text) {
+        ResolutionStrategyKind::AcceptBase
+    } else if content == format!("{left}\n{right}") {
+        ResolutionStrategyKind::AcceptBoth(AcceptBothOptions::default())
+    } else if content == format!("{right}\n{left}") {
+        ResolutionStrategyKind::AcceptBoth(AcceptBothOptions {
+            order: BothOrder::RightThenLeft,
+            ..AcceptBothOptions::default()
+        })
+    } else {
+        ResolutionStrategyKind::Manual
+    };
+    Resolution {
+        kind,
+        content,
+        metadata: ResolutionMetadata::default(),
+    }
+}
+
+/// Finds `needle` in `lines` at or after `from`.
+fn find(lines: &[&str], needle: &[&str], from: usize) -> Option<usize> {
+    if needle.len() > lines.len() {
+        return None;
+    }
+    (from..=lines.len() - needle.len()).find(|&start| lines[start..start + needle.len()] == *needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn session(content: &str) -> MergeSession {
+        MergeSession::from_conflicted(content, PathBuf::from("f.rs")).unwrap()
+    }
+
+    #[test]
+    fn recovers_each_hunk_between_clean_lines() {
+        let session = session(
+            "fn a() {\n<<<<<<< HEAD\n    one();\n=======\n    two();\n>>>>>>> x\n}\n\n\
+             fn b() {\n<<<<<<< HEAD\n    three();\n=======\n    four();\n>>>>>>> x\n}\n",
+        );
+        let merged = "fn a() {\n    one();\n    two();\n}\n\nfn b() {\n    both();\n}\n";
+
+        let recovered = recover_resolutions(&session, merged);
+        assert_eq!(recovered.len(), 2);
+        assert_eq!(
+            recovered[0].1.kind,
+            ResolutionStrategyKind::AcceptBoth(AcceptBothOptions::default())
+        );
+        assert_eq!(recovered[1].1.kind, ResolutionStrategyKind::Manual);
+        assert_eq!(recovered[1].1.content, "    both();");
+    }
+
+    #[test]
+    fn hunks_at_either_end_and_removed_hunks() {
+        let session = session(
+            "<<<<<<< HEAD\nfirst\n=======\n1st\n>>>>>>> x\nmiddle\n<<<<<<< HEAD\nlast\n=======\n>>>>>>> x\n",
+        );
+        let recovered = recover_resolutions(&session, "1st\nmiddle\n");
+        assert_eq!(recovered[0].1.kind, ResolutionStrategyKind::AcceptRight);
+        assert_eq!(recovered[1].1.kind, ResolutionStrategyKind::AcceptRight);
+        assert_eq!(recovered[1].1.content, "");
+    }
+
+    #[test]
+    fn edited_context_stops_recovery() {
+        let session = session("a\n<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> t\nb\n");
+        assert!(recover_resolutions(&session, "a\nx\nc\n").is_empty());
+        assert!(recover_resolutions(&session, "z\na\nx\nb\n").is_empty());
+    }
+}
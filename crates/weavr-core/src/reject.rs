@@ -0,0 +1,300 @@
+//! Turning rejected patch hunks into conflicts.
+//!
+//! When a patch no longer applies, `git apply --reject` applies what it can
+//! and writes the failing hunks to a `.rej` file. [`place_rejected_hunks`]
+//! finds where each of those hunks most likely belongs in the file as it is
+//! now and writes it there as a conflict: the current lines on one side, the
+//! patched lines on the other, and the lines the patch expected as the base.
+//! The hunks can then be resolved like any other conflict.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+/// A hunk of a unified diff that failed to apply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedHunk {
+    /// 1-based line where the hunk expected to start.
+    pub old_start: usize,
+    /// Lines the patch expected: context and removed lines.
+    pub old: Vec<String>,
+    /// Lines the patch produces: context and added lines.
+    pub new: Vec<String>,
+}
+
+/// Parses the hunks of a `.rej` file, or of any unified diff for one file.
+///
+/// Hunks with several separate changes are split between them, sharing out
+/// the context lines in between, so each change is placed on its own. Header
+/// lines before the first `@@` are ignored, as are `\ No newline at end of
+/// file` notes.
+#[must_use]
+pub fn parse_rejected_hunks(text: &str) -> Vec<RejectedHunk> {
+    let mut hunks = Vec::new();
+    // Start and lines of the hunk being read, each with its diff prefix
+    let mut current: Option<(usize, Vec<(char, String)>)> = None;
+    for line in text.lines() {
+        if let Some(header) = line.strip_prefix("@@ -") {
+            if let Some((start, lines)) = current.take() {
+                hunks.extend(split(start, &lines));
+            }
+            let start = header
+                .split([',', ' '])
+                .next()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1);
+            current = Some((start, Vec::new()));
+            continue;
+        }
+        let Some((_, lines)) = current.as_mut() else {
+            continue;
+        };
+        match line.chars().next() {
+            Some(kind @ (' ' | '-' | '+')) => lines.push((kind, line[1..].to_string())),
+            // Some tools strip the space from empty context lines
+            None => lines.push((' ', String::new())),
+            _ => {}
+        }
+    }
+    if let Some((start, lines)) = current {
+        hunks.extend(split(start, &lines));
+    }
+    hunks
+}
+
+/// Splits one hunk into a hunk per run of changed lines.
+fn split(start: usize, lines: &[(char, String)]) -> Vec<RejectedHunk> {
+    // Ranges of changed lines
+    let mut changes: Vec<(usize, usize)> = Vec::new();
+    for (i, (kind, _)) in lines.iter().enumerate() {
+        if *kind == ' ' {
+            continue;
+        }
+        match changes.last_mut() {
+            Some((_, end)) if *end == i => *end = i + 1,
+            _ => changes.push((i, i + 1)),
+        }
+    }
+    if changes.is_empty() {
+        return Vec::new();
+    }
+
+    // Share the context between neighbouring changes; the ends keep theirs
+    let mut bounds = vec![0];
+    for pair in changes.windows(2) {
+        let (end, next) = (pair[0].1, pair[1].0);
+        bounds.push(end + (next - end).div_ceil(2));
+    }
+    bounds.push(lines.len());
+
+    let mut hunks = Vec::new();
+    let mut old_start = start;
+    for range in bounds.windows(2) {
+        let mut hunk = RejectedHunk {
+            old_start,
+            old: Vec::new(),
+            new: Vec::new(),
+        };
+        for (kind, text) in &lines[range[0]..range[1]] {
+            if *kind != '+' {
+                hunk.old.push(text.clone());
+            }
+            if *kind != '-' {
+                hunk.new.push(text.clone());
+            }
+        }
+        old_start += hunk.old.len();
+        hunks.push(hunk);
+    }
+    hunks
+}
+
+/// Writes each of `hunks` into `content` as a conflict, labelled with
+/// `labels` (current, expected, patched).
+///
+/// Each hunk goes where the lines it expected best match the file, searching
+/// after the previous hunk and preferring the line it expected on a tie.
+/// Leading and trailing lines the hunk leaves unchanged and that the file
+/// still has are kept outside the conflict, and a hunk whose lines the file
+/// still has exactly is simply applied.
+///
+/// # Examples
+///
+/// ```
+/// use weavr_core::{place_rejected_hunks, RejectedHunk};
+///
+/// let hunk = RejectedHunk {
+///     old_start: 1,
+///     old: vec!["a".into(), "b".into(), "c".into()],
+///     new: vec!["a".into(), "B".into(), "c".into()],
+/// };
+/// let placed = place_rejected_hunks("x\na\nbee\nc\n", &[hunk], ["ours", "base", "patch"]);
+/// assert_eq!(
+///     placed,
+///     "x\na\n<<<<<<< ours\nbee\n||||||| base\nb\n=======\nB\n>>>>>>> patch\nc\n"
+/// );
+/// ```
+#[must_use]
+pub fn place_rejected_hunks(content: &str, hunks: &[RejectedHunk], labels: [&str; 3]) -> String {
+    let trailing_newline = content.ends_with('\n');
+    let body = content.strip_suffix('\n').unwrap_or(content);
+    let lines: Vec<&str> = if body.is_empty() {
+        Vec::new()
+    } else {
+        body.split('\n').collect()
+    };
+
+    let mut out: Vec<String> = Vec::new();
+    let mut cursor = 0;
+    // Where the previous hunk was expected and where it went, as the file
+    // has likely moved by the same amount since
+    let mut previous = (0, 0);
+    for hunk in hunks {
+        let expected = hunk.old_start.saturating_sub(1);
+        let hint = (expected + previous.1).saturating_sub(previous.0);
+        let start = best_start(&lines, &hunk.old, cursor, hint);
+        previous = (expected, start);
+        let end = (start + hunk.old.len()).min(lines.len());
+        let region = &lines[start..end];
+
+        // Keep unchanged context that the file still has outside the conflict
+        let same = |a: &str, b: &String, c: &String| a == b && b == c;
+        let mut prefix = 0;
+        while prefix < region.len().min(hunk.old.len()).min(hunk.new.len())
+            && same(region[prefix], &hunk.old[prefix], &hunk.new[prefix])
+        {
+            prefix += 1;
+        }
+        let mut suffix = 0;
+        while suffix
+            < (region.len() - prefix)
+                .min(hunk.old.len() - prefix)
+                .min(hunk.new.len() - prefix)
+            && same(
+                region[region.len() - 1 - suffix],
+                &hunk.old[hunk.old.len() - 1 - suffix],
+                &hunk.new[hunk.new.len() - 1 - suffix],
+            )
+        {
+            suffix += 1;
+        }
+
+        out.extend(
+            lines[cursor..start + prefix]
+                .iter()
+                .map(|l| (*l).to_string()),
+        );
+        let current = &region[prefix..region.len() - suffix];
+        let old = &hunk.old[prefix..hunk.old.len() - suffix];
+        let new = &hunk.new[prefix..hunk.new.len() - suffix];
+        if current.iter().eq(old.iter()) {
+            // The file still has what the patch expected: nothing to resolve
+            out.extend_from_slice(new);
+        } else {
+            out.push(format!("<<<<<<< {}", labels[0]));
+            out.extend(current.iter().map(|l| (*l).to_string()));
+            out.push(format!("||||||| {}", labels[1]));
+            out.extend_from_slice(old);
+            out.push("=======".to_string());
+            out.extend_from_slice(new);
+            out.push(format!(">>>>>>> {}", labels[2]));
+        }
+        out.extend(
+            region[region.len() - suffix..]
+                .iter()
+                .map(|l| (*l).to_string()),
+        );
+        cursor = end;
+    }
+    out.extend(lines[cursor..].iter().map(|l| (*l).to_string()));
+
+    let mut placed = out.join("\n");
+    if trailing_newline || !hunks.is_empty() {
+        placed.push('\n');
+    }
+    placed
+}
+
+/// Finds where `expected` best matches `lines`, at or after `from`.
+///
+/// The score is the number of lines equal at the same offset; ties go to the
+/// start nearest `hint`. Without any matching line the hunk goes at `hint`.
+fn best_start(lines: &[&str], expected: &[String], from: usize, hint: usize) -> usize {
+    let hint = hint.clamp(from, lines.len());
+    let last = lines.len().saturating_sub(expected.len()).max(from);
+    let mut best = (0, hint);
+    for start in from..=last.min(lines.len()) {
+        let score = expected
+            .iter()
+            .zip(&lines[start..])
+            .filter(|(want, have)| *want == *have)
+            .count();
+        let closer = start.abs_diff(hint) < best.1.abs_diff(hint);
+        if score > best.0 || (score == best.0 && score > 0 && closer) {
+            best = (score, start);
+        }
+    }
+    best.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REJ: &str = "diff a/f b/f\t(rejected hunks)\n\
+                       @@ -1,4 +1,4 @@\n 1\n-2\n+two\n 3\n 4\n\
+                       @@ -8,3 +8,3 @@\n 8\n-9\n+nine\n 10\n\\ No newline at end of file\n";
+
+    #[test]
+    fn parses_hunks_of_a_rej_file() {
+        let hunks = parse_rejected_hunks(REJ);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].old_start, 1);
+        assert_eq!(hunks[0].old, ["1", "2", "3", "4"]);
+        assert_eq!(hunks[0].new, ["1", "two", "3", "4"]);
+        assert_eq!(hunks[1].old_start, 8);
+        assert_eq!(hunks[1].new, ["8", "nine", "10"]);
+    }
+
+    #[test]
+    fn hunks_are_placed_where_they_match_best() {
+        // Lines were inserted above both hunks, and the first was edited
+        let content = "new\nnew\n1\nTWO\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let placed = place_rejected_hunks(content, &parse_rejected_hunks(REJ), ["a", "b", "c"]);
+        assert_eq!(
+            placed,
+            "new\nnew\n1\n<<<<<<< a\nTWO\n||||||| b\n2\n=======\ntwo\n>>>>>>> c\n3\n4\n5\n6\n7\n\
+             8\nnine\n10\n"
+        );
+        let session =
+            crate::MergeSession::from_conflicted(&placed, std::path::PathBuf::from("f")).unwrap();
+        assert_eq!(session.hunks().len(), 1);
+    }
+
+    #[test]
+    fn separate_changes_in_one_hunk_become_separate_conflicts() {
+        let rej = "@@ -1,7 +1,7 @@\n 1\n-2\n+two\n 3\n 4\n 5\n-6\n+six\n 7\n";
+        let hunks = parse_rejected_hunks(rej);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].old, ["1", "2", "3", "4"]);
+        assert_eq!(hunks[1].old_start, 5);
+        assert_eq!(hunks[1].old, ["5", "6", "7"]);
+
+        let placed = place_rejected_hunks("1\nTWO\n3\n4\n5\n6\n7\n", &hunks, ["a", "b", "c"]);
+        assert_eq!(
+            placed,
+            "1\n<<<<<<< a\nTWO\n||||||| b\n2\n=======\ntwo\n>>>>>>> c\n3\n4\n5\nsix\n7\n"
+        );
+    }
+
+    #[test]
+    fn unmatched_hunks_go_to_the_expected_line() {
+        let hunk = RejectedHunk {
+            old_start: 2,
+            old: vec!["gone".into()],
+            new: vec!["added".into()],
+        };
+        assert_eq!(
+            place_rejected_hunks("a\nb\nc\n", &[hunk], ["a", "b", "c"]),
+            "a\n<<<<<<< a\nb\n||||||| b\ngone\n=======\nadded\n>>>>>>> c\nc\n"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Resolution memory for recurring conflicts.
+//!
+//! Rebasing a long branch replays the same conflict commit after commit.
+//! Like git's rerere ("reuse recorded resolution"), a resolution chosen for
+//! a conflict can be remembered under a key derived from the conflict's
+//! content and reused whenever that conflict comes up again. Where the
+//! memory is kept is up to the caller; see [`MergeSession::recall`] and
+//! [`MergeSession::remembered`](crate::MergeSession::remembered).
+//!
+//! Keys are computed from normalized content: line endings and trailing
+//! whitespace are ignored, and the two sides are put in a fixed order, so a
+//! conflict keeps its key when ours and theirs swap places, as they do
+//! between a merge and a rebase of the same branches.
+//!
+//! A [`RememberedResolution`] keeps the sides of the conflict it resolved,
+//! so that recalling it can relabel a resolution that took one side after
+//! the sides swapped, and can refuse a different conflict that happens to
+//! share the key.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+//!
+//! [`MergeSession::recall`]: crate::MergeSession::recall
+
+use serde::{Deserialize, Serialize};
+
+use crate::{preimage_key, BothOrder, ConflictHunk, Resolution, ResolutionStrategyKind};
+
+/// A resolution kept in the resolution memory, with the conflict it
+/// resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RememberedResolution {
+    /// Left side of the conflict, normalized.
+    pub left: String,
+    /// Base of the conflict, normalized, if it had one.
+    pub base: Option<String>,
+    /// Right side of the conflict, normalized.
+    pub right: String,
+    /// The resolution chosen.
+    pub resolution: Resolution,
+}
+
+impl RememberedResolution {
+    /// Remembers `resolution` as the resolution of `hunk`.
+    #[must_use]
+    pub fn new(hunk: &ConflictHunk, resolution: Resolution) -> Self {
+        Self {
+            left: normalize(&hunk.left.text),
+            base: hunk.base.as_ref().map(|b| normalize(&b.text)),
+            right: normalize(&hunk.right.text),
+            resolution,
+        }
+    }
+
+    /// Returns the remembered resolution for `hunk`, if it is the conflict
+    /// that was resolved.
+    ///
+    /// If ours and theirs have swapped since, a resolution that took one
+    /// side is relabeled as taking the other, keeping its content. A
+    /// different conflict with the same key gets `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use weavr_core::{MergeSession, RememberedResolution, Resolution, ResolutionStrategyKind};
+    /// use std::path::PathBuf;
+    ///
+    /// let merge = MergeSession::from_conflicted(
+    ///     "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> b\n",
+    ///     PathBuf::from("f"),
+    /// )
+    /// .unwrap();
+    /// let hunk = &merge.hunks()[0];
+    /// let remembered = RememberedResolution::new(hunk, Resolution::accept_left(hunk));
+    ///
+    /// // The same conflict in a rebase, where the sides are swapped
+    /// let rebase = MergeSession::from_conflicted(
+    ///     "<<<<<<< HEAD\ntheirs\n=======\nours\n>>>>>>> b\n",
+    ///     PathBuf::from("f"),
+    /// )
+    /// .unwrap();
+    /// let recalled = remembered.resolution_for(&rebase.hunks()[0]).unwrap();
+    /// assert_eq!(recalled.kind, ResolutionStrategyKind::AcceptRight);
+    /// assert_eq!(recalled.content, "ours");
+    /// ```
+    #[must_use]
+    pub fn resolution_for(&self, hunk: &ConflictHunk) -> Option<Resolution> {
+        let left = normalize(&hunk.left.text);
+        let right = normalize(&hunk.right.text);
+        if hunk.base.as_ref().map(|b| normalize(&b.text)) != self.base {
+            return None;
+        }
+        if left == self.left && right == self.right {
+            return Some(self.resolution.clone());
+        }
+        if left != self.right || right != self.left {
+            return None;
+        }
+
+        let mut resolution = self.resolution.clone();
+        resolution.kind = match resolution.kind {
+            ResolutionStrategyKind::AcceptLeft => ResolutionStrategyKind::AcceptRight,
+            ResolutionStrategyKind::AcceptRight => ResolutionStrategyKind::AcceptLeft,
+            ResolutionStrategyKind::AcceptBoth(mut options) => {
+                options.order = match options.order {
+                    BothOrder::LeftThenRight => BothOrder::RightThenLeft,
+                    BothOrder::RightThenLeft => BothOrder::LeftThenRight,
+                };
+                ResolutionStrategyKind::AcceptBoth(options)
+            }
+            kind => kind,
+        };
+        Some(resolution)
+    }
+}
+
+/// Returns the resolution memory key of a conflict's content.
+///
+/// # Examples
+///
+/// ```
+/// use weavr_core::rerere_key;
+///
+/// let key = rerere_key("a = 1\n", Some("a = 0"), "a = 2");
+/// assert_eq!(key, rerere_key("a = 1  ", Some("a = 0"), "a = 2\r\n"));
+/// // Swapping the sides keeps the key
+/// assert_eq!(key, rerere_key("a = 2", Some("a = 0"), "a = 1"));
+/// assert_ne!(key, rerere_key("a = 1", Some("a = 0"), "a = 3"));
+/// ```
+#[must_use]
+pub fn rerere_key(left: &str, base: Option<&str>, right: &str) -> String {
+    let (left, right) = (normalize(left), normalize(right));
+    let base = base.map(normalize);
+    let (first, second) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    preimage_key(&first, base.as_deref(), &second)
+}
+
+impl ConflictHunk {
+    /// Returns the resolution memory key of this hunk's content.
+    ///
+    /// See [`rerere_key`].
+    #[must_use]
+    pub fn rerere_key(&self) -> String {
+        rerere_key(
+            &self.left.text,
+            self.base.as_ref().map(|b| b.text.as_str()),
+            &self.right.text,
+        )
+    }
+}
+
+/// Strips trailing whitespace from each line, and trailing blank lines.
+fn normalize(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_end_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_base_differs_from_empty_base() {
+        assert_ne!(rerere_key("a", None, "b"), rerere_key("a", Some(""), "b"));
+    }
+
+    fn hunk(content: &str) -> ConflictHunk {
+        crate::MergeSession::from_conflicted(content, std::path::PathBuf::from("f"))
+            .unwrap()
+            .hunks()[0]
+            .clone()
+    }
+
+    #[test]
+    fn swapped_sides_relabel_both_orders() {
+        let merge = hunk("<<<<<<< a\nx\n=======\ny\n>>>>>>> b\n");
+        let rebase = hunk("<<<<<<< a\ny\n=======\nx\n>>>>>>> b\n");
+        let both = Resolution::accept_both(&merge, &crate::AcceptBothOptions::default());
+        let remembered = RememberedResolution::new(&merge, both.clone());
+
+        assert_eq!(remembered.resolution_for(&merge), Some(both));
+        let recalled = remembered.resolution_for(&rebase).unwrap();
+        assert_eq!(recalled.content, "x\ny");
+        assert!(matches!(
+            recalled.kind,
+            ResolutionStrategyKind::AcceptBoth(options) if options.order == BothOrder::RightThenLeft
+        ));
+    }
+
+    #[test]
+    fn other_conflicts_are_not_resolved() {
+        let resolved = hunk("<<<<<<< a\nx\n=======\ny\n>>>>>>> b\n");
+        let remembered = RememberedResolution::new(&resolved, Resolution::accept_left(&resolved));
+        // As if another conflict's key had collided with this one's
+        assert!(remembered
+            .resolution_for(&hunk("<<<<<<< a\nx\n=======\nz\n>>>>>>> b\n"))
+            .is_none());
+        assert!(remembered
+            .resolution_for(&hunk("<<<<<<< a\nx\n||||||| o\nw\n=======\ny\n>>>>>>> b\n"))
+            .is_none());
+        // Whitespace the key ignores is ignored here too
+        assert!(remembered
+            .resolution_for(&hunk("<<<<<<< a\nx  \n=======\ny\n>>>>>>> b\n"))
+            .is_some());
+    }
+
+    #[test]
+    fn leading_whitespace_still_counts() {
+        assert_ne!(rerere_key("  a", None, "b"), rerere_key("a", None, "b"));
+        assert_eq!(rerere_key("a\n\n", None, "b"), rerere_key("a", None, "b"));
+    }
+}
@@ -6,7 +6,7 @@ use std::collections::HashSet;
 
 use serde::{Deserialize, Serialize};
 
-use crate::hunk::ConflictHunk;
+use crate::hunk::{ConflictHunk, HunkId};
 
 /// Simple concatenation with proper newline handling.
 fn combine_simple(first: &str, second: &str) -> String {
@@ -87,6 +87,8 @@ pub enum ResolutionStrategyKind {
     AcceptLeft,
     /// Use right content verbatim.
     AcceptRight,
+    /// Use the base (common ancestor) content verbatim, undoing both sides.
+    AcceptBase,
     /// Combine left and right.
     AcceptBoth(AcceptBothOptions),
     /// User-provided content.
@@ -103,6 +105,24 @@ pub enum ResolutionStrategyKind {
     },
 }
 
+impl ResolutionStrategyKind {
+    /// Returns a stable, kebab-case identifier for this kind of resolution.
+    ///
+    /// Options carried by a variant (such as `AcceptBoth`) are not included.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AcceptLeft => "accept-left",
+            Self::AcceptRight => "accept-right",
+            Self::AcceptBase => "accept-base",
+            Self::AcceptBoth(_) => "accept-both",
+            Self::Manual => "manual",
+            Self::AstMerged { .. } => "ast-merged",
+            Self::AiSuggested { .. } => "ai-suggested",
+        }
+    }
+}
+
 /// Source of a resolution.
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum ResolutionSource {
@@ -113,6 +133,24 @@ pub enum ResolutionSource {
     Ai,
     /// Resolution from AST analysis.
     Ast,
+    /// Resolution applied by a rule, such as a headless strategy.
+    Rule,
+    /// Resolution reused from one recorded for the same conflict.
+    Rerere,
+}
+
+impl ResolutionSource {
+    /// Returns a stable, lowercase identifier for this source.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Ai => "ai",
+            Self::Ast => "ast",
+            Self::Rule => "rule",
+            Self::Rerere => "rerere",
+        }
+    }
 }
 
 /// Metadata about a resolution.
@@ -122,6 +160,9 @@ pub struct ResolutionMetadata {
     pub source: ResolutionSource,
     /// Optional notes.
     pub notes: Option<String>,
+    /// Hunk whose identical resolution was copied to this one, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub propagated_from: Option<HunkId>,
 }
 
 /// An explicit decision applied to a hunk.
@@ -156,6 +197,20 @@ impl Resolution {
         }
     }
 
+    /// Create a resolution that accepts the base (common ancestor) content
+    /// verbatim.
+    ///
+    /// Returns `None` if the hunk has no base, as with the default `merge`
+    /// conflict style.
+    #[must_use]
+    pub fn accept_base(hunk: &ConflictHunk) -> Option<Resolution> {
+        hunk.base.as_ref().map(|base| Resolution {
+            kind: ResolutionStrategyKind::AcceptBase,
+            content: base.text.clone(),
+            metadata: ResolutionMetadata::default(),
+        })
+    }
+
     /// Create a resolution that combines both left and right content.
     ///
     /// Options control the combination behavior:
@@ -254,6 +309,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn accept_base_needs_a_base() {
+        let mut hunk = test_hunk("left", "right");
+        assert!(Resolution::accept_base(&hunk).is_none());
+
+        hunk.base = Some(HunkContent {
+            text: "base".to_string(),
+        });
+        let resolution = Resolution::accept_base(&hunk).unwrap();
+        assert_eq!(resolution.kind, ResolutionStrategyKind::AcceptBase);
+        assert_eq!(resolution.content, "base");
+    }
+
     #[test]
     fn both_order_default() {
         assert_eq!(BothOrder::default(), BothOrder::LeftThenRight);
@@ -0,0 +1,265 @@
+//! Standalone HTML review pages.
+//!
+//! A review page shows every hunk of one or more sessions side by side with
+//! the resolution chosen for it, how it was chosen, and any notes, in a
+//! single HTML file with no external assets. It lets reviewers who do not
+//! use weavr audit merge decisions in a browser.
+//!
+//! [`format_review_html`] renders one session as a section;
+//! [`format_review_page`] wraps sections into a complete document.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use std::fmt::Write;
+
+use crate::patch::describe_strategy;
+use crate::{ConflictHunk, HunkState, MergeSession, ResolutionBreakdown};
+
+/// Styles embedded in every review page.
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 80rem; color: #222; }
+h1 { font-size: 1.5rem; }
+h2 { font-size: 1.2rem; border-bottom: 1px solid #ccc; padding-bottom: .25rem; margin-top: 2.5rem; }
+h3 { font-size: 1rem; margin: 0 0 .5rem; }
+.meta, .lines { color: #666; font-weight: normal; font-size: .9rem; }
+.hunk { border: 1px solid #ddd; border-radius: 6px; padding: 1rem; margin: 1rem 0; }
+.hunk.unresolved { border-color: #d9822b; }
+table { width: 100%; border-collapse: collapse; table-layout: fixed; }
+th { text-align: left; font-size: .85rem; color: #555; padding: .25rem .5rem; }
+td { vertical-align: top; padding: 0 .25rem; }
+pre { margin: 0; padding: .5rem; overflow-x: auto; font-size: .85rem; border-radius: 4px; white-space: pre-wrap; }
+.ours pre { background: #e8f1fb; }
+.base pre { background: #f3f3f3; }
+.theirs pre { background: #fbeee8; }
+.resolution pre { background: #eaf7ea; }
+pre.context { background: none; color: #888; padding: 0 .5rem; }
+dl { display: grid; grid-template-columns: max-content auto; gap: .1rem 1rem; font-size: .9rem; }
+dt { color: #666; }
+dd { margin: 0; }
+blockquote { margin: .5rem 0 0; padding: .25rem .75rem; border-left: 3px solid #999; color: #444; white-space: pre-wrap; }
+.status { color: #d9822b; font-weight: bold; }
+";
+
+/// Renders a session as a section of a review page.
+///
+/// Each hunk shows its sides next to each other, with the base when the
+/// conflict has one, followed by its resolution and provenance, or a note
+/// that it is unresolved. The heading counts the resolved hunks by how they
+/// were resolved.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use weavr_core::{format_review_html, MergeSession, Resolution};
+///
+/// let content = "<<<<<<< HEAD\na < b\n=======\nright\n>>>>>>> feature\n";
+/// let mut session = MergeSession::from_conflicted(content, PathBuf::from("f.txt")).unwrap();
+/// let hunk = session.hunks()[0].clone();
+/// session.set_resolution(hunk.id, Resolution::accept_left(&hunk)).unwrap();
+///
+/// let html = format_review_html(&session);
+/// assert!(html.contains("<h2>f.txt"));
+/// assert!(html.contains("a &lt; b"));
+/// assert!(html.contains("accept-left"));
+/// ```
+#[must_use]
+pub fn format_review_html(session: &MergeSession) -> String {
+    let hunks = session.hunks();
+    let resolved = hunks
+        .iter()
+        .filter(|hunk| matches!(hunk.state, HunkState::Resolved(_)))
+        .count();
+
+    let breakdown = if resolved > 0 {
+        format!(": {}", ResolutionBreakdown::of_hunks(hunks))
+    } else {
+        String::new()
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "<section class=\"file\">");
+    let _ = writeln!(
+        out,
+        "<h2>{} <span class=\"meta\">{resolved} of {} hunks resolved{breakdown}</span></h2>",
+        escape(&session.input().left.path.display().to_string()),
+        hunks.len()
+    );
+    for hunk in hunks {
+        write_hunk(&mut out, hunk);
+    }
+    let _ = writeln!(out, "</section>");
+    out
+}
+
+/// Wraps sections rendered by [`format_review_html`] into a complete,
+/// self-contained HTML document.
+#[must_use]
+pub fn format_review_page(title: &str, sections: &[String]) -> String {
+    let title = escape(title);
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">");
+    let _ = writeln!(out, "<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{title}</title>");
+    let _ = writeln!(out, "<style>\n{STYLE}</style>");
+    let _ = writeln!(out, "</head>");
+    let _ = writeln!(out, "<body>");
+    let _ = writeln!(out, "<h1>{title}</h1>");
+    for section in sections {
+        out.push_str(section);
+    }
+    let _ = writeln!(out, "</body>");
+    let _ = writeln!(out, "</html>");
+    out
+}
+
+/// Writes one hunk: context, sides, and resolution.
+fn write_hunk(out: &mut String, hunk: &ConflictHunk) {
+    let state = if matches!(hunk.state, HunkState::Resolved(_)) {
+        "resolved"
+    } else {
+        "unresolved"
+    };
+    let _ = writeln!(out, "<article class=\"hunk {state}\">");
+    let _ = writeln!(
+        out,
+        "<h3>Hunk {} <span class=\"lines\">line {}</span></h3>",
+        hunk.id.0 + 1,
+        hunk.context.start_line_left
+    );
+
+    write_context(out, &hunk.context.before);
+    let mut sides = vec![("ours", "Ours", &hunk.left.text)];
+    if let Some(base) = &hunk.base {
+        sides.push(("base", "Base", &base.text));
+    }
+    sides.push(("theirs", "Theirs", &hunk.right.text));
+
+    let _ = writeln!(out, "<table>");
+    let _ = write!(out, "<tr>");
+    for (_, heading, _) in &sides {
+        let _ = write!(out, "<th>{heading}</th>");
+    }
+    let _ = writeln!(out, "</tr>");
+    let _ = write!(out, "<tr>");
+    for (class, _, text) in &sides {
+        let _ = write!(
+            out,
+            "<td class=\"{class}\"><pre>{}</pre></td>",
+            escape(text)
+        );
+    }
+    let _ = writeln!(out, "</tr>");
+    let _ = writeln!(out, "</table>");
+    write_context(out, &hunk.context.after);
+
+    match &hunk.state {
+        HunkState::Resolved(resolution) => {
+            let _ = writeln!(out, "<div class=\"resolution\">");
+            let _ = writeln!(out, "<h3>Resolution</h3>");
+            let _ = writeln!(out, "<pre>{}</pre>", escape(&resolution.content));
+            let _ = writeln!(out, "<dl>");
+            let _ = writeln!(
+                out,
+                "<dt>Strategy</dt><dd>{}</dd>",
+                escape(&describe_strategy(&resolution.kind))
+            );
+            let _ = writeln!(
+                out,
+                "<dt>Source</dt><dd>{}</dd>",
+                resolution.metadata.source.name()
+            );
+            if let Some(from) = resolution.metadata.propagated_from {
+                let _ = writeln!(out, "<dt>Copied from</dt><dd>hunk {}</dd>", from.0 + 1);
+            }
+            let _ = writeln!(out, "</dl>");
+            if let Some(notes) = &resolution.metadata.notes {
+                let _ = writeln!(out, "<blockquote>{}</blockquote>", escape(notes));
+            }
+            let _ = writeln!(out, "</div>");
+        }
+        _ => {
+            let _ = writeln!(out, "<p class=\"status\">Unresolved</p>");
+        }
+    }
+    let _ = writeln!(out, "</article>");
+}
+
+/// Writes context lines around a conflict, if there are any.
+fn write_context(out: &mut String, lines: &[String]) {
+    if !lines.is_empty() {
+        let _ = writeln!(
+            out,
+            "<pre class=\"context\">{}</pre>",
+            escape(&lines.join("\n"))
+        );
+    }
+}
+
+/// Escapes text for HTML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{HunkId, Resolution};
+
+    fn session() -> MergeSession {
+        let content =
+            "fn a() {}\n<<<<<<< HEAD\nleft\n||||||| base\norig\n=======\n<b>\n>>>>>>> feature\n\
+                       <<<<<<< HEAD\ntwo\n=======\n2\n>>>>>>> feature\n";
+        MergeSession::from_conflicted(content, PathBuf::from("src/a.rs")).unwrap()
+    }
+
+    #[test]
+    fn resolved_hunks_show_resolution_and_provenance() {
+        let mut session = session();
+        let hunk = session.hunks()[0].clone();
+        let mut resolution = Resolution::accept_right(&hunk);
+        resolution.metadata.notes = Some("keep the <b> tag".to_string());
+        resolution.metadata.propagated_from = Some(HunkId(1));
+        session.set_resolution(hunk.id, resolution).unwrap();
+
+        let html = format_review_html(&session);
+        assert!(html.contains(
+            "<h2>src/a.rs <span class=\"meta\">1 of 2 hunks resolved: 1 identical</span></h2>"
+        ));
+        assert!(html.contains("<td class=\"base\"><pre>orig</pre></td>"));
+        assert!(html.contains("<pre class=\"context\">fn a() {}</pre>"));
+        assert!(html.contains("<dt>Strategy</dt><dd>accept-right</dd>"));
+        assert!(html.contains("<dt>Source</dt><dd>user</dd>"));
+        assert!(html.contains("<dt>Copied from</dt><dd>hunk 2</dd>"));
+        assert!(html.contains("<blockquote>keep the &lt;b&gt; tag</blockquote>"));
+        assert_eq!(
+            html.matches("<p class=\"status\">Unresolved</p>").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn page_is_self_contained() {
+        let page = format_review_page("Review & merge", &[format_review_html(&session())]);
+        assert!(page.starts_with("<!DOCTYPE html>\n"));
+        assert!(page.contains("<title>Review &amp; merge</title>"));
+        assert!(page.contains("<style>"));
+        assert!(!page.contains("<link") && !page.contains("<script"));
+        assert!(page.ends_with("</html>\n"));
+    }
+}
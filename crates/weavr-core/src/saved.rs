@@ -0,0 +1,166 @@
+//! Saved progress on a merge session.
+//!
+//! A session left partly resolved can be saved with
+//! [`MergeSession::save`] and picked up again with
+//! [`MergeSession::restore`]. The saved progress keeps each resolution,
+//! manual edits included, along with a hash of the conflicted content it
+//! was made for: if the conflicts have changed since, restoring fails
+//! rather than putting old resolutions on new hunks. Where the progress is
+//! kept is up to the caller.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::patch::fnv1a;
+use crate::{HunkId, HunkState, MergeSession, Resolution, RestoreError};
+
+/// The resolutions of a partly resolved session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// Path of the file the session resolves.
+    pub path: PathBuf,
+    /// [`content_hash`] of the conflicted content the session was parsed
+    /// from.
+    pub content_hash: String,
+    /// Number of hunks in the session.
+    pub total_hunks: usize,
+    /// Each resolved hunk.
+    pub hunks: Vec<SavedHunk>,
+}
+
+/// A resolved hunk of a saved session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedHunk {
+    /// The hunk.
+    pub id: HunkId,
+    /// [`preimage_key`](crate::preimage_key) of the hunk's content when it
+    /// was resolved.
+    pub preimage: String,
+    /// The hunk's resolution.
+    pub resolution: Resolution,
+}
+
+/// Returns the hash saved sessions identify conflicted content by.
+///
+/// Like [`preimage_key`](crate::preimage_key), it is 16 hex digits that do
+/// not depend on the platform or Rust version.
+///
+/// # Examples
+///
+/// ```
+/// use weavr_core::content_hash;
+///
+/// assert_eq!(content_hash("a\n"), content_hash("a\n"));
+/// assert_ne!(content_hash("a\n"), content_hash("b\n"));
+/// ```
+#[must_use]
+pub fn content_hash(content: &str) -> String {
+    fnv1a(&[content.as_bytes()])
+}
+
+impl SavedSession {
+    /// Returns true if the progress was saved for this session's conflicts.
+    #[must_use]
+    pub fn matches(&self, session: &MergeSession) -> bool {
+        self.content_hash == content_hash(session.conflicted())
+            && self.total_hunks == session.hunks().len()
+    }
+}
+
+impl MergeSession {
+    /// Saves the session's resolutions, to restore them later.
+    #[must_use]
+    pub fn save(&self) -> SavedSession {
+        SavedSession {
+            path: self.input().left.path.clone(),
+            content_hash: content_hash(self.conflicted()),
+            total_hunks: self.hunks().len(),
+            hunks: self
+                .hunks()
+                .iter()
+                .filter_map(|hunk| match &hunk.state {
+                    HunkState::Resolved(resolution) => Some(SavedHunk {
+                        id: hunk.id,
+                        preimage: hunk.preimage_key(),
+                        resolution: resolution.clone(),
+                    }),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Restores saved resolutions, returning the hunks resolved.
+    ///
+    /// Hunks whose content changed after they were resolved, such as by
+    /// [`replace_right`](Self::replace_right), are left unresolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RestoreError::Stale` if the progress was saved for other
+    /// conflicts, and `RestoreError::Resolution` if the session state doesn't
+    /// allow resolution.
+    pub fn restore(&mut self, saved: &SavedSession) -> Result<Vec<HunkId>, RestoreError> {
+        if !saved.matches(self) {
+            return Err(RestoreError::Stale);
+        }
+        let mut restored = Vec::new();
+        for entry in &saved.hunks {
+            let current = self
+                .hunk(entry.id)
+                .ok()
+                .map(crate::ConflictHunk::preimage_key);
+            if current.as_deref() == Some(entry.preimage.as_str()) {
+                self.set_resolution(entry.id, entry.resolution.clone())?;
+                restored.push(entry.id);
+            }
+        }
+        Ok(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFLICTED: &str =
+        "<<<<<<< HEAD\na\n=======\nb\n>>>>>>> x\nm\n<<<<<<< HEAD\nc\n=======\nd\n>>>>>>> x\n";
+
+    fn session(content: &str) -> MergeSession {
+        MergeSession::from_conflicted(content, PathBuf::from("f.rs")).unwrap()
+    }
+
+    #[test]
+    fn restore_brings_back_manual_edits() {
+        let mut first = session(CONFLICTED);
+        let id = first.hunks()[1].id;
+        first
+            .set_resolution(id, Resolution::manual("c and d\n".to_string()))
+            .unwrap();
+        let saved = first.save();
+        assert_eq!(saved.hunks.len(), 1);
+
+        let mut second = session(CONFLICTED);
+        assert_eq!(second.restore(&saved).unwrap(), vec![id]);
+        assert_eq!(second.resolutions()[&id].content, "c and d\n");
+        assert_eq!(second.unresolved_hunks(), vec![second.hunks()[0].id]);
+    }
+
+    #[test]
+    fn restore_refuses_progress_on_changed_conflicts() {
+        let mut first = session(CONFLICTED);
+        let id = first.hunks()[0].id;
+        let hunk = first.hunks()[0].clone();
+        first
+            .set_resolution(id, Resolution::accept_left(&hunk))
+            .unwrap();
+        let saved = first.save();
+
+        let mut changed = session(&CONFLICTED.replace("\nd\n", "\ne\n"));
+        assert_eq!(changed.restore(&saved), Err(RestoreError::Stale));
+        assert!(changed.resolutions().is_empty());
+    }
+}
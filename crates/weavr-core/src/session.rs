@@ -2,15 +2,19 @@
 //!
 //! All types in this module are **stable** and covered by semantic versioning.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use crate::{check_balance, find_duplicates, hunk_similarity, similar_hunks, transfer_resolution};
 use crate::{
-    parse_conflict_markers, ApplyError, CompletionError, ConflictHunk, FileVersion, HunkId,
-    HunkState, LifecycleError, MergeInput, MergeResult, MergeSummary, ParseError, ParsedConflict,
-    Resolution, ResolutionError, Segment, ValidationError,
+    parse_conflict_markers, ApplyError, CompletionError, ConflictHunk, ConflictStyle, FileVersion,
+    HunkId, HunkState, LifecycleError, LineEnding, MergeInput, MergeResult, MergeSummary,
+    MergeWarning, ParseError, ParsedConflict, RecordedResolution, RememberedResolution, Resolution,
+    ResolutionError, ResolutionSource, Segment, Syntax, ValidationError, ValidationProfile,
 };
 
 /// The state of a merge session.
@@ -33,11 +37,36 @@ pub enum MergeState {
     Completed,
 }
 
+/// Outcome of replaying recorded resolutions onto a session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Hunks that received a recorded resolution.
+    pub applied: Vec<HunkId>,
+    /// Hunks that were unresolved and had no matching recording.
+    pub unmatched: Vec<HunkId>,
+}
+
+/// How a resolution is carried to other hunks with identical content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropagationMode {
+    /// Leave other hunks alone.
+    Off,
+    /// Propose the resolution; the user accepts it per hunk.
+    #[default]
+    Offer,
+    /// Resolve the other hunks right away.
+    Apply,
+}
+
 /// Represents a single merge attempt for a file.
 #[derive(Debug, Clone)]
 pub struct MergeSession {
     /// The original merge inputs.
     input: MergeInput,
+    /// The file with conflict markers that the hunks were parsed from, if
+    /// not the left version itself.
+    conflicted: Option<String>,
     /// Parsed conflict regions.
     hunks: Vec<ConflictHunk>,
     /// File structure (clean segments and conflict references).
@@ -46,6 +75,8 @@ pub struct MergeSession {
     state: MergeState,
     /// Applied resolutions.
     resolutions: HashMap<HunkId, Resolution>,
+    /// Suggested resolutions, kept so a hunk returns to `Proposed` when cleared.
+    proposals: HashMap<HunkId, Vec<Resolution>>,
 }
 
 impl MergeSession {
@@ -60,11 +91,13 @@ impl MergeSession {
     pub fn new(input: MergeInput) -> Result<Self, ParseError> {
         // Placeholder implementation - actual parsing will be implemented later
         Ok(Self {
+            conflicted: None,
             input,
             hunks: Vec::new(),
             segments: Vec::new(),
             state: MergeState::Parsed,
             resolutions: HashMap::new(),
+            proposals: HashMap::new(),
         })
     }
 
@@ -75,7 +108,9 @@ impl MergeSession {
     ///
     /// # Arguments
     ///
-    /// * `content` - File content with Git conflict markers.
+    /// * `content` - File content with Git conflict markers. An owned
+    ///   `String` is kept as is rather than copied, so pass one when the
+    ///   file is large.
     /// * `path` - Path for identification (not used for I/O).
     ///
     /// # Errors
@@ -99,8 +134,9 @@ impl MergeSession {
     /// let session = MergeSession::from_conflicted(content, PathBuf::from("file.rs")).unwrap();
     /// assert_eq!(session.hunks().len(), 1);
     /// ```
-    pub fn from_conflicted(content: &str, path: PathBuf) -> Result<Self, ParseError> {
-        let ParsedConflict { hunks, segments } = parse_conflict_markers(content)?;
+    pub fn from_conflicted(content: impl Into<String>, path: PathBuf) -> Result<Self, ParseError> {
+        let content = content.into();
+        let ParsedConflict { hunks, segments } = parse_conflict_markers(&content)?;
 
         // Determine state based on whether conflicts were found
         let state = if hunks.is_empty() {
@@ -109,12 +145,12 @@ impl MergeSession {
             MergeState::Parsed
         };
 
-        // Store segments for later reconstruction
-        // For now, we store the original content in the input
+        // The content is kept as the left version; clean segments are
+        // ranges into it
         let input = MergeInput {
             left: FileVersion {
                 path: path.clone(),
-                content: content.to_string(),
+                content,
             },
             right: FileVersion {
                 path,
@@ -125,19 +161,69 @@ impl MergeSession {
 
         Ok(Self {
             input,
+            conflicted: None,
             hunks,
             state,
             resolutions: HashMap::new(),
+            proposals: HashMap::new(),
             segments,
         })
     }
 
+    /// Creates a merge session by merging the versions in `input`, for
+    /// resolving conflicts without a file that already has conflict markers
+    /// (as when run as a git mergetool).
+    ///
+    /// Regions changed on one side only are merged; the rest become hunks,
+    /// each with its base content when `input` has a base. See
+    /// [`merge_with_markers`](crate::merge_with_markers).
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if a version contains lines that look like
+    /// conflict markers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use weavr_core::{FileVersion, MergeInput, MergeSession};
+    ///
+    /// let version = |content: &str| FileVersion {
+    ///     path: PathBuf::from("file.rs"),
+    ///     content: content.to_string(),
+    /// };
+    /// let session = MergeSession::from_three_way(MergeInput {
+    ///     left: version("a\nleft\n"),
+    ///     right: version("a\nright\n"),
+    ///     base: Some(version("a\nbase\n")),
+    /// })
+    /// .unwrap();
+    /// assert_eq!(session.hunks().len(), 1);
+    /// assert_eq!(session.hunks()[0].base.as_ref().unwrap().text, "base");
+    /// ```
+    pub fn from_three_way(input: MergeInput) -> Result<Self, ParseError> {
+        let conflicted = crate::merge_with_markers(&input);
+        let mut session = Self::from_conflicted(conflicted, input.left.path.clone())?;
+        let parsed = std::mem::replace(&mut session.input, input);
+        session.conflicted = Some(parsed.left.content);
+        Ok(session)
+    }
+
     /// Returns all conflict hunks.
     #[must_use]
     pub fn hunks(&self) -> &[ConflictHunk] {
         &self.hunks
     }
 
+    /// Returns the conflict marker style detected in the hunks.
+    ///
+    /// See [`ConflictStyle::detect`] for how zdiff3 is reported.
+    #[must_use]
+    pub fn conflict_style(&self) -> ConflictStyle {
+        ConflictStyle::detect(&self.hunks)
+    }
+
     /// Returns the current session state.
     #[must_use]
     pub fn state(&self) -> MergeState {
@@ -150,6 +236,15 @@ impl MergeSession {
         &self.input
     }
 
+    /// Returns the file with conflict markers that the hunks were parsed
+    /// from, or were merged into by [`from_three_way`](Self::from_three_way).
+    #[must_use]
+    pub fn conflicted(&self) -> &str {
+        self.conflicted
+            .as_deref()
+            .unwrap_or(&self.input.left.content)
+    }
+
     /// Returns the resolutions map.
     #[must_use]
     pub fn resolutions(&self) -> &HashMap<HunkId, Resolution> {
@@ -164,12 +259,18 @@ impl MergeSession {
         &self.segments
     }
 
+    /// Returns the text of a [`Segment::Clean`] range from
+    /// [`segments`](Self::segments).
+    #[must_use]
+    pub fn clean_text(&self, range: &Range<usize>) -> Cow<'_, str> {
+        crate::clean_text(self.conflicted(), range)
+    }
+
     /// Checks if all hunks are resolved.
     #[must_use]
     pub fn is_fully_resolved(&self) -> bool {
-        self.hunks
-            .iter()
-            .all(|h| matches!(h.state, HunkState::Resolved(_)))
+        // Every resolved hunk has an entry in `resolutions`
+        self.resolutions.len() == self.hunks.len()
     }
 
     /// Returns the IDs of unresolved hunks.
@@ -182,6 +283,25 @@ impl MergeSession {
             .collect()
     }
 
+    /// Finds a hunk by ID. The parser numbers hunks in file order, so the
+    /// ID is tried as an index before searching.
+    fn hunk_index(&self, hunk_id: HunkId) -> Result<usize, ResolutionError> {
+        usize::try_from(hunk_id.0)
+            .ok()
+            .filter(|&index| self.hunks.get(index).is_some_and(|h| h.id == hunk_id))
+            .or_else(|| self.hunks.iter().position(|h| h.id == hunk_id))
+            .ok_or(ResolutionError::HunkNotFound(hunk_id))
+    }
+
+    pub(crate) fn hunk(&self, hunk_id: HunkId) -> Result<&ConflictHunk, ResolutionError> {
+        Ok(&self.hunks[self.hunk_index(hunk_id)?])
+    }
+
+    fn hunk_mut(&mut self, hunk_id: HunkId) -> Result<&mut ConflictHunk, ResolutionError> {
+        let index = self.hunk_index(hunk_id)?;
+        Ok(&mut self.hunks[index])
+    }
+
     // --- State Transition Helpers ---
 
     /// Checks if a transition is valid according to the state machine.
@@ -291,11 +411,7 @@ impl MergeSession {
         }
 
         // Find and update the hunk
-        let hunk = self
-            .hunks
-            .iter_mut()
-            .find(|h| h.id == hunk_id)
-            .ok_or(ResolutionError::HunkNotFound(hunk_id))?;
+        let hunk = self.hunk_mut(hunk_id)?;
 
         hunk.state = HunkState::Resolved(resolution.clone());
         self.resolutions.insert(hunk_id, resolution);
@@ -327,21 +443,453 @@ impl MergeSession {
         }
 
         // Find and update the hunk
-        let hunk = self
-            .hunks
-            .iter_mut()
-            .find(|h| h.id == hunk_id)
-            .ok_or(ResolutionError::HunkNotFound(hunk_id))?;
+        let state = match self.proposals.get(&hunk_id) {
+            Some(proposals) => HunkState::Proposed(proposals.clone()),
+            None => HunkState::Unresolved,
+        };
+        self.hunk_mut(hunk_id)?.state = state;
+        self.resolutions.remove(&hunk_id);
+
+        // Update session state based on hunk status
+        self.update_state_from_hunks();
 
+        Ok(())
+    }
+
+    /// Sets how many unchanged lines before and after each hunk are kept as
+    /// its context, which resolution patches and review exports include and
+    /// [`ConflictHunk::locate_in`] matches on.
+    ///
+    /// Parsing keeps [`DEFAULT_CONTEXT_LINES`](crate::DEFAULT_CONTEXT_LINES).
+    /// Context never reaches into a neighboring hunk.
+    pub fn set_context_lines(&mut self, count: usize) {
+        for (position, segment) in self.segments.iter().enumerate() {
+            let Segment::Conflict(index) = segment else {
+                continue;
+            };
+            let clean = |at: Option<usize>| match at.and_then(|at| self.segments.get(at)) {
+                Some(Segment::Clean(range)) => Some(self.clean_text(range)),
+                _ => None,
+            };
+            // Only the lines next to the conflict are split off
+            let before_text = clean(position.checked_sub(1));
+            let mut before: Vec<String> = before_text
+                .iter()
+                .flat_map(|text| text.rsplit('\n'))
+                .take(count)
+                .map(str::to_string)
+                .collect();
+            before.reverse();
+            let after_text = clean(Some(position + 1));
+            let after: Vec<String> = after_text
+                .iter()
+                .flat_map(|text| text.split('\n'))
+                .take(count)
+                .map(str::to_string)
+                .collect();
+
+            let context = &mut self.hunks[*index].context;
+            context.before = before;
+            context.after = after;
+        }
+    }
+
+    /// Replaces the right side of a hunk, e.g. with the same region from
+    /// another revision.
+    ///
+    /// Any resolution or proposal for the hunk is dropped, since it was made
+    /// for the old content.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ResolutionError::HunkNotFound` if the hunk doesn't exist.
+    /// Returns `ResolutionError::InvalidResolution` if the session state doesn't allow changes.
+    pub fn replace_right(&mut self, hunk_id: HunkId, text: String) -> Result<(), ResolutionError> {
+        match self.state {
+            MergeState::Parsed | MergeState::Active | MergeState::FullyResolved => {}
+            state => {
+                return Err(ResolutionError::InvalidResolution(format!(
+                    "cannot replace content in state {state:?}"
+                )));
+            }
+        }
+
+        let hunk = self.hunk_mut(hunk_id)?;
+
+        hunk.right.text = text;
         hunk.state = HunkState::Unresolved;
         self.resolutions.remove(&hunk_id);
+        self.proposals.remove(&hunk_id);
 
-        // Update session state based on hunk status
         self.update_state_from_hunks();
 
         Ok(())
     }
 
+    /// Suggests resolutions for a hunk without applying them.
+    ///
+    /// An unresolved hunk moves to `HunkState::Proposed`. A resolved hunk
+    /// keeps its resolution; the proposals take effect if it is cleared.
+    /// Passing an empty list withdraws earlier proposals.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ResolutionError::HunkNotFound` if the hunk doesn't exist.
+    /// Returns `ResolutionError::InvalidResolution` if the session state doesn't allow resolution.
+    pub fn propose(
+        &mut self,
+        hunk_id: HunkId,
+        proposals: Vec<Resolution>,
+    ) -> Result<(), ResolutionError> {
+        match self.state {
+            MergeState::Parsed | MergeState::Active | MergeState::FullyResolved => {}
+            state => {
+                return Err(ResolutionError::InvalidResolution(format!(
+                    "cannot propose resolutions in state {state:?}"
+                )));
+            }
+        }
+
+        let hunk = self.hunk_mut(hunk_id)?;
+
+        if !matches!(hunk.state, HunkState::Resolved(_)) {
+            hunk.state = if proposals.is_empty() {
+                HunkState::Unresolved
+            } else {
+                HunkState::Proposed(proposals.clone())
+            };
+        }
+
+        if proposals.is_empty() {
+            self.proposals.remove(&hunk_id);
+        } else {
+            self.proposals.insert(hunk_id, proposals);
+        }
+
+        Ok(())
+    }
+
+    /// Applies recorded resolutions to the matching unresolved hunks.
+    ///
+    /// A recording matches a hunk when its left, right, and (if both have
+    /// one) base content are identical. Each recording is used at most once,
+    /// in order, so repeated identical conflicts replay their own resolutions.
+    /// Hunks that are already resolved are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ResolutionError::InvalidResolution` if the session state doesn't allow resolution.
+    pub fn replay(
+        &mut self,
+        recorded: &[RecordedResolution],
+    ) -> Result<ReplayReport, ResolutionError> {
+        let mut used = vec![false; recorded.len()];
+        let mut report = ReplayReport::default();
+
+        for hunk in self.hunks.clone() {
+            if matches!(hunk.state, HunkState::Resolved(_)) {
+                continue;
+            }
+
+            let found = recorded
+                .iter()
+                .enumerate()
+                .find(|(i, r)| !used[*i] && r.matches(&hunk));
+            match found {
+                Some((i, r)) => {
+                    used[i] = true;
+                    self.set_resolution(hunk.id, r.resolution.clone())?;
+                    report.applied.push(hunk.id);
+                }
+                None => report.unmatched.push(hunk.id),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Resolves unresolved hunks with remembered resolutions.
+    ///
+    /// `lookup` is asked for each hunk that is not yet resolved, typically by
+    /// its [`rerere_key`](ConflictHunk::rerere_key); the resolutions it
+    /// returns are applied with source `ResolutionSource::Rerere`, so they
+    /// can be told apart from the user's own. Returns the hunks resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ResolutionError::InvalidResolution` if the session state doesn't allow resolution.
+    pub fn recall<F>(&mut self, mut lookup: F) -> Result<Vec<HunkId>, ResolutionError>
+    where
+        F: FnMut(&ConflictHunk) -> Option<Resolution>,
+    {
+        let mut recalled = Vec::new();
+        for hunk in self.hunks.clone() {
+            if matches!(hunk.state, HunkState::Resolved(_)) {
+                continue;
+            }
+            if let Some(mut resolution) = lookup(&hunk) {
+                resolution.metadata.source = ResolutionSource::Rerere;
+                self.set_resolution(hunk.id, resolution)?;
+                recalled.push(hunk.id);
+            }
+        }
+        Ok(recalled)
+    }
+
+    /// Returns the resolutions worth remembering, by
+    /// [`rerere_key`](ConflictHunk::rerere_key).
+    ///
+    /// Resolutions that were themselves recalled are left out, as they are
+    /// remembered already.
+    #[must_use]
+    pub fn remembered(&self) -> Vec<(String, RememberedResolution)> {
+        self.hunks
+            .iter()
+            .filter_map(|hunk| match &hunk.state {
+                HunkState::Resolved(resolution)
+                    if resolution.metadata.source != ResolutionSource::Rerere =>
+                {
+                    Some((
+                        hunk.rerere_key(),
+                        RememberedResolution::new(hunk, resolution.clone()),
+                    ))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Applies a hunk's resolution to the unresolved hunks similar to it.
+    ///
+    /// The resolution is carried over with [`transfer_resolution`]; hunks it
+    /// cannot be carried over to are left unresolved. Returns the hunks that
+    /// were resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ResolutionError::HunkNotFound` if the hunk doesn't exist.
+    /// Returns `ResolutionError::InvalidResolution` if the hunk is not resolved
+    /// or the session state doesn't allow resolution.
+    pub fn apply_to_similar(
+        &mut self,
+        hunk_id: HunkId,
+        threshold: f64,
+    ) -> Result<Vec<HunkId>, ResolutionError> {
+        let source = self.hunk(hunk_id)?.clone();
+        let HunkState::Resolved(resolution) = &source.state else {
+            return Err(ResolutionError::InvalidResolution(
+                "hunk is not resolved".to_string(),
+            ));
+        };
+
+        let mut applied = Vec::new();
+        for id in similar_hunks(&self.hunks, hunk_id, threshold) {
+            let Ok(target) = self.hunk(id) else {
+                continue;
+            };
+            if matches!(target.state, HunkState::Resolved(_)) {
+                continue;
+            }
+            if let Some(transferred) = transfer_resolution(resolution, &source, target) {
+                self.set_resolution(id, transferred)?;
+                applied.push(id);
+            }
+        }
+
+        Ok(applied)
+    }
+
+    /// Carries a hunk's resolution to the other hunks with identical content.
+    ///
+    /// Hunks whose left, right, and base content are byte-identical to the
+    /// resolved hunk and that are not yet resolved receive a copy of its
+    /// resolution, marked with [`ResolutionMetadata::propagated_from`]. With
+    /// [`PropagationMode::Offer`] the copy is proposed ahead of any existing
+    /// proposals; with [`PropagationMode::Apply`] it is applied. Returns the
+    /// hunks that received it.
+    ///
+    /// [`ResolutionMetadata::propagated_from`]: crate::ResolutionMetadata::propagated_from
+    ///
+    /// # Errors
+    ///
+    /// Returns `ResolutionError::HunkNotFound` if the hunk doesn't exist.
+    /// Returns `ResolutionError::InvalidResolution` if the hunk is not resolved
+    /// or the session state doesn't allow resolution.
+    pub fn propagate(
+        &mut self,
+        hunk_id: HunkId,
+        mode: PropagationMode,
+    ) -> Result<Vec<HunkId>, ResolutionError> {
+        let source = self.hunk(hunk_id)?.clone();
+        let HunkState::Resolved(resolution) = source.state else {
+            return Err(ResolutionError::InvalidResolution(
+                "hunk is not resolved".to_string(),
+            ));
+        };
+        if mode == PropagationMode::Off {
+            return Ok(Vec::new());
+        }
+
+        // A copy of a copy still points at the hunk that was resolved first
+        let mut copy = resolution;
+        copy.metadata.propagated_from.get_or_insert(hunk_id);
+
+        let targets: Vec<HunkId> = self
+            .hunks
+            .iter()
+            .filter(|h| {
+                h.id != hunk_id
+                    && !matches!(h.state, HunkState::Resolved(_))
+                    && h.left == source.left
+                    && h.right == source.right
+                    && h.base == source.base
+            })
+            .map(|h| h.id)
+            .collect();
+
+        for &id in &targets {
+            if mode == PropagationMode::Apply {
+                self.set_resolution(id, copy.clone())?;
+            } else {
+                let mut proposals = vec![copy.clone()];
+                if let Some(existing) = self.proposals.get(&id) {
+                    proposals.extend(existing.iter().filter(|p| **p != copy).cloned());
+                }
+                self.propose(id, proposals)?;
+            }
+        }
+
+        Ok(targets)
+    }
+
+    /// Proposes resolutions taken from similar hunks resolved elsewhere.
+    ///
+    /// `resolved` holds hunks from other files in `HunkState::Resolved`.
+    /// Each unresolved hunk without proposals is matched to the most similar
+    /// of them, and the carried-over resolution is proposed. Returns the
+    /// number of hunks that received a proposal.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ResolutionError::InvalidResolution` if the session state doesn't allow resolution.
+    pub fn propose_from_similar(
+        &mut self,
+        resolved: &[ConflictHunk],
+        threshold: f64,
+    ) -> Result<usize, ResolutionError> {
+        let mut proposed = 0;
+
+        for hunk in self.hunks.clone() {
+            if !matches!(hunk.state, HunkState::Unresolved) {
+                continue;
+            }
+
+            let best = resolved
+                .iter()
+                .filter_map(|other| match &other.state {
+                    HunkState::Resolved(resolution) => {
+                        Some((hunk_similarity(other, &hunk), other, resolution))
+                    }
+                    _ => None,
+                })
+                .filter(|(score, _, _)| *score >= threshold)
+                .max_by(|a, b| a.0.total_cmp(&b.0));
+
+            if let Some((_, other, resolution)) = best {
+                if let Some(transferred) = transfer_resolution(resolution, other, &hunk) {
+                    self.propose(hunk.id, vec![transferred])?;
+                    proposed += 1;
+                }
+            }
+        }
+
+        Ok(proposed)
+    }
+
+    /// Recovers the resolutions made in a resolved copy of this file.
+    ///
+    /// Clean segments are located in `resolved`, in order, and used as
+    /// anchors; the lines between two anchors are taken as the resolution of
+    /// the conflict they surround. An anchor only counts if it has a single
+    /// place in `resolved` that keeps the anchors in order: a clean line
+    /// such as `}` may also turn up inside a resolution, and guessing which
+    /// is which would record the wrong lines. Conflicts that cannot be
+    /// isolated this way (adjacent conflicts, or anchors that are missing or
+    /// ambiguous) are skipped.
+    ///
+    /// Content equal to one side is recorded as accepting that side;
+    /// anything else is recorded as a manual edit.
+    #[must_use]
+    pub fn extract_resolutions(&self, resolved: &str) -> Vec<RecordedResolution> {
+        let lines: Vec<&str> = resolved.lines().collect();
+
+        // Each clean segment with the conflicts just before it
+        let mut clean: Vec<(Cow<'_, str>, Vec<&ConflictHunk>)> = Vec::new();
+        let mut pending: Vec<&ConflictHunk> = Vec::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Conflict(index) => pending.push(&self.hunks[*index]),
+                Segment::Clean(range) => {
+                    clean.push((self.clean_text(range), std::mem::take(&mut pending)));
+                }
+            }
+        }
+        let anchors: Vec<(Vec<&str>, &[&ConflictHunk])> = clean
+            .iter()
+            .map(|(text, conflicts)| (text.split('\n').collect(), conflicts.as_slice()))
+            .collect();
+
+        // The earliest place of each anchor, up to the first one missing
+        let mut earliest = Vec::new();
+        let mut cursor = 0;
+        for (anchor, _) in &anchors {
+            let Some(start) = find_lines(&lines, anchor, cursor) else {
+                break;
+            };
+            earliest.push(start);
+            cursor = start + anchor.len();
+        }
+        // The latest place of each of those; where the two agree, the
+        // anchor has only one place
+        let mut latest = vec![0; earliest.len()];
+        let mut end = lines.len();
+        for (i, (anchor, _)) in anchors.iter().enumerate().take(earliest.len()).rev() {
+            latest[i] = rfind_lines(&lines, anchor, end).unwrap_or(earliest[i]);
+            end = latest[i];
+        }
+        let placed = |i: usize| earliest[i] == latest[i];
+
+        let mut recorded = Vec::new();
+        for (i, (_, conflicts)) in anchors.iter().enumerate().take(earliest.len()) {
+            let from = match i.checked_sub(1) {
+                None => Some(0),
+                Some(previous) if placed(previous) => {
+                    Some(earliest[previous] + anchors[previous].0.len())
+                }
+                Some(_) => None,
+            };
+            if let (Some(from), [hunk], true) = (from, *conflicts, placed(i)) {
+                recorded.push(record(hunk, &lines[from..earliest[i]]));
+            }
+        }
+
+        // A conflict after the last clean segment runs to the end
+        if let [hunk] = pending.as_slice() {
+            let from = match anchors.len().checked_sub(1) {
+                None => Some(0),
+                Some(last) if last < earliest.len() && placed(last) => {
+                    Some(earliest[last] + anchors[last].0.len())
+                }
+                Some(_) => None,
+            };
+            if let Some(from) = from {
+                recorded.push(record(hunk, &lines[from.min(lines.len())..]));
+            }
+        }
+
+        recorded
+    }
+
     // --- Lifecycle Methods ---
 
     /// Generates the merged output text from all resolutions.
@@ -367,17 +915,91 @@ impl MergeSession {
         Ok(output)
     }
 
-    /// Validates that the session is ready for completion.
+    /// Returns the merged content as it currently stands.
+    ///
+    /// Resolved hunks show their resolution; unresolved hunks are shown as
+    /// conflict blocks with generic marker labels. Unlike [`apply`](Self::apply),
+    /// this works in any state and leaves the session unchanged.
+    #[must_use]
+    pub fn preview(&self) -> String {
+        self.preview_parts()
+            .into_iter()
+            .map(|(_, part)| part)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the lines each hunk occupies in [`preview`](Self::preview).
+    ///
+    /// Ranges are 0-based line indices into the preview, in document order.
+    /// Tools that report problems by line number can be mapped back to the
+    /// hunk whose resolution produced the line.
+    #[must_use]
+    pub fn preview_line_ranges(&self) -> Vec<(HunkId, std::ops::Range<usize>)> {
+        let mut ranges = Vec::new();
+        let mut line = 0;
+        for (hunk_id, part) in self.preview_parts() {
+            let lines = part.matches('\n').count() + 1;
+            if let Some(hunk_id) = hunk_id {
+                ranges.push((hunk_id, line..line + lines));
+            }
+            line += lines;
+        }
+        ranges
+    }
+
+    /// Returns the preview segment by segment, tagged with their hunk.
+    fn preview_parts(&self) -> Vec<(Option<HunkId>, Cow<'_, str>)> {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Clean(range) => (None, self.clean_text(range)),
+                Segment::Conflict(hunk_index) => {
+                    let hunk = &self.hunks[*hunk_index];
+                    let text = match &hunk.state {
+                        HunkState::Resolved(resolution) => {
+                            Cow::Borrowed(resolution.content.as_str())
+                        }
+                        _ => crate::patch::marker_block(
+                            &hunk.left.text,
+                            hunk.base.as_ref().map(|b| b.text.as_str()),
+                            &hunk.right.text,
+                        )
+                        .join("\n")
+                        .into(),
+                    };
+                    (Some(hunk.id), text)
+                }
+            })
+            .collect()
+    }
+
+    /// Validates that the session is ready for completion, using the
+    /// [`basic`](ValidationProfile::basic) profile.
+    ///
+    /// # Errors
+    ///
+    /// See [`validate_with`](Self::validate_with).
+    pub fn validate(&mut self) -> Result<(), ValidationError> {
+        self.validate_with(&ValidationProfile::default())
+    }
+
+    /// Validates that the session is ready for completion, running the
+    /// checks of `profile`.
     ///
     /// Checks:
     /// - Session is in `Applied` state
-    /// - No conflict markers remain in resolved content
+    /// - No conflict markers remain in resolved content (`markers`)
+    /// - Resolutions keep brackets balanced (`balance`)
+    /// - Resolutions do not repeat declarations (`duplicates`)
     ///
     /// # Errors
     ///
     /// Returns `ValidationError::UnresolvedHunks` if not in correct state.
     /// Returns `ValidationError::MarkersRemain` if conflict markers found.
-    pub fn validate(&mut self) -> Result<(), ValidationError> {
+    /// Returns `ValidationError::ChecksFailed` with every balance and
+    /// duplicate problem found.
+    pub fn validate_with(&mut self, profile: &ValidationProfile) -> Result<(), ValidationError> {
         // Check state is Applied
         if self.state != MergeState::Applied {
             let unresolved = self.unresolved_hunks();
@@ -385,9 +1007,22 @@ impl MergeSession {
         }
 
         // Check for conflict markers in resolved content
-        let marker_count = self.count_conflict_markers();
-        if marker_count > 0 {
-            return Err(ValidationError::MarkersRemain(marker_count));
+        if profile.markers {
+            let marker_count = self.count_conflict_markers();
+            if marker_count > 0 {
+                return Err(ValidationError::MarkersRemain(marker_count));
+            }
+        }
+
+        let mut problems = Vec::new();
+        if profile.balance {
+            problems.extend(self.balance_warnings());
+        }
+        if profile.duplicates {
+            problems.extend(self.duplicate_warnings());
+        }
+        if !problems.is_empty() {
+            return Err(ValidationError::ChecksFailed(problems));
         }
 
         // Transition to Validated
@@ -416,6 +1051,77 @@ impl MergeSession {
         count
     }
 
+    /// Checks each resolved hunk's bracket and string balance against its
+    /// sides.
+    ///
+    /// Returns a warning for every resolution whose balance matches neither
+    /// side, which usually means a block was truncated or duplicated. Files
+    /// whose language is not recognized by extension are not checked.
+    #[must_use]
+    pub fn balance_warnings(&self) -> Vec<MergeWarning> {
+        let Some(syntax) = Syntax::for_path(&self.input.left.path) else {
+            return Vec::new();
+        };
+        self.hunks
+            .iter()
+            .filter_map(|hunk| {
+                let HunkState::Resolved(resolution) = &hunk.state else {
+                    return None;
+                };
+                check_balance(hunk, &resolution.content, &syntax).map(|message| MergeWarning {
+                    message,
+                    hunk_id: Some(hunk.id),
+                })
+            })
+            .collect()
+    }
+
+    /// Looks for declarations the resolutions repeat.
+    ///
+    /// Returns a warning for each function, config key, or dependency that
+    /// the merged output declares more often than either side did, when one
+    /// of its declarations comes from a resolved hunk.
+    #[must_use]
+    pub fn duplicate_warnings(&self) -> Vec<MergeWarning> {
+        let path = &self.input.left.path;
+        let ours = self.side_text(|hunk| &hunk.left.text);
+        let theirs = self.side_text(|hunk| &hunk.right.text);
+        let ranges: Vec<_> = self
+            .preview_line_ranges()
+            .into_iter()
+            .filter(|(id, _)| self.resolutions.contains_key(id))
+            .collect();
+        let hunk_at = |line: usize| {
+            ranges
+                .iter()
+                .find(|(_, range)| range.contains(&line))
+                .map(|(id, _)| *id)
+        };
+
+        find_duplicates(&self.preview(), &[&ours, &theirs], path)
+            .into_iter()
+            .filter_map(|duplicate| {
+                let hunk_id = hunk_at(duplicate.line).or_else(|| hunk_at(duplicate.first_line))?;
+                Some(MergeWarning {
+                    message: duplicate.to_string(),
+                    hunk_id: Some(hunk_id),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the file as one side wrote it, taking `side` from each hunk.
+    fn side_text(&self, side: impl Fn(&ConflictHunk) -> &String) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Clean(range) => self.clean_text(range),
+                Segment::Conflict(hunk_index) => side(&self.hunks[*hunk_index]).as_str().into(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     /// Finalizes the session and returns the immutable result.
     ///
     /// This consumes the session.
@@ -436,6 +1142,8 @@ impl MergeSession {
 
         // Generate final output
         let content = self.generate_output()?;
+        let mut warnings = self.balance_warnings();
+        warnings.extend(self.duplicate_warnings());
 
         // Build summary
         let total_hunks = self.hunks.len();
@@ -451,7 +1159,7 @@ impl MergeSession {
         Ok(MergeResult {
             content,
             unresolved_hunks: vec![],
-            warnings: vec![],
+            warnings,
             summary: MergeSummary {
                 total_hunks,
                 resolved_hunks,
@@ -461,13 +1169,13 @@ impl MergeSession {
 
     /// Internal helper to generate output from resolved hunks.
     fn generate_output(&self) -> Result<String, ApplyError> {
-        let mut output = String::new();
+        let mut output = String::with_capacity(self.input.left.content.len());
         let segment_count = self.segments.len();
 
         for (i, segment) in self.segments.iter().enumerate() {
             match segment {
-                Segment::Clean(text) => {
-                    output.push_str(text);
+                Segment::Clean(range) => {
+                    output.push_str(&self.clean_text(range));
                 }
                 Segment::Conflict(hunk_index) => {
                     let hunk = &self.hunks[*hunk_index];
@@ -485,8 +1193,47 @@ impl MergeSession {
             }
         }
 
-        Ok(output)
+        // Restore what splitting the file into lines dropped
+        let original = &self.input.left.content;
+        if original.ends_with('\n') && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        match LineEnding::detect(original) {
+            // Already split on and joined with \n
+            LineEnding::Lf if !output.contains('\r') => Ok(output),
+            ending => Ok(ending.apply(&output)),
+        }
+    }
+}
+
+/// Finds the first occurrence of `needle` in `haystack` at or after `from`.
+fn find_lines(haystack: &[&str], needle: &[&str], from: usize) -> Option<usize> {
+    if needle.len() > haystack.len() {
+        return None;
     }
+    (from..=haystack.len() - needle.len()).find(|&i| haystack[i..i + needle.len()] == *needle)
+}
+
+/// Finds the last occurrence of `needle` in `haystack` that ends by `end`.
+fn rfind_lines(haystack: &[&str], needle: &[&str], end: usize) -> Option<usize> {
+    let last = end.checked_sub(needle.len())?;
+    (0..=last)
+        .rev()
+        .find(|&i| haystack[i..i + needle.len()] == *needle)
+}
+
+/// Records a hunk's resolution from the lines that replaced it.
+fn record(hunk: &ConflictHunk, lines: &[&str]) -> RecordedResolution {
+    let content = lines.join("\n");
+    let resolution = if content == hunk.left.text {
+        Resolution::accept_left(hunk)
+    } else if content == hunk.right.text {
+        Resolution::accept_right(hunk)
+    } else {
+        Resolution::manual(content)
+    };
+
+    RecordedResolution::from_hunk(hunk, resolution)
 }
 
 #[cfg(test)]
@@ -494,7 +1241,7 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
-    use crate::FileVersion;
+    use crate::{AcceptBothOptions, FileVersion};
 
     fn test_input() -> MergeInput {
         MergeInput {
@@ -960,4 +1707,455 @@ after";
             MergeState::Parsed
         ));
     }
+
+    const TWO_CONFLICTS: &str = "fn a() {\n<<<<<<< HEAD\nleft1\n=======\nright1\n>>>>>>> f\n}\nfn b() {\n<<<<<<< HEAD\nleft2\n=======\nright2\n>>>>>>> f\n}";
+
+    #[test]
+    fn extract_resolutions_from_resolved_copy() {
+        let session = MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        let resolved = "fn a() {\nright1\n}\nfn b() {\nmerged\nlines\n}\n";
+
+        let recorded = session.extract_resolutions(resolved);
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(
+            recorded[0].resolution.kind,
+            crate::ResolutionStrategyKind::AcceptRight
+        );
+        assert_eq!(recorded[1].left, "left2");
+        assert_eq!(recorded[1].resolution.content, "merged\nlines");
+        assert_eq!(
+            recorded[1].resolution.kind,
+            crate::ResolutionStrategyKind::Manual
+        );
+    }
+
+    #[test]
+    fn extract_resolutions_skips_adjacent_conflicts() {
+        let content = "a\n<<<<<<< HEAD\nl1\n=======\nr1\n>>>>>>> f\n<<<<<<< HEAD\nl2\n=======\nr2\n>>>>>>> f\nz";
+        let session = MergeSession::from_conflicted(content, PathBuf::from("t.rs")).unwrap();
+
+        assert!(session.extract_resolutions("a\nr1\nl2\nz").is_empty());
+    }
+
+    #[test]
+    fn extract_resolutions_skips_conflicts_around_repeated_context() {
+        let content = "fn a() {\n<<<<<<< HEAD\nl1\n=======\nr1\n>>>>>>> f\n}\n<<<<<<< HEAD\nl2\n=======\nr2\n>>>>>>> f\n// two\n<<<<<<< HEAD\nl3\n=======\nr3\n>>>>>>> f\n// three";
+        let session = MergeSession::from_conflicted(content, PathBuf::from("t.rs")).unwrap();
+        // The first resolution ends with a `}` line, so either `}` could be
+        // the clean one
+        let resolved = "fn a() {\nx\n}\n}\ny\n// two\nz\n// three";
+
+        let recorded = session.extract_resolutions(resolved);
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].left, "l3");
+        assert_eq!(recorded[0].resolution.content, "z");
+    }
+
+    #[test]
+    fn replay_applies_matching_recordings() {
+        let source = MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        let recorded = source.extract_resolutions("fn a() {\nright1\n}\nfn b() {\nleft2\n}");
+
+        // The target has the second conflict first and an extra unknown one
+        let target_content = "<<<<<<< HEAD\nleft2\n=======\nright2\n>>>>>>> f\nx\n<<<<<<< HEAD\nnew\n=======\nother\n>>>>>>> f";
+        let mut target =
+            MergeSession::from_conflicted(target_content, PathBuf::from("t.rs")).unwrap();
+        let report = target.replay(&recorded).unwrap();
+
+        assert_eq!(report.applied, vec![HunkId(0)]);
+        assert_eq!(report.unmatched, vec![HunkId(1)]);
+        assert!(matches!(
+            &target.hunks()[0].state,
+            HunkState::Resolved(r) if r.content == "left2"
+        ));
+        assert_eq!(target.state(), MergeState::Active);
+    }
+
+    #[test]
+    fn replay_leaves_resolved_hunks_alone() {
+        let mut session =
+            MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        session
+            .set_resolution(HunkId(0), Resolution::manual("kept".to_string()))
+            .unwrap();
+        let recorded = session.extract_resolutions("fn a() {\nleft1\n}\nfn b() {\nright2\n}");
+
+        let report = session.replay(&recorded).unwrap();
+        assert_eq!(report.applied, vec![HunkId(1)]);
+        assert_eq!(session.resolutions()[&HunkId(0)].content, "kept");
+        assert_eq!(session.state(), MergeState::FullyResolved);
+    }
+
+    #[test]
+    fn propose_keeps_hunk_unresolved() {
+        let mut session =
+            MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        let suggestion = Resolution::manual("suggested".to_string());
+        session
+            .propose(HunkId(0), vec![suggestion.clone()])
+            .unwrap();
+
+        assert!(matches!(
+            &session.hunks()[0].state,
+            HunkState::Proposed(p) if p == &vec![suggestion.clone()]
+        ));
+        assert_eq!(session.unresolved_hunks(), vec![HunkId(0), HunkId(1)]);
+        assert_eq!(session.state(), MergeState::Parsed);
+    }
+
+    #[test]
+    fn clearing_resolution_restores_proposals() {
+        let mut session =
+            MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        let suggestion = Resolution::manual("suggested".to_string());
+        session.propose(HunkId(0), vec![suggestion]).unwrap();
+        session
+            .set_resolution(HunkId(0), Resolution::manual("chosen".to_string()))
+            .unwrap();
+        session.clear_resolution(HunkId(0)).unwrap();
+        assert!(matches!(&session.hunks()[0].state, HunkState::Proposed(_)));
+
+        session.propose(HunkId(0), Vec::new()).unwrap();
+        assert!(matches!(&session.hunks()[0].state, HunkState::Unresolved));
+    }
+
+    #[test]
+    fn propose_rejects_unknown_hunk() {
+        let mut session =
+            MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        assert!(matches!(
+            session.propose(HunkId(9), Vec::new()),
+            Err(ResolutionError::HunkNotFound(HunkId(9)))
+        ));
+    }
+
+    const REPEATED_CONFLICTS: &str = "<<<<<<< HEAD\nlog::info!(\"a\");\n=======\ntracing::info!(\"a\");\n>>>>>>> f\nmid\n<<<<<<< HEAD\nlet x = 1;\n=======\nlet x = 2;\n>>>>>>> f\nmid\n<<<<<<< HEAD\nlog::info!(\"b\");\n=======\ntracing::info!(\"b\");\n>>>>>>> f";
+
+    #[test]
+    fn apply_to_similar_resolves_matching_hunks() {
+        let mut session =
+            MergeSession::from_conflicted(REPEATED_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        let resolution = Resolution::accept_right(&session.hunks()[0]);
+        session.set_resolution(HunkId(0), resolution).unwrap();
+
+        let applied = session
+            .apply_to_similar(HunkId(0), crate::SIMILARITY_THRESHOLD)
+            .unwrap();
+        assert_eq!(applied, vec![HunkId(2)]);
+        assert_eq!(
+            session.resolutions()[&HunkId(2)].content,
+            "tracing::info!(\"b\");"
+        );
+        assert!(!session.resolutions().contains_key(&HunkId(1)));
+    }
+
+    #[test]
+    fn apply_to_similar_requires_resolved_hunk() {
+        let mut session =
+            MergeSession::from_conflicted(REPEATED_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        assert!(matches!(
+            session.apply_to_similar(HunkId(0), crate::SIMILARITY_THRESHOLD),
+            Err(ResolutionError::InvalidResolution(_))
+        ));
+    }
+
+    #[test]
+    fn propose_from_similar_uses_other_files() {
+        let mut earlier =
+            MergeSession::from_conflicted(REPEATED_CONFLICTS, PathBuf::from("a.rs")).unwrap();
+        let resolution = Resolution::accept_right(&earlier.hunks()[0]);
+        earlier.set_resolution(HunkId(0), resolution).unwrap();
+
+        let mut session =
+            MergeSession::from_conflicted(REPEATED_CONFLICTS, PathBuf::from("b.rs")).unwrap();
+        let proposed = session
+            .propose_from_similar(earlier.hunks(), crate::SIMILARITY_THRESHOLD)
+            .unwrap();
+
+        assert_eq!(proposed, 2);
+        assert!(matches!(
+            &session.hunks()[2].state,
+            HunkState::Proposed(p) if p[0].content == "tracing::info!(\"b\");"
+        ));
+        assert!(matches!(session.hunks()[1].state, HunkState::Unresolved));
+        assert!(session.resolutions().is_empty());
+    }
+
+    const IDENTICAL_CONFLICTS: &str = "<<<<<<< HEAD\nsame\n=======\nother\n>>>>>>> f\nmid\n<<<<<<< HEAD\nsame\n=======\nother\n>>>>>>> f\nmid\n<<<<<<< HEAD\nsame!\n=======\nother\n>>>>>>> f";
+
+    #[test]
+    fn propagate_applies_to_identical_hunks_only() {
+        let mut session =
+            MergeSession::from_conflicted(IDENTICAL_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        let resolution = Resolution::accept_right(&session.hunks()[0]);
+        session.set_resolution(HunkId(0), resolution).unwrap();
+
+        let targets = session
+            .propagate(HunkId(0), PropagationMode::Apply)
+            .unwrap();
+        assert_eq!(targets, vec![HunkId(1)]);
+
+        let copied = &session.resolutions()[&HunkId(1)];
+        assert_eq!(copied.content, "other");
+        assert_eq!(copied.metadata.propagated_from, Some(HunkId(0)));
+        assert!(!session.resolutions().contains_key(&HunkId(2)));
+    }
+
+    #[test]
+    fn propagate_offer_proposes_without_resolving() {
+        let mut session =
+            MergeSession::from_conflicted(IDENTICAL_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        let resolution = Resolution::accept_left(&session.hunks()[1]);
+        session.set_resolution(HunkId(1), resolution).unwrap();
+
+        let targets = session
+            .propagate(HunkId(1), PropagationMode::Offer)
+            .unwrap();
+        assert_eq!(targets, vec![HunkId(0)]);
+        assert!(matches!(
+            &session.hunks()[0].state,
+            HunkState::Proposed(p) if p[0].metadata.propagated_from == Some(HunkId(1))
+        ));
+        assert_eq!(session.resolutions().len(), 1);
+
+        assert!(session
+            .propagate(HunkId(1), PropagationMode::Off)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn preview_keeps_markers_for_unresolved_hunks() {
+        let mut session =
+            MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        session
+            .set_resolution(HunkId(0), Resolution::manual("merged".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            session.preview(),
+            "fn a() {\nmerged\n}\nfn b() {\n<<<<<<< ours\nleft2\n=======\nright2\n>>>>>>> theirs\n}"
+        );
+        assert_eq!(session.state(), MergeState::Active);
+    }
+
+    #[test]
+    fn preview_line_ranges_locate_hunks() {
+        let mut session =
+            MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        session
+            .set_resolution(HunkId(0), Resolution::manual("one\ntwo".to_string()))
+            .unwrap();
+
+        // Lines: "fn a() {", one, two, "}", "fn b() {", five marker lines, "}"
+        assert_eq!(
+            session.preview_line_ranges(),
+            vec![(HunkId(0), 1..3), (HunkId(1), 5..10)]
+        );
+        let preview = session.preview();
+        let lines: Vec<&str> = preview.lines().collect();
+        assert_eq!(lines[1..3], ["one", "two"]);
+        assert_eq!(lines[5], "<<<<<<< ours");
+    }
+
+    #[test]
+    fn three_way_session_resolves_to_merged_file() {
+        let version = |content: &str| FileVersion {
+            path: PathBuf::from("f.rs"),
+            content: content.to_string(),
+        };
+        let mut session = MergeSession::from_three_way(MergeInput {
+            left: version("one\r\nleft\r\nthree\r\nfour\r\n"),
+            right: version("one\nright\nthree\nFOUR\n"),
+            base: Some(version("one\nbase\nthree\nfour\n")),
+        })
+        .unwrap();
+        assert_eq!(session.input().right.content, "one\nright\nthree\nFOUR\n");
+        assert!(session.conflicted().contains("||||||| base\nbase\n"));
+
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(hunk.id, Resolution::accept_right(&hunk))
+            .unwrap();
+        session.apply().unwrap();
+        session.validate().unwrap();
+        assert_eq!(
+            session.complete().unwrap().content,
+            "one\r\nright\r\nthree\r\nFOUR\r\n"
+        );
+    }
+
+    #[test]
+    fn context_lines_can_be_changed() {
+        let content = "a\nb\nc\nd\n<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> t\ne\n\
+                       <<<<<<< HEAD\n1\n=======\n2\n>>>>>>> t\nf\ng\n";
+        let mut session = MergeSession::from_conflicted(content, PathBuf::from("f")).unwrap();
+        let parsed: Vec<crate::HunkContext> =
+            session.hunks().iter().map(|h| h.context.clone()).collect();
+
+        session.set_context_lines(1);
+        let hunks = session.hunks();
+        assert_eq!(hunks[0].context.before, vec!["d"]);
+        assert_eq!(hunks[0].context.after, vec!["e"]);
+        assert_eq!(hunks[1].context.before, vec!["e"]);
+        assert_eq!(hunks[1].context.after, vec!["f"]);
+        assert_eq!(hunks[0].context.start_line_left, parsed[0].start_line_left);
+
+        session.set_context_lines(crate::DEFAULT_CONTEXT_LINES);
+        let contexts: Vec<crate::HunkContext> =
+            session.hunks().iter().map(|h| h.context.clone()).collect();
+        assert_eq!(contexts, parsed);
+    }
+
+    #[test]
+    fn replace_right_drops_stale_resolution() {
+        let mut session =
+            MergeSession::from_conflicted(TWO_CONFLICTS, PathBuf::from("t.rs")).unwrap();
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(hunk.id, Resolution::accept_right(&hunk))
+            .unwrap();
+
+        session.replace_right(hunk.id, "older".to_string()).unwrap();
+        assert_eq!(session.hunks()[0].right.text, "older");
+        assert_eq!(session.hunks()[0].state, HunkState::Unresolved);
+        assert!(session.resolutions().is_empty());
+        assert!(session.replace_right(HunkId(9), String::new()).is_err());
+    }
+
+    #[test]
+    fn unbalanced_resolutions_are_warned_about() {
+        let content = "<<<<<<< ours\nfn run(a: u32) {\n=======\nfn run(a: u64) {\n>>>>>>> theirs\n    go();\n}\n";
+        let mut session = MergeSession::from_conflicted(content, PathBuf::from("t.rs")).unwrap();
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(
+                hunk.id,
+                Resolution::accept_both(&hunk, &AcceptBothOptions::default()),
+            )
+            .unwrap();
+        assert_eq!(
+            session.balance_warnings(),
+            vec![MergeWarning {
+                message: "1 unclosed '{' compared to ours".to_string(),
+                hunk_id: Some(hunk.id),
+            }]
+        );
+
+        session
+            .set_resolution(hunk.id, Resolution::accept_right(&hunk))
+            .unwrap();
+        session.apply().unwrap();
+        session.validate().unwrap();
+        assert!(session.complete().unwrap().warnings.is_empty());
+    }
+
+    #[test]
+    fn repeated_declarations_are_warned_about() {
+        let content = "[dependencies]\n<<<<<<< ours\nserde = \"1\"\n=======\nserde = \"1.0.200\"\n>>>>>>> theirs\ntoml = \"0.8\"\n";
+        let mut session =
+            MergeSession::from_conflicted(content, PathBuf::from("Cargo.toml")).unwrap();
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(
+                hunk.id,
+                Resolution::accept_both(&hunk, &AcceptBothOptions::default()),
+            )
+            .unwrap();
+        assert_eq!(
+            session.duplicate_warnings(),
+            vec![MergeWarning {
+                message: "duplicate key `dependencies.serde` (first on line 2)".to_string(),
+                hunk_id: Some(hunk.id),
+            }]
+        );
+
+        session
+            .set_resolution(hunk.id, Resolution::accept_left(&hunk))
+            .unwrap();
+        assert!(session.duplicate_warnings().is_empty());
+    }
+
+    #[test]
+    fn strict_profile_rejects_unbalanced_resolutions() {
+        let content = "<<<<<<< ours\nif a {\n=======\nif b {\n>>>>>>> theirs\n    go();\n}\n";
+        let mut session = MergeSession::from_conflicted(content, PathBuf::from("t.rs")).unwrap();
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(
+                hunk.id,
+                Resolution::accept_both(&hunk, &AcceptBothOptions::default()),
+            )
+            .unwrap();
+        session.apply().unwrap();
+
+        let result = session.validate_with(&ValidationProfile::strict());
+        assert!(matches!(result, Err(ValidationError::ChecksFailed(ref p)) if p.len() == 1));
+        assert_eq!(session.state(), MergeState::Applied);
+
+        // The basic profile only looks for markers
+        session.validate().unwrap();
+        assert_eq!(session.state(), MergeState::Validated);
+    }
+
+    #[test]
+    fn marker_check_can_be_disabled() {
+        let content = "<<<<<<< ours\na\n=======\nb\n>>>>>>> theirs\n";
+        let mut session = MergeSession::from_conflicted(content, PathBuf::from("a.md")).unwrap();
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(hunk.id, Resolution::manual("Title\n=======".to_string()))
+            .unwrap();
+        session.apply().unwrap();
+        assert!(session.validate().is_err());
+
+        let profile = ValidationProfile {
+            markers: false,
+            ..ValidationProfile::basic()
+        };
+        session.validate_with(&profile).unwrap();
+    }
+
+    #[test]
+    fn output_keeps_line_endings_and_final_newline() {
+        let content = "a\r\n<<<<<<< ours\r\nb\r\n=======\r\nc\r\n>>>>>>> theirs\r\nd\r\n";
+        let mut session = MergeSession::from_conflicted(content, PathBuf::from("t.txt")).unwrap();
+        let hunk = session.hunks()[0].clone();
+        assert_eq!(hunk.left.text, "b");
+        session
+            .set_resolution(hunk.id, Resolution::manual("x\r\ny".to_string()))
+            .unwrap();
+        assert_eq!(session.apply().unwrap(), "a\r\nx\r\ny\r\nd\r\n");
+
+        let content = "<<<<<<< ours\nb\n=======\nc\n>>>>>>> theirs";
+        let mut session = MergeSession::from_conflicted(content, PathBuf::from("t.txt")).unwrap();
+        let hunk = session.hunks()[0].clone();
+        session
+            .set_resolution(hunk.id, Resolution::accept_left(&hunk))
+            .unwrap();
+        assert_eq!(session.apply().unwrap(), "b");
+    }
+
+    #[test]
+    fn recalled_resolutions_are_marked_and_not_remembered_again() {
+        let content = "<<<<<<< HEAD\nx = 1\n=======\nx = 2\n>>>>>>> b\nmid\n\
+                       <<<<<<< HEAD\ny = 1\n=======\ny = 2\n>>>>>>> b\n";
+        let mut first = MergeSession::from_conflicted(content, PathBuf::from("a.txt")).unwrap();
+        first
+            .set_resolution(HunkId(0), Resolution::manual("x = 3".to_string()))
+            .unwrap();
+        let memory: HashMap<String, RememberedResolution> =
+            first.remembered().into_iter().collect();
+        assert_eq!(memory.len(), 1);
+
+        let mut second = MergeSession::from_conflicted(content, PathBuf::from("a.txt")).unwrap();
+        let recalled = second
+            .recall(|hunk| memory.get(&hunk.rerere_key())?.resolution_for(hunk))
+            .unwrap();
+        assert_eq!(recalled, vec![HunkId(0)]);
+        let resolution = &second.resolutions()[&HunkId(0)];
+        assert_eq!(resolution.content, "x = 3");
+        assert_eq!(resolution.metadata.source, ResolutionSource::Rerere);
+        assert!(second.remembered().is_empty());
+    }
 }
@@ -0,0 +1,243 @@
+//! Similarity between conflict hunks.
+//!
+//! Merges often repeat the same mechanical conflict many times: a renamed
+//! import, a bumped version, a reformatted call. These helpers find hunks
+//! whose content is identical or nearly so, and carry a resolution chosen for
+//! one of them over to the others.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use std::collections::HashMap;
+
+use crate::{ConflictHunk, HunkId, Resolution, ResolutionStrategyKind};
+
+/// Default minimum similarity for two hunks to be treated as alike.
+pub const SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// Returns how similar two hunks are, from `0.0` (unrelated) to `1.0`.
+///
+/// Each side is split into tokens (identifiers, numbers, and individual
+/// punctuation characters) and compared with its counterpart; the score is
+/// the average of the left and right scores. Hunks with identical left and
+/// right content always score `1.0`.
+#[must_use]
+pub fn hunk_similarity(a: &ConflictHunk, b: &ConflictHunk) -> f64 {
+    if a.left.text == b.left.text && a.right.text == b.right.text {
+        return 1.0;
+    }
+    (token_similarity(&a.left.text, &b.left.text) + token_similarity(&a.right.text, &b.right.text))
+        / 2.0
+}
+
+/// Returns the other hunks at least `threshold` similar to `target`.
+#[must_use]
+pub fn similar_hunks(hunks: &[ConflictHunk], target: HunkId, threshold: f64) -> Vec<HunkId> {
+    let Some(target) = hunks.iter().find(|h| h.id == target) else {
+        return Vec::new();
+    };
+    hunks
+        .iter()
+        .filter(|h| h.id != target.id && hunk_similarity(target, h) >= threshold)
+        .map(|h| h.id)
+        .collect()
+}
+
+/// Clusters hunks that are at least `threshold` similar to each other.
+///
+/// Each group is seeded by its first hunk in file order and collects the
+/// later hunks similar to it. Only groups of two or more hunks are returned.
+#[must_use]
+pub fn group_similar_hunks(hunks: &[ConflictHunk], threshold: f64) -> Vec<Vec<HunkId>> {
+    let mut grouped = vec![false; hunks.len()];
+    let mut groups = Vec::new();
+
+    for (i, seed) in hunks.iter().enumerate() {
+        if grouped[i] {
+            continue;
+        }
+        let mut group = vec![seed.id];
+        for (j, other) in hunks.iter().enumerate().skip(i + 1) {
+            if !grouped[j] && hunk_similarity(seed, other) >= threshold {
+                grouped[j] = true;
+                group.push(other.id);
+            }
+        }
+        if group.len() > 1 {
+            groups.push(group);
+        }
+    }
+
+    groups
+}
+
+/// Carries a resolution chosen for `from` over to the similar hunk `to`.
+///
+/// Side-picking strategies are re-applied to `to`'s own content, which for
+/// accepting the base needs `to` to have one. Other resolutions (manual
+/// edits, AST or AI merges) only carry over when both hunks have identical
+/// content; otherwise `None` is returned. Metadata such as notes is kept.
+#[must_use]
+pub fn transfer_resolution(
+    resolution: &Resolution,
+    from: &ConflictHunk,
+    to: &ConflictHunk,
+) -> Option<Resolution> {
+    let mut transferred = match &resolution.kind {
+        ResolutionStrategyKind::AcceptLeft => Resolution::accept_left(to),
+        ResolutionStrategyKind::AcceptRight => Resolution::accept_right(to),
+        ResolutionStrategyKind::AcceptBase => Resolution::accept_base(to)?,
+        ResolutionStrategyKind::AcceptBoth(options) => Resolution::accept_both(to, options),
+        _ if from.left == to.left && from.right == to.right && from.base == to.base => {
+            resolution.clone()
+        }
+        _ => return None,
+    };
+    transferred.metadata = resolution.metadata.clone();
+    Some(transferred)
+}
+
+/// Scores two texts by the tokens they share (Dice coefficient).
+fn token_similarity(a: &str, b: &str) -> f64 {
+    let a = tokens(a);
+    let b = tokens(b);
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for token in &a {
+        *counts.entry(token).or_default() += 1;
+    }
+    let mut shared = 0usize;
+    for token in &b {
+        if let Some(count) = counts.get_mut(token) {
+            if *count > 0 {
+                *count -= 1;
+                shared += 1;
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let score = (2 * shared) as f64 / (a.len() + b.len()) as f64;
+    score
+}
+
+/// Splits text into word tokens and single punctuation characters.
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+
+    for (i, c) in text.char_indices() {
+        if c.is_alphanumeric() || c == '_' {
+            word_start.get_or_insert(i);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            tokens.push(&text[start..i]);
+        }
+        if !c.is_whitespace() {
+            tokens.push(&text[i..i + c.len_utf8()]);
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AcceptBothOptions, HunkContent, HunkContext, HunkState};
+
+    fn hunk(id: u32, left: &str, right: &str) -> ConflictHunk {
+        ConflictHunk {
+            id: HunkId(id),
+            left: HunkContent {
+                text: left.to_string(),
+            },
+            right: HunkContent {
+                text: right.to_string(),
+            },
+            base: None,
+            context: HunkContext::default(),
+            state: HunkState::Unresolved,
+        }
+    }
+
+    #[test]
+    fn tokens_split_words_and_punctuation() {
+        assert_eq!(
+            tokens("version = \"1.2\";"),
+            vec!["version", "=", "\"", "1", ".", "2", "\"", ";"]
+        );
+    }
+
+    #[test]
+    fn identical_hunks_are_fully_similar() {
+        let a = hunk(0, "use a;\n", "use b;\n");
+        let b = hunk(1, "use a;\n", "use b;\n");
+        assert!((hunk_similarity(&a, &b) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn small_edits_are_similar_and_rewrites_are_not() {
+        let a = hunk(0, "version = \"1.1.0\"\n", "version = \"2.0.0\"\n");
+        let b = hunk(1, "version = \"1.1.0\"\n", "version = \"2.1.0\"\n");
+        let c = hunk(2, "fn main() {}\n", "struct Config;\n");
+
+        assert!(hunk_similarity(&a, &b) >= SIMILARITY_THRESHOLD);
+        assert!(hunk_similarity(&a, &c) < SIMILARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn group_collects_similar_hunks() {
+        let hunks = [
+            hunk(0, "log::info!(\"a\");\n", "tracing::info!(\"a\");\n"),
+            hunk(1, "fn unrelated() {}\n", "fn other() {}\n"),
+            hunk(2, "log::info!(\"a\");\n", "tracing::info!(\"a\");\n"),
+            hunk(3, "log::info!(\"b\");\n", "tracing::info!(\"b\");\n"),
+        ];
+
+        assert_eq!(
+            group_similar_hunks(&hunks, SIMILARITY_THRESHOLD),
+            vec![vec![HunkId(0), HunkId(2), HunkId(3)]]
+        );
+        assert_eq!(
+            similar_hunks(&hunks, HunkId(3), SIMILARITY_THRESHOLD),
+            vec![HunkId(0), HunkId(2)]
+        );
+    }
+
+    #[test]
+    fn transfer_reapplies_side_strategies() {
+        let from = hunk(0, "left a\n", "right a\n");
+        let to = hunk(1, "left b\n", "right b\n");
+
+        let mut resolution = Resolution::accept_right(&from);
+        resolution.metadata.notes = Some("theirs wins".to_string());
+        let transferred = transfer_resolution(&resolution, &from, &to).unwrap();
+        assert_eq!(transferred.content, "right b\n");
+        assert_eq!(transferred.metadata.notes.as_deref(), Some("theirs wins"));
+
+        let both = Resolution::accept_both(&from, &AcceptBothOptions::default());
+        let transferred = transfer_resolution(&both, &from, &to).unwrap();
+        assert_eq!(transferred.content, "left b\nright b\n");
+    }
+
+    #[test]
+    fn transfer_manual_requires_identical_content() {
+        let from = hunk(0, "left\n", "right\n");
+        let same = hunk(1, "left\n", "right\n");
+        let other = hunk(2, "left!\n", "right\n");
+        let manual = Resolution::manual("merged\n".to_string());
+
+        assert_eq!(
+            transfer_resolution(&manual, &from, &same).unwrap().content,
+            "merged\n"
+        );
+        assert!(transfer_resolution(&manual, &from, &other).is_none());
+    }
+}
@@ -0,0 +1,588 @@
+//! Conflict statistics aggregated over a directory tree.
+//!
+//! A merge that conflicts in dozens of files is easier to plan once it is
+//! clear where the conflicts concentrate. [`ConflictTree`] rolls per-file
+//! counts up into their directories so the busiest parts of the repository
+//! stand out. [`Complexity`] gives a rough idea of how hard each conflict
+//! will be to resolve, and [`ResolutionBreakdown`] counts how the conflicts
+//! were resolved in the end, showing how much of the merging was automated.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+
+use std::fmt;
+use std::path::{Component, Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ConflictHunk, HunkState, Resolution, ResolutionSource, ResolutionStrategyKind};
+
+/// Conflict counts for a file or a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ConflictStats {
+    /// Number of conflicted files.
+    pub files: usize,
+    /// Number of conflict hunks.
+    pub hunks: usize,
+    /// Number of conflicting lines across all sides, a rough measure of how
+    /// much reading the conflicts takes.
+    pub lines: usize,
+}
+
+impl ConflictStats {
+    /// Returns the statistics of one file with the given hunks.
+    #[must_use]
+    pub fn of_file(hunks: &[ConflictHunk]) -> Self {
+        let lines = hunks
+            .iter()
+            .map(|hunk| {
+                hunk.left.text.lines().count()
+                    + hunk.right.text.lines().count()
+                    + hunk.base.as_ref().map_or(0, |b| b.text.lines().count())
+            })
+            .sum();
+        Self {
+            files: 1,
+            hunks: hunks.len(),
+            lines,
+        }
+    }
+
+    fn add(&mut self, other: Self) {
+        self.files += other.files;
+        self.hunks += other.hunks;
+        self.lines += other.lines;
+    }
+}
+
+/// Conflicting lines beyond which a hunk counts as [`Complexity::Complex`].
+pub const COMPLEX_LINES: usize = 30;
+
+/// A rough estimate of the effort a conflict takes to resolve.
+///
+/// Variants are ordered from least to most effort, so the complexity of a
+/// file is the maximum over its hunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Complexity {
+    /// One side left the base alone, or the sides differ only in whitespace.
+    Trivial,
+    /// Both sides changed the same few lines.
+    Moderate,
+    /// Both sides changed more than [`COMPLEX_LINES`] lines between them.
+    Complex,
+}
+
+impl Complexity {
+    /// Estimates the complexity of one hunk.
+    #[must_use]
+    pub fn of_hunk(hunk: &ConflictHunk) -> Self {
+        let squash = |text: &str| text.split_whitespace().collect::<String>();
+        let unchanged = |text: &str| hunk.base.as_ref().is_some_and(|base| base.text == text);
+        if squash(&hunk.left.text) == squash(&hunk.right.text)
+            || unchanged(&hunk.left.text)
+            || unchanged(&hunk.right.text)
+        {
+            return Self::Trivial;
+        }
+        if ConflictStats::of_file(std::slice::from_ref(hunk)).lines > COMPLEX_LINES {
+            Self::Complex
+        } else {
+            Self::Moderate
+        }
+    }
+
+    /// Estimates the complexity of a file: that of its hardest hunk, or
+    /// `None` if it has no hunks.
+    #[must_use]
+    pub fn of_file(hunks: &[ConflictHunk]) -> Option<Self> {
+        hunks.iter().map(Self::of_hunk).max()
+    }
+
+    /// Returns the lowercase name used in reports.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Trivial => "trivial",
+            Self::Moderate => "moderate",
+            Self::Complex => "complex",
+        }
+    }
+}
+
+/// How a hunk came to be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionOrigin {
+    /// Our side, accepted by hand.
+    Left,
+    /// Their side, accepted by hand.
+    Right,
+    /// Both sides, accepted by hand.
+    Both,
+    /// The base, accepted by hand.
+    Base,
+    /// Written or edited by hand.
+    Edited,
+    /// Applied by a rule.
+    Rule,
+    /// Reused from a resolution recorded for the same conflict.
+    Rerere,
+    /// Suggested by AI.
+    Ai,
+    /// Merged by AST analysis.
+    Ast,
+    /// Copied from an identical hunk resolved earlier.
+    Identical,
+}
+
+impl ResolutionOrigin {
+    /// Every origin, in report order.
+    pub const ALL: [Self; 10] = [
+        Self::Left,
+        Self::Right,
+        Self::Both,
+        Self::Base,
+        Self::Edited,
+        Self::Rule,
+        Self::Rerere,
+        Self::Ai,
+        Self::Ast,
+        Self::Identical,
+    ];
+
+    /// Classifies a resolution.
+    ///
+    /// Copies from identical hunks count as such whoever made the original;
+    /// otherwise the source decides, and resolutions made by the user are
+    /// told apart by their kind.
+    #[must_use]
+    pub fn of(resolution: &Resolution) -> Self {
+        if resolution.metadata.propagated_from.is_some() {
+            return Self::Identical;
+        }
+        match (&resolution.metadata.source, &resolution.kind) {
+            (ResolutionSource::Rule, _) => Self::Rule,
+            (ResolutionSource::Rerere, _) => Self::Rerere,
+            (ResolutionSource::Ai, _) | (_, ResolutionStrategyKind::AiSuggested { .. }) => Self::Ai,
+            (ResolutionSource::Ast, _) | (_, ResolutionStrategyKind::AstMerged { .. }) => Self::Ast,
+            (ResolutionSource::User, ResolutionStrategyKind::AcceptLeft) => Self::Left,
+            (ResolutionSource::User, ResolutionStrategyKind::AcceptRight) => Self::Right,
+            (ResolutionSource::User, ResolutionStrategyKind::AcceptBoth(_)) => Self::Both,
+            (ResolutionSource::User, ResolutionStrategyKind::AcceptBase) => Self::Base,
+            (ResolutionSource::User, ResolutionStrategyKind::Manual) => Self::Edited,
+        }
+    }
+
+    /// Returns the lowercase name used in reports.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Both => "both",
+            Self::Base => "base",
+            Self::Edited => "edited",
+            Self::Rule => "rule",
+            Self::Rerere => "rerere",
+            Self::Ai => "ai",
+            Self::Ast => "ast",
+            Self::Identical => "identical",
+        }
+    }
+
+    /// Returns true if the hunk was resolved without a person choosing how.
+    #[must_use]
+    pub fn is_automated(self) -> bool {
+        !matches!(
+            self,
+            Self::Left | Self::Right | Self::Both | Self::Base | Self::Edited
+        )
+    }
+}
+
+/// Number of resolved hunks by [`ResolutionOrigin`], for a file or a whole
+/// session.
+///
+/// Displays as the non-zero counts in report order, e.g. `2 left, 1 edited,
+/// 3 rule`.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use weavr_core::{MergeSession, Resolution, ResolutionBreakdown};
+///
+/// let content = "<<<<<<< HEAD\na\n=======\nb\n>>>>>>> x\n";
+/// let mut session = MergeSession::from_conflicted(content, PathBuf::from("f")).unwrap();
+/// let hunk = session.hunks()[0].clone();
+/// session.set_resolution(hunk.id, Resolution::accept_right(&hunk)).unwrap();
+///
+/// let breakdown = ResolutionBreakdown::of_hunks(session.hunks());
+/// assert_eq!(breakdown.to_string(), "1 right");
+/// assert_eq!(breakdown.automated(), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ResolutionBreakdown {
+    /// Our side, accepted by hand.
+    pub left: usize,
+    /// Their side, accepted by hand.
+    pub right: usize,
+    /// Both sides, accepted by hand.
+    pub both: usize,
+    /// The base, accepted by hand.
+    #[serde(default)]
+    pub base: usize,
+    /// Written or edited by hand.
+    pub edited: usize,
+    /// Applied by a rule.
+    pub rule: usize,
+    /// Reused from a recorded resolution.
+    pub rerere: usize,
+    /// Suggested by AI.
+    pub ai: usize,
+    /// Merged by AST analysis.
+    pub ast: usize,
+    /// Copied from an identical hunk.
+    pub identical: usize,
+}
+
+impl ResolutionBreakdown {
+    /// Counts the resolved hunks among `hunks`.
+    #[must_use]
+    pub fn of_hunks(hunks: &[ConflictHunk]) -> Self {
+        let mut breakdown = Self::default();
+        for hunk in hunks {
+            if let HunkState::Resolved(resolution) = &hunk.state {
+                breakdown.record(ResolutionOrigin::of(resolution));
+            }
+        }
+        breakdown
+    }
+
+    /// Counts one more hunk of the given origin.
+    pub fn record(&mut self, origin: ResolutionOrigin) {
+        *self.count_mut(origin) += 1;
+    }
+
+    /// Adds another breakdown's counts to this one.
+    pub fn add(&mut self, other: &Self) {
+        for origin in ResolutionOrigin::ALL {
+            *self.count_mut(origin) += other.count(origin);
+        }
+    }
+
+    /// Returns the number of hunks of the given origin.
+    #[must_use]
+    pub fn count(&self, origin: ResolutionOrigin) -> usize {
+        match origin {
+            ResolutionOrigin::Left => self.left,
+            ResolutionOrigin::Right => self.right,
+            ResolutionOrigin::Both => self.both,
+            ResolutionOrigin::Base => self.base,
+            ResolutionOrigin::Edited => self.edited,
+            ResolutionOrigin::Rule => self.rule,
+            ResolutionOrigin::Rerere => self.rerere,
+            ResolutionOrigin::Ai => self.ai,
+            ResolutionOrigin::Ast => self.ast,
+            ResolutionOrigin::Identical => self.identical,
+        }
+    }
+
+    fn count_mut(&mut self, origin: ResolutionOrigin) -> &mut usize {
+        match origin {
+            ResolutionOrigin::Left => &mut self.left,
+            ResolutionOrigin::Right => &mut self.right,
+            ResolutionOrigin::Both => &mut self.both,
+            ResolutionOrigin::Base => &mut self.base,
+            ResolutionOrigin::Edited => &mut self.edited,
+            ResolutionOrigin::Rule => &mut self.rule,
+            ResolutionOrigin::Rerere => &mut self.rerere,
+            ResolutionOrigin::Ai => &mut self.ai,
+            ResolutionOrigin::Ast => &mut self.ast,
+            ResolutionOrigin::Identical => &mut self.identical,
+        }
+    }
+
+    /// Returns the number of resolved hunks.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        ResolutionOrigin::ALL.iter().map(|o| self.count(*o)).sum()
+    }
+
+    /// Returns the number of hunks resolved without a person choosing how.
+    #[must_use]
+    pub fn automated(&self) -> usize {
+        ResolutionOrigin::ALL
+            .iter()
+            .filter(|o| o.is_automated())
+            .map(|o| self.count(*o))
+            .sum()
+    }
+}
+
+impl fmt::Display for ResolutionBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = ResolutionOrigin::ALL
+            .iter()
+            .filter(|o| self.count(**o) > 0)
+            .map(|o| format!("{} {}", self.count(*o), o.name()))
+            .collect();
+        if parts.is_empty() {
+            f.write_str("none")
+        } else {
+            f.write_str(&parts.join(", "))
+        }
+    }
+}
+
+/// A directory tree of conflicted files with rolled-up statistics.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ConflictTree {
+    /// File or directory name; empty for the root.
+    pub name: String,
+    /// Totals for this node and everything below it.
+    pub stats: ConflictStats,
+    /// Entries below a directory, sorted by name; empty for files.
+    pub children: Vec<ConflictTree>,
+}
+
+/// One line of a flattened [`ConflictTree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictTreeRow {
+    /// Nesting depth, starting at 0 below the root.
+    pub depth: usize,
+    /// Display name; directories end with `/`, and single-directory chains
+    /// are joined (`src/parser/`).
+    pub name: String,
+    /// Whether this row is a directory.
+    pub is_dir: bool,
+    /// Totals for this row.
+    pub stats: ConflictStats,
+}
+
+impl ConflictTree {
+    /// Builds the tree from per-file statistics.
+    ///
+    /// Paths are split into their normal components; `.` and root prefixes
+    /// are ignored, so relative paths should be used for a readable tree.
+    pub fn build<'a, I>(files: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a Path, ConflictStats)>,
+    {
+        let mut root = Self::default();
+        for (path, stats) in files {
+            let names: Vec<String> = path
+                .components()
+                .filter_map(|c| match c {
+                    Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                    _ => None,
+                })
+                .collect();
+            root.insert(&names, stats);
+        }
+        root.sort();
+        root
+    }
+
+    /// Returns true if this node is a directory (or the root).
+    #[must_use]
+    pub fn is_dir(&self) -> bool {
+        !self.children.is_empty() || self.name.is_empty()
+    }
+
+    /// Flattens the tree below the root into display rows, depth first.
+    #[must_use]
+    pub fn rows(&self) -> Vec<ConflictTreeRow> {
+        let mut rows = Vec::new();
+        for child in &self.children {
+            child.push_rows(0, &mut rows);
+        }
+        rows
+    }
+
+    fn insert(&mut self, names: &[String], stats: ConflictStats) {
+        self.stats.add(stats);
+        let Some((first, rest)) = names.split_first() else {
+            return;
+        };
+        let index = if let Some(index) = self.children.iter().position(|c| c.name == *first) {
+            index
+        } else {
+            self.children.push(Self {
+                name: first.clone(),
+                ..Self::default()
+            });
+            self.children.len() - 1
+        };
+        self.children[index].insert(rest, stats);
+    }
+
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| a.name.cmp(&b.name));
+        self.children.iter_mut().for_each(Self::sort);
+    }
+
+    fn push_rows(&self, depth: usize, rows: &mut Vec<ConflictTreeRow>) {
+        if !self.is_dir() {
+            rows.push(ConflictTreeRow {
+                depth,
+                name: self.name.clone(),
+                is_dir: false,
+                stats: self.stats,
+            });
+            return;
+        }
+
+        // Join chains of directories that contain only one directory
+        let mut node = self;
+        let mut name = format!("{}/", self.name);
+        while let [only] = node.children.as_slice() {
+            if !only.is_dir() {
+                break;
+            }
+            name.push_str(&only.name);
+            name.push('/');
+            node = only;
+        }
+
+        rows.push(ConflictTreeRow {
+            depth,
+            name,
+            is_dir: true,
+            stats: node.stats,
+        });
+        for child in &node.children {
+            child.push_rows(depth + 1, rows);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn stats(hunks: usize, lines: usize) -> ConflictStats {
+        ConflictStats {
+            files: 1,
+            hunks,
+            lines,
+        }
+    }
+
+    #[test]
+    fn file_stats_count_lines_on_every_side() {
+        let content = "<<<<<<< ours\na\nb\n||||||| base\nc\n=======\nd\n>>>>>>> theirs\nx\n<<<<<<< ours\ne\n=======\nf\n>>>>>>> theirs\n";
+        let session = crate::MergeSession::from_conflicted(content, PathBuf::from("f.rs")).unwrap();
+        assert_eq!(ConflictStats::of_file(session.hunks()), stats(2, 6));
+    }
+
+    #[test]
+    fn complexity_grows_with_how_much_both_sides_changed() {
+        let long = "line\n".repeat(COMPLEX_LINES);
+        let content = format!(
+            "<<<<<<< ours\na  b\n=======\na b\n>>>>>>> theirs\n\
+             <<<<<<< ours\nx\n||||||| base\nx\n=======\ny\n>>>>>>> theirs\n\
+             <<<<<<< ours\none\n=======\ntwo\n>>>>>>> theirs\n\
+             <<<<<<< ours\n{long}=======\nother\n>>>>>>> theirs\n"
+        );
+        let session =
+            crate::MergeSession::from_conflicted(&content, PathBuf::from("f.rs")).unwrap();
+        let complexity: Vec<_> = session.hunks().iter().map(Complexity::of_hunk).collect();
+        assert_eq!(
+            complexity,
+            vec![
+                Complexity::Trivial,
+                Complexity::Trivial,
+                Complexity::Moderate,
+                Complexity::Complex
+            ]
+        );
+        assert_eq!(
+            Complexity::of_file(session.hunks()),
+            Some(Complexity::Complex)
+        );
+        assert_eq!(Complexity::of_file(&[]), None);
+    }
+
+    #[test]
+    fn breakdown_tells_manual_from_automated_resolutions() {
+        let content = "<<<<<<< ours\na\n=======\nb\n>>>>>>> theirs\n".repeat(5);
+        let mut session =
+            crate::MergeSession::from_conflicted(&content, PathBuf::from("f.rs")).unwrap();
+        let hunks = session.hunks().to_vec();
+        session
+            .set_resolution(hunks[0].id, Resolution::accept_left(&hunks[0]))
+            .unwrap();
+        session
+            .set_resolution(hunks[1].id, Resolution::manual("c\n".to_string()))
+            .unwrap();
+        let mut rule = Resolution::accept_right(&hunks[2]);
+        rule.metadata.source = ResolutionSource::Rule;
+        session.set_resolution(hunks[2].id, rule).unwrap();
+        let mut copy = Resolution::accept_left(&hunks[3]);
+        copy.metadata.propagated_from = Some(hunks[0].id);
+        session.set_resolution(hunks[3].id, copy).unwrap();
+
+        let breakdown = ResolutionBreakdown::of_hunks(session.hunks());
+        assert_eq!(
+            breakdown.to_string(),
+            "1 left, 1 edited, 1 rule, 1 identical"
+        );
+        assert_eq!((breakdown.total(), breakdown.automated()), (4, 2));
+
+        let mut overall = breakdown;
+        overall.add(&breakdown);
+        assert_eq!(overall.edited, 2);
+        assert_eq!(ResolutionBreakdown::default().to_string(), "none");
+    }
+
+    #[test]
+    fn tree_rolls_up_directories() {
+        let files = [
+            (PathBuf::from("src/parser/lexer.rs"), stats(3, 30)),
+            (PathBuf::from("src/lib.rs"), stats(1, 4)),
+            (PathBuf::from("./Cargo.lock"), stats(5, 50)),
+        ];
+        let tree = ConflictTree::build(files.iter().map(|(p, s)| (p.as_path(), *s)));
+
+        assert_eq!(
+            tree.stats,
+            ConflictStats {
+                files: 3,
+                hunks: 9,
+                lines: 84
+            }
+        );
+        let rows = tree.rows();
+        let rows: Vec<(usize, &str, usize)> = rows
+            .iter()
+            .map(|r| (r.depth, r.name.as_str(), r.stats.hunks))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (0, "Cargo.lock", 5),
+                (0, "src/", 4),
+                (1, "lib.rs", 1),
+                (1, "parser/", 3),
+                (2, "lexer.rs", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_directory_chains_are_joined() {
+        let files = [
+            (PathBuf::from("crates/core/src/a.rs"), stats(1, 2)),
+            (PathBuf::from("crates/core/src/b.rs"), stats(2, 2)),
+        ];
+        let tree = ConflictTree::build(files.iter().map(|(p, s)| (p.as_path(), *s)));
+        let rows = tree.rows();
+        assert_eq!(rows[0].name, "crates/core/src/");
+        assert_eq!(rows[0].stats.files, 2);
+        assert_eq!(rows[1].depth, 1);
+        assert_eq!(rows[1].name, "a.rs");
+    }
+}
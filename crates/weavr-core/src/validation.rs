@@ -0,0 +1,80 @@
+//! Validation profiles.
+//!
+//! A profile selects the checks [`MergeSession::validate_with`] runs before a
+//! session can complete. `basic` only rejects leftover conflict markers;
+//! `strict` also rejects unbalanced brackets and duplicated declarations.
+//! Projects can define their own, e.g. to turn marker checks off for files
+//! that legitimately contain `=======` lines.
+//!
+//! All types in this module are **stable** and covered by semantic versioning.
+//!
+//! [`MergeSession::validate_with`]: crate::MergeSession::validate_with
+
+use serde::{Deserialize, Serialize};
+
+/// Checks run when validating a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationProfile {
+    /// Reject resolutions containing conflict markers.
+    pub markers: bool,
+    /// Reject resolutions whose bracket balance matches neither side.
+    pub balance: bool,
+    /// Reject declarations the resolutions repeat.
+    pub duplicates: bool,
+}
+
+impl ValidationProfile {
+    /// Names of the built-in profiles.
+    pub const BUILTIN: &'static [&'static str] = &["basic", "strict"];
+
+    /// Only rejects leftover conflict markers.
+    #[must_use]
+    pub fn basic() -> Self {
+        Self {
+            markers: true,
+            balance: false,
+            duplicates: false,
+        }
+    }
+
+    /// Runs every check.
+    #[must_use]
+    pub fn strict() -> Self {
+        Self {
+            markers: true,
+            balance: true,
+            duplicates: true,
+        }
+    }
+
+    /// Returns the built-in profile with the given name.
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "basic" => Some(Self::basic()),
+            "strict" => Some(Self::strict()),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ValidationProfile {
+    fn default() -> Self {
+        Self::basic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_profiles() {
+        assert_eq!(ValidationProfile::default(), ValidationProfile::basic());
+        for name in ValidationProfile::BUILTIN {
+            assert!(ValidationProfile::builtin(name).is_some());
+        }
+        assert!(ValidationProfile::builtin("paranoid").is_none());
+    }
+}
@@ -26,6 +26,10 @@ pub enum GitError {
         stderr: String,
     },
 
+    /// No merge, rebase, cherry-pick, or revert is in progress.
+    #[error("no merge, rebase, cherry-pick, or revert in progress")]
+    NoOperation,
+
     /// Failed to parse Git output.
     #[error("failed to parse git output: {0}")]
     ParseError(String),
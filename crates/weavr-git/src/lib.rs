@@ -25,11 +25,13 @@
 #![warn(missing_docs)]
 
 mod error;
+mod log;
 mod porcelain;
 mod repo;
 mod state;
 
 pub use error::GitError;
+pub use log::LogEntry;
 pub use porcelain::{ConflictEntry, ConflictType};
 pub use repo::GitRepo;
 pub use state::GitOperation;
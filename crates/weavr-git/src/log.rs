@@ -0,0 +1,57 @@
+//! Parser for `git log` output in weavr's record format.
+
+/// Format string passed to `git log --format`, one record per line with
+/// fields separated by the ASCII unit separator.
+pub(crate) const LOG_FORMAT: &str = "%H%x1f%h%x1f%an%x1f%ar%x1f%s";
+
+/// A commit from the history of a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Full commit hash.
+    pub id: String,
+    /// Abbreviated commit hash.
+    pub short_id: String,
+    /// Author name.
+    pub author: String,
+    /// Commit date relative to now, e.g. `2 days ago`.
+    pub date: String,
+    /// First line of the commit message.
+    pub summary: String,
+}
+
+/// Parses `git log` output produced with [`LOG_FORMAT`].
+pub fn parse_log(output: &str) -> Vec<LogEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, '\x1f');
+            Some(LogEntry {
+                id: fields.next()?.to_string(),
+                short_id: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                summary: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_records() {
+        let output = "abc123\x1fabc\x1fAda\x1f2 days ago\x1fFix: parse a\x1fb\nbad line\n";
+        assert_eq!(
+            parse_log(output),
+            vec![LogEntry {
+                id: "abc123".to_string(),
+                short_id: "abc".to_string(),
+                author: "Ada".to_string(),
+                date: "2 days ago".to_string(),
+                summary: "Fix: parse a\x1fb".to_string(),
+            }]
+        );
+    }
+}
@@ -35,7 +35,8 @@ pub struct ConflictEntry {
 /// - `AU`, `UD` = added by us, deleted by them
 /// - `UA`, `DU` = added by them, deleted by us
 ///
-/// Handles quoted filenames with C-style escape sequences.
+/// Handles quoted filenames with C-style escape sequences. Paths use the
+/// platform's separator, so they compare equal to paths given by the user.
 #[must_use]
 pub fn parse_porcelain_v1(output: &str) -> Vec<ConflictEntry> {
     output
@@ -50,7 +51,7 @@ pub fn parse_porcelain_v1(output: &str) -> Vec<ConflictEntry> {
 
             // Path starts at position 3 (after "XY ")
             let raw_path = &line[3..];
-            let path = PathBuf::from(unquote_path(raw_path));
+            let path = native_path(&unquote_path(raw_path));
 
             Some(ConflictEntry {
                 path,
@@ -80,7 +81,8 @@ fn is_unmerged(xy: &str) -> Option<ConflictType> {
 /// - `\"` -> `"`
 /// - `\n` -> newline
 /// - `\t` -> tab
-/// - `\xxx` -> octal escape sequences
+/// - `\xxx` -> octal escape sequences, one byte each; consecutive bytes
+///   form UTF-8 characters
 fn unquote_path(s: &str) -> String {
     // If not quoted, return as-is
     if !s.starts_with('"') {
@@ -93,17 +95,20 @@ fn unquote_path(s: &str) -> String {
         .and_then(|s| s.strip_suffix('"'))
         .unwrap_or(s);
 
-    let mut result = String::with_capacity(inner.len());
+    let mut result: Vec<u8> = Vec::with_capacity(inner.len());
     let mut chars = inner.chars().peekable();
+    let push = |result: &mut Vec<u8>, c: char| {
+        result.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    };
 
     while let Some(c) = chars.next() {
         if c == '\\' {
             match chars.next() {
-                Some('\\') | None => result.push('\\'),
-                Some('"') => result.push('"'),
-                Some('n') => result.push('\n'),
-                Some('t') => result.push('\t'),
-                Some('r') => result.push('\r'),
+                Some('\\') | None => push(&mut result, '\\'),
+                Some('"') => push(&mut result, '"'),
+                Some('n') => push(&mut result, '\n'),
+                Some('t') => push(&mut result, '\t'),
+                Some('r') => push(&mut result, '\r'),
                 // Octal escape sequence (e.g., \302\240 for non-breaking space)
                 Some(d1) if d1.is_ascii_digit() => {
                     let mut octal = String::new();
@@ -119,21 +124,26 @@ fn unquote_path(s: &str) -> String {
                         }
                     }
                     if let Ok(byte) = u8::from_str_radix(&octal, 8) {
-                        result.push(byte as char);
+                        result.push(byte);
                     }
                 }
                 Some(other) => {
                     // Unknown escape, preserve literally
-                    result.push('\\');
-                    result.push(other);
+                    push(&mut result, '\\');
+                    push(&mut result, other);
                 }
             }
         } else {
-            result.push(c);
+            push(&mut result, c);
         }
     }
 
-    result
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+/// Converts a path as git prints it, with `/` separators, to a native path.
+fn native_path(path: &str) -> PathBuf {
+    path.split('/').collect()
 }
 
 #[cfg(test)]
@@ -285,4 +295,18 @@ mod tests {
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].path, PathBuf::from("file with \"quotes\".rs"));
     }
+
+    #[test]
+    fn unquote_utf8_octal_escapes() {
+        assert_eq!(unquote_path("\"caf\\303\\251 menu.rs\""), "café menu.rs");
+    }
+
+    #[test]
+    fn paths_use_native_separators() {
+        let entries = parse_porcelain_v1("UU \"src/caf\\303\\251/a b.rs\"\n");
+        assert_eq!(
+            entries[0].path,
+            PathBuf::from("src").join("café").join("a b.rs")
+        );
+    }
 }
@@ -1,9 +1,11 @@
 //! Git repository abstraction.
 
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 use crate::error::GitError;
+use crate::log::{parse_log, LogEntry, LOG_FORMAT};
 use crate::porcelain::{parse_porcelain_v1, ConflictEntry};
 use crate::state::GitOperation;
 
@@ -82,6 +84,30 @@ impl GitRepo {
         Ok(Self { root, git_dir })
     }
 
+    /// Initializes a new repository in an existing directory.
+    ///
+    /// The initial branch is named `main`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn init(path: impl AsRef<Path>) -> Result<Self, GitError> {
+        let output = Command::new("git")
+            .args(["init", "--quiet", "--initial-branch=main"])
+            .current_dir(path.as_ref())
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        if !output.status.success() {
+            return Err(GitError::CommandError {
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+
+        Self::discover_from(path)
+    }
+
     /// Returns the root directory of the repository's working tree.
     #[must_use]
     pub fn root(&self) -> &Path {
@@ -96,6 +122,34 @@ impl GitRepo {
         &self.git_dir
     }
 
+    /// Returns the directory git runs hooks from.
+    ///
+    /// This honors `core.hooksPath`; without it, hooks live in the common
+    /// git directory shared by all worktrees.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn hooks_dir(&self) -> Result<PathBuf, GitError> {
+        let output = self.run_git(&["rev-parse", "--git-path", "hooks"])?;
+        Ok(self.root.join(output.trim()))
+    }
+
+    /// Returns where git keeps `name` inside its directory.
+    ///
+    /// Paths git shares between worktrees resolve to the common git
+    /// directory, as with `git rev-parse --git-path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn git_path(&self, name: &str) -> Result<PathBuf, GitError> {
+        let output = self.run_git(&["rev-parse", "--git-path", name])?;
+        Ok(self.root.join(output.trim()))
+    }
+
     /// Returns a list of files with merge conflicts.
     ///
     /// Uses `git status --porcelain=v1` to detect unmerged paths.
@@ -131,6 +185,92 @@ impl GitRepo {
         Ok(())
     }
 
+    /// Sets a repository-local configuration value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn set_config(&self, key: &str, value: &str) -> Result<(), GitError> {
+        self.run_git(&["config", key, value])?;
+        Ok(())
+    }
+
+    /// Reads a configuration value, as git resolves it across the repository,
+    /// user and system configuration.
+    ///
+    /// Returns `None` if the key is not set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if the key is invalid.
+    pub fn config(&self, key: &str) -> Result<Option<String>, GitError> {
+        match self.run_git(&["config", "--get", key]) {
+            Ok(value) => Ok(Some(value.trim_end().to_string())),
+            // An unset key exits with an error but says nothing
+            Err(GitError::CommandError { stderr }) if stderr.trim().is_empty() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stages every change in the working tree and commits it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn commit_all(&self, message: &str) -> Result<(), GitError> {
+        self.run_git(&["add", "--all"])?;
+        self.run_git(&["commit", "--quiet", "-m", message])?;
+        Ok(())
+    }
+
+    /// Creates a branch at the current commit without switching to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn create_branch(&self, name: &str) -> Result<(), GitError> {
+        self.run_git(&["branch", name])?;
+        Ok(())
+    }
+
+    /// Switches the working tree to a branch.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn checkout(&self, branch: &str) -> Result<(), GitError> {
+        self.run_git(&["checkout", "--quiet", branch])?;
+        Ok(())
+    }
+
+    /// Merges a branch into the current branch.
+    ///
+    /// Extra `options` are passed to `git merge` before the branch name.
+    /// Returns `true` if the merge completed and `false` if it stopped with
+    /// conflicts for the user to resolve.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if the merge failed for a reason other
+    /// than conflicts.
+    pub fn merge(&self, branch: &str, options: &[&str]) -> Result<bool, GitError> {
+        let mut args = vec!["merge", "--no-edit", "--quiet"];
+        args.extend_from_slice(options);
+        args.push(branch);
+
+        match self.run_git(&args) {
+            Ok(_) => Ok(true),
+            Err(GitError::CommandError { .. }) if self.is_in_merge() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Returns true if a merge is in progress.
     #[must_use]
     pub fn is_in_merge(&self) -> bool {
@@ -171,6 +311,670 @@ impl GitRepo {
         }
     }
 
+    /// Reads a file as our side had it before the conflicting operation.
+    ///
+    /// Uses the "ours" stage of the index (stage 2), which git fills in when
+    /// a merge, rebase, or cherry-pick stops with conflicts, and falls back to
+    /// `HEAD`. `path` is relative to the repository root. Returns `None` if
+    /// neither has the file, e.g. when it was added by the other side.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    pub fn pre_merge_version(&self, path: &Path) -> Result<Option<String>, GitError> {
+        let path = path.to_string_lossy();
+        if let Some(content) = self.read_blob(&format!(":2:{path}"))? {
+            return Ok(Some(content));
+        }
+        self.read_blob(&format!("HEAD:{path}"))
+    }
+
+    /// Reads `path` as it is in `rev`, if it exists there.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if git cannot be run.
+    pub fn file_at(&self, rev: &str, path: &Path) -> Result<Option<String>, GitError> {
+        self.read_blob(&format!("{rev}:{}", path.to_string_lossy()))
+    }
+
+    /// Returns up to `limit` commits reachable from `rev` that changed `path`,
+    /// newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn path_log(
+        &self,
+        rev: &str,
+        path: &Path,
+        limit: usize,
+    ) -> Result<Vec<LogEntry>, GitError> {
+        let output = self.run_git(&[
+            "log",
+            &format!("--max-count={limit}"),
+            &format!("--format={LOG_FORMAT}"),
+            rev,
+            "--",
+            &path.to_string_lossy(),
+        ])?;
+        Ok(parse_log(&output))
+    }
+
+    /// Returns the path in `from` of the file at `path` in `to`, following a
+    /// rename between the two, or `None` if `from` has no such file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if either commit does not exist.
+    pub fn path_before(
+        &self,
+        from: &str,
+        to: &str,
+        path: &Path,
+    ) -> Result<Option<PathBuf>, GitError> {
+        if self.file_at(from, path)?.is_some() {
+            return Ok(Some(path.to_path_buf()));
+        }
+        // Records are "R<score>", the old path, and the new path
+        let output = self.run_git(&[
+            "diff",
+            "--name-status",
+            "--find-renames",
+            "--diff-filter=R",
+            "-z",
+            from,
+            to,
+        ])?;
+        let fields: Vec<&str> = output.split('\0').collect();
+        Ok(fields
+            .chunks_exact(3)
+            .find(|record| Path::new(record[2]) == path)
+            .map(|record| PathBuf::from(record[1])))
+    }
+
+    /// Returns the commits in `range` (e.g. `base..HEAD`) that changed
+    /// `path`, newest first. With `lines`, only commits that changed that
+    /// 1-based inclusive line range of the file at the tip of `range` count.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status,
+    /// e.g. when `lines` is past the end of the file.
+    pub fn line_log(
+        &self,
+        range: &str,
+        path: &Path,
+        lines: Option<(usize, usize)>,
+    ) -> Result<Vec<LogEntry>, GitError> {
+        let path = path.to_string_lossy();
+        // -L always prints patches, so records are marked to pick them out
+        let format = format!("--format=%x1e{LOG_FORMAT}");
+        let output = match lines {
+            Some((start, end)) => {
+                self.run_git(&["log", &format, &format!("-L{start},{end}:{path}"), range])?
+            }
+            None => self.run_git(&["log", &format, range, "--", &path])?,
+        };
+        let records: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix('\x1e'))
+            .collect();
+        Ok(parse_log(&records.join("\n")))
+    }
+
+    /// Returns commit `rev`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if `rev` is not a commit.
+    pub fn commit(&self, rev: &str) -> Result<Option<LogEntry>, GitError> {
+        let output = self.run_git(&[
+            "log",
+            "--max-count=1",
+            &format!("--format={LOG_FORMAT}"),
+            rev,
+            "--",
+        ])?;
+        Ok(parse_log(&output).into_iter().next())
+    }
+
+    /// Returns the parents of commit `rev`, first parent first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if `rev` is not a commit.
+    pub fn parents(&self, rev: &str) -> Result<Vec<String>, GitError> {
+        let output = self.run_git(&["rev-list", "--parents", "--max-count=1", rev, "--"])?;
+        Ok(output
+            .split_whitespace()
+            .skip(1)
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Returns the best common ancestor of two commits, or `None` if their
+    /// histories are unrelated.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if either commit does not exist.
+    pub fn merge_base(&self, a: &str, b: &str) -> Result<Option<String>, GitError> {
+        match self.run_git(&["merge-base", a, b]) {
+            Ok(output) => Ok(Some(output.trim().to_string())),
+            // No common ancestor: exit status 1 and nothing on stderr
+            Err(GitError::CommandError { stderr }) if stderr.trim().is_empty() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the paths that differ between two commits, relative to the
+    /// repository root.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn changed_paths(&self, from: &str, to: &str) -> Result<Vec<PathBuf>, GitError> {
+        let output = self.run_git(&["diff", "--name-only", "--no-renames", "-z", from, to])?;
+        Ok(output
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    /// Merges three versions of a file's content the way git merges files,
+    /// returning the result with conflict markers (including the base, as
+    /// with `merge.conflictStyle=diff3`) labelled with `labels`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::FileError` if the scratch files cannot be written.
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git cannot merge the contents.
+    pub fn merge_file(
+        &self,
+        ours: &str,
+        base: &str,
+        theirs: &str,
+        labels: [&str; 3],
+    ) -> Result<String, GitError> {
+        let scratch = self
+            .git_dir
+            .join(format!("weavr-merge-file-{}", std::process::id()));
+        let result = Self::merge_in(&scratch, [ours, base, theirs], labels);
+        let _ = std::fs::remove_dir_all(&scratch);
+        result
+    }
+
+    /// Writes the three versions into `scratch` and merges them there.
+    fn merge_in(
+        scratch: &Path,
+        contents: [&str; 3],
+        labels: [&str; 3],
+    ) -> Result<String, GitError> {
+        std::fs::create_dir_all(scratch).map_err(|source| GitError::FileError {
+            path: scratch.to_path_buf(),
+            source,
+        })?;
+        let mut args = vec!["merge-file", "-p", "--diff3"];
+        for label in labels {
+            args.extend(["-L", label]);
+        }
+        let names = ["ours", "base", "theirs"];
+        for (name, content) in names.iter().zip(contents) {
+            let path = scratch.join(name);
+            std::fs::write(&path, content)
+                .map_err(|source| GitError::FileError { path, source })?;
+        }
+        args.extend(names);
+
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(scratch)
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        // The exit status is the number of conflicts; errors are negative
+        match output.status.code() {
+            Some(0..=127) => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+            _ => Err(GitError::CommandError {
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            }),
+        }
+    }
+
+    /// Returns the tracked files whose working tree copy has conflict
+    /// markers, relative to the repository root.
+    ///
+    /// A file counts when it has lines starting with each of `<<<<<<<`,
+    /// `=======`, and `>>>>>>>`. Binary files are skipped. `pathspecs`, if
+    /// not empty, limits the search as it would `git grep`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if git cannot be run.
+    /// Returns `GitError::CommandError` if a pathspec is invalid.
+    pub fn files_with_markers(&self, pathspecs: &[String]) -> Result<Vec<PathBuf>, GitError> {
+        let mut args = vec![
+            "grep",
+            "-l",
+            "-z",
+            "-I",
+            "--all-match",
+            "-e",
+            "^<<<<<<<",
+            "-e",
+            "^=======",
+            "-e",
+            "^>>>>>>>",
+            "--",
+        ];
+        args.extend(pathspecs.iter().map(String::as_str));
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(&self.root)
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        // Exit status 1 means nothing matched
+        match output.status.code() {
+            Some(0 | 1) => Ok(String::from_utf8_lossy(&output.stdout)
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .collect()),
+            _ => Err(GitError::CommandError {
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            }),
+        }
+    }
+
+    /// Merges `theirs` into `ours` in memory and returns the files that would
+    /// conflict, with their content as the merge would leave it.
+    ///
+    /// Uses `git merge-tree --write-tree` (git 2.38 or later), which touches
+    /// neither the working tree nor the index. Conflict markers include the
+    /// base, as with `merge.conflictStyle=diff3`. Paths are relative to the
+    /// repository root; a conflicted path the merge deletes, as in a
+    /// modify/delete conflict, comes back as `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if git cannot be run.
+    /// Returns `GitError::CommandError` if either commit does not exist or
+    /// git is too old to merge in memory.
+    pub fn preview_merge(
+        &self,
+        ours: &str,
+        theirs: &str,
+    ) -> Result<Vec<(PathBuf, Option<String>)>, GitError> {
+        let output = Command::new("git")
+            .args([
+                "-c",
+                "merge.conflictStyle=diff3",
+                "merge-tree",
+                "--write-tree",
+                "--name-only",
+                "--no-messages",
+                "-z",
+                ours,
+                theirs,
+            ])
+            .current_dir(&self.root)
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        // Exit status 1 means conflicts, or an error if no tree was printed
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut fields = stdout.split('\0').filter(|field| !field.is_empty());
+        let (Some(0 | 1), Some(tree)) = (output.status.code(), fields.next()) else {
+            return Err(GitError::CommandError {
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        };
+
+        let mut seen = std::collections::BTreeSet::new();
+        fields
+            .filter(|path| seen.insert(*path))
+            .map(|path| {
+                let content = self.read_blob(&format!("{tree}:{path}"))?;
+                Ok((PathBuf::from(path), content))
+            })
+            .collect()
+    }
+
+    /// Applies `patch` to the working tree and index with a three-way merge,
+    /// leaving conflict markers where it conflicts.
+    ///
+    /// Returns `false` without changing anything if the patch cannot be
+    /// applied at all, e.g. because the repository lacks the blobs it was
+    /// made against and it no longer applies directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if git cannot be run.
+    pub fn apply_three_way(&self, patch: &Path) -> Result<bool, GitError> {
+        let output = Command::new("git")
+            .args(["-c", "merge.conflictStyle=diff3", "apply", "--3way"])
+            .arg(patch)
+            .current_dir(&self.root)
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        // Conflicts still count as applied; git reports them on stderr
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(output.status.success() || stderr.contains("with conflicts"))
+    }
+
+    /// Applies the hunks of `patch` that apply and writes the others to a
+    /// `.rej` file beside each file, returning the files that have one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if git cannot be run.
+    /// Returns `GitError::CommandError` if git fails for another reason than
+    /// rejected hunks, e.g. a malformed patch or a missing file.
+    pub fn apply_with_rejects(&self, patch: &Path) -> Result<Vec<PathBuf>, GitError> {
+        let output = Command::new("git")
+            .args(["apply", "--reject"])
+            .arg(patch)
+            .current_dir(&self.root)
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        // "Applying patch <path> with <n> reject(s)..." names each file
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let rejected: Vec<PathBuf> = stderr
+            .lines()
+            .filter_map(|line| line.strip_prefix("Applying patch "))
+            .filter_map(|rest| rest.rsplit_once(" with "))
+            .filter(|(_, count)| !count.starts_with("0 "))
+            .map(|(path, _)| PathBuf::from(path))
+            .collect();
+        if output.status.success() || !rejected.is_empty() {
+            Ok(rejected)
+        } else {
+            Err(GitError::CommandError {
+                stderr: stderr.into_owned(),
+            })
+        }
+    }
+
+    /// Re-runs the operation in progress with extra strategy options and
+    /// returns the resulting content of each of `paths`.
+    ///
+    /// The operation is replayed in a scratch worktree detached at `HEAD`, so
+    /// the working tree and index are left alone. Each option is passed as
+    /// `-X<option>`, e.g. `ignore-space-change` or `find-renames=70`. Paths
+    /// are relative to the repository root; a path the operation deletes
+    /// comes back as `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::NoOperation` if nothing is in progress.
+    /// Returns `GitError::CommandFailed` if git cannot be run.
+    /// Returns `GitError::CommandError` if the worktree cannot be set up or
+    /// the operation fails for a reason other than conflicts.
+    pub fn remerge(
+        &self,
+        paths: &[PathBuf],
+        options: &[&str],
+    ) -> Result<Vec<(PathBuf, Option<String>)>, GitError> {
+        let operation = self.current_operation();
+        let incoming = operation.incoming_head().ok_or(GitError::NoOperation)?;
+        let incoming = self
+            .resolve_ref(incoming)?
+            .ok_or_else(|| GitError::ParseError(format!("cannot resolve {incoming}")))?;
+
+        let scratch = self
+            .git_dir
+            .join(format!("weavr-remerge-{}", std::process::id()));
+        let scratch_str = scratch.to_string_lossy().into_owned();
+        self.run_git(&[
+            "worktree",
+            "add",
+            "--detach",
+            "--quiet",
+            &scratch_str,
+            "HEAD",
+        ])?;
+
+        let result = Self::replay(&scratch, operation, &incoming, options).map(|()| {
+            paths
+                .iter()
+                .map(|path| {
+                    (
+                        path.clone(),
+                        std::fs::read_to_string(scratch.join(path)).ok(),
+                    )
+                })
+                .collect()
+        });
+
+        // Clean up even if the replay failed
+        if self
+            .run_git(&["worktree", "remove", "--force", &scratch_str])
+            .is_err()
+        {
+            let _ = std::fs::remove_dir_all(&scratch);
+            let _ = self.run_git(&["worktree", "prune"]);
+        }
+        result
+    }
+
+    /// Applies `incoming` in `worktree` the way `operation` did.
+    fn replay(
+        worktree: &Path,
+        operation: GitOperation,
+        incoming: &str,
+        options: &[&str],
+    ) -> Result<(), GitError> {
+        let mut args: Vec<String> = match operation {
+            GitOperation::Merge => vec!["merge".into(), "--no-commit".into(), "--no-ff".into()],
+            GitOperation::Revert => vec!["revert".into(), "--no-commit".into()],
+            GitOperation::Rebase | GitOperation::CherryPick | GitOperation::None => {
+                vec!["cherry-pick".into(), "--no-commit".into()]
+            }
+        };
+        args.extend(options.iter().map(|option| format!("-X{option}")));
+        args.push(incoming.to_string());
+
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(worktree)
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        // Conflicts are an expected outcome; anything else is a failure
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if output.status.success() || stdout.contains("CONFLICT") {
+            Ok(())
+        } else {
+            Err(GitError::CommandError {
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            })
+        }
+    }
+
+    /// Reads the prepared commit message (`MERGE_MSG`), if one exists.
+    ///
+    /// Git writes this file when a merge, cherry-pick, or revert stops with
+    /// conflicts, and uses it as the default message for the next commit.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::FileError` if the file exists but cannot be read.
+    pub fn merge_message(&self) -> Result<Option<String>, GitError> {
+        let path = self.git_dir.join("MERGE_MSG");
+        match std::fs::read_to_string(&path) {
+            Ok(message) => Ok(Some(message)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(GitError::FileError { path, source }),
+        }
+    }
+
+    /// Appends text to the prepared commit message.
+    ///
+    /// Returns `false` without writing anything if there is no prepared
+    /// message, i.e. no merge, cherry-pick, or revert is waiting to be
+    /// committed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::FileError` if the file cannot be read or written.
+    pub fn append_merge_message(&self, text: &str) -> Result<bool, GitError> {
+        let Some(mut message) = self.merge_message()? else {
+            return Ok(false);
+        };
+
+        if !message.is_empty() && !message.ends_with('\n') {
+            message.push('\n');
+        }
+        message.push('\n');
+        message.push_str(text);
+
+        let path = self.git_dir.join("MERGE_MSG");
+        std::fs::write(&path, message).map_err(|source| GitError::FileError { path, source })?;
+        Ok(true)
+    }
+
+    /// Reads a file from the tree of the commit a ref points to.
+    ///
+    /// Returns `None` if the ref does not exist or has no such file. This
+    /// lets weavr keep shared data on a ref (e.g. `refs/weavr/resolutions`)
+    /// that is exchanged with `git push` and `git fetch`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn read_ref_file(&self, refname: &str, name: &str) -> Result<Option<String>, GitError> {
+        if self.resolve_ref(refname)?.is_none() {
+            return Ok(None);
+        }
+
+        let listing = self.run_git(&["ls-tree", "--name-only", refname, "--", name])?;
+        if listing.trim().is_empty() {
+            return Ok(None);
+        }
+
+        self.run_git(&["cat-file", "blob", &format!("{refname}:{name}")])
+            .map(Some)
+    }
+
+    /// Writes a file to the tree of a ref by committing on top of it.
+    ///
+    /// The ref is created if it does not exist. Only top-level file names
+    /// are supported. The working tree and index are not touched.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::CommandFailed` if the git command fails to execute.
+    /// Returns `GitError::CommandError` if git returns a non-zero exit status.
+    pub fn write_ref_file(
+        &self,
+        refname: &str,
+        name: &str,
+        content: &str,
+        message: &str,
+    ) -> Result<(), GitError> {
+        let parent = self.resolve_ref(refname)?;
+        let blob = self.run_git_with_input(&["hash-object", "-w", "--stdin"], content)?;
+
+        // Rebuild the tree with the new entry replacing any existing one
+        let mut entries = match &parent {
+            Some(commit) => self.run_git(&["ls-tree", commit])?,
+            None => String::new(),
+        }
+        .lines()
+        .filter(|line| line.split_once('\t').map(|(_, path)| path) != Some(name))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+        entries.push(format!("100644 blob {}\t{name}", blob.trim()));
+        let tree = self.run_git_with_input(&["mktree"], &(entries.join("\n") + "\n"))?;
+
+        let mut args = vec!["commit-tree", tree.trim(), "-m", message];
+        if let Some(commit) = &parent {
+            args.extend(["-p", commit.as_str()]);
+        }
+        let commit = self.run_git(&args)?;
+
+        let mut update = vec!["update-ref", refname, commit.trim()];
+        if let Some(old) = &parent {
+            update.push(old.as_str());
+        }
+        self.run_git(&update)?;
+        Ok(())
+    }
+
+    /// Resolves a ref to a commit ID, returning `None` if it does not exist.
+    fn resolve_ref(&self, refname: &str) -> Result<Option<String>, GitError> {
+        let output = Command::new("git")
+            .args([
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{refname}^{{commit}}"),
+            ])
+            .current_dir(&self.root)
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    /// Reads a blob by revision spec, or `None` if it does not exist.
+    fn read_blob(&self, spec: &str) -> Result<Option<String>, GitError> {
+        let output = Command::new("git")
+            .args(["cat-file", "blob", spec])
+            .current_dir(&self.root)
+            .output()
+            .map_err(GitError::CommandFailed)?;
+
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    /// Runs a git command with `input` on stdin and returns stdout as a string.
+    fn run_git_with_input(&self, args: &[&str], input: &str) -> Result<String, GitError> {
+        let mut child = Command::new("git")
+            .args(args)
+            .current_dir(&self.root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(GitError::CommandFailed)?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(GitError::CommandFailed)?;
+        }
+
+        let output = child.wait_with_output().map_err(GitError::CommandFailed)?;
+        if !output.status.success() {
+            return Err(GitError::CommandError {
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
     /// Runs a git command and returns stdout as a string.
     fn run_git(&self, args: &[&str]) -> Result<String, GitError> {
         let output = Command::new("git")
@@ -21,6 +21,18 @@ impl GitOperation {
     pub fn has_conflicts(&self) -> bool {
         !matches!(self, GitOperation::None)
     }
+
+    /// Returns the ref naming the incoming ("theirs") commit, if any.
+    #[must_use]
+    pub fn incoming_head(&self) -> Option<&'static str> {
+        match self {
+            GitOperation::None => None,
+            GitOperation::Merge => Some("MERGE_HEAD"),
+            GitOperation::Rebase => Some("REBASE_HEAD"),
+            GitOperation::CherryPick => Some("CHERRY_PICK_HEAD"),
+            GitOperation::Revert => Some("REVERT_HEAD"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -51,4 +63,11 @@ mod tests {
     fn revert_has_conflicts() {
         assert!(GitOperation::Revert.has_conflicts());
     }
+
+    #[test]
+    fn incoming_head_names_their_commit() {
+        assert_eq!(GitOperation::None.incoming_head(), None);
+        assert_eq!(GitOperation::Merge.incoming_head(), Some("MERGE_HEAD"));
+        assert_eq!(GitOperation::Rebase.incoming_head(), Some("REBASE_HEAD"));
+    }
 }
@@ -343,3 +343,519 @@ fn conflicted_entries_returns_conflict_types() {
         weavr_git::ConflictType::BothModified
     );
 }
+
+#[test]
+fn conflicted_paths_with_spaces_and_unicode() {
+    let dir = setup_git_repo();
+    let name = "src/caf\u{e9} menu.txt";
+    commit_file(&dir, name, "initial", "Initial commit");
+
+    Command::new("git")
+        .args(["checkout", "-b", "feature"])
+        .current_dir(dir.path())
+        .output()
+        .expect("create branch");
+    commit_file(&dir, name, "feature", "Feature commit");
+
+    Command::new("git")
+        .args(["checkout", "main"])
+        .current_dir(dir.path())
+        .output()
+        .expect("checkout main");
+    commit_file(&dir, name, "main", "Main commit");
+
+    Command::new("git")
+        .args(["merge", "feature"])
+        .current_dir(dir.path())
+        .output()
+        .ok();
+
+    let repo = GitRepo::discover_from(dir.path()).expect("discover repo");
+    let files = repo.conflicted_files().expect("get conflicts");
+
+    // Paths use native separators and exist on disk
+    assert_eq!(files, vec![PathBuf::from("src").join("caf\u{e9} menu.txt")]);
+    assert!(dir.path().join(&files[0]).exists());
+}
+
+#[test]
+fn ref_files_roundtrip_without_touching_worktree() {
+    let dir = setup_git_repo();
+    commit_file(&dir, "file.txt", "content\n", "Initial commit");
+    let repo = GitRepo::discover_from(dir.path()).expect("discover");
+    let refname = "refs/weavr/test";
+
+    assert_eq!(repo.read_ref_file(refname, "a.patch").unwrap(), None);
+
+    repo.write_ref_file(refname, "a.patch", "first\n", "Record a")
+        .expect("write a");
+    repo.write_ref_file(refname, "b.patch", "second\n", "Record b")
+        .expect("write b");
+    repo.write_ref_file(refname, "a.patch", "updated\n", "Update a")
+        .expect("update a");
+
+    assert_eq!(
+        repo.read_ref_file(refname, "a.patch").unwrap().as_deref(),
+        Some("updated\n")
+    );
+    assert_eq!(
+        repo.read_ref_file(refname, "b.patch").unwrap().as_deref(),
+        Some("second\n")
+    );
+    assert_eq!(repo.read_ref_file(refname, "c.patch").unwrap(), None);
+
+    // The working tree and HEAD are unaffected
+    assert!(repo.conflicted_files().unwrap().is_empty());
+    assert!(!dir.path().join("a.patch").exists());
+}
+
+#[test]
+fn init_branch_and_merge_with_conflicts() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+    repo.set_config("user.email", "test@test.com").unwrap();
+    repo.set_config("user.name", "Test").unwrap();
+
+    fs::write(dir.path().join("file.txt"), "base\n").unwrap();
+    repo.commit_all("Base").unwrap();
+    repo.create_branch("feature").unwrap();
+
+    fs::write(dir.path().join("file.txt"), "ours\n").unwrap();
+    repo.commit_all("Ours").unwrap();
+
+    repo.checkout("feature").unwrap();
+    fs::write(dir.path().join("file.txt"), "theirs\n").unwrap();
+    repo.commit_all("Theirs").unwrap();
+    repo.checkout("main").unwrap();
+
+    assert!(!repo.merge("feature", &[]).unwrap());
+    assert_eq!(repo.current_operation(), GitOperation::Merge);
+    assert_eq!(
+        repo.conflicted_files().unwrap(),
+        vec![PathBuf::from("file.txt")]
+    );
+}
+
+#[test]
+fn merge_message_and_pre_merge_version() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+    repo.set_config("user.email", "test@test.com").unwrap();
+    repo.set_config("user.name", "Test").unwrap();
+
+    fs::write(dir.path().join("file.txt"), "base\n").unwrap();
+    repo.commit_all("Base").unwrap();
+    assert_eq!(repo.merge_message().unwrap(), None);
+    assert!(!repo.append_merge_message("Notes\n").unwrap());
+
+    repo.create_branch("feature").unwrap();
+    fs::write(dir.path().join("file.txt"), "ours\n").unwrap();
+    repo.commit_all("Ours").unwrap();
+    repo.checkout("feature").unwrap();
+    fs::write(dir.path().join("file.txt"), "theirs\n").unwrap();
+    repo.commit_all("Theirs").unwrap();
+    repo.checkout("main").unwrap();
+    assert!(!repo.merge("feature", &[]).unwrap());
+
+    assert_eq!(
+        repo.pre_merge_version(std::path::Path::new("file.txt"))
+            .unwrap()
+            .as_deref(),
+        Some("ours\n")
+    );
+    assert_eq!(
+        repo.pre_merge_version(std::path::Path::new("missing.txt"))
+            .unwrap(),
+        None
+    );
+
+    assert!(repo.append_merge_message("Notes\n").unwrap());
+    let message = repo.merge_message().unwrap().unwrap();
+    assert!(message.starts_with("Merge branch 'feature'"));
+    assert!(message.ends_with("\n\nNotes\n"));
+}
+
+#[test]
+fn path_log_and_file_at_revision() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+    repo.set_config("user.email", "test@test.com").unwrap();
+    repo.set_config("user.name", "Test").unwrap();
+
+    let path = std::path::Path::new("file.txt");
+    fs::write(dir.path().join("file.txt"), "one\n").unwrap();
+    repo.commit_all("First").unwrap();
+    fs::write(dir.path().join("other.txt"), "x\n").unwrap();
+    repo.commit_all("Unrelated").unwrap();
+    fs::write(dir.path().join("file.txt"), "two\n").unwrap();
+    repo.commit_all("Second").unwrap();
+
+    let log = repo.path_log("HEAD", path, 10).unwrap();
+    let summaries: Vec<&str> = log.iter().map(|e| e.summary.as_str()).collect();
+    assert_eq!(summaries, vec!["Second", "First"]);
+    assert_eq!(repo.path_log("HEAD", path, 1).unwrap().len(), 1);
+
+    assert_eq!(
+        repo.file_at(&log[1].id, path).unwrap().as_deref(),
+        Some("one\n")
+    );
+    assert_eq!(
+        repo.file_at(&log[0].short_id, path).unwrap().as_deref(),
+        Some("two\n")
+    );
+    assert_eq!(
+        repo.file_at("HEAD", std::path::Path::new("nope")).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn line_log_follows_a_line_range() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+    repo.set_config("user.email", "test@test.com").unwrap();
+    repo.set_config("user.name", "Test").unwrap();
+
+    let path = std::path::Path::new("file.txt");
+    fs::write(dir.path().join("file.txt"), "a\nb\nc\n").unwrap();
+    repo.commit_all("Base").unwrap();
+    let base = repo.commit("HEAD").unwrap().unwrap();
+    fs::write(dir.path().join("file.txt"), "A\nb\nc\n").unwrap();
+    repo.commit_all("Change a").unwrap();
+    fs::write(dir.path().join("file.txt"), "A\nb\nC\n").unwrap();
+    repo.commit_all("Change c").unwrap();
+
+    let range = format!("{}..HEAD", base.id);
+    let summaries = |lines| -> Vec<String> {
+        repo.line_log(&range, path, lines)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.summary)
+            .collect()
+    };
+    assert_eq!(summaries(None), vec!["Change c", "Change a"]);
+    assert_eq!(summaries(Some((1, 1))), vec!["Change a"]);
+    assert_eq!(summaries(Some((2, 2))), Vec::<String>::new());
+    assert_eq!(base.author, "Test");
+    assert_eq!(base.summary, "Base");
+}
+
+#[test]
+fn path_before_follows_renames() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+    repo.set_config("user.email", "test@test.com").unwrap();
+    repo.set_config("user.name", "Test").unwrap();
+
+    fs::write(dir.path().join("old.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+    repo.commit_all("Add").unwrap();
+    let before = repo.commit("HEAD").unwrap().unwrap().id;
+    fs::rename(dir.path().join("old.txt"), dir.path().join("new.txt")).unwrap();
+    repo.commit_all("Rename").unwrap();
+
+    let path_before = |path: &str| repo.path_before(&before, "HEAD", std::path::Path::new(path));
+    assert_eq!(
+        path_before("new.txt").unwrap(),
+        Some(PathBuf::from("old.txt"))
+    );
+    assert_eq!(
+        path_before("old.txt").unwrap(),
+        Some(PathBuf::from("old.txt"))
+    );
+    assert_eq!(path_before("missing.txt").unwrap(), None);
+}
+
+#[test]
+fn merge_without_conflicts_completes() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+    repo.set_config("user.email", "test@test.com").unwrap();
+    repo.set_config("user.name", "Test").unwrap();
+
+    fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+    repo.commit_all("Base").unwrap();
+    repo.create_branch("feature").unwrap();
+    repo.checkout("feature").unwrap();
+    fs::write(dir.path().join("b.txt"), "b\n").unwrap();
+    repo.commit_all("Add b").unwrap();
+    repo.checkout("main").unwrap();
+
+    assert!(repo.merge("feature", &["--no-ff"]).unwrap());
+    assert!(!repo.is_in_merge());
+    assert!(dir.path().join("b.txt").exists());
+}
+
+#[test]
+fn remerge_with_strategy_options() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+    repo.set_config("user.email", "test@test.com").unwrap();
+    repo.set_config("user.name", "Test").unwrap();
+
+    let path = PathBuf::from("file.txt");
+    fs::write(dir.path().join("file.txt"), "a\n  b\nc\n").unwrap();
+    repo.commit_all("Base").unwrap();
+    repo.create_branch("feature").unwrap();
+    repo.checkout("feature").unwrap();
+    fs::write(dir.path().join("file.txt"), "a\n  B\nc\n").unwrap();
+    repo.commit_all("Change b").unwrap();
+    repo.checkout("main").unwrap();
+    fs::write(dir.path().join("file.txt"), "a\n    b\nc\n").unwrap();
+    repo.commit_all("Reindent b").unwrap();
+
+    assert!(matches!(
+        repo.remerge(std::slice::from_ref(&path), &[]),
+        Err(weavr_git::GitError::NoOperation)
+    ));
+    assert!(!repo.merge("feature", &[]).unwrap());
+    let conflicted = fs::read_to_string(dir.path().join("file.txt")).unwrap();
+
+    let plain = repo.remerge(std::slice::from_ref(&path), &[]).unwrap();
+    assert!(plain[0].1.as_deref().unwrap().contains("<<<<<<<"));
+
+    let remerged = repo
+        .remerge(std::slice::from_ref(&path), &["ignore-space-change"])
+        .unwrap();
+    assert_eq!(remerged, vec![(path, Some("a\n  B\nc\n".to_string()))]);
+
+    // The user's merge is untouched and the scratch worktree is gone
+    assert!(repo.is_in_merge());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+        conflicted
+    );
+    let worktrees = Command::new("git")
+        .args(["worktree", "list"])
+        .current_dir(dir.path())
+        .output()
+        .expect("git worktree list");
+    assert_eq!(
+        String::from_utf8_lossy(&worktrees.stdout).lines().count(),
+        1
+    );
+}
+
+#[test]
+fn hooks_dir_honors_hooks_path() {
+    let dir = setup_git_repo();
+    let repo = GitRepo::discover_from(dir.path()).expect("discover");
+    assert_eq!(
+        canonicalize_for_comparison(&repo.hooks_dir().expect("hooks dir")),
+        canonicalize_for_comparison(&dir.path().join(".git").join("hooks"))
+    );
+
+    repo.set_config("core.hooksPath", ".githooks")
+        .expect("set hooks path");
+    fs::create_dir_all(dir.path().join(".githooks")).expect("create hooks dir");
+    assert_eq!(
+        canonicalize_for_comparison(&repo.hooks_dir().expect("hooks dir")),
+        canonicalize_for_comparison(&dir.path().join(".githooks"))
+    );
+}
+
+#[test]
+fn reconstruct_conflicts_of_a_merge_commit() {
+    let dir = setup_git_repo();
+    commit_file(&dir, "file.txt", "a\nshared\nz\n", "Initial commit");
+    let run = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .expect("git")
+    };
+    run(&["checkout", "-b", "feature"]);
+    commit_file(&dir, "file.txt", "a\nfeature\nz\n", "Feature commit");
+    run(&["checkout", "main"]);
+    commit_file(&dir, "file.txt", "a\nmain\nz\n", "Main commit");
+    run(&["merge", "feature"]);
+    fs::write(dir.path().join("file.txt"), "a\nmain\nfeature\nz\n").unwrap();
+    run(&["commit", "-am", "Merge feature"]);
+
+    let repo = GitRepo::discover_from(dir.path()).expect("discover repo");
+    let parents = repo.parents("HEAD").expect("parents");
+    assert_eq!(parents.len(), 2);
+    let base = repo
+        .merge_base(&parents[0], &parents[1])
+        .expect("merge base")
+        .expect("common ancestor");
+
+    let file = PathBuf::from("file.txt");
+    assert_eq!(
+        repo.changed_paths(&parents[0], "HEAD").unwrap(),
+        vec![file.clone()]
+    );
+    assert_eq!(
+        repo.changed_paths(&parents[1], "HEAD").unwrap(),
+        vec![file.clone()]
+    );
+
+    let read = |rev: &str| repo.file_at(rev, &file).unwrap().unwrap();
+    let merged = repo
+        .merge_file(
+            &read(&parents[0]),
+            &read(&base),
+            &read(&parents[1]),
+            ["ours", "base", "theirs"],
+        )
+        .expect("merge file");
+    assert_eq!(
+        merged,
+        "a\n<<<<<<< ours\nmain\n||||||| base\nshared\n=======\nfeature\n>>>>>>> theirs\nz\n"
+    );
+    assert!(!repo
+        .git_dir()
+        .join(format!("weavr-merge-file-{}", std::process::id()))
+        .exists());
+}
+
+#[test]
+fn preview_merge_leaves_the_working_tree_alone() {
+    let dir = setup_git_repo();
+    commit_file(&dir, "file.txt", "a\nshared\nz\n", "Initial commit");
+    commit_file(&dir, "clean.txt", "one\n", "Add clean file");
+    let run = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .expect("git")
+    };
+    run(&["checkout", "-b", "feature"]);
+    commit_file(&dir, "file.txt", "a\nfeature\nz\n", "Feature commit");
+    commit_file(&dir, "clean.txt", "two\n", "Change clean file");
+    run(&["checkout", "main"]);
+    commit_file(&dir, "file.txt", "a\nmain\nz\n", "Main commit");
+
+    let repo = GitRepo::discover_from(dir.path()).expect("discover repo");
+    let conflicts = repo.preview_merge("HEAD", "feature").expect("preview");
+    assert_eq!(conflicts.len(), 1);
+    let (path, content) = &conflicts[0];
+    assert_eq!(path, &PathBuf::from("file.txt"));
+    let content = content.as_deref().expect("content");
+    assert!(content.contains("<<<<<<< HEAD\nmain\n||||||| "));
+    assert!(content.contains("=======\nfeature\n>>>>>>> feature\n"));
+
+    assert!(!repo.is_in_merge());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+        "a\nmain\nz\n"
+    );
+    assert!(repo.preview_merge("HEAD", "HEAD~1").unwrap().is_empty());
+}
+
+#[test]
+fn files_with_markers_finds_committed_conflicts() {
+    let dir = setup_git_repo();
+    commit_file(
+        &dir,
+        "broken.txt",
+        "<<<<<<< HEAD\na\n=======\nb\n>>>>>>> feature\n",
+        "Commit a conflict",
+    );
+    commit_file(&dir, "docs.md", "Lines like ======= are fine\n", "Docs");
+    commit_file(
+        &dir,
+        "sub/other.txt",
+        "<<<<<<< HEAD\n=======\n>>>>>>> x\n",
+        "More",
+    );
+
+    let repo = GitRepo::discover_from(dir.path()).expect("discover repo");
+    assert_eq!(
+        repo.files_with_markers(&[]).unwrap(),
+        vec![PathBuf::from("broken.txt"), PathBuf::from("sub/other.txt")]
+    );
+    assert_eq!(
+        repo.files_with_markers(&["sub".to_string()]).unwrap(),
+        vec![PathBuf::from("sub/other.txt")]
+    );
+    assert!(repo
+        .files_with_markers(&["docs.md".to_string()])
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn apply_falls_back_to_rejects_without_the_base_blobs() {
+    let dir = setup_git_repo();
+    commit_file(
+        &dir,
+        "file.txt",
+        "1\n2\n3\n4\n5\n6\n7\n8\n9\n",
+        "Initial commit",
+    );
+    let patch = dir.path().join("change.patch");
+    fs::write(
+        &patch,
+        "diff --git a/file.txt b/file.txt\n\
+         index 1111111..2222222 100644\n\
+         --- a/file.txt\n\
+         +++ b/file.txt\n\
+         @@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n\
+         @@ -7,3 +7,3 @@\n 7\n-eight\n+EIGHT\n 9\n",
+    )
+    .unwrap();
+
+    let repo = GitRepo::discover_from(dir.path()).expect("discover repo");
+    assert!(!repo.apply_three_way(&patch).unwrap());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+        "1\n2\n3\n4\n5\n6\n7\n8\n9\n"
+    );
+
+    assert_eq!(
+        repo.apply_with_rejects(&patch).unwrap(),
+        vec![PathBuf::from("file.txt")]
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+        "1\ntwo\n3\n4\n5\n6\n7\n8\n9\n"
+    );
+    let rej = fs::read_to_string(dir.path().join("file.txt.rej")).unwrap();
+    assert!(rej.contains("-eight\n+EIGHT"));
+}
+
+#[test]
+fn apply_three_way_leaves_conflict_markers() {
+    let dir = setup_git_repo();
+    commit_file(&dir, "file.txt", "a\nb\nc\n", "Initial commit");
+    fs::write(dir.path().join("file.txt"), "a\nB\nc\n").unwrap();
+    let diff = Command::new("git")
+        .args(["diff"])
+        .current_dir(dir.path())
+        .output()
+        .expect("git diff");
+    let patch = dir.path().join("change.patch");
+    fs::write(&patch, diff.stdout).unwrap();
+    commit_file(&dir, "file.txt", "a\nbee\nc\n", "Local change");
+
+    let repo = GitRepo::discover_from(dir.path()).expect("discover repo");
+    assert!(repo.apply_three_way(&patch).unwrap());
+    let content = fs::read_to_string(dir.path().join("file.txt")).unwrap();
+    assert!(content.contains("<<<<<<< ours\nbee\n||||||| "));
+    assert_eq!(repo.conflicted_files().unwrap().len(), 1);
+}
+
+#[test]
+fn config_reads_set_and_unset_keys() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+    repo.set_config("merge.conflictStyle", "zdiff3").unwrap();
+
+    assert_eq!(
+        repo.config("merge.conflictStyle").unwrap().as_deref(),
+        Some("zdiff3")
+    );
+    assert_eq!(repo.config("weavr.unset").unwrap(), None);
+}
+
+#[test]
+fn git_path_is_inside_the_git_directory() {
+    let dir = TempDir::new().expect("create temp dir");
+    let repo = GitRepo::init(dir.path()).expect("init");
+
+    let path = repo.git_path("weavr/rerere").unwrap();
+    assert!(path.ends_with(".git/weavr/rerere"));
+    assert!(path.starts_with(repo.root()));
+}
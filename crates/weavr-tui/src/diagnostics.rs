@@ -0,0 +1,250 @@
+//! Diagnostics from a check command, mapped back to hunks.
+//!
+//! Two output formats are understood: cargo/rustc JSON messages
+//! (`--message-format=json`) and the common `file:line[:col]: message`
+//! format used by most compilers and linters.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use weavr_core::HunkId;
+
+/// A problem reported by the check command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Line in the checked file (1-based).
+    pub line: usize,
+    /// Severity as reported, e.g. `error` or `warning`.
+    pub level: String,
+    /// The diagnostic message.
+    pub message: String,
+}
+
+/// One line of check output, interpreted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ParsedLine {
+    /// Lines to show in the output panel.
+    pub display: Vec<String>,
+    /// Diagnostics found on the line, with the file they refer to.
+    pub diagnostics: Vec<(PathBuf, Diagnostic)>,
+}
+
+/// Interprets one line of check output.
+#[must_use]
+pub fn parse_line(line: &str) -> ParsedLine {
+    if line.trim_start().starts_with('{') {
+        if let Ok(value) = serde_json::from_str::<Value>(line) {
+            return parse_json(&value);
+        }
+    }
+
+    ParsedLine {
+        display: vec![line.to_string()],
+        diagnostics: parse_location(line).into_iter().collect(),
+    }
+}
+
+/// Returns true if a path reported by a tool names `target`.
+///
+/// Tools usually report paths relative to their project root, which may
+/// differ from the working directory, so relative paths match by suffix.
+#[must_use]
+pub fn refers_to(reported: &Path, target: &Path) -> bool {
+    if reported.is_absolute() {
+        return std::fs::canonicalize(reported).map_or(reported == target, |p| p == target);
+    }
+    let reported: PathBuf = reported
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .collect();
+    !reported.as_os_str().is_empty() && target.ends_with(reported)
+}
+
+/// Groups diagnostics by the hunk whose lines they fall in.
+///
+/// `ranges` are the 0-based line ranges of each hunk in the checked content.
+/// Returns the grouped diagnostics and the number outside any hunk.
+#[must_use]
+pub fn map_to_hunks(
+    diagnostics: &[Diagnostic],
+    ranges: &[(HunkId, Range<usize>)],
+) -> (HashMap<HunkId, Vec<Diagnostic>>, usize) {
+    let mut by_hunk: HashMap<HunkId, Vec<Diagnostic>> = HashMap::new();
+    let mut outside = 0;
+    for diagnostic in diagnostics {
+        let index = diagnostic.line.saturating_sub(1);
+        match ranges.iter().find(|(_, range)| range.contains(&index)) {
+            Some((hunk_id, _)) => by_hunk
+                .entry(*hunk_id)
+                .or_default()
+                .push(diagnostic.clone()),
+            None => outside += 1,
+        }
+    }
+    (by_hunk, outside)
+}
+
+/// Interprets a cargo or rustc JSON message.
+fn parse_json(value: &Value) -> ParsedLine {
+    // cargo wraps the rustc diagnostic; rustc alone emits it directly
+    let message = match value.get("reason").and_then(Value::as_str) {
+        Some("compiler-message") => value.get("message"),
+        Some(_) => None,
+        None => value.get("spans").map(|_| value),
+    };
+    let Some(message) = message else {
+        return ParsedLine::default();
+    };
+
+    let level = message
+        .get("level")
+        .and_then(Value::as_str)
+        .unwrap_or("error");
+    let text = message
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    let display = match message.get("rendered").and_then(Value::as_str) {
+        Some(rendered) => rendered.lines().map(str::to_string).collect(),
+        None => vec![format!("{level}: {text}")],
+    };
+
+    let diagnostics = message
+        .get("spans")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|span| span.get("is_primary").and_then(Value::as_bool) == Some(true))
+        .filter_map(|span| {
+            let file = span.get("file_name")?.as_str()?;
+            let line = usize::try_from(span.get("line_start")?.as_u64()?).ok()?;
+            Some((
+                PathBuf::from(file),
+                Diagnostic {
+                    line,
+                    level: level.to_string(),
+                    message: text.to_string(),
+                },
+            ))
+        })
+        .collect();
+
+    ParsedLine {
+        display,
+        diagnostics,
+    }
+}
+
+/// Parses `file:line[:col]: [level:] message`.
+fn parse_location(line: &str) -> Option<(PathBuf, Diagnostic)> {
+    let (file, rest) = line.split_once(':')?;
+    if file.is_empty() || file.contains(char::is_whitespace) {
+        return None;
+    }
+    let (line_no, rest) = rest.split_once(':')?;
+    let line_no: usize = line_no.parse().ok()?;
+    let rest = match rest.split_once(':') {
+        Some((col, message)) if !col.is_empty() && col.bytes().all(|b| b.is_ascii_digit()) => {
+            message
+        }
+        _ => rest,
+    };
+
+    let rest = rest.trim();
+    let (level, message) = match rest.split_once(':') {
+        Some((level, message)) if matches!(level.trim(), "error" | "warning" | "note") => {
+            (level.trim(), message.trim())
+        }
+        _ => ("error", rest),
+    };
+
+    Some((
+        PathBuf::from(file),
+        Diagnostic {
+            line: line_no,
+            level: level.to_string(),
+            message: message.to_string(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(line: usize, message: &str) -> Diagnostic {
+        Diagnostic {
+            line,
+            level: "error".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_cargo_json_messages() {
+        let line = r#"{"reason":"compiler-message","message":{"level":"error","message":"cannot find value `x`","rendered":"error: cannot find value `x`\n --> src/lib.rs:3:5\n","spans":[{"file_name":"src/lib.rs","line_start":3,"is_primary":true},{"file_name":"src/other.rs","line_start":9,"is_primary":false}]}}"#;
+        let parsed = parse_line(line);
+        assert_eq!(
+            parsed.display,
+            vec!["error: cannot find value `x`", " --> src/lib.rs:3:5"]
+        );
+        assert_eq!(
+            parsed.diagnostics,
+            vec![(
+                PathBuf::from("src/lib.rs"),
+                diagnostic(3, "cannot find value `x`")
+            )]
+        );
+
+        // Build progress is neither shown nor a diagnostic
+        let artifact = parse_line(r#"{"reason":"compiler-artifact","target":{}}"#);
+        assert_eq!(artifact, ParsedLine::default());
+    }
+
+    #[test]
+    fn parses_file_line_col_messages() {
+        let parsed = parse_line("src/app.ts:12:4: error: Type 'string' is not assignable");
+        assert_eq!(
+            parsed.display,
+            vec!["src/app.ts:12:4: error: Type 'string' is not assignable"]
+        );
+        assert_eq!(
+            parsed.diagnostics,
+            vec![(
+                PathBuf::from("src/app.ts"),
+                diagnostic(12, "Type 'string' is not assignable")
+            )]
+        );
+
+        let (_, warning) = parse_location("main.c:7: warning: unused variable").unwrap();
+        assert_eq!(warning.line, 7);
+        assert_eq!(warning.level, "warning");
+
+        assert!(parse_line("Compiling foo v0.1.0").diagnostics.is_empty());
+        assert!(parse_line("  --> src/lib.rs:3:5").diagnostics.is_empty());
+    }
+
+    #[test]
+    fn relative_paths_match_by_suffix() {
+        let target = Path::new("/repo/crates/foo/src/lib.rs");
+        assert!(refers_to(Path::new("src/lib.rs"), target));
+        assert!(refers_to(Path::new("./crates/foo/src/lib.rs"), target));
+        assert!(!refers_to(Path::new("src/main.rs"), target));
+        assert!(!refers_to(Path::new("b/src/lib.rs"), target));
+    }
+
+    #[test]
+    fn diagnostics_map_to_containing_hunk() {
+        let ranges = vec![(HunkId(0), 1..3), (HunkId(1), 5..10)];
+        let (by_hunk, outside) = map_to_hunks(
+            &[diagnostic(2, "a"), diagnostic(7, "b"), diagnostic(4, "c")],
+            &ranges,
+        );
+        assert_eq!(by_hunk[&HunkId(0)], vec![diagnostic(2, "a")]);
+        assert_eq!(by_hunk[&HunkId(1)], vec![diagnostic(7, "b")]);
+        assert_eq!(outside, 1);
+    }
+}
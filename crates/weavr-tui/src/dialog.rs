@@ -3,10 +3,14 @@
 //! This module handles:
 //! - Help dialog
 //! - `AcceptBoth` options dialog
+//! - Pre-merge comparison overlay
+//! - Final diff confirmed before the result is written
 
-use weavr_core::{AcceptBothOptions, BothOrder, Resolution};
+use weavr_core::{AcceptBothOptions, BothOrder};
 
-use crate::input::{AcceptBothOptionsState, Dialog, InputMode};
+use crate::input::{
+    AcceptBothOptionsState, ConfirmWriteState, Dialog, HeadCompareState, InputMode, OverviewState,
+};
 use crate::resolution;
 use crate::App;
 
@@ -62,7 +66,56 @@ pub fn confirm_accept_both(app: &mut App) {
     close_dialog(app);
 
     // Apply resolution with extracted options
+    let language = app.language.clone();
     resolution::apply_resolution(app, "Accept both", |hunk| {
-        Resolution::accept_both(hunk, &options)
+        language.accept_both(hunk, &options)
     });
 }
+
+/// Shows the diff between the pre-merge version and the current result.
+pub fn show_head_compare(app: &mut App) {
+    if app.head_version.is_none() {
+        app.set_status_message("Pre-merge version not available");
+        return;
+    }
+    app.active_dialog = Some(Dialog::HeadCompare(HeadCompareState::default()));
+    app.input_mode = InputMode::Dialog;
+}
+
+/// Scrolls the pre-merge comparison by `delta` lines.
+pub fn scroll_head_compare(app: &mut App, delta: i16) {
+    if let Some(Dialog::HeadCompare(ref mut state)) = app.active_dialog {
+        state.scroll = state.scroll.saturating_add_signed(delta);
+    }
+}
+
+/// Shows the diff from the conflicted file to the result, to confirm before
+/// it is written.
+pub fn show_confirm_write(app: &mut App) {
+    app.active_dialog = Some(Dialog::ConfirmWrite(ConfirmWriteState::default()));
+    app.input_mode = InputMode::Dialog;
+}
+
+/// Scrolls the final diff by `delta` lines.
+pub fn scroll_confirm_write(app: &mut App, delta: i16) {
+    if let Some(Dialog::ConfirmWrite(ref mut state)) = app.active_dialog {
+        state.scroll = state.scroll.saturating_add_signed(delta);
+    }
+}
+
+/// Shows the conflict heatmap of the whole merge.
+pub fn show_overview(app: &mut App) {
+    if app.overview.is_none() {
+        app.set_status_message("Overview not available");
+        return;
+    }
+    app.active_dialog = Some(Dialog::Overview(OverviewState::default()));
+    app.input_mode = InputMode::Dialog;
+}
+
+/// Scrolls the conflict heatmap by `delta` lines.
+pub fn scroll_overview(app: &mut App, delta: i16) {
+    if let Some(Dialog::Overview(ref mut state)) = app.active_dialog {
+        state.scroll = state.scroll.saturating_add_signed(delta);
+    }
+}
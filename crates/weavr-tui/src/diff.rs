@@ -3,7 +3,17 @@
 //! This module provides line-level and word-level diff computation
 //! for highlighting changes between conflict sides in the TUI.
 
-use similar::{ChangeTag, TextDiff};
+use std::time::{Duration, Instant};
+
+use similar::{capture_diff_slices, Algorithm, ChangeTag, DiffOp, TextDiff};
+
+/// Lines longer than this many bytes, such as minified code or JSON blobs,
+/// are diffed in chunks.
+pub const LONG_LINE: usize = 1000;
+
+/// Longest a word diff of a long line may take before the rest of it is
+/// treated as changed.
+const WORD_DIFF_DEADLINE: Duration = Duration::from_millis(50);
 
 /// Represents a line with diff information for rendering.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -95,11 +105,65 @@ pub fn compute_line_diffs(left: &str, right: &str) -> LineDiffs {
     result
 }
 
+/// Computes a unified line diff showing only changed regions.
+///
+/// Each region keeps up to `context` unchanged lines around it. Regions
+/// are separated by a `⋯` line tagged `Equal`. Returns an empty list when
+/// the texts have the same lines.
+#[must_use]
+pub fn compute_unified_lines(old: &str, new: &str, context: usize) -> Vec<DiffLine> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+
+    for (i, group) in diff.grouped_ops(context).iter().enumerate() {
+        if i > 0 {
+            lines.push(DiffLine::new("⋯", ChangeTag::Equal));
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let text = change.value().trim_end_matches('\n');
+                lines.push(DiffLine::new(text, change.tag()));
+            }
+        }
+    }
+
+    lines
+}
+
+/// Pairs the changed lines of `left` and `right` that replace each other,
+/// as indices into the lines of each side.
+///
+/// The n-th line of a changed run on the left is paired with the n-th line
+/// of the run replacing it on the right.
+#[must_use]
+pub fn changed_line_pairs(left: &str, right: &str) -> Vec<(usize, usize)> {
+    let diff = TextDiff::from_lines(left, right);
+    let mut pairs = Vec::new();
+    for op in diff.ops() {
+        if let DiffOp::Replace {
+            old_index,
+            old_len,
+            new_index,
+            new_len,
+        } = *op
+        {
+            pairs.extend((0..old_len.min(new_len)).map(|i| (old_index + i, new_index + i)));
+        }
+    }
+    pairs
+}
+
 /// Computes word-level diffs between two lines.
 ///
-/// Useful for highlighting specific changes within modified lines.
+/// Useful for highlighting specific changes within modified lines. Long
+/// lines are split into chunks at separators such as `,` and `;`, the
+/// chunks are diffed as a whole, and only the chunks that changed are
+/// diffed by words.
 #[must_use]
 pub fn compute_word_diffs(old_line: &str, new_line: &str) -> Vec<WordChange> {
+    if old_line.len() > LONG_LINE || new_line.len() > LONG_LINE {
+        return compute_chunked_word_diffs(old_line, new_line);
+    }
     let diff = TextDiff::from_words(old_line, new_line);
 
     diff.iter_all_changes()
@@ -107,16 +171,59 @@ pub fn compute_word_diffs(old_line: &str, new_line: &str) -> Vec<WordChange> {
         .collect()
 }
 
+/// Diffs long lines chunk by chunk, bounding the time spent on words.
+fn compute_chunked_word_diffs(old_line: &str, new_line: &str) -> Vec<WordChange> {
+    let old_chunks = chunks(old_line);
+    let new_chunks = chunks(new_line);
+    let deadline = Instant::now() + WORD_DIFF_DEADLINE;
+    let mut changes = Vec::new();
+
+    for op in capture_diff_slices(Algorithm::Myers, &old_chunks, &new_chunks) {
+        let (_, old_range, new_range) = op.as_tag_tuple();
+        let old_text = old_chunks[old_range].concat();
+        let new_text = new_chunks[new_range].concat();
+        match op {
+            DiffOp::Equal { .. } => changes.push(WordChange::new(old_text, ChangeTag::Equal)),
+            DiffOp::Delete { .. } => changes.push(WordChange::new(old_text, ChangeTag::Delete)),
+            DiffOp::Insert { .. } => changes.push(WordChange::new(new_text, ChangeTag::Insert)),
+            DiffOp::Replace { .. } => {
+                let diff = TextDiff::configure()
+                    .deadline(deadline)
+                    .diff_words(old_text.as_str(), new_text.as_str());
+                changes.extend(
+                    diff.iter_all_changes()
+                        .map(|change| WordChange::new(change.value(), change.tag())),
+                );
+            }
+        }
+    }
+    changes
+}
+
+/// Splits a line after each separator, keeping the separators.
+fn chunks(line: &str) -> Vec<&str> {
+    line.split_inclusive([',', ';', '{', '}', '[', ']'])
+        .collect()
+}
+
 /// Configuration for diff display behavior.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DiffConfig {
     /// Enable word-level diff highlighting within changed lines.
     pub word_diff: bool,
+    /// Show the sides of the current hunk pretty-printed.
+    pub pretty_sides: bool,
+    /// Unchanged lines shown around each hunk, or `None` for whole files.
+    pub context_lines: Option<usize>,
 }
 
 impl Default for DiffConfig {
     fn default() -> Self {
-        Self { word_diff: true }
+        Self {
+            word_diff: true,
+            pretty_sides: false,
+            context_lines: None,
+        }
     }
 }
 
@@ -227,9 +334,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn word_diff_of_long_lines_isolates_the_changed_chunk() {
+        let items: Vec<String> = (0..200).map(|i| format!("\"key{i}\":{i}")).collect();
+        let old = format!("{{{}}}", items.join(","));
+        let new = old.replace("\"key150\":150", "\"key150\":151");
+        assert!(old.len() > LONG_LINE);
+
+        let changes = compute_word_diffs(&old, &new);
+        let side = |skip: ChangeTag| -> String {
+            changes
+                .iter()
+                .filter(|c| c.tag != skip)
+                .map(|c| c.text.as_str())
+                .collect()
+        };
+        assert_eq!(side(ChangeTag::Insert), old);
+        assert_eq!(side(ChangeTag::Delete), new);
+        let edits: Vec<&str> = changes
+            .iter()
+            .filter(|c| c.tag != ChangeTag::Equal)
+            .map(|c| c.text.as_str())
+            .collect();
+        assert_eq!(edits, vec!["\"key150\":150,", "\"key150\":151,"]);
+    }
+
+    #[test]
+    fn changed_lines_pair_up_by_position() {
+        let left = "a\nb\nc\nd\n";
+        let right = "a\nB\nC\nx\nd\n";
+        assert_eq!(changed_line_pairs(left, right), vec![(1, 1), (2, 2)]);
+        assert!(changed_line_pairs("a\n", "a\nb\n").is_empty());
+    }
+
     #[test]
     fn diff_config_default() {
         let config = DiffConfig::default();
         assert!(config.word_diff);
+        assert!(!config.pretty_sides);
+    }
+
+    #[test]
+    fn unified_lines_show_changes_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nB\nc\nd\ne\nf\nG\n";
+
+        let lines = compute_unified_lines(old, new, 1);
+        let rendered: Vec<String> = lines
+            .iter()
+            .map(|l| match l.tag {
+                ChangeTag::Delete => format!("-{}", l.text),
+                ChangeTag::Insert => format!("+{}", l.text),
+                ChangeTag::Equal => format!(" {}", l.text),
+            })
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![" a", "-b", "+B", " c", " ⋯", " f", "-g", "+G"]
+        );
+
+        assert!(compute_unified_lines(old, old, 3).is_empty());
     }
 }
@@ -0,0 +1,212 @@
+//! External diff tool integration.
+//!
+//! `E` hands the focused hunk to a configured tool such as meld, difft, or
+//! Beyond Compare. The hunk's sides are written to temporary files and the
+//! command line is filled in the way `git mergetool` fills in its own:
+//!
+//! - `$LOCAL`: our side
+//! - `$REMOTE`: their side
+//! - `$BASE`: the common ancestor (empty when the conflict has none)
+//! - `$MERGED`: the hunk as it stands, with conflict markers if unresolved
+//!
+//! A command without placeholders gets `$LOCAL $REMOTE` appended. If the
+//! tool exits successfully having changed `$MERGED`, its content becomes the
+//! hunk's resolution; viewers that never write it leave the hunk alone.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use weavr_core::{HunkId, Resolution};
+
+use crate::resolution;
+use crate::App;
+
+/// A hunk waiting to be opened in the diff tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffToolRequest {
+    /// The hunk being resolved.
+    pub hunk_id: HunkId,
+    /// Our side.
+    pub local: String,
+    /// Their side.
+    pub remote: String,
+    /// The base, if the conflict has one.
+    pub base: Option<String>,
+    /// The hunk as it stands.
+    pub merged: String,
+}
+
+/// Temporary files handed to the tool.
+struct Files {
+    local: PathBuf,
+    remote: PathBuf,
+    base: PathBuf,
+    merged: PathBuf,
+}
+
+/// Prepares the focused hunk for the diff tool and sets pending state.
+/// Returns true if the tool should be launched.
+pub fn prepare_diff_tool(app: &mut App) -> bool {
+    if app.diff_tool.is_none() {
+        app.set_status_message("No diff tool configured");
+        return false;
+    }
+    let Some(session) = app.session.as_ref() else {
+        app.set_status_message("No hunk to open");
+        return false;
+    };
+    let Some(hunk) = session.hunks().get(app.current_hunk_index) else {
+        app.set_status_message("No hunk to open");
+        return false;
+    };
+    let merged = crate::editor::current_hunk_content(session, hunk);
+    app.diff_tool_pending = Some(DiffToolRequest {
+        hunk_id: hunk.id,
+        local: hunk.left.text.clone(),
+        remote: hunk.right.text.clone(),
+        base: hunk.base.as_ref().map(|base| base.text.clone()),
+        merged,
+    });
+    true
+}
+
+/// Takes the pending request, clearing the pending state.
+pub fn take_diff_tool_pending(app: &mut App) -> Option<DiffToolRequest> {
+    app.diff_tool_pending.take()
+}
+
+/// Runs `command` on the request's files.
+///
+/// Returns the merged content if the tool exited successfully and changed
+/// it, `None` otherwise.
+///
+/// # Errors
+///
+/// Returns an error if the temporary files cannot be written or read, or the
+/// tool cannot be started.
+pub fn run_diff_tool(command: &str, request: &DiffToolRequest) -> io::Result<Option<String>> {
+    let dir = tempfile::tempdir()?;
+    let files = Files {
+        local: dir.path().join("LOCAL"),
+        remote: dir.path().join("REMOTE"),
+        base: dir.path().join("BASE"),
+        merged: dir.path().join("MERGED"),
+    };
+    std::fs::write(&files.local, &request.local)?;
+    std::fs::write(&files.remote, &request.remote)?;
+    std::fs::write(&files.base, request.base.as_deref().unwrap_or_default())?;
+    std::fs::write(&files.merged, &request.merged)?;
+
+    let status = shell(&command_line(command, &files)).status()?;
+    if !status.success() {
+        return Ok(None);
+    }
+    let merged = std::fs::read_to_string(&files.merged)?;
+    Ok((merged != request.merged).then_some(merged))
+}
+
+/// Applies the tool's merged output to the hunk it was opened for.
+pub fn apply_diff_tool_result(app: &mut App, hunk_id: HunkId, content: &str) {
+    let Some(index) = app
+        .session
+        .as_ref()
+        .and_then(|session| session.hunks().iter().position(|h| h.id == hunk_id))
+    else {
+        return;
+    };
+    app.current_hunk_index = index;
+    let owned = content.replace("\r\n", "\n");
+    resolution::apply_resolution(app, "Diff tool merge", |_hunk| {
+        Resolution::manual(owned.clone())
+    });
+}
+
+/// Fills in the placeholders of `command` with quoted paths.
+fn command_line(command: &str, files: &Files) -> String {
+    let placeholders = [
+        ("$LOCAL", &files.local),
+        ("$REMOTE", &files.remote),
+        ("$BASE", &files.base),
+        ("$MERGED", &files.merged),
+    ];
+    if !placeholders.iter().any(|(name, _)| command.contains(name)) {
+        return format!("{command} {} {}", quote(&files.local), quote(&files.remote));
+    }
+    placeholders
+        .iter()
+        .fold(command.to_string(), |line, (name, path)| {
+            line.replace(name, &quote(path))
+        })
+}
+
+/// Quotes a path for the platform's shell.
+fn quote(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if cfg!(windows) {
+        format!("\"{path}\"")
+    } else {
+        shell_words::quote(&path).into_owned()
+    }
+}
+
+/// Builds a command running `line` through the platform's shell.
+fn shell(line: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", line]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", line]);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Files {
+        Files {
+            local: PathBuf::from("/tmp/x/LOCAL"),
+            remote: PathBuf::from("/tmp/x/REMOTE"),
+            base: PathBuf::from("/tmp/x/BASE"),
+            merged: PathBuf::from("/tmp/my dir/MERGED"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn placeholders_are_replaced_with_quoted_paths() {
+        assert_eq!(
+            command_line("meld $LOCAL $MERGED $REMOTE --output $MERGED", &files()),
+            "meld /tmp/x/LOCAL '/tmp/my dir/MERGED' /tmp/x/REMOTE --output '/tmp/my dir/MERGED'"
+        );
+        assert_eq!(
+            command_line("difft", &files()),
+            "difft /tmp/x/LOCAL /tmp/x/REMOTE"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn merged_output_is_returned_only_when_changed() {
+        let request = DiffToolRequest {
+            hunk_id: HunkId(0),
+            local: "left\n".to_string(),
+            remote: "right\n".to_string(),
+            base: None,
+            merged: "<<<<<<< OURS\nleft\n=======\nright\n>>>>>>> THEIRS".to_string(),
+        };
+
+        let merged = run_diff_tool("cat $LOCAL $REMOTE > $MERGED", &request).unwrap();
+        assert_eq!(merged.as_deref(), Some("left\nright\n"));
+
+        assert_eq!(run_diff_tool("true", &request).unwrap(), None);
+        assert_eq!(
+            run_diff_tool("cp $BASE $MERGED; false", &request).unwrap(),
+            None
+        );
+    }
+}
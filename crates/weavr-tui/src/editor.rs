@@ -3,12 +3,56 @@
 //! This module handles:
 //! - Preparing content for external editing
 //! - Applying edited content as manual resolution
+//! - Accepting a side and editing it as one undoable step
 
-use weavr_core::Resolution;
+use weavr_core::{ConflictHunk, HunkId, MergeSession, Resolution};
 
 use crate::resolution;
 use crate::App;
 
+/// A side accepted for the pending edit. It stays out of the undo history
+/// until the editor closes, when it is recorded together with the edit, or
+/// alone if the edit is cancelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PendingAccept {
+    /// The hunk the side was accepted for.
+    pub hunk_id: HunkId,
+    /// Whether their side was accepted rather than ours.
+    pub right: bool,
+    /// The hunk's resolution before the accept.
+    pub previous: Option<Resolution>,
+}
+
+impl PendingAccept {
+    /// Returns the name of the accepted side.
+    fn side(&self) -> &'static str {
+        if self.right {
+            "theirs"
+        } else {
+            "ours"
+        }
+    }
+
+    /// Returns the resolution accepting the side.
+    fn accept(&self, hunk: &ConflictHunk) -> Resolution {
+        if self.right {
+            Resolution::accept_right(hunk)
+        } else {
+            Resolution::accept_left(hunk)
+        }
+    }
+
+    /// Puts back the resolution from before the accept.
+    fn restore(&self, app: &mut App) {
+        if let Some(session) = app.session.as_mut() {
+            let _ = match &self.previous {
+                Some(resolution) => session.set_resolution(self.hunk_id, resolution.clone()),
+                None => session.clear_resolution(self.hunk_id),
+            };
+        }
+    }
+}
+
 /// Prepares content for external editor and sets pending state.
 /// Returns true if editor should be launched.
 pub fn prepare_editor(app: &mut App) -> bool {
@@ -21,32 +65,94 @@ pub fn prepare_editor(app: &mut App) -> bool {
     }
 }
 
+/// Accepts one side of the current hunk and opens it in the external editor,
+/// for resolutions that are mostly one side. Returns true if the editor
+/// should be launched.
+///
+/// If the editor is cancelled the side stays accepted; either way a single
+/// undo reverts the hunk to how it was before.
+pub fn accept_then_edit(app: &mut App, right: bool) -> bool {
+    let Some((pending, resolution)) = app.session.as_ref().and_then(|session| {
+        session.hunks().get(app.current_hunk_index).map(|hunk| {
+            let pending = PendingAccept {
+                hunk_id: hunk.id,
+                right,
+                previous: session.resolutions().get(&hunk.id).cloned(),
+            };
+            let resolution = pending.accept(hunk);
+            (pending, resolution)
+        })
+    }) else {
+        app.set_status_message("No hunk to edit");
+        return false;
+    };
+
+    let accepted = app
+        .session
+        .as_mut()
+        .is_some_and(|session| session.set_resolution(pending.hunk_id, resolution).is_ok());
+    if !accepted {
+        app.set_status_message("Failed to apply resolution");
+        return false;
+    }
+    app.editor_accept = Some(pending);
+    prepare_editor(app)
+}
+
 /// Takes the pending editor content, clearing the pending state.
 pub fn take_editor_pending(app: &mut App) -> Option<String> {
     app.editor_pending.take()
 }
 
 /// Applies content returned from the external editor as a manual resolution.
+///
+/// Editors on Windows may save with CRLF line endings; they are normalized
+/// here, and the session restores the file's own line endings on output.
 pub fn apply_editor_result(app: &mut App, content: &str) {
-    let owned = content.to_string();
-    resolution::apply_resolution(app, "Manual edit", |_hunk| {
-        Resolution::manual(owned.clone())
-    });
+    let owned = content.replace("\r\n", "\n");
+    let action = match app.editor_accept.take() {
+        Some(pending) => {
+            // Record the edit over what the accept replaced
+            pending.restore(app);
+            format!("Accept {} and edit", pending.side())
+        }
+        None => "Manual edit".to_string(),
+    };
+    resolution::apply_resolution(app, &action, |_hunk| Resolution::manual(owned.clone()));
+}
+
+/// Notes that the external editor was closed without saving; a side accepted
+/// for the edit is kept.
+pub fn cancel_editor(app: &mut App) {
+    let Some(pending) = app.editor_accept.take() else {
+        app.set_status_message("Editor cancelled");
+        return;
+    };
+    pending.restore(app);
+    let action = format!("Accept {}", pending.side());
+    resolution::apply_resolution(app, &action, |hunk| pending.accept(hunk));
+    app.set_status_message(&format!("Editor cancelled · kept {}", pending.side()));
 }
 
 /// Gets the content of the current hunk for editing.
 fn get_current_hunk_content(app: &App) -> Option<String> {
     app.session.as_ref().and_then(|session| {
-        session.hunks().get(app.current_hunk_index).map(|hunk| {
-            // If already resolved, use that; otherwise combine left/right
-            if let Some(resolution) = session.resolutions().get(&hunk.id) {
-                resolution.content.clone()
-            } else {
-                format!(
-                    "<<<<<<< OURS\n{}\n=======\n{}\n>>>>>>> THEIRS",
-                    hunk.left.text, hunk.right.text
-                )
-            }
-        })
+        session
+            .hunks()
+            .get(app.current_hunk_index)
+            .map(|hunk| current_hunk_content(session, hunk))
     })
 }
+
+/// Returns a hunk's resolution if it has one, otherwise both sides between
+/// conflict markers.
+pub(crate) fn current_hunk_content(session: &MergeSession, hunk: &ConflictHunk) -> String {
+    if let Some(resolution) = session.resolutions().get(&hunk.id) {
+        resolution.content.clone()
+    } else {
+        format!(
+            "<<<<<<< OURS\n{}\n=======\n{}\n>>>>>>> THEIRS",
+            hunk.left.text, hunk.right.text
+        )
+    }
+}
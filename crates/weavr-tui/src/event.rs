@@ -7,7 +7,7 @@ use std::time::Duration;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
 use crate::input::{Dialog, InputMode};
-use crate::{App, KEY_SEQUENCE_TIMEOUT};
+use crate::{App, KEY_SEQUENCE_TIMEOUT, READ_ONLY_MESSAGE};
 
 /// Polls for an event with the given timeout.
 ///
@@ -28,6 +28,7 @@ pub fn poll_event(timeout: Duration) -> std::io::Result<Option<Event>> {
 pub fn handle_event(app: &mut App, event: &Event) {
     if let Event::Key(key) = event {
         handle_key_event(app, *key);
+        crate::guide::advance(app);
     }
     // Resize and other events are handled automatically by ratatui on next draw
 }
@@ -66,9 +67,14 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
     // Clear pending key for any other keypress
     app.key_sequence.clear();
 
+    if app.is_read_only() && is_editing_key(key) {
+        app.set_status_message(READ_ONLY_MESSAGE);
+        return;
+    }
+
     match key.code {
         // Quit
-        KeyCode::Char('q') => app.quit(),
+        KeyCode::Char('q') => app.request_quit(),
 
         // Command mode
         KeyCode::Char(':') => app.enter_command_mode(),
@@ -105,17 +111,45 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
         }
         KeyCode::PageDown => app.scroll_down(20),
         KeyCode::PageUp => app.scroll_up(20),
+        KeyCode::Char('h') | KeyCode::Left => app.scroll_left(20),
+        KeyCode::Char('l') | KeyCode::Right => app.scroll_right(20),
+        KeyCode::Char('p') => app.toggle_pretty_sides(),
+        KeyCode::Char('m') => app.toggle_base_pane(),
+        KeyCode::Char('+') => app.more_context(),
+        KeyCode::Char('-') => app.less_context(),
 
         // Resolution
+        KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.accept_then_edit(false);
+        }
+        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.accept_then_edit(true);
+        }
         KeyCode::Char('o') => app.resolve_left(), // 'o' for ours
         KeyCode::Char('t') => app.resolve_right(), // 't' for theirs
+        KeyCode::Char('c') => app.resolve_base(), // 'c' for common ancestor
         KeyCode::Char('b') => app.resolve_both(),
         KeyCode::Char('B') => app.show_accept_both_dialog(), // Shift-B for options
+        KeyCode::Char('a') => app.accept_proposal(),
+        KeyCode::Char('A') => app.apply_to_similar(),
+        KeyCode::Char('H') => app.show_head_compare(),
+        KeyCode::Char('O') => app.show_overview(),
+        KeyCode::Char('R') => app.show_revision_picker(),
+        KeyCode::Char('s') => app.request_suggestion(),
+        KeyCode::Char('T') => app.run_tests(),
+        KeyCode::Char('C') => app.run_check(),
+        KeyCode::Esc => app.close_test_panel(),
         KeyCode::Char('x') => app.clear_current_resolution(),
         KeyCode::Char('u') if !key.modifiers.contains(KeyModifiers::CONTROL) => app.undo(),
         KeyCode::Char('e') => {
             app.prepare_editor();
         }
+        KeyCode::Char('E') => {
+            app.prepare_diff_tool();
+        }
+
+        // Quick actions
+        KeyCode::Char(c @ '0'..='9') => app.run_quick_action(c),
 
         // Help
         KeyCode::Char('?') => app.show_help(),
@@ -124,6 +158,32 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
     }
 }
 
+/// Returns true if `key` changes a resolution or runs a command on the file
+/// in normal mode.
+fn is_editing_key(key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('u') => !key.modifiers.contains(KeyModifiers::CONTROL),
+        KeyCode::Char(
+            'o'
+            | 't'
+            | 'c'
+            | 'b'
+            | 'B'
+            | 'a'
+            | 'A'
+            | 'R'
+            | 's'
+            | 'T'
+            | 'C'
+            | 'x'
+            | 'e'
+            | 'E'
+            | '0'..='9',
+        ) => true,
+        _ => false,
+    }
+}
+
 /// Handles key events in command mode.
 fn handle_command_mode(app: &mut App, key: KeyEvent) {
     match key.code {
@@ -162,6 +222,51 @@ fn handle_dialog_mode(app: &mut App, key: KeyEvent) {
                 _ => {}
             }
         }
+        Some(Dialog::HeadCompare(_)) => match key.code {
+            KeyCode::Esc | KeyCode::Char('q' | 'H') => app.close_dialog(),
+            KeyCode::Char('j') | KeyCode::Down => app.scroll_head_compare(1),
+            KeyCode::Char('k') | KeyCode::Up => app.scroll_head_compare(-1),
+            KeyCode::PageDown => app.scroll_head_compare(10),
+            KeyCode::PageUp => app.scroll_head_compare(-10),
+            _ => {}
+        },
+        Some(Dialog::ConfirmWrite(_)) => match key.code {
+            KeyCode::Esc | KeyCode::Char('n' | 'q') => app.close_dialog(),
+            KeyCode::Enter | KeyCode::Char('y') => app.quit(),
+            KeyCode::Char('j') | KeyCode::Down => app.scroll_confirm_write(1),
+            KeyCode::Char('k') | KeyCode::Up => app.scroll_confirm_write(-1),
+            KeyCode::PageDown => app.scroll_confirm_write(10),
+            KeyCode::PageUp => app.scroll_confirm_write(-10),
+            _ => {}
+        },
+        Some(Dialog::Overview(_)) => match key.code {
+            KeyCode::Esc | KeyCode::Char('q' | 'O') => app.close_dialog(),
+            KeyCode::Char('j') | KeyCode::Down => app.scroll_overview(1),
+            KeyCode::Char('k') | KeyCode::Up => app.scroll_overview(-1),
+            KeyCode::PageDown => app.scroll_overview(10),
+            KeyCode::PageUp => app.scroll_overview(-10),
+            _ => {}
+        },
+        Some(Dialog::RevisionPicker(_)) => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => app.close_dialog(),
+            KeyCode::Char('j') | KeyCode::Down => app.select_revision(1),
+            KeyCode::Char('k') | KeyCode::Up => app.select_revision(-1),
+            KeyCode::Enter => app.confirm_revision(),
+            _ => {}
+        },
+        Some(Dialog::Suggestion(_)) => match key.code {
+            KeyCode::Esc | KeyCode::Char('n' | 'q') => app.reject_suggestion(),
+            KeyCode::Enter | KeyCode::Char('y') => app.accept_suggestion(),
+            KeyCode::Char('e') => {
+                app.edit_suggestion();
+            }
+            _ => {}
+        },
+        Some(Dialog::Substitute(_)) => match key.code {
+            KeyCode::Esc | KeyCode::Char('n' | 'q') => app.close_dialog(),
+            KeyCode::Enter | KeyCode::Char('y') => app.confirm_substitute(),
+            _ => {}
+        },
         None => {}
     }
 }
@@ -387,6 +492,63 @@ mod tests {
         assert_eq!(app.left_right_scroll(), 10);
     }
 
+    #[test]
+    fn h_and_l_scroll_sideways_within_the_longest_line() {
+        let line = "x".repeat(50);
+        let content = format!("<<<<<<< HEAD\n{line}\n=======\ny\n>>>>>>> b\n");
+        let mut app = App::new();
+        app.set_session(weavr_core::MergeSession::from_conflicted(&content, "f".into()).unwrap());
+
+        let key = |app: &mut App, c| {
+            handle_event(
+                app,
+                &Event::Key(make_key_event(KeyCode::Char(c), KeyModifiers::NONE)),
+            );
+        };
+        key(&mut app, 'l');
+        assert_eq!(app.horizontal_scroll(), 20);
+        key(&mut app, 'l');
+        key(&mut app, 'l');
+        assert_eq!(app.horizontal_scroll(), 49);
+        key(&mut app, 'h');
+        assert_eq!(app.horizontal_scroll(), 29);
+
+        key(&mut app, 'p');
+        assert!(app.pretty_sides());
+        assert_eq!(app.horizontal_scroll(), 0);
+    }
+
+    #[test]
+    fn plus_and_minus_change_context_lines() {
+        let content = "a\nb\nc\nd\n<<<<<<< HEAD\nx\n=======\ny\n>>>>>>> b\n";
+        let mut app = App::new();
+        app.set_session(weavr_core::MergeSession::from_conflicted(content, "f".into()).unwrap());
+
+        let key = |app: &mut App, c| {
+            handle_event(
+                app,
+                &Event::Key(make_key_event(KeyCode::Char(c), KeyModifiers::NONE)),
+            );
+        };
+        assert_eq!(app.context_lines(), None);
+        key(&mut app, '-');
+        assert_eq!(app.context_lines(), Some(10));
+        for _ in 0..9 {
+            key(&mut app, '-');
+        }
+        assert_eq!(app.context_lines(), Some(1));
+        let hunk = &app.session().unwrap().hunks()[0];
+        assert_eq!(hunk.context.before, vec!["d"]);
+
+        key(&mut app, '-');
+        key(&mut app, '-');
+        assert_eq!(app.context_lines(), Some(0));
+        for _ in 0..11 {
+            key(&mut app, '+');
+        }
+        assert_eq!(app.context_lines(), None);
+    }
+
     #[test]
     fn shift_b_opens_accept_both_dialog() {
         use crate::input::InputMode;
@@ -401,6 +563,98 @@ mod tests {
         assert!(app.active_dialog().is_some());
     }
 
+    #[test]
+    fn read_only_review_refuses_changes() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_read_only(true);
+
+        for c in ['o', 't', 'b', 'e'] {
+            let event = Event::Key(make_key_event(KeyCode::Char(c), KeyModifiers::NONE));
+            handle_event(&mut app, &event);
+        }
+        assert!(app.session().unwrap().resolutions().is_empty());
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some(READ_ONLY_MESSAGE)
+        );
+        assert!(app.take_editor_pending().is_none());
+
+        // Navigation still works, and :q quits despite the unresolved hunk
+        let event = Event::Key(make_key_event(KeyCode::Char('j'), KeyModifiers::NONE));
+        handle_event(&mut app, &event);
+        app.command_buffer = "q".to_string();
+        app.execute_command();
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn quitting_resolved_session_confirms_final_diff() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch\n";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_confirm_write(true);
+        let press = |app: &mut App, c: char| {
+            let event = Event::Key(make_key_event(KeyCode::Char(c), KeyModifiers::NONE));
+            handle_event(app, &event);
+        };
+
+        // Nothing is written while hunks are unresolved, so q just quits
+        press(&mut app, 'q');
+        assert!(app.should_quit());
+
+        app.should_quit = false;
+        press(&mut app, 'o');
+        press(&mut app, 'q');
+        assert!(!app.should_quit());
+        assert!(matches!(app.active_dialog(), Some(Dialog::ConfirmWrite(_))));
+
+        press(&mut app, 'n');
+        assert!(app.active_dialog().is_none());
+        assert!(!app.should_quit());
+
+        press(&mut app, 'q');
+        press(&mut app, 'y');
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn digit_keys_run_bound_quick_actions() {
+        use std::collections::BTreeMap;
+        use std::path::PathBuf;
+
+        let content =
+            "<<<<<<< HEAD\na\n=======\nb\n>>>>>>> x\nc\n<<<<<<< HEAD\nd\n=======\ne\n>>>>>>> x\n";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        let mut actions = BTreeMap::new();
+        actions.insert('1', "accept left + next unresolved".parse().unwrap());
+        app.set_quick_actions(actions);
+
+        let event = Event::Key(make_key_event(KeyCode::Char('1'), KeyModifiers::NONE));
+        handle_event(&mut app, &event);
+        assert_eq!(app.session().unwrap().resolutions().len(), 1);
+        assert_eq!(app.current_hunk_index(), 1);
+
+        let event = Event::Key(make_key_event(KeyCode::Char('2'), KeyModifiers::NONE));
+        handle_event(&mut app, &event);
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Nothing bound to 2")
+        );
+    }
+
     #[test]
     fn e_key_prepares_editor() {
         let mut app = App::new();
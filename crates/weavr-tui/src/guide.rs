@@ -0,0 +1,144 @@
+//! Step-by-step guidance for scripted sessions.
+//!
+//! A [`Guide`] shows one instruction at a time below the panes and moves on
+//! once the user has done what it asks. Each step verifies the action by
+//! inspecting the application state after every key press.
+
+use crate::App;
+
+/// A single instruction and the check that completes it.
+#[derive(Debug, Clone)]
+pub struct GuideStep {
+    /// Text shown to the user.
+    pub instruction: String,
+    /// Returns true once the user has carried out the instruction.
+    pub check: fn(&App) -> bool,
+}
+
+impl GuideStep {
+    /// Creates a step from an instruction and its completion check.
+    #[must_use]
+    pub fn new(instruction: impl Into<String>, check: fn(&App) -> bool) -> Self {
+        Self {
+            instruction: instruction.into(),
+            check,
+        }
+    }
+}
+
+/// An ordered list of steps and the user's progress through them.
+#[derive(Debug, Clone)]
+pub struct Guide {
+    steps: Vec<GuideStep>,
+    current: usize,
+}
+
+impl Guide {
+    /// Creates a guide starting at its first step.
+    #[must_use]
+    pub fn new(steps: Vec<GuideStep>) -> Self {
+        Self { steps, current: 0 }
+    }
+
+    /// Returns the step the user is working on, or `None` once all are done.
+    #[must_use]
+    pub fn current_step(&self) -> Option<&GuideStep> {
+        self.steps.get(self.current)
+    }
+
+    /// Returns the number of completed steps and the total.
+    #[must_use]
+    pub fn progress(&self) -> (usize, usize) {
+        (self.current, self.steps.len())
+    }
+
+    /// Returns true once every step has been completed.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+}
+
+/// Completes every step whose check now passes, in order.
+///
+/// Called after each event so that an action can be verified as soon as
+/// it happens.
+pub fn advance(app: &mut App) {
+    let Some(guide) = app.guide.take() else {
+        return;
+    };
+
+    let mut guide = guide;
+    let before = guide.current;
+    while guide.current_step().is_some_and(|step| (step.check)(app)) {
+        guide.current += 1;
+    }
+
+    if guide.current > before {
+        let (done, total) = guide.progress();
+        if guide.is_complete() {
+            app.set_status_message("Tutorial complete!");
+        } else {
+            app.set_status_message(&format!("Step {done}/{total} done"));
+        }
+    }
+
+    app.guide = Some(guide);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps() -> Vec<GuideStep> {
+        vec![
+            GuideStep::new("Open help", |app| app.active_dialog().is_some()),
+            GuideStep::new("Quit", App::should_quit),
+        ]
+    }
+
+    #[test]
+    fn guide_starts_at_first_step() {
+        let guide = Guide::new(steps());
+        assert_eq!(guide.progress(), (0, 2));
+        assert_eq!(guide.current_step().unwrap().instruction, "Open help");
+        assert!(!guide.is_complete());
+    }
+
+    #[test]
+    fn advance_waits_for_check() {
+        let mut app = App::new();
+        app.set_guide(Guide::new(steps()));
+
+        advance(&mut app);
+        assert_eq!(app.guide().unwrap().progress(), (0, 2));
+
+        app.show_help();
+        advance(&mut app);
+        assert_eq!(app.guide().unwrap().progress(), (1, 2));
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Step 1/2 done")
+        );
+    }
+
+    #[test]
+    fn advance_completes_consecutive_steps() {
+        let mut app = App::new();
+        app.set_guide(Guide::new(steps()));
+        app.show_help();
+        app.quit();
+
+        advance(&mut app);
+        assert!(app.guide().unwrap().is_complete());
+        assert!(app.guide().unwrap().current_step().is_none());
+    }
+
+    #[test]
+    fn advance_without_guide_is_noop() {
+        let mut app = App::new();
+        advance(&mut app);
+        assert!(app.guide().is_none());
+        assert!(app.status_message().is_none());
+    }
+}
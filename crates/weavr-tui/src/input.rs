@@ -19,7 +19,7 @@ pub enum InputMode {
     Dialog,
 }
 
-use weavr_core::BothOrder;
+use weavr_core::{BothOrder, HunkId, Resolution};
 
 /// The type of dialog currently open.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,6 +28,91 @@ pub enum Dialog {
     Help,
     /// `AcceptBoth` options configuration dialog.
     AcceptBothOptions(AcceptBothOptionsState),
+    /// Preview of a `:s` substitution awaiting confirmation.
+    Substitute(SubstitutePreview),
+    /// Diff of the pre-merge version against the current result.
+    HeadCompare(HeadCompareState),
+    /// Diff of the conflicted file against the result, confirmed before the
+    /// result is written.
+    ConfirmWrite(ConfirmWriteState),
+    /// Conflict heatmap of the whole merge.
+    Overview(OverviewState),
+    /// Choice of revision to take the current hunk's right side from.
+    RevisionPicker(RevisionPicker),
+    /// A resolution suggested by the AI provider, awaiting a decision.
+    Suggestion(SuggestionState),
+}
+
+/// State for the pre-merge comparison overlay.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeadCompareState {
+    /// Lines scrolled past at the top.
+    pub scroll: u16,
+}
+
+/// State for the final diff shown before writing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfirmWriteState {
+    /// Lines scrolled past at the top.
+    pub scroll: u16,
+}
+
+/// State for the conflict heatmap overlay.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OverviewState {
+    /// Lines scrolled past at the top.
+    pub scroll: u16,
+}
+
+/// State for the AI suggestion dialog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestionState {
+    /// The hunk the suggestion is for.
+    pub hunk_id: HunkId,
+    /// The suggested resolution, with the provider's confidence as its note.
+    pub resolution: Resolution,
+}
+
+/// State for the revision picker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevisionPicker {
+    /// The hunk whose right side is being replaced.
+    pub hunk_id: HunkId,
+    /// Available choices; the first is the original right side.
+    pub choices: Vec<RevisionChoice>,
+    /// Index of the highlighted choice.
+    pub selected: usize,
+}
+
+/// One entry in the revision picker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevisionChoice {
+    /// Description of the revision.
+    pub label: String,
+    /// The hunk's region in that revision, if it could be located.
+    pub region: Option<String>,
+}
+
+/// A pending substitution and the changes it would make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstitutePreview {
+    /// The substitution being previewed.
+    pub substitution: Substitution,
+    /// Changed resolutions, in hunk order.
+    pub changes: Vec<SubstituteChange>,
+}
+
+/// The effect of a substitution on one hunk's resolution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstituteChange {
+    /// The hunk whose resolution changes.
+    pub hunk_id: HunkId,
+    /// Number of replacements made.
+    pub count: usize,
+    /// Changed lines as (before, after) pairs.
+    pub lines: Vec<(String, String)>,
+    /// The full resolution content after replacement.
+    pub content: String,
 }
 
 /// State for the `AcceptBoth` options dialog.
@@ -62,6 +147,13 @@ pub enum Command {
     WriteQuit,
     /// Force quit without saving (`:q!`).
     ForceQuit,
+    /// Attach a note to the current hunk (`:note <text>`); empty clears it.
+    Note(String),
+    /// Replace text in resolutions (`:s/old/new/` or `:%s/old/new/g`).
+    Substitute(Substitution),
+    /// Insert a resolution template (`:snippet <name> [key=value ...]`);
+    /// without a name, list the available templates.
+    Snippet(SnippetCall),
     /// Unknown or invalid command.
     Unknown(String),
 }
@@ -77,7 +169,18 @@ impl Command {
             "q" => Self::Quit,
             "wq" | "x" => Self::WriteQuit,
             "q!" => Self::ForceQuit,
-            other => Self::Unknown(other.to_string()),
+            "note" => Self::Note(String::new()),
+            other => {
+                if let Some(text) = other.strip_prefix("note ") {
+                    Self::Note(text.trim().to_string())
+                } else if let Some(call) = SnippetCall::parse(other) {
+                    Self::Snippet(call)
+                } else if let Some(substitution) = Substitution::parse(other) {
+                    Self::Substitute(substitution)
+                } else {
+                    Self::Unknown(other.to_string())
+                }
+            }
         }
     }
 
@@ -89,11 +192,133 @@ impl Command {
             Self::Quit => "quit",
             Self::WriteQuit => "write and quit",
             Self::ForceQuit => "force quit",
+            Self::Note(_) => "note",
+            Self::Substitute(_) => "substitute",
+            Self::Snippet(_) => "snippet",
             Self::Unknown(_) => "unknown command",
         }
     }
 }
 
+/// A parsed `:snippet` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetCall {
+    /// Name of the snippet; empty to list them.
+    pub name: String,
+    /// Placeholder values given as `key=value`.
+    pub args: Vec<(String, String)>,
+}
+
+impl SnippetCall {
+    /// Parses `snippet [name [key=value ...]]`.
+    ///
+    /// Values containing spaces can be quoted shell-style. Returns `None` if
+    /// the input is not a snippet command or an argument lacks a `=`.
+    #[must_use]
+    pub fn parse(input: &str) -> Option<Self> {
+        let rest = input.strip_prefix("snippet")?;
+        if !rest.is_empty() && !rest.starts_with(' ') {
+            return None;
+        }
+        let words = shell_words::split(rest).ok()?;
+        let Some((name, args)) = words.split_first() else {
+            return Some(Self {
+                name: String::new(),
+                args: Vec::new(),
+            });
+        };
+        let args = args
+            .iter()
+            .map(|arg| {
+                arg.split_once('=')
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+            })
+            .collect::<Option<_>>()?;
+        Some(Self {
+            name: name.clone(),
+            args,
+        })
+    }
+}
+
+/// A literal search-and-replace over resolution content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+    /// Text to search for.
+    pub pattern: String,
+    /// Text to put in its place.
+    pub replacement: String,
+    /// Apply to every resolved hunk (`%`) instead of the current one.
+    pub all_hunks: bool,
+    /// Replace every occurrence on a line (`g`) instead of the first.
+    pub global: bool,
+}
+
+impl Substitution {
+    /// Parses `s/old/new/[g]`, optionally prefixed with `%`.
+    ///
+    /// A `/` inside the pattern or replacement is written as `\/`. The
+    /// closing delimiter may be omitted. Returns `None` for anything else,
+    /// including an empty pattern.
+    #[must_use]
+    pub fn parse(input: &str) -> Option<Self> {
+        let (all_hunks, rest) = match input.strip_prefix('%') {
+            Some(rest) => (true, rest),
+            None => (false, input),
+        };
+        let rest = rest.strip_prefix("s/")?;
+
+        let mut parts = vec![String::new()];
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('/') => parts.last_mut()?.push('/'),
+                    Some(other) => {
+                        let part = parts.last_mut()?;
+                        part.push('\\');
+                        part.push(other);
+                    }
+                    None => parts.last_mut()?.push('\\'),
+                },
+                '/' => parts.push(String::new()),
+                c => parts.last_mut()?.push(c),
+            }
+        }
+
+        let (pattern, replacement, flags) = match parts.as_slice() {
+            [pattern] => (pattern, "", ""),
+            [pattern, replacement] => (pattern, replacement.as_str(), ""),
+            [pattern, replacement, flags] => (pattern, replacement.as_str(), flags.as_str()),
+            _ => return None,
+        };
+        if pattern.is_empty() || !flags.chars().all(|f| f == 'g') {
+            return None;
+        }
+
+        Some(Self {
+            pattern: pattern.clone(),
+            replacement: replacement.to_string(),
+            all_hunks,
+            global: flags.contains('g'),
+        })
+    }
+
+    /// Applies the substitution to one line, returning it and the number
+    /// of replacements made.
+    #[must_use]
+    pub fn apply_line(&self, line: &str) -> (String, usize) {
+        if self.global {
+            let count = line.matches(self.pattern.as_str()).count();
+            (line.replace(&self.pattern, &self.replacement), count)
+        } else if line.contains(self.pattern.as_str()) {
+            (line.replacen(&self.pattern, &self.replacement, 1), 1)
+        } else {
+            (line.to_string(), 0)
+        }
+    }
+}
+
 /// Tracks pending keys for multi-key sequence detection (e.g., 'gg').
 #[derive(Debug, Clone, Default)]
 pub struct KeySequence {
@@ -158,6 +383,85 @@ mod tests {
         assert_eq!(Command::parse("q!"), Command::ForceQuit);
     }
 
+    #[test]
+    fn parse_note() {
+        assert_eq!(
+            Command::parse("note kept theirs because of API change "),
+            Command::Note("kept theirs because of API change".to_string())
+        );
+        assert_eq!(Command::parse("note"), Command::Note(String::new()));
+        assert_eq!(
+            Command::parse("notes"),
+            Command::Unknown("notes".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_snippet() {
+        assert_eq!(
+            Command::parse("snippet flag name=\"new parser\" owner=core"),
+            Command::Snippet(SnippetCall {
+                name: "flag".to_string(),
+                args: vec![
+                    ("name".to_string(), "new parser".to_string()),
+                    ("owner".to_string(), "core".to_string()),
+                ],
+            })
+        );
+        assert_eq!(
+            Command::parse("snippet"),
+            Command::Snippet(SnippetCall {
+                name: String::new(),
+                args: Vec::new(),
+            })
+        );
+        assert_eq!(
+            Command::parse("snippet flag oops"),
+            Command::Unknown("snippet flag oops".to_string())
+        );
+        assert_eq!(
+            Command::parse("snippets"),
+            Command::Unknown("snippets".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_substitute() {
+        assert_eq!(
+            Command::parse("%s/old_name/new_name/g"),
+            Command::Substitute(Substitution {
+                pattern: "old_name".to_string(),
+                replacement: "new_name".to_string(),
+                all_hunks: true,
+                global: true,
+            })
+        );
+
+        let Command::Substitute(sub) = Command::parse("s/a\\/b/c") else {
+            panic!("expected substitute");
+        };
+        assert_eq!(sub.pattern, "a/b");
+        assert_eq!(sub.replacement, "c");
+        assert!(!sub.all_hunks && !sub.global);
+
+        assert!(matches!(Command::parse("s//x/"), Command::Unknown(_)));
+        assert!(matches!(Command::parse("s/a/b/x"), Command::Unknown(_)));
+        assert!(matches!(
+            Command::parse("s/a/b/g/extra"),
+            Command::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn substitution_apply_line() {
+        let sub = Substitution::parse("s/foo/bar/").unwrap();
+        assert_eq!(sub.apply_line("foo foo"), ("bar foo".to_string(), 1));
+        assert_eq!(sub.apply_line("baz"), ("baz".to_string(), 0));
+
+        let sub = Substitution::parse("s/foo/bar/g").unwrap();
+        assert_eq!(sub.apply_line("foo foo"), ("bar bar".to_string(), 2));
+    }
+
     #[test]
     fn parse_unknown() {
         assert_eq!(Command::parse("foo"), Command::Unknown("foo".to_string()));
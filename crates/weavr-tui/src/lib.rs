@@ -14,23 +14,49 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use weavr_core::{ConflictHunk, MergeSession};
+use weavr_ai::SuggestionProvider;
+
+use weavr_core::{
+    ConflictHunk, ConflictStyle, ConflictTree, HunkId, LanguageHeuristics, MergeSession,
+    PropagationMode, Segment, DEFAULT_CONTEXT_LINES,
+};
 
 /// Timeout for multi-key sequences like 'gg'.
 const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(500);
 
+/// Most context lines `-` starts from when showing whole files; `+` beyond
+/// this shows whole files again.
+const MAX_FOLDED_CONTEXT: usize = 10;
+
+/// Shown when an action is refused in a read-only review.
+const READ_ONLY_MESSAGE: &str = "Read-only review: nothing can be changed";
+
+pub mod diagnostics;
 pub mod dialog;
 pub mod diff;
+pub mod difftool;
 pub mod editor;
 pub mod event;
+pub mod guide;
 pub mod input;
 pub mod navigation;
+pub mod notify;
+pub mod pretty;
+pub mod quick;
 pub mod resolution;
+pub mod revision;
+pub mod snippet;
+pub mod substitute;
+pub mod suggest;
 pub mod theme;
 pub mod ui;
 pub mod undo;
+pub mod verify;
 
 use input::{Command, Dialog, InputMode, KeySequence};
 use undo::UndoStack;
@@ -40,12 +66,15 @@ use undo::UndoStack;
 pub struct LayoutConfig {
     /// Percentage of height for top row (left/right panes). Default: 60
     pub top_ratio_percent: u16,
+    /// Whether the top row shows the base between left and right.
+    pub show_base: bool,
 }
 
 impl Default for LayoutConfig {
     fn default() -> Self {
         Self {
             top_ratio_percent: 60,
+            show_base: false,
         }
     }
 }
@@ -68,6 +97,9 @@ pub enum FocusedPane {
 pub struct App {
     /// The active merge session.
     pub(crate) session: Option<MergeSession>,
+    /// Lines in each segment of the session that is clean text, counted
+    /// once so panes can step over the segments they don't show.
+    pub(crate) segment_lines: Vec<usize>,
     /// Whether the application should quit.
     pub(crate) should_quit: bool,
     /// Which pane has focus.
@@ -80,6 +112,8 @@ pub struct App {
     pub(crate) left_right_scroll: u16,
     /// Independent scroll offset for result pane.
     pub(crate) result_scroll: u16,
+    /// Columns scrolled past on the left, shared by all panes.
+    pub(crate) horizontal_scroll: usize,
     /// Layout configuration.
     pub(crate) layout_config: LayoutConfig,
     /// Tracker for multi-key sequences (e.g., 'gg').
@@ -96,8 +130,56 @@ pub struct App {
     pub(crate) active_dialog: Option<Dialog>,
     /// Content pending for external editor (Phase 7).
     pub(crate) editor_pending: Option<String>,
+    /// Side accepted for the pending edit, undone together with it.
+    pub(crate) editor_accept: Option<editor::PendingAccept>,
+    /// Conflict marker style of the file, shown in the title bar.
+    pub(crate) conflict_style: ConflictStyle,
     /// Configuration for diff highlighting.
     pub(crate) diff_config: diff::DiffConfig,
+    /// Step-by-step guidance, when running a scripted session.
+    pub(crate) guide: Option<guide::Guide>,
+    /// How resolutions carry over to hunks with identical content.
+    pub(crate) propagation: PropagationMode,
+    /// The file as our side had it before the merge, for comparison.
+    pub(crate) head_version: Option<String>,
+    /// Command that tests the merged result, run with `T`.
+    pub(crate) test_command: Option<String>,
+    /// The current or most recent test run.
+    pub(crate) test_run: Option<verify::TestRun>,
+    /// The merged content the last passing test run was given.
+    pub(crate) verified_content: Option<String>,
+    /// Command that checks the merged result, run with `C`.
+    pub(crate) check_command: Option<String>,
+    /// Alerts the user when a test or check run finishes.
+    pub(crate) notifier: notify::Notifier,
+    /// Diagnostics from the last check, by hunk, with the resolved content
+    /// they were reported for.
+    pub(crate) diagnostics: HashMap<HunkId, (Option<String>, Vec<diagnostics::Diagnostic>)>,
+    /// Conflict counts across every file in the merge, for the `O` overview.
+    pub(crate) overview: Option<ConflictTree>,
+    /// Earlier versions of the file to take a hunk's right side from.
+    pub(crate) alternate_revisions: Vec<revision::AlternateRevision>,
+    /// Hunks whose right side was taken from another revision.
+    pub(crate) replaced_right: HashMap<HunkId, revision::ReplacedRight>,
+    /// Resolution templates by name, inserted with `:snippet`.
+    pub(crate) snippets: BTreeMap<String, String>,
+    /// Heuristics for the file's language, used when accepting both sides.
+    pub(crate) language: LanguageHeuristics,
+    /// External diff tool command, opened on the focused hunk with `E`.
+    pub(crate) diff_tool: Option<String>,
+    /// Hunk pending for the external diff tool.
+    pub(crate) diff_tool_pending: Option<difftool::DiffToolRequest>,
+    /// Whether the session is only being reviewed; nothing can be changed.
+    pub(crate) read_only: bool,
+    /// Quick actions by digit key.
+    pub(crate) quick_actions: BTreeMap<char, quick::QuickAction>,
+    /// Whether quitting with every hunk resolved first shows the final diff
+    /// for confirmation, as the result is then written.
+    pub(crate) confirm_write: bool,
+    /// Provider asked for a suggestion with `s`.
+    pub(crate) suggestion_provider: Option<Arc<dyn SuggestionProvider>>,
+    /// Suggestion the provider is working on.
+    pub(crate) pending_suggestion: Option<suggest::PendingSuggestion>,
 }
 
 impl App {
@@ -106,12 +188,14 @@ impl App {
     pub fn new() -> Self {
         Self {
             session: None,
+            segment_lines: Vec::new(),
             should_quit: false,
             focused_pane: FocusedPane::default(),
             theme: Theme::from(ThemeName::default()),
             current_hunk_index: 0,
             left_right_scroll: 0,
             result_scroll: 0,
+            horizontal_scroll: 0,
             layout_config: LayoutConfig::default(),
             key_sequence: KeySequence::new(),
             status_message: None,
@@ -120,7 +204,30 @@ impl App {
             command_buffer: String::new(),
             active_dialog: None,
             editor_pending: None,
+            editor_accept: None,
+            conflict_style: ConflictStyle::default(),
             diff_config: diff::DiffConfig::default(),
+            guide: None,
+            propagation: PropagationMode::default(),
+            head_version: None,
+            test_command: None,
+            test_run: None,
+            verified_content: None,
+            check_command: None,
+            notifier: notify::Notifier::default(),
+            diagnostics: HashMap::new(),
+            overview: None,
+            alternate_revisions: Vec::new(),
+            replaced_right: HashMap::new(),
+            snippets: BTreeMap::new(),
+            language: LanguageHeuristics::default(),
+            diff_tool: None,
+            diff_tool_pending: None,
+            read_only: false,
+            confirm_write: false,
+            quick_actions: BTreeMap::new(),
+            suggestion_provider: None,
+            pending_suggestion: None,
         }
     }
 
@@ -129,12 +236,14 @@ impl App {
     pub fn with_theme(theme_name: ThemeName) -> Self {
         Self {
             session: None,
+            segment_lines: Vec::new(),
             should_quit: false,
             focused_pane: FocusedPane::default(),
             theme: Theme::from(theme_name),
             current_hunk_index: 0,
             left_right_scroll: 0,
             result_scroll: 0,
+            horizontal_scroll: 0,
             layout_config: LayoutConfig::default(),
             key_sequence: KeySequence::new(),
             status_message: None,
@@ -143,15 +252,269 @@ impl App {
             command_buffer: String::new(),
             active_dialog: None,
             editor_pending: None,
+            editor_accept: None,
+            conflict_style: ConflictStyle::default(),
             diff_config: diff::DiffConfig::default(),
+            guide: None,
+            propagation: PropagationMode::default(),
+            head_version: None,
+            test_command: None,
+            test_run: None,
+            verified_content: None,
+            check_command: None,
+            notifier: notify::Notifier::default(),
+            diagnostics: HashMap::new(),
+            overview: None,
+            alternate_revisions: Vec::new(),
+            replaced_right: HashMap::new(),
+            snippets: BTreeMap::new(),
+            language: LanguageHeuristics::default(),
+            diff_tool: None,
+            diff_tool_pending: None,
+            read_only: false,
+            confirm_write: false,
+            quick_actions: BTreeMap::new(),
+            suggestion_provider: None,
+            pending_suggestion: None,
         }
     }
 
     /// Sets the merge session to display.
-    pub fn set_session(&mut self, session: MergeSession) {
+    pub fn set_session(&mut self, mut session: MergeSession) {
+        if let Some(count) = self.diff_config.context_lines {
+            session.set_context_lines(count);
+        }
+        // A three-way merge has a base worth seeing from the start
+        self.layout_config.show_base = session.input().base.is_some();
+        self.conflict_style = session.conflict_style();
+        self.segment_lines = session
+            .segments()
+            .iter()
+            .map(|segment| match segment {
+                Segment::Clean(range) => session.conflicted()[range.clone()].lines().count(),
+                Segment::Conflict(_) => 0,
+            })
+            .collect();
         self.session = Some(session);
     }
 
+    /// Returns the number of lines in each clean segment of the session.
+    #[must_use]
+    pub(crate) fn segment_lines(&self) -> &[usize] {
+        &self.segment_lines
+    }
+
+    /// Sets the conflict marker style shown in the title bar.
+    ///
+    /// The style is detected from the file when the session is set; zdiff3
+    /// looks like diff3 there, so callers that know it was configured can
+    /// say so.
+    pub fn set_conflict_style(&mut self, style: ConflictStyle) {
+        self.conflict_style = style;
+    }
+
+    /// Returns the conflict marker style of the file.
+    #[must_use]
+    pub fn conflict_style(&self) -> ConflictStyle {
+        self.conflict_style
+    }
+
+    /// Sets how resolutions carry over to hunks with identical content.
+    pub fn set_propagation(&mut self, mode: PropagationMode) {
+        self.propagation = mode;
+    }
+
+    /// Sets the pre-merge version of the file, enabling the `H` comparison.
+    pub fn set_head_version(&mut self, content: String) {
+        self.head_version = Some(content);
+    }
+
+    /// Returns the pre-merge version of the file, if known.
+    #[must_use]
+    pub fn head_version(&self) -> Option<&str> {
+        self.head_version.as_deref()
+    }
+
+    /// Sets the command that tests the merged result.
+    pub fn set_test_command(&mut self, command: String) {
+        self.test_command = Some(command);
+    }
+
+    /// Sets how the user is alerted when a test or check run finishes.
+    pub fn set_notifier(&mut self, notifier: notify::Notifier) {
+        self.notifier = notifier;
+    }
+
+    /// Returns the current or most recent test run, if any.
+    #[must_use]
+    pub fn test_run(&self) -> Option<&verify::TestRun> {
+        self.test_run.as_ref()
+    }
+
+    /// Returns true if the current resolutions passed the test command.
+    #[must_use]
+    pub fn is_verified(&self) -> bool {
+        match (&self.session, &self.verified_content) {
+            (Some(session), Some(content)) => session.preview() == *content,
+            _ => false,
+        }
+    }
+
+    /// Sets the command that checks the merged result.
+    pub fn set_check_command(&mut self, command: String) {
+        self.check_command = Some(command);
+    }
+
+    /// Sets the external diff tool command; see [`difftool`] for the
+    /// placeholders it may use.
+    pub fn set_diff_tool(&mut self, command: String) {
+        self.diff_tool = Some(command);
+    }
+
+    /// Runs the test command on the current result.
+    pub fn run_tests(&mut self) {
+        verify::start(self, verify::RunKind::Test);
+    }
+
+    /// Runs the check command on the current result.
+    pub fn run_check(&mut self) {
+        verify::start(self, verify::RunKind::Check);
+    }
+
+    /// Returns the diagnostics from the last check that fall in a hunk.
+    ///
+    /// Diagnostics are dropped once the hunk's resolution has changed.
+    #[must_use]
+    pub fn hunk_diagnostics(&self, hunk_id: HunkId) -> &[diagnostics::Diagnostic] {
+        let Some((content, found)) = self.diagnostics.get(&hunk_id) else {
+            return &[];
+        };
+        let current = self
+            .session
+            .as_ref()
+            .and_then(|s| s.hunks().iter().find(|h| h.id == hunk_id))
+            .and_then(|hunk| verify::resolved_content(&hunk.state));
+        if current == *content {
+            found
+        } else {
+            &[]
+        }
+    }
+
+    /// Hides the test output panel once the run has finished.
+    pub fn close_test_panel(&mut self) {
+        verify::close_panel(self);
+    }
+
+    /// Sets the conflict tree of the whole merge, enabling the `O` overview.
+    pub fn set_overview(&mut self, tree: ConflictTree) {
+        self.overview = Some(tree);
+    }
+
+    /// Returns the conflict tree of the whole merge, if known.
+    #[must_use]
+    pub fn overview(&self) -> Option<&ConflictTree> {
+        self.overview.as_ref()
+    }
+
+    /// Sets the heuristics for the file's language.
+    pub fn set_language_heuristics(&mut self, heuristics: LanguageHeuristics) {
+        self.language = heuristics;
+    }
+
+    /// Sets the resolution templates available to `:snippet`.
+    pub fn set_snippets(&mut self, snippets: BTreeMap<String, String>) {
+        self.snippets = snippets;
+    }
+
+    /// Binds quick actions to digit keys.
+    pub fn set_quick_actions(&mut self, actions: BTreeMap<char, quick::QuickAction>) {
+        self.quick_actions = actions;
+    }
+
+    /// Returns the quick actions by digit key.
+    #[must_use]
+    pub fn quick_actions(&self) -> &BTreeMap<char, quick::QuickAction> {
+        &self.quick_actions
+    }
+
+    /// Runs the quick action bound to `key`.
+    pub fn run_quick_action(&mut self, key: char) {
+        quick::run(self, key);
+    }
+
+    /// Sets the earlier versions of the file offered by the `R` picker.
+    pub fn set_alternate_revisions(&mut self, revisions: Vec<revision::AlternateRevision>) {
+        self.alternate_revisions = revisions;
+    }
+
+    /// Returns the label of the revision a hunk's right side was taken
+    /// from, if it was replaced.
+    #[must_use]
+    pub fn replaced_right(&self, hunk_id: HunkId) -> Option<&str> {
+        self.replaced_right.get(&hunk_id).map(|r| r.label.as_str())
+    }
+
+    /// Opens the picker for taking the current hunk's right side from
+    /// another revision.
+    pub fn show_revision_picker(&mut self) {
+        revision::show_picker(self);
+    }
+
+    /// Moves the revision picker selection by `delta`.
+    pub fn select_revision(&mut self, delta: isize) {
+        revision::select(self, delta);
+    }
+
+    /// Applies the revision selected in the picker.
+    pub fn confirm_revision(&mut self) {
+        revision::confirm(self);
+    }
+
+    /// Sets the provider asked for suggestions with `s`.
+    pub fn set_suggestion_provider(&mut self, provider: Arc<dyn SuggestionProvider>) {
+        self.suggestion_provider = Some(provider);
+    }
+
+    /// Asks the AI provider for a suggestion for the current hunk.
+    pub fn request_suggestion(&mut self) {
+        suggest::start(self);
+    }
+
+    /// Resolves the hunk with the suggestion shown in the dialog.
+    pub fn accept_suggestion(&mut self) {
+        suggest::accept(self);
+    }
+
+    /// Opens the suggestion shown in the dialog in the external editor.
+    /// Returns true if editor should be launched.
+    pub fn edit_suggestion(&mut self) -> bool {
+        suggest::edit(self)
+    }
+
+    /// Dismisses the suggestion shown in the dialog.
+    pub fn reject_suggestion(&mut self) {
+        suggest::reject(self);
+    }
+
+    /// Returns a spinner and the provider's name while a suggestion is
+    /// pending.
+    #[must_use]
+    pub fn suggestion_progress(&self) -> Option<String> {
+        suggest::progress(self)
+    }
+
+    /// Sets step-by-step guidance to show while the user works.
+    pub fn set_guide(&mut self, guide: guide::Guide) {
+        self.guide = Some(guide);
+    }
+
+    /// Returns the active guide, if any.
+    #[must_use]
+    pub fn guide(&self) -> Option<&guide::Guide> {
+        self.guide.as_ref()
+    }
+
     /// Returns a reference to the current session, if any.
     #[must_use]
     pub fn session(&self) -> Option<&MergeSession> {
@@ -177,6 +540,41 @@ impl App {
         self.should_quit = true;
     }
 
+    /// Quits, first showing the diff from the conflicted file to the result
+    /// for confirmation if the result is about to be written.
+    pub fn request_quit(&mut self) {
+        let writes = self.confirm_write
+            && !self.read_only
+            && self
+                .session
+                .as_ref()
+                .is_some_and(MergeSession::is_fully_resolved);
+        if writes {
+            dialog::show_confirm_write(self);
+        } else {
+            self.quit();
+        }
+    }
+
+    /// Sets whether quitting with every hunk resolved asks for confirmation
+    /// of the final diff first.
+    pub fn set_confirm_write(&mut self, confirm: bool) {
+        self.confirm_write = confirm;
+    }
+
+    /// Makes the session read-only, for reviewing a finished merge: keys and
+    /// commands that would change a resolution, run a command, or write a
+    /// file are refused.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Returns true if the session is only being reviewed.
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Returns the currently focused pane.
     #[must_use]
     pub fn focused_pane(&self) -> FocusedPane {
@@ -264,11 +662,35 @@ impl App {
         resolution::resolve_right(self);
     }
 
+    /// Resolves the current hunk by accepting the base (common ancestor).
+    pub fn resolve_base(&mut self) {
+        resolution::resolve_base(self);
+    }
+
     /// Resolves the current hunk by accepting both sides (left then right).
     pub fn resolve_both(&mut self) {
         resolution::resolve_both(self);
     }
 
+    /// Resolves the current hunk with its first suggested resolution.
+    pub fn accept_proposal(&mut self) {
+        resolution::accept_proposal(self);
+    }
+
+    /// Applies the current hunk's resolution to all similar unresolved hunks.
+    pub fn apply_to_similar(&mut self) {
+        resolution::apply_to_similar(self);
+    }
+
+    /// Returns the number of other hunks similar to the current one.
+    #[must_use]
+    pub fn similar_hunk_count(&self) -> usize {
+        self.current_hunk().map_or(0, |hunk| {
+            let hunks = self.session.as_ref().map_or(&[][..], MergeSession::hunks);
+            weavr_core::similar_hunks(hunks, hunk.id, weavr_core::SIMILARITY_THRESHOLD).len()
+        })
+    }
+
     /// Clears the resolution for the current hunk, returning it to unresolved state.
     pub fn clear_current_resolution(&mut self) {
         resolution::clear_current_resolution(self);
@@ -301,12 +723,47 @@ impl App {
         self.result_scroll
     }
 
+    /// Scrolls all panes left by the specified number of columns.
+    pub fn scroll_left(&mut self, columns: usize) {
+        navigation::scroll_left(self, columns);
+    }
+
+    /// Scrolls all panes right by the specified number of columns.
+    pub fn scroll_right(&mut self, columns: usize) {
+        navigation::scroll_right(self, columns);
+    }
+
+    /// Returns the number of columns scrolled past on the left.
+    #[must_use]
+    pub fn horizontal_scroll(&self) -> usize {
+        self.horizontal_scroll
+    }
+
     /// Returns a reference to the layout configuration.
     #[must_use]
     pub fn layout_config(&self) -> &LayoutConfig {
         &self.layout_config
     }
 
+    /// Shows or hides the base pane, if the file's hunks have a base.
+    pub fn toggle_base_pane(&mut self) {
+        let has_base = self
+            .session
+            .as_ref()
+            .is_some_and(|session| session.hunks().iter().any(|hunk| hunk.base.is_some()));
+        if !has_base {
+            self.set_status_message("No base version in this file");
+            return;
+        }
+        self.layout_config.show_base = !self.layout_config.show_base;
+        let status = if self.layout_config.show_base {
+            "Showing base"
+        } else {
+            "Hiding base"
+        };
+        self.set_status_message(status);
+    }
+
     /// Returns a reference to the diff configuration.
     #[must_use]
     pub fn diff_config(&self) -> &diff::DiffConfig {
@@ -324,6 +781,75 @@ impl App {
         self.set_status_message(status);
     }
 
+    /// Returns true if the sides of the current hunk are shown pretty-printed.
+    #[must_use]
+    pub fn pretty_sides(&self) -> bool {
+        self.diff_config.pretty_sides
+    }
+
+    /// Toggles showing the sides of the current hunk pretty-printed, which
+    /// makes conflicts on minified single lines readable. Resolutions still
+    /// take the sides as written.
+    pub fn toggle_pretty_sides(&mut self) {
+        self.diff_config.pretty_sides = !self.diff_config.pretty_sides;
+        self.horizontal_scroll = 0;
+        let status = if self.diff_config.pretty_sides {
+            "Showing sides pretty-printed (view only)"
+        } else {
+            "Showing sides as written"
+        };
+        self.set_status_message(status);
+    }
+
+    /// Returns how many unchanged lines are shown around each hunk, or `None`
+    /// if whole files are shown.
+    #[must_use]
+    pub fn context_lines(&self) -> Option<usize> {
+        self.diff_config.context_lines
+    }
+
+    /// Sets how many unchanged lines are shown around each hunk, folding the
+    /// rest, or shows whole files with `None`. The session keeps as many
+    /// lines as each hunk's context for exports.
+    pub fn set_context_lines(&mut self, count: Option<usize>) {
+        self.diff_config.context_lines = count;
+        if let Some(session) = &mut self.session {
+            session.set_context_lines(count.unwrap_or(DEFAULT_CONTEXT_LINES));
+        }
+    }
+
+    /// Shows one more unchanged line around each hunk, or whole files once
+    /// past the most that is folded.
+    pub fn more_context(&mut self) {
+        let count = match self.diff_config.context_lines {
+            Some(count) if count < MAX_FOLDED_CONTEXT => Some(count + 1),
+            _ => None,
+        };
+        self.change_context(count);
+    }
+
+    /// Shows one less unchanged line around each hunk, folding whole files
+    /// down to the most that is folded first.
+    pub fn less_context(&mut self) {
+        let count = match self.diff_config.context_lines {
+            Some(count) => count.saturating_sub(1),
+            None => MAX_FOLDED_CONTEXT,
+        };
+        self.change_context(Some(count));
+    }
+
+    fn change_context(&mut self, count: Option<usize>) {
+        self.set_context_lines(count);
+        self.left_right_scroll = 0;
+        self.result_scroll = 0;
+        let status = match count {
+            Some(1) => "Showing 1 line of context around hunks".to_string(),
+            Some(count) => format!("Showing {count} lines of context around hunks"),
+            None => "Showing whole files".to_string(),
+        };
+        self.set_status_message(&status);
+    }
+
     /// Sets a status message to display in the status bar.
     ///
     /// The message will auto-clear after a few seconds.
@@ -374,6 +900,15 @@ impl App {
     /// Executes the current command buffer.
     pub fn execute_command(&mut self) {
         let cmd = Command::parse(&self.command_buffer);
+        self.exit_command_mode();
+        if self.read_only {
+            match cmd {
+                Command::Quit | Command::ForceQuit => self.quit(),
+                Command::Unknown(s) if s.is_empty() => {}
+                _ => self.set_status_message(READ_ONLY_MESSAGE),
+            }
+            return;
+        }
         match cmd {
             Command::Write => self.write_file(),
             Command::Quit => self.try_quit(),
@@ -382,13 +917,15 @@ impl App {
                 self.set_status_message(":wq not yet implemented - use :q! to force quit");
             }
             Command::ForceQuit => self.quit(),
+            Command::Note(text) => resolution::set_note(self, &text),
+            Command::Substitute(substitution) => substitute::preview(self, substitution),
+            Command::Snippet(call) => snippet::run(self, &call),
             Command::Unknown(s) => {
                 if !s.is_empty() {
                     self.set_status_message(&format!("Unknown command: {s}"));
                 }
             }
         }
-        self.exit_command_mode();
     }
 
     /// Writes the resolved file. Currently a placeholder.
@@ -408,7 +945,7 @@ impl App {
             let count = self.unresolved_count();
             self.set_status_message(&format!("{count} unresolved hunks. Use :q! to force quit"));
         } else {
-            self.quit();
+            self.request_quit();
         }
     }
 
@@ -460,6 +997,36 @@ impl App {
         dialog::confirm_accept_both(self);
     }
 
+    /// Shows the diff between the pre-merge version and the current result.
+    pub fn show_head_compare(&mut self) {
+        dialog::show_head_compare(self);
+    }
+
+    /// Scrolls the pre-merge comparison by `delta` lines.
+    pub fn scroll_head_compare(&mut self, delta: i16) {
+        dialog::scroll_head_compare(self, delta);
+    }
+
+    /// Scrolls the final diff shown before writing by `delta` lines.
+    pub fn scroll_confirm_write(&mut self, delta: i16) {
+        dialog::scroll_confirm_write(self, delta);
+    }
+
+    /// Shows the conflict heatmap of the whole merge.
+    pub fn show_overview(&mut self) {
+        dialog::show_overview(self);
+    }
+
+    /// Scrolls the conflict heatmap by `delta` lines.
+    pub fn scroll_overview(&mut self, delta: i16) {
+        dialog::scroll_overview(self, delta);
+    }
+
+    /// Applies the substitution shown in the preview dialog.
+    pub fn confirm_substitute(&mut self) {
+        substitute::confirm(self);
+    }
+
     // --- Phase 7: Editor Integration ---
 
     /// Prepares content for external editor and sets pending state.
@@ -468,6 +1035,13 @@ impl App {
         editor::prepare_editor(self)
     }
 
+    /// Accepts ours (or theirs, if `right`) for the current hunk and opens it
+    /// in the external editor, as one undoable step.
+    /// Returns true if editor should be launched.
+    pub fn accept_then_edit(&mut self, right: bool) -> bool {
+        editor::accept_then_edit(self, right)
+    }
+
     /// Takes the pending editor content, clearing the pending state.
     pub fn take_editor_pending(&mut self) -> Option<String> {
         editor::take_editor_pending(self)
@@ -477,6 +1051,27 @@ impl App {
     pub fn apply_editor_result(&mut self, content: &str) {
         editor::apply_editor_result(self, content);
     }
+
+    /// Notes that the external editor was closed without saving.
+    pub fn cancel_editor(&mut self) {
+        editor::cancel_editor(self);
+    }
+
+    /// Prepares the focused hunk for the external diff tool.
+    /// Returns true if the tool should be launched.
+    pub fn prepare_diff_tool(&mut self) -> bool {
+        difftool::prepare_diff_tool(self)
+    }
+
+    /// Takes the hunk pending for the diff tool, clearing the pending state.
+    pub fn take_diff_tool_pending(&mut self) -> Option<difftool::DiffToolRequest> {
+        difftool::take_diff_tool_pending(self)
+    }
+
+    /// Applies the diff tool's merged output to the hunk it was opened for.
+    pub fn apply_diff_tool_result(&mut self, hunk_id: HunkId, content: &str) {
+        difftool::apply_diff_tool_result(self, hunk_id, content);
+    }
 }
 
 impl Default for App {
@@ -508,6 +1103,7 @@ impl FocusedPane {
 pub fn run(app: &mut App) -> std::io::Result<()> {
     let mut terminal = ratatui::init();
     let result = run_event_loop(&mut terminal, app);
+    verify::stop(app);
     ratatui::restore();
     result
 }
@@ -530,11 +1126,30 @@ fn run_event_loop(terminal: &mut ratatui::DefaultTerminal, app: &mut App) -> std
             if let Some(new_content) = result {
                 app.apply_editor_result(&new_content);
             } else {
-                app.set_status_message("Editor cancelled");
+                app.cancel_editor();
+            }
+            continue;
+        }
+
+        // Check for a hunk pending for the external diff tool
+        if let Some(request) = app.take_diff_tool_pending() {
+            let Some(command) = app.diff_tool.clone() else {
+                continue;
+            };
+            ratatui::restore();
+            let result = difftool::run_diff_tool(&command, &request);
+            *terminal = ratatui::init();
+
+            match result {
+                Ok(Some(merged)) => app.apply_diff_tool_result(request.hunk_id, &merged),
+                Ok(None) => app.set_status_message("Diff tool closed without merging"),
+                Err(e) => app.set_status_message(&format!("Diff tool failed: {e}")),
             }
             continue;
         }
 
+        verify::poll(app);
+        suggest::poll(app);
         terminal.draw(|frame| ui::draw(frame, app))?;
 
         if let Some(evt) = event::poll_event(Duration::from_millis(100))? {
@@ -551,31 +1166,19 @@ fn run_event_loop(terminal: &mut ratatui::DefaultTerminal, app: &mut App) -> std
 fn run_editor(content: &str) -> std::io::Result<Option<String>> {
     use std::io::Write;
 
-    // Prefer VISUAL, then EDITOR, then fall back to vi
+    // Prefer VISUAL, then EDITOR, then fall back to the platform's editor
     let editor_cmd = std::env::var("VISUAL")
         .or_else(|_| std::env::var("EDITOR"))
-        .unwrap_or_else(|_| "vi".into());
-
-    // Parse the editor command into program + args using shell-style splitting
-    let mut parts = shell_words::split(&editor_cmd)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
-
-    if parts.is_empty() {
-        parts.push("vi".into());
-    }
-
-    let program = parts.remove(0);
+        .ok()
+        .filter(|cmd| !cmd.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.into());
 
     // Create temp file with content
     let mut tmp = tempfile::NamedTempFile::new()?;
     tmp.write_all(content.as_bytes())?;
     tmp.flush()?;
 
-    // Run editor with any additional arguments
-    let status = std::process::Command::new(&program)
-        .args(&parts)
-        .arg(tmp.path())
-        .status()?;
+    let status = editor_command(&editor_cmd, tmp.path())?.status()?;
 
     if status.success() {
         Ok(Some(std::fs::read_to_string(tmp.path())?))
@@ -584,6 +1187,44 @@ fn run_editor(content: &str) -> std::io::Result<Option<String>> {
     }
 }
 
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Builds the command running `editor_cmd` on `path`.
+///
+/// On Windows the command goes through `cmd /S /C`, so `.cmd` and `.bat` shims
+/// such as `code --wait` resolve the way they do at a prompt, and backslashes
+/// in paths are left alone.
+#[cfg(windows)]
+#[allow(clippy::unnecessary_wraps)] // Matches the fallible Unix version
+fn editor_command(editor_cmd: &str, path: &Path) -> std::io::Result<std::process::Command> {
+    use std::os::windows::process::CommandExt;
+
+    let mut command = std::process::Command::new("cmd");
+    command
+        .args(["/S", "/C"])
+        .raw_arg(format!("\"{editor_cmd} \"{}\"\"", path.display()));
+    Ok(command)
+}
+
+/// Builds the command running `editor_cmd` on `path`, splitting the command
+/// shell-style into program and arguments.
+#[cfg(not(windows))]
+fn editor_command(editor_cmd: &str, path: &Path) -> std::io::Result<std::process::Command> {
+    let mut parts = shell_words::split(editor_cmd)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    if parts.is_empty() {
+        parts.push(DEFAULT_EDITOR.into());
+    }
+    let program = parts.remove(0);
+
+    let mut command = std::process::Command::new(program);
+    command.args(parts).arg(path);
+    Ok(command)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -848,4 +1489,408 @@ mod tests {
         // Second call returns None
         assert!(app.take_editor_pending().is_none());
     }
+
+    #[test]
+    fn editor_result_line_endings_are_normalized() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+        let hunk_id = session.hunks()[0].id;
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.apply_editor_result("one\r\ntwo\r\n");
+
+        assert_eq!(
+            app.session().unwrap().resolutions()[&hunk_id].content,
+            "one\ntwo\n"
+        );
+    }
+
+    #[test]
+    fn accept_then_edit_is_undone_in_one_step() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+        let hunk_id = session.hunks()[0].id;
+
+        let mut app = App::new();
+        app.set_session(session);
+        assert!(app.accept_then_edit(true));
+        assert_eq!(app.take_editor_pending().as_deref(), Some("right"));
+        app.apply_editor_result("right\nmine\n");
+        assert_eq!(
+            app.session().unwrap().resolutions()[&hunk_id].content,
+            "right\nmine\n"
+        );
+
+        app.undo();
+        assert!(app.session().unwrap().resolutions().is_empty());
+        assert_eq!(
+            app.status_message().unwrap().0,
+            "Undid: Accept theirs and edit"
+        );
+    }
+
+    #[test]
+    fn cancelled_accept_then_edit_keeps_the_side() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+        let hunk_id = session.hunks()[0].id;
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.accept_then_edit(false);
+        app.take_editor_pending();
+        app.cancel_editor();
+        assert_eq!(
+            app.session().unwrap().resolutions()[&hunk_id].content,
+            "left"
+        );
+
+        app.undo();
+        assert!(app.session().unwrap().resolutions().is_empty());
+    }
+
+    #[test]
+    fn diff_tool_requires_configuration() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+        let hunk_id = session.hunks()[0].id;
+
+        let mut app = App::new();
+        app.set_session(session);
+        assert!(!app.prepare_diff_tool());
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("No diff tool configured")
+        );
+
+        app.set_diff_tool("meld $LOCAL $MERGED $REMOTE --output $MERGED".to_string());
+        assert!(app.prepare_diff_tool());
+        let request = app.take_diff_tool_pending().unwrap();
+        assert_eq!(request.hunk_id, hunk_id);
+        assert_eq!(request.local, "left");
+        assert!(request.merged.starts_with("<<<<<<< OURS"));
+
+        app.apply_diff_tool_result(hunk_id, "merged\r\n");
+        assert_eq!(
+            app.session().unwrap().resolutions()[&hunk_id].content,
+            "merged\n"
+        );
+    }
+
+    #[test]
+    fn accept_proposal_applies_first_suggestion() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch";
+        let mut session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+        let hunk_id = session.hunks()[0].id;
+        session
+            .propose(
+                hunk_id,
+                vec![weavr_core::Resolution::manual("shared".to_string())],
+            )
+            .unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.accept_proposal();
+
+        let session = app.session().unwrap();
+        assert_eq!(session.resolutions()[&hunk_id].content, "shared");
+
+        // Undo returns the hunk to its suggested state
+        app.undo();
+        let session = app.session().unwrap();
+        assert!(matches!(
+            session.hunks()[0].state,
+            weavr_core::HunkState::Proposed(_)
+        ));
+    }
+
+    #[test]
+    fn accept_proposal_without_suggestion_is_noop() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.accept_proposal();
+
+        assert!(app.session().unwrap().resolutions().is_empty());
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("No suggestion for this hunk")
+        );
+    }
+
+    #[test]
+    fn unbalanced_resolution_is_flagged_in_status() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nif a {\n=======\nif b {\n>>>>>>> f\n    go();\n}\n";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.resolve_both();
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Accept both · unbalanced: 1 unclosed '{' compared to ours")
+        );
+
+        app.resolve_right();
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Accept theirs")
+        );
+    }
+
+    #[test]
+    fn repeated_definition_is_flagged_in_status() {
+        use std::path::PathBuf;
+
+        let content =
+            "<<<<<<< HEAD\ndef run():\n    pass\n=======\ndef run():\n    return 1\n>>>>>>> f\n";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.py")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.resolve_both();
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Accept both · duplicate definition `def run()` (first on line 1)")
+        );
+    }
+
+    #[test]
+    fn accept_base_needs_a_diff3_hunk() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> f\nmid\n\
+                       <<<<<<< HEAD\nours\n||||||| base\noriginal\n=======\ntheirs\n>>>>>>> f";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        assert_eq!(app.conflict_style(), ConflictStyle::Diff3);
+
+        app.resolve_base();
+        assert!(app.session().unwrap().resolutions().is_empty());
+
+        app.next_hunk();
+        app.resolve_base();
+        assert_eq!(
+            app.session().unwrap().resolutions()[&weavr_core::HunkId(1)].content,
+            "original"
+        );
+    }
+
+    #[test]
+    fn apply_to_similar_resolves_and_undoes_each_hunk() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nuse a::x;\n=======\nuse b::x;\n>>>>>>> f\nmid\n\
+                       <<<<<<< HEAD\nuse a::y;\n=======\nuse b::y;\n>>>>>>> f";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        assert_eq!(app.similar_hunk_count(), 1);
+
+        app.apply_to_similar();
+        assert!(app.session().unwrap().resolutions().is_empty());
+
+        app.resolve_right();
+        app.apply_to_similar();
+        let session = app.session().unwrap();
+        assert!(session.is_fully_resolved());
+        assert_eq!(
+            session.resolutions()[&weavr_core::HunkId(1)].content,
+            "use b::y;"
+        );
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Applied to 1 similar hunks")
+        );
+
+        app.undo();
+        assert_eq!(app.session().unwrap().resolutions().len(), 1);
+    }
+
+    #[test]
+    fn apply_to_similar_beyond_undo_depth_is_undone_in_one_step() {
+        use std::path::PathBuf;
+
+        let content: Vec<String> = (0..120)
+            .map(|i| format!("<<<<<<< HEAD\nuse a::x{i};\n=======\nuse b::x{i};\n>>>>>>> f"))
+            .collect();
+        let session = weavr_core::MergeSession::from_conflicted(
+            content.join("\nmid\n"),
+            PathBuf::from("test.rs"),
+        )
+        .unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.resolve_right();
+        app.apply_to_similar();
+        assert!(app.session().unwrap().is_fully_resolved());
+
+        app.undo();
+        assert_eq!(app.session().unwrap().resolutions().len(), 1);
+    }
+
+    #[test]
+    fn propagation_beyond_undo_depth_is_undone_in_one_step() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nsame\n=======\nother\n>>>>>>> f\n".repeat(120);
+        let content = content.replace(">>>>>>> f\n<", ">>>>>>> f\nmid\n<");
+        let session =
+            weavr_core::MergeSession::from_conflicted(&content, PathBuf::from("test.rs")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_propagation(PropagationMode::Apply);
+        app.resolve_left();
+        assert!(app.session().unwrap().is_fully_resolved());
+
+        app.undo();
+        assert_eq!(app.session().unwrap().resolutions().len(), 1);
+    }
+
+    #[test]
+    fn resolving_propagates_to_identical_hunks() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nsame\n=======\nother\n>>>>>>> f\nmid\n\
+                       <<<<<<< HEAD\nsame\n=======\nother\n>>>>>>> f";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session.clone());
+        app.resolve_left();
+        assert_eq!(app.session().unwrap().resolutions().len(), 1);
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Accept ours · offered to 1 identical hunks")
+        );
+        assert!(matches!(
+            app.session().unwrap().hunks()[1].state,
+            weavr_core::HunkState::Proposed(_)
+        ));
+
+        // Undoing withdraws the offer and keeps the resolution
+        app.undo();
+        let undone = app.session().unwrap();
+        assert_eq!(undone.resolutions().len(), 1);
+        assert_eq!(undone.hunks()[1].state, weavr_core::HunkState::Unresolved);
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_propagation(PropagationMode::Apply);
+        app.resolve_left();
+        assert!(app.session().unwrap().is_fully_resolved());
+
+        app.undo();
+        assert_eq!(app.session().unwrap().resolutions().len(), 1);
+    }
+
+    #[test]
+    fn head_compare_requires_pre_merge_version() {
+        let mut app = App::new();
+        app.show_head_compare();
+        assert!(app.active_dialog().is_none());
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Pre-merge version not available")
+        );
+
+        app.set_head_version("fn main() {}\n".to_string());
+        app.show_head_compare();
+        app.scroll_head_compare(2);
+        app.scroll_head_compare(-5);
+        assert!(matches!(
+            app.active_dialog(),
+            Some(Dialog::HeadCompare(state)) if state.scroll == 0
+        ));
+    }
+
+    #[test]
+    fn overview_requires_conflict_tree() {
+        let mut app = App::new();
+        app.show_overview();
+        assert!(app.active_dialog().is_none());
+
+        app.set_overview(ConflictTree::default());
+        app.show_overview();
+        app.scroll_overview(3);
+        assert!(matches!(
+            app.active_dialog(),
+            Some(Dialog::Overview(state)) if state.scroll == 3
+        ));
+    }
+
+    #[test]
+    fn note_command_annotates_resolved_hunk() {
+        use std::path::PathBuf;
+
+        let content = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> branch";
+        let session =
+            weavr_core::MergeSession::from_conflicted(content, PathBuf::from("test.rs")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+
+        app.enter_command_mode();
+        "note too early"
+            .chars()
+            .for_each(|c| app.append_to_command(c));
+        app.execute_command();
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Resolve the hunk before adding a note")
+        );
+
+        app.resolve_right();
+        app.enter_command_mode();
+        "note kept theirs"
+            .chars()
+            .for_each(|c| app.append_to_command(c));
+        app.execute_command();
+
+        let notes = |app: &App| {
+            app.session().unwrap().resolutions()[&weavr_core::HunkId(0)]
+                .metadata
+                .notes
+                .clone()
+        };
+        assert_eq!(notes(&app), Some("kept theirs".to_string()));
+
+        app.undo();
+        assert_eq!(notes(&app), None);
+        assert!(!app.session().unwrap().resolutions().is_empty());
+    }
 }
@@ -74,7 +74,7 @@ pub fn next_unresolved_hunk(app: &mut App) {
         // Search forward from current position
         for i in 1..=total {
             let idx = (app.current_hunk_index + i) % total;
-            if matches!(hunks[idx].state, HunkState::Unresolved) {
+            if !matches!(hunks[idx].state, HunkState::Resolved(_)) {
                 app.current_hunk_index = idx;
                 reset_scroll(app);
                 return;
@@ -95,7 +95,7 @@ pub fn prev_unresolved_hunk(app: &mut App) {
         // Search backward from current position
         for i in 1..=total {
             let idx = (app.current_hunk_index + total - i) % total;
-            if matches!(hunks[idx].state, HunkState::Unresolved) {
+            if !matches!(hunks[idx].state, HunkState::Resolved(_)) {
                 app.current_hunk_index = idx;
                 reset_scroll(app);
                 return;
@@ -130,8 +130,31 @@ pub fn scroll_down(app: &mut App, lines: u16) {
     }
 }
 
+/// Scrolls all panes left by the specified number of columns.
+pub fn scroll_left(app: &mut App, columns: usize) {
+    app.horizontal_scroll = app.horizontal_scroll.saturating_sub(columns);
+}
+
+/// Scrolls all panes right by the specified number of columns, stopping
+/// before the end of the file's longest line.
+pub fn scroll_right(app: &mut App, columns: usize) {
+    let longest = app.session.as_ref().map_or(0, |session| {
+        session
+            .conflicted()
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+    });
+    app.horizontal_scroll = app
+        .horizontal_scroll
+        .saturating_add(columns)
+        .min(longest.saturating_sub(1));
+}
+
 /// Resets scroll positions when changing hunks.
 fn reset_scroll(app: &mut App) {
     app.left_right_scroll = 0;
     app.result_scroll = 0;
+    app.horizontal_scroll = 0;
 }
@@ -0,0 +1,100 @@
+//! Notifications when long operations finish.
+//!
+//! Users often switch to another window while tests run or a large merge is
+//! resolved headlessly. A [`Notifier`] rings the terminal bell or sends an
+//! OSC 9 desktop notification, which most modern terminals show, once an
+//! operation that took long enough finishes. Notifications go to stderr, and only when it is a
+//! terminal.
+
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+/// How the user is alerted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alert {
+    /// The terminal bell.
+    Bell,
+    /// An OSC 9 desktop notification carrying the message.
+    Desktop,
+}
+
+/// Alerts the user when an operation finishes, if it took long enough.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Notifier {
+    /// How to alert, or `None` to stay quiet.
+    pub alert: Option<Alert>,
+    /// Operations finishing sooner than this do not alert.
+    pub after: Duration,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self {
+            alert: None,
+            after: Duration::from_secs(10),
+        }
+    }
+}
+
+impl Notifier {
+    /// Alerts that an operation taking `elapsed` finished with `message`.
+    pub fn finished(&self, elapsed: Duration, message: &str) {
+        let Some(sequence) = self.sequence(elapsed, message) else {
+            return;
+        };
+        let mut stderr = std::io::stderr();
+        if stderr.is_terminal() {
+            let _ = stderr.write_all(sequence.as_bytes());
+            let _ = stderr.flush();
+        }
+    }
+
+    /// Returns the escape sequence to write, if the operation alerts.
+    fn sequence(&self, elapsed: Duration, message: &str) -> Option<String> {
+        if elapsed < self.after {
+            return None;
+        }
+        match self.alert? {
+            Alert::Bell => Some("\x07".to_string()),
+            Alert::Desktop => {
+                // Control characters would end the sequence early
+                let message: String = message
+                    .chars()
+                    .map(|c| if c.is_control() { ' ' } else { c })
+                    .collect();
+                Some(format!("\x1b]9;weavr: {message}\x07"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_long_operations_alert() {
+        let notifier = Notifier {
+            alert: Some(Alert::Desktop),
+            after: Duration::from_secs(5),
+        };
+        assert_eq!(notifier.sequence(Duration::from_secs(1), "done"), None);
+        assert_eq!(
+            notifier.sequence(Duration::from_secs(5), "Tests\npassed"),
+            Some("\x1b]9;weavr: Tests passed\x07".to_string())
+        );
+
+        let bell = Notifier {
+            alert: Some(Alert::Bell),
+            ..notifier
+        };
+        assert_eq!(
+            bell.sequence(Duration::from_secs(9), "done"),
+            Some("\x07".to_string())
+        );
+        assert_eq!(
+            Notifier::default().sequence(Duration::from_secs(60), "done"),
+            None
+        );
+    }
+}
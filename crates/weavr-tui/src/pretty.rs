@@ -0,0 +1,107 @@
+//! Temporary pretty-printing of conflict sides.
+//!
+//! Conflicts in minified code or JSON blobs sit on a single huge line, where
+//! a line diff shows nothing useful. Breaking the text after brackets and
+//! separators and indenting it by nesting gives lines that can be compared
+//! side by side. This is for display only: resolutions still take the sides
+//! as written.
+
+/// Indentation per nesting level.
+const INDENT: &str = "  ";
+
+/// Breaks `text` into one line per element, indented by bracket nesting.
+///
+/// Quoted strings are kept intact, and empty brackets stay on one line.
+#[must_use]
+pub fn pretty_print(text: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    // Whether only indentation has been written on the current line
+    let mut fresh = true;
+
+    for c in text.chars() {
+        if let Some(open) = quote {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == open {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '{' | '[' => {
+                out.push(c);
+                depth += 1;
+                newline(&mut out, depth, &mut fresh);
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(['{', '[']) {
+                    out.truncate(trimmed);
+                } else if !fresh {
+                    newline(&mut out, depth, &mut fresh);
+                } else {
+                    // Already on a fresh line, indented one level too deep
+                    out.truncate(trimmed);
+                    newline(&mut out, depth, &mut fresh);
+                }
+                out.push(c);
+                fresh = false;
+            }
+            ',' | ';' => {
+                out.push(c);
+                newline(&mut out, depth, &mut fresh);
+            }
+            '\n' => newline(&mut out, depth, &mut fresh),
+            ' ' | '\t' | '\r' if fresh => {}
+            _ => {
+                if matches!(c, '"' | '\'' | '`') {
+                    quote = Some(c);
+                }
+                out.push(c);
+                fresh = false;
+            }
+        }
+    }
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    out
+}
+
+/// Starts a new line indented to `depth`, unless the current one is empty.
+fn newline(out: &mut String, depth: usize, fresh: &mut bool) {
+    let trimmed = out.trim_end_matches([' ', '\t']).len();
+    out.truncate(trimmed);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    if !out.is_empty() {
+        out.push_str(&INDENT.repeat(depth));
+    }
+    *fresh = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_minified_json_by_nesting() {
+        let text = r#"{"a":[1,2],"b":{},"c":"x, {y}"}"#;
+        assert_eq!(
+            pretty_print(text),
+            "{\n  \"a\":[\n    1,\n    2\n  ],\n  \"b\":{},\n  \"c\":\"x, {y}\"\n}"
+        );
+    }
+
+    #[test]
+    fn keeps_escaped_quotes_inside_strings() {
+        assert_eq!(pretty_print(r#"f("a\",b");g()"#), "f(\"a\\\",b\");\ng()");
+    }
+}
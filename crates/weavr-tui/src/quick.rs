@@ -0,0 +1,192 @@
+//! Quick actions bound to the digit keys.
+//!
+//! A quick action is a short sequence of steps joined with `+`, such as
+//! `accept left + next unresolved`, so mechanical conflicts can be worked
+//! through one key per hunk. The steps are:
+//!
+//! - `accept left`, `accept right`, `accept both`, `accept both deduped`,
+//!   `accept suggestion`, `clear`: resolve the current hunk
+//! - `next`, `prev`, `next unresolved`, `prev unresolved`: move to a hunk
+//!
+//! A step that fails, such as accepting a suggestion the hunk does not have,
+//! stops the rest of the action.
+
+use std::fmt;
+use std::str::FromStr;
+
+use weavr_core::{AcceptBothOptions, HunkState};
+
+use crate::{navigation, resolution, App};
+
+/// One step of a quick action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickStep {
+    /// Accept our side.
+    AcceptLeft,
+    /// Accept their side.
+    AcceptRight,
+    /// Accept both sides, left then right.
+    AcceptBoth,
+    /// Accept both sides, dropping lines repeated on the right.
+    AcceptBothDeduped,
+    /// Accept the first suggestion.
+    AcceptSuggestion,
+    /// Clear the resolution.
+    Clear,
+    /// Move to the next hunk.
+    Next,
+    /// Move to the previous hunk.
+    Prev,
+    /// Move to the next unresolved hunk.
+    NextUnresolved,
+    /// Move to the previous unresolved hunk.
+    PrevUnresolved,
+}
+
+impl QuickStep {
+    /// Every step with the name it is written as.
+    const NAMES: &'static [(&'static str, Self)] = &[
+        ("accept left", Self::AcceptLeft),
+        ("accept right", Self::AcceptRight),
+        ("accept both", Self::AcceptBoth),
+        ("accept both deduped", Self::AcceptBothDeduped),
+        ("accept suggestion", Self::AcceptSuggestion),
+        ("clear", Self::Clear),
+        ("next", Self::Next),
+        ("prev", Self::Prev),
+        ("next unresolved", Self::NextUnresolved),
+        ("prev unresolved", Self::PrevUnresolved),
+    ];
+
+    /// Returns the name the step is written as.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, step)| *step == self)
+            .map_or("", |(name, _)| name)
+    }
+}
+
+impl FromStr for QuickStep {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&words))
+            .map(|(_, step)| *step)
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::NAMES.iter().map(|(name, _)| *name).collect();
+                format!(
+                    "unknown step '{}' (known: {})",
+                    text.trim(),
+                    known.join(", ")
+                )
+            })
+    }
+}
+
+/// A sequence of steps run by one key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickAction {
+    /// Steps in the order they run.
+    pub steps: Vec<QuickStep>,
+}
+
+impl FromStr for QuickAction {
+    type Err = String;
+
+    /// Parses steps joined with `+`, e.g. `accept left + next unresolved`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let steps = text
+            .split('+')
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { steps })
+    }
+}
+
+impl fmt::Display for QuickAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.steps.iter().map(|step| step.name()).collect();
+        f.write_str(&names.join(" + "))
+    }
+}
+
+/// Runs the action bound to `key`, if any.
+pub fn run(app: &mut App, key: char) {
+    let Some(action) = app.quick_actions.get(&key).cloned() else {
+        app.set_status_message(&format!("Nothing bound to {key}"));
+        return;
+    };
+    for step in action.steps {
+        if !run_step(app, step) {
+            break;
+        }
+    }
+}
+
+/// Runs one step, returning false if it could not be done.
+fn run_step(app: &mut App, step: QuickStep) -> bool {
+    let resolved = |app: &App| {
+        app.current_hunk()
+            .is_some_and(|hunk| matches!(hunk.state, HunkState::Resolved(_)))
+    };
+    match step {
+        QuickStep::AcceptLeft => resolution::resolve_left(app),
+        QuickStep::AcceptRight => resolution::resolve_right(app),
+        QuickStep::AcceptBoth => resolution::resolve_both(app),
+        QuickStep::AcceptBothDeduped => {
+            let language = app.language.clone();
+            let options = AcceptBothOptions {
+                deduplicate: true,
+                ..AcceptBothOptions::default()
+            };
+            resolution::apply_resolution(app, "Accept both (deduped)", |hunk| {
+                language.accept_both(hunk, &options)
+            });
+        }
+        QuickStep::AcceptSuggestion => resolution::accept_proposal(app),
+        QuickStep::Clear => {
+            resolution::clear_current_resolution(app);
+            return true;
+        }
+        QuickStep::Next => {
+            navigation::next_hunk(app);
+            return true;
+        }
+        QuickStep::Prev => {
+            navigation::prev_hunk(app);
+            return true;
+        }
+        QuickStep::NextUnresolved => {
+            navigation::next_unresolved_hunk(app);
+            return true;
+        }
+        QuickStep::PrevUnresolved => {
+            navigation::prev_unresolved_hunk(app);
+            return true;
+        }
+    }
+    resolved(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_parse_steps_joined_with_plus() {
+        let action: QuickAction = "Accept Left +  next   unresolved".parse().unwrap();
+        assert_eq!(
+            action.steps,
+            vec![QuickStep::AcceptLeft, QuickStep::NextUnresolved]
+        );
+        assert_eq!(action.to_string(), "accept left + next unresolved");
+
+        let err = "accept left + jump".parse::<QuickAction>().unwrap_err();
+        assert!(err.starts_with("unknown step 'jump' (known: accept left,"));
+    }
+}
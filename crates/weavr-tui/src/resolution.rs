@@ -1,11 +1,18 @@
 //! Resolution handling for conflict hunks.
 //!
 //! This module handles:
-//! - Applying resolutions (left, right, both, manual)
+//! - Applying resolutions (left, right, base, both, manual)
 //! - Clearing resolutions
+//! - Per-hunk notes
+//! - Applying a resolution to similar hunks
 //! - Undo support
 
-use weavr_core::{AcceptBothOptions, ConflictHunk, Resolution};
+use std::collections::HashMap;
+
+use weavr_core::{
+    check_balance, AcceptBothOptions, ConflictHunk, HunkId, HunkState, PropagationMode, Resolution,
+    Syntax, SIMILARITY_THRESHOLD,
+};
 
 use crate::App;
 
@@ -19,13 +26,77 @@ pub fn resolve_right(app: &mut App) {
     apply_resolution(app, "Accept theirs", Resolution::accept_right);
 }
 
+/// Resolves the current hunk by accepting the base (common ancestor) content.
+pub fn resolve_base(app: &mut App) {
+    if !app.current_hunk().is_some_and(|hunk| hunk.base.is_some()) {
+        app.set_status_message("No base for this hunk - it needs diff3 or zdiff3 markers");
+        return;
+    }
+
+    apply_resolution(app, "Accept base", |hunk| {
+        match Resolution::accept_base(hunk) {
+            Some(resolution) => resolution,
+            None => unreachable!("checked above"),
+        }
+    });
+}
+
 /// Resolves the current hunk by accepting both sides (left then right).
 pub fn resolve_both(app: &mut App) {
+    let language = app.language.clone();
     apply_resolution(app, "Accept both", |hunk| {
-        Resolution::accept_both(hunk, &AcceptBothOptions::default())
+        language.accept_both(hunk, &AcceptBothOptions::default())
+    });
+}
+
+/// Resolves the current hunk with its first suggested resolution.
+pub fn accept_proposal(app: &mut App) {
+    let has_proposal = app.session.as_ref().is_some_and(|session| {
+        session
+            .hunks()
+            .get(app.current_hunk_index)
+            .is_some_and(|hunk| matches!(&hunk.state, HunkState::Proposed(p) if !p.is_empty()))
+    });
+    if !has_proposal {
+        app.set_status_message("No suggestion for this hunk");
+        return;
+    }
+
+    apply_resolution(app, "Accept suggestion", |hunk| match &hunk.state {
+        HunkState::Proposed(proposals) => proposals[0].clone(),
+        _ => unreachable!("checked above"),
     });
 }
 
+/// Applies the current hunk's resolution to every similar unresolved hunk.
+///
+/// A single undo reverts all of them.
+pub fn apply_to_similar(app: &mut App) {
+    let Some(hunk_id) = app.current_hunk().map(|hunk| hunk.id) else {
+        return;
+    };
+    let Some(session) = app.session.as_mut() else {
+        return;
+    };
+    if !session.resolutions().contains_key(&hunk_id) {
+        app.set_status_message("Resolve this hunk first, then apply it to similar hunks");
+        return;
+    }
+
+    match session.apply_to_similar(hunk_id, SIMILARITY_THRESHOLD) {
+        Ok(applied) if applied.is_empty() => {
+            app.set_status_message("No similar unresolved hunks");
+        }
+        Ok(applied) => {
+            let count = applied.len();
+            let undo = applied.into_iter().map(|id| (id, None)).collect();
+            app.undo_stack.push_group(undo, "Apply to similar");
+            app.set_status_message(&format!("Applied to {count} similar hunks"));
+        }
+        Err(_) => app.set_status_message("Failed to apply resolution"),
+    }
+}
+
 /// Clears the resolution for the current hunk, returning it to unresolved state.
 pub fn clear_current_resolution(app: &mut App) {
     // Get hunk info and current resolution for undo
@@ -54,24 +125,66 @@ pub fn clear_current_resolution(app: &mut App) {
     }
 }
 
+/// Attaches a note to the current hunk's resolution; an empty note clears it.
+///
+/// Notes are kept with the resolution, so the hunk must be resolved first.
+pub fn set_note(app: &mut App, text: &str) {
+    let Some((hunk_id, prev)) = app.session.as_ref().and_then(|session| {
+        session
+            .hunks()
+            .get(app.current_hunk_index)
+            .map(|hunk| (hunk.id, session.resolutions().get(&hunk.id).cloned()))
+    }) else {
+        return;
+    };
+    let Some(prev) = prev else {
+        app.set_status_message("Resolve the hunk before adding a note");
+        return;
+    };
+
+    let mut resolution = prev.clone();
+    resolution.metadata.notes = (!text.is_empty()).then(|| text.to_string());
+    let action = if text.is_empty() {
+        "Clear note"
+    } else {
+        "Add note"
+    };
+
+    if let Some(session) = app.session.as_mut() {
+        match session.set_resolution(hunk_id, resolution) {
+            Ok(()) => {
+                app.undo_stack.push(hunk_id, Some(prev), action);
+                app.set_status_message(action);
+            }
+            Err(_) => app.set_status_message("Failed to update note"),
+        }
+    }
+}
+
 /// Undoes the last resolution action.
 pub fn undo(app: &mut App) {
-    let Some(entry) = app.undo_stack.pop() else {
+    let step = app.undo_stack.pop_step();
+    let Some(action) = step.first().map(|entry| entry.action.clone()) else {
         app.set_status_message("Nothing to undo");
         return;
     };
 
     if let Some(session) = &mut app.session {
-        let result = if let Some(resolution) = entry.previous_resolution {
-            // Restore previous resolution
-            session.set_resolution(entry.hunk_id, resolution)
-        } else {
-            // Was unresolved before
-            session.clear_resolution(entry.hunk_id)
-        };
+        let result = step.into_iter().try_for_each(|entry| {
+            if let Some(proposals) = entry.previous_proposals {
+                // Only offered a resolution; take the offer back
+                session.propose(entry.hunk_id, proposals)
+            } else if let Some(resolution) = entry.previous_resolution {
+                // Restore previous resolution
+                session.set_resolution(entry.hunk_id, resolution)
+            } else {
+                // Was unresolved before
+                session.clear_resolution(entry.hunk_id)
+            }
+        });
 
         match result {
-            Ok(()) => app.set_status_message(&format!("Undid: {}", entry.action)),
+            Ok(()) => app.set_status_message(&format!("Undid: {action}")),
             Err(_) => app.set_status_message("Failed to undo"),
         }
     }
@@ -105,7 +218,15 @@ where
         match session.set_resolution(hunk_id, resolution) {
             Ok(()) => {
                 app.undo_stack.push(hunk_id, prev, action);
-                app.set_status_message(action);
+                let warning = resolution_warning(app, hunk_id);
+                let mut message = match propagate(app, hunk_id) {
+                    Some(suffix) => format!("{action} · {suffix}"),
+                    None => action.to_string(),
+                };
+                if let Some(warning) = warning {
+                    message = format!("{message} · {warning}");
+                }
+                app.set_status_message(&message);
             }
             Err(_) => {
                 app.set_status_message("Failed to apply resolution");
@@ -113,3 +234,65 @@ where
         }
     }
 }
+
+/// Checks a hunk's new resolution for unbalanced brackets and repeated
+/// declarations.
+fn resolution_warning(app: &App, hunk_id: HunkId) -> Option<String> {
+    let session = app.session.as_ref()?;
+    let unbalanced = Syntax::for_path(&session.input().left.path).and_then(|syntax| {
+        let hunk = session.hunks().iter().find(|h| h.id == hunk_id)?;
+        let resolution = session.resolutions().get(&hunk_id)?;
+        check_balance(hunk, &resolution.content, &syntax)
+    });
+    unbalanced.map(|u| format!("unbalanced: {u}")).or_else(|| {
+        session
+            .duplicate_warnings()
+            .into_iter()
+            .find(|w| w.hunk_id == Some(hunk_id))
+            .map(|w| w.message)
+    })
+}
+
+/// Carries a new resolution to hunks with identical content.
+///
+/// Either way the hunks it reached are undone in a single step: applied
+/// copies are cleared, and offered ones withdrawn by restoring each hunk's
+/// earlier proposals.
+///
+/// Returns a status message suffix describing what happened, if anything.
+fn propagate(app: &mut App, hunk_id: HunkId) -> Option<String> {
+    let mode = app.propagation;
+    let session = app.session.as_mut()?;
+    // Proposals the offer will displace, to restore on undo
+    let mut proposals: HashMap<HunkId, Vec<Resolution>> = if mode == PropagationMode::Offer {
+        session
+            .hunks()
+            .iter()
+            .filter_map(|hunk| match &hunk.state {
+                HunkState::Proposed(proposals) => Some((hunk.id, proposals.clone())),
+                _ => None,
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    let targets = session.propagate(hunk_id, mode).ok()?;
+    if targets.is_empty() {
+        return None;
+    }
+
+    let count = targets.len();
+    if mode == PropagationMode::Apply {
+        let undo = targets.into_iter().map(|id| (id, None)).collect();
+        app.undo_stack.push_group(undo, "Propagate resolution");
+        Some(format!("applied to {count} identical hunks"))
+    } else {
+        let undo = targets
+            .into_iter()
+            .map(|id| (id, proposals.remove(&id).unwrap_or_default()))
+            .collect();
+        app.undo_stack
+            .push_proposals_group(undo, "Offer resolution");
+        Some(format!("offered to {count} identical hunks"))
+    }
+}
@@ -0,0 +1,211 @@
+//! Taking the "theirs" side of a hunk from a different revision.
+//!
+//! Sometimes the right answer is "the version from two commits ago". The
+//! caller supplies earlier versions of the file; `R` lists them, and picking
+//! one swaps the current hunk's right side for the same region in that
+//! version. The original content stays available as the first choice.
+
+use weavr_core::HunkId;
+
+use crate::input::{Dialog, InputMode, RevisionChoice, RevisionPicker};
+use crate::{dialog, App};
+
+/// Another version of the file, offered as a source for the right side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlternateRevision {
+    /// Short description shown in the picker, e.g. `abc1234 Fix parser`.
+    pub label: String,
+    /// The full file content at that revision.
+    pub content: String,
+}
+
+/// The right side of a hunk before it was swapped for another revision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplacedRight {
+    /// Label of the revision now shown.
+    pub label: String,
+    /// The original right side.
+    pub original: String,
+}
+
+/// Opens the revision picker for the current hunk.
+pub fn show_picker(app: &mut App) {
+    if app.alternate_revisions.is_empty() {
+        app.set_status_message("No other revisions available");
+        return;
+    }
+    let Some(hunk) = app
+        .session
+        .as_ref()
+        .and_then(|s| s.hunks().get(app.current_hunk_index))
+    else {
+        return;
+    };
+
+    let original = match app.replaced_right.get(&hunk.id) {
+        Some(replaced) => replaced.original.clone(),
+        None => hunk.right.text.clone(),
+    };
+    let mut choices = vec![RevisionChoice {
+        label: "Original theirs".to_string(),
+        region: Some(original),
+    }];
+    choices.extend(app.alternate_revisions.iter().map(|rev| RevisionChoice {
+        label: rev.label.clone(),
+        region: hunk.locate_in(&rev.content),
+    }));
+
+    app.active_dialog = Some(Dialog::RevisionPicker(RevisionPicker {
+        hunk_id: hunk.id,
+        choices,
+        selected: 0,
+    }));
+    app.input_mode = InputMode::Dialog;
+}
+
+/// Moves the picker selection by `delta`, staying within the list.
+pub fn select(app: &mut App, delta: isize) {
+    if let Some(Dialog::RevisionPicker(ref mut picker)) = app.active_dialog {
+        let last = picker.choices.len().saturating_sub(1);
+        picker.selected = picker.selected.saturating_add_signed(delta).min(last);
+    }
+}
+
+/// Replaces the hunk's right side with the selected revision's region.
+pub fn confirm(app: &mut App) {
+    let Some(Dialog::RevisionPicker(picker)) = app.active_dialog.take() else {
+        return;
+    };
+    dialog::close_dialog(app);
+
+    let Some(choice) = picker.choices.get(picker.selected) else {
+        return;
+    };
+    let Some(region) = choice.region.clone() else {
+        app.set_status_message(&format!("Hunk not found in {}", choice.label));
+        return;
+    };
+    replace(
+        app,
+        picker.hunk_id,
+        picker.selected == 0,
+        &choice.label,
+        region,
+    );
+}
+
+/// Swaps the right side, remembering the original for later restores.
+fn replace(app: &mut App, hunk_id: HunkId, restore: bool, label: &str, region: String) {
+    let Some(session) = app.session.as_mut() else {
+        return;
+    };
+    let Some(current) = session.hunks().iter().find(|h| h.id == hunk_id) else {
+        return;
+    };
+    if current.right.text == region {
+        app.set_status_message("Theirs is unchanged");
+        return;
+    }
+
+    let original = current.right.text.clone();
+    if let Err(e) = session.replace_right(hunk_id, region) {
+        app.set_status_message(&format!("Error: {e}"));
+        return;
+    }
+
+    if restore {
+        app.replaced_right.remove(&hunk_id);
+        app.set_status_message("Restored the original theirs; resolve the hunk again");
+    } else {
+        let original = app
+            .replaced_right
+            .remove(&hunk_id)
+            .map_or(original, |replaced| replaced.original);
+        app.replaced_right.insert(
+            hunk_id,
+            ReplacedRight {
+                label: label.to_string(),
+                original,
+            },
+        );
+        app.set_status_message(&format!(
+            "Theirs taken from {label}; resolve the hunk again"
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use weavr_core::MergeSession;
+
+    use super::*;
+
+    const CONTENT: &str = "fn a() {}\n<<<<<<< HEAD\nx = 1\n=======\nx = 2\n>>>>>>> b\nfn z() {}\n";
+
+    fn app() -> App {
+        let session = MergeSession::from_conflicted(CONTENT, PathBuf::from("t.rs")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_alternate_revisions(vec![
+            AlternateRevision {
+                label: "abc1234 Older".to_string(),
+                content: "fn a() {}\nx = 0\nfn z() {}\n".to_string(),
+            },
+            AlternateRevision {
+                label: "def5678 Unrelated".to_string(),
+                content: "other\n".to_string(),
+            },
+        ]);
+        app
+    }
+
+    fn right(app: &App) -> &str {
+        &app.session().unwrap().hunks()[0].right.text
+    }
+
+    #[test]
+    fn picker_requires_revisions() {
+        let session = MergeSession::from_conflicted(CONTENT, PathBuf::from("t.rs")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        show_picker(&mut app);
+        assert!(app.active_dialog().is_none());
+    }
+
+    #[test]
+    fn picking_a_revision_replaces_and_restores_theirs() {
+        let mut app = app();
+        app.resolve_right();
+
+        show_picker(&mut app);
+        let Some(Dialog::RevisionPicker(picker)) = app.active_dialog() else {
+            panic!("expected picker");
+        };
+        assert_eq!(picker.choices.len(), 3);
+        assert_eq!(picker.choices[1].region.as_deref(), Some("x = 0"));
+        assert_eq!(picker.choices[2].region, None);
+
+        select(&mut app, 1);
+        confirm(&mut app);
+        assert_eq!(right(&app), "x = 0");
+        assert!(app.session().unwrap().resolutions().is_empty());
+        assert_eq!(app.replaced_right(HunkId(0)), Some("abc1234 Older"));
+
+        // A revision without the hunk's context leaves theirs alone
+        show_picker(&mut app);
+        select(&mut app, 5);
+        confirm(&mut app);
+        assert_eq!(right(&app), "x = 0");
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Hunk not found in def5678 Unrelated")
+        );
+
+        show_picker(&mut app);
+        confirm(&mut app);
+        assert_eq!(right(&app), "x = 2");
+        assert_eq!(app.replaced_right(HunkId(0)), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! Named resolution templates.
+//!
+//! Long-lived branch merges keep producing the same shapes of resolution:
+//! a feature-flag wrapper around both sides, a deprecation shim. Snippets
+//! are templates for them, inserted with `:snippet <name> [key=value ...]`.
+//!
+//! Templates refer to the hunk with `{{ours}}`, `{{theirs}}`, `{{base}}`,
+//! and `{{current}}` (the current resolution); any other `{{name}}` takes
+//! its value from the command. A placeholder indented on its own line
+//! indents every line of its value.
+
+use weavr_core::Resolution;
+
+use crate::input::SnippetCall;
+use crate::{resolution, App};
+
+/// Renders `template`, looking placeholder values up with `value`.
+///
+/// # Errors
+///
+/// Returns the names of the placeholders without a value if any are missing.
+pub fn render<F>(template: &str, value: F) -> Result<String, Vec<String>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut out = String::new();
+    let mut missing = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        out.push_str(&rest[..start]);
+        rest = &rest[start + 2 + len + 2..];
+
+        let Some(text) = value(name) else {
+            if !missing.iter().any(|m| m == name) {
+                missing.push(name.to_string());
+            }
+            continue;
+        };
+
+        // Carry the placeholder's indentation onto continuation lines
+        let line_start = out.rfind('\n').map_or(0, |i| i + 1);
+        let indent = &out[line_start..];
+        if indent.chars().all(char::is_whitespace) && !indent.is_empty() {
+            let indent = indent.to_string();
+            out.push_str(&text.replace('\n', &format!("\n{indent}")));
+        } else {
+            out.push_str(&text);
+        }
+    }
+    out.push_str(rest);
+
+    if missing.is_empty() {
+        Ok(out.strip_suffix('\n').unwrap_or(&out).to_string())
+    } else {
+        Err(missing)
+    }
+}
+
+/// Runs `:snippet`: lists the snippets without a name, otherwise inserts the
+/// named snippet as the current hunk's resolution.
+pub fn run(app: &mut App, call: &SnippetCall) {
+    if call.name.is_empty() {
+        let message = if app.snippets.is_empty() {
+            "No snippets configured".to_string()
+        } else {
+            let names: Vec<&str> = app.snippets.keys().map(String::as_str).collect();
+            format!("Snippets: {}", names.join(", "))
+        };
+        app.set_status_message(&message);
+        return;
+    }
+
+    let Some(template) = app.snippets.get(&call.name) else {
+        app.set_status_message(&format!("Unknown snippet: {}", call.name));
+        return;
+    };
+    let Some(session) = app.session.as_ref() else {
+        return;
+    };
+    let Some(hunk) = session.hunks().get(app.current_hunk_index) else {
+        return;
+    };
+    let current = session
+        .resolutions()
+        .get(&hunk.id)
+        .map(|r| r.content.clone());
+
+    let rendered = render(template, |name| match name {
+        "ours" => Some(hunk.left.text.clone()),
+        "theirs" => Some(hunk.right.text.clone()),
+        "base" => Some(
+            hunk.base
+                .as_ref()
+                .map(|b| b.text.clone())
+                .unwrap_or_default(),
+        ),
+        "current" => current.clone(),
+        _ => call
+            .args
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone()),
+    });
+
+    match rendered {
+        Ok(content) => {
+            let action = format!("Insert snippet {}", call.name);
+            resolution::apply_resolution(app, &action, |_| Resolution::manual(content));
+        }
+        Err(missing) if missing.iter().any(|m| m == "current") => {
+            app.set_status_message(&format!(
+                "Snippet {} wraps the current resolution; resolve the hunk first",
+                call.name
+            ));
+        }
+        Err(missing) => {
+            let needed: Vec<String> = missing.iter().map(|m| format!("{m}=...")).collect();
+            app.set_status_message(&format!("Snippet {} needs {}", call.name, needed.join(" ")));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use weavr_core::MergeSession;
+
+    use super::*;
+
+    const FLAG: &str = "if flag(\"{{name}}\") {\n    {{theirs}}\n} else {\n    {{ours}}\n}\n";
+
+    fn values(name: &str) -> Option<String> {
+        match name {
+            "ours" => Some("a();\nb();".to_string()),
+            "theirs" => Some("c();".to_string()),
+            "name" => Some("new_parser".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn renders_placeholders_with_indentation() {
+        assert_eq!(
+            render(FLAG, values).unwrap(),
+            "if flag(\"new_parser\") {\n    c();\n} else {\n    a();\n    b();\n}"
+        );
+        assert_eq!(render("x {{ ours }} y", values).unwrap(), "x a();\nb(); y");
+        assert_eq!(render("{{unclosed", values).unwrap(), "{{unclosed");
+    }
+
+    #[test]
+    fn reports_missing_placeholders_once() {
+        assert_eq!(
+            render("{{a}} {{b}} {{a}} {{ours}}", values),
+            Err(vec!["a".to_string(), "b".to_string()])
+        );
+    }
+
+    #[test]
+    fn snippet_command_inserts_resolution() {
+        let content = "<<<<<<< HEAD\nold();\n=======\nnew();\n>>>>>>> b\n";
+        let session = MergeSession::from_conflicted(content, PathBuf::from("a.rs")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_snippets(BTreeMap::from([("flag".to_string(), FLAG.to_string())]));
+
+        let mut call = SnippetCall {
+            name: "flag".to_string(),
+            args: Vec::new(),
+        };
+        run(&mut app, &call);
+        assert!(app.session().unwrap().resolutions().is_empty());
+        assert_eq!(
+            app.status_message().map(|(m, _)| m.as_str()),
+            Some("Snippet flag needs name=...")
+        );
+
+        call.args.push(("name".to_string(), "beta".to_string()));
+        run(&mut app, &call);
+        let resolutions = app.session().unwrap().resolutions();
+        assert_eq!(
+            resolutions.values().next().unwrap().content,
+            "if flag(\"beta\") {\n    new();\n} else {\n    old();\n}"
+        );
+    }
+}
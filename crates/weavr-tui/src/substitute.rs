@@ -0,0 +1,199 @@
+//! Search-and-replace over resolutions.
+//!
+//! `:s/old/new/` changes the current hunk's resolution and `:%s/old/new/`
+//! every resolution in the file. The changes are previewed in a dialog first
+//! and applied as a single undo step once confirmed.
+
+use weavr_core::{HunkId, HunkState, Resolution, ResolutionStrategyKind};
+
+use crate::dialog;
+use crate::input::{Dialog, InputMode, SubstituteChange, SubstitutePreview, Substitution};
+use crate::App;
+
+/// Computes the changes a substitution would make and opens the preview.
+pub fn preview(app: &mut App, substitution: Substitution) {
+    let Some(session) = app.session.as_ref() else {
+        return;
+    };
+
+    let candidates: Vec<_> = if substitution.all_hunks {
+        session.hunks().iter().collect()
+    } else {
+        let Some(hunk) = session.hunks().get(app.current_hunk_index) else {
+            return;
+        };
+        if !matches!(hunk.state, HunkState::Resolved(_)) {
+            app.set_status_message("Resolve the hunk before substituting in it");
+            return;
+        }
+        vec![hunk]
+    };
+
+    let changes: Vec<SubstituteChange> = candidates
+        .into_iter()
+        .filter_map(|hunk| match &hunk.state {
+            HunkState::Resolved(resolution) => substitute(&substitution, resolution, hunk.id),
+            _ => None,
+        })
+        .collect();
+
+    if changes.is_empty() {
+        app.set_status_message(&format!("Pattern not found: {}", substitution.pattern));
+        return;
+    }
+
+    app.active_dialog = Some(Dialog::Substitute(SubstitutePreview {
+        substitution,
+        changes,
+    }));
+    app.input_mode = InputMode::Dialog;
+}
+
+/// Applies the previewed substitution.
+pub fn confirm(app: &mut App) {
+    let Some(Dialog::Substitute(preview)) = app.active_dialog.take() else {
+        return;
+    };
+    dialog::close_dialog(app);
+
+    let Some(session) = app.session.as_mut() else {
+        return;
+    };
+
+    let mut undo = Vec::new();
+    let mut replaced = 0;
+    for change in preview.changes {
+        let Some(previous) = session.resolutions().get(&change.hunk_id).cloned() else {
+            continue;
+        };
+        let resolution = Resolution {
+            kind: ResolutionStrategyKind::Manual,
+            content: change.content,
+            metadata: previous.metadata.clone(),
+        };
+        if session.set_resolution(change.hunk_id, resolution).is_ok() {
+            replaced += change.count;
+            undo.push((change.hunk_id, Some(previous)));
+        }
+    }
+
+    let hunks = undo.len();
+    app.undo_stack.push_group(undo, "Substitute");
+    app.set_status_message(&format!("Replaced {replaced} occurrences in {hunks} hunks"));
+}
+
+/// Applies a substitution to one resolution, if it changes anything.
+fn substitute(
+    substitution: &Substitution,
+    resolution: &Resolution,
+    hunk_id: HunkId,
+) -> Option<SubstituteChange> {
+    let mut content = String::with_capacity(resolution.content.len());
+    let mut lines = Vec::new();
+    let mut count = 0;
+
+    for line in resolution.content.split_inclusive('\n') {
+        let (text, ending) = match line.strip_suffix('\n') {
+            Some(text) => (text, "\n"),
+            None => (line, ""),
+        };
+        let (replaced, n) = substitution.apply_line(text);
+        if n > 0 {
+            count += n;
+            lines.push((text.to_string(), replaced.clone()));
+        }
+        content.push_str(&replaced);
+        content.push_str(ending);
+    }
+
+    (count > 0).then_some(SubstituteChange {
+        hunk_id,
+        count,
+        lines,
+        content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use weavr_core::MergeSession;
+
+    use super::*;
+
+    const CONTENT: &str = "<<<<<<< HEAD\nold_name(1);\n=======\nnew_call(old_name);\n>>>>>>> f\nmid\n<<<<<<< HEAD\nlet x = old_name;\n=======\nlet y = 2;\n>>>>>>> f";
+
+    fn app() -> App {
+        let session = MergeSession::from_conflicted(CONTENT, PathBuf::from("t.rs")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app
+    }
+
+    fn command(app: &mut App, text: &str) {
+        app.enter_command_mode();
+        text.chars().for_each(|c| app.append_to_command(c));
+        app.execute_command();
+    }
+
+    #[test]
+    fn substitute_requires_resolved_current_hunk() {
+        let mut app = app();
+        command(&mut app, "s/old_name/renamed/");
+        assert!(app.active_dialog().is_none());
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Resolve the hunk before substituting in it")
+        );
+    }
+
+    #[test]
+    fn substitute_all_previews_then_applies_as_one_step() {
+        let mut app = app();
+        app.resolve_right();
+        app.next_hunk();
+        app.resolve_left();
+
+        command(&mut app, "%s/old_name/renamed/g");
+        let Some(Dialog::Substitute(preview)) = app.active_dialog() else {
+            panic!("expected preview dialog");
+        };
+        assert_eq!(preview.changes.len(), 2);
+        assert_eq!(
+            preview.changes[0].lines,
+            vec![(
+                "new_call(old_name);".to_string(),
+                "new_call(renamed);".to_string()
+            )]
+        );
+        // Nothing changes until confirmed
+        assert_eq!(
+            app.session().unwrap().resolutions()[&HunkId(1)].content,
+            "let x = old_name;"
+        );
+
+        confirm(&mut app);
+        let resolutions = app.session().unwrap().resolutions();
+        assert_eq!(resolutions[&HunkId(0)].content, "new_call(renamed);");
+        assert_eq!(resolutions[&HunkId(1)].content, "let x = renamed;");
+        assert_eq!(resolutions[&HunkId(1)].kind, ResolutionStrategyKind::Manual);
+
+        app.undo();
+        let resolutions = app.session().unwrap().resolutions();
+        assert_eq!(resolutions[&HunkId(0)].content, "new_call(old_name);");
+        assert_eq!(resolutions[&HunkId(1)].content, "let x = old_name;");
+    }
+
+    #[test]
+    fn substitute_reports_missing_pattern() {
+        let mut app = app();
+        app.resolve_left();
+        command(&mut app, "s/absent/x/");
+        assert!(app.active_dialog().is_none());
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Pattern not found: absent")
+        );
+    }
+}
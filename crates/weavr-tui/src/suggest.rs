@@ -0,0 +1,269 @@
+//! Asking an AI provider how to resolve the current hunk.
+//!
+//! `s` sends the hunk's sides, base, and surrounding lines to the configured
+//! provider on a background thread, and the title bar shows a spinner while
+//! it works. The answer opens in a dialog to accept, edit in `$EDITOR`
+//! first, or reject; nothing is resolved without the user's say.
+
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Instant;
+
+use weavr_ai::{AiError, Suggestion, SuggestionRequest};
+use weavr_core::HunkId;
+
+use crate::input::{Dialog, InputMode, SuggestionState};
+use crate::{dialog, resolution, App};
+
+/// Spinner frames, advanced every 100ms.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// A suggestion being worked on by the provider.
+#[derive(Debug)]
+pub(crate) struct PendingSuggestion {
+    /// The hunk the suggestion is for.
+    hunk_id: HunkId,
+    /// Name of the provider asked.
+    provider: String,
+    /// When the provider was asked.
+    started: Instant,
+    /// Receives the provider's answer.
+    receiver: Receiver<Result<Suggestion, AiError>>,
+}
+
+/// Asks the provider for a suggestion for the current hunk.
+pub fn start(app: &mut App) {
+    let Some(provider) = app.suggestion_provider.clone() else {
+        app.set_status_message("No AI provider configured");
+        return;
+    };
+    if app.pending_suggestion.is_some() {
+        app.set_status_message("Already waiting for a suggestion");
+        return;
+    }
+    let Some(session) = app.session.as_ref() else {
+        return;
+    };
+    let Some(hunk) = session.hunks().get(app.current_hunk_index) else {
+        app.set_status_message("No hunk to suggest for");
+        return;
+    };
+
+    let request = SuggestionRequest::from_hunk(&session.input().left.path, hunk);
+    let (sender, receiver) = mpsc::channel();
+    let name = provider.name().to_string();
+    std::thread::spawn(move || {
+        // The app may be gone by the time the answer arrives
+        let _ = sender.send(provider.suggest(&request));
+    });
+    app.pending_suggestion = Some(PendingSuggestion {
+        hunk_id: hunk.id,
+        provider: name.clone(),
+        started: Instant::now(),
+        receiver,
+    });
+    app.set_status_message(&format!("Asking {name} for a suggestion"));
+}
+
+/// Shows the provider's answer once it arrives.
+///
+/// Called on every pass of the event loop. The answer waits while another
+/// dialog or the command line is open.
+pub fn poll(app: &mut App) {
+    if app.input_mode != InputMode::Normal {
+        return;
+    }
+    let Some(pending) = app.pending_suggestion.as_ref() else {
+        return;
+    };
+    let result = match pending.receiver.try_recv() {
+        Ok(result) => result,
+        Err(TryRecvError::Empty) => return,
+        Err(TryRecvError::Disconnected) => Err(AiError::Http("provider stopped".to_string())),
+    };
+    let Some(pending) = app.pending_suggestion.take() else {
+        return;
+    };
+
+    match result {
+        Ok(suggestion) => {
+            let Some(index) = app
+                .session
+                .as_ref()
+                .and_then(|s| s.hunks().iter().position(|h| h.id == pending.hunk_id))
+            else {
+                return;
+            };
+            app.go_to_hunk(index);
+            app.active_dialog = Some(Dialog::Suggestion(SuggestionState {
+                hunk_id: pending.hunk_id,
+                resolution: suggestion.into_resolution(&pending.provider),
+            }));
+            app.input_mode = InputMode::Dialog;
+        }
+        Err(e) => app.set_status_message(&format!("Suggestion failed: {e}")),
+    }
+}
+
+/// Returns the spinner and provider name while a suggestion is pending.
+#[must_use]
+pub fn progress(app: &App) -> Option<String> {
+    let pending = app.pending_suggestion.as_ref()?;
+    let frame = pending.started.elapsed().as_millis() / 100;
+    // The modulo keeps the index in range, so the cast cannot truncate
+    #[allow(clippy::cast_possible_truncation)]
+    let spinner = SPINNER[(frame % SPINNER.len() as u128) as usize];
+    Some(format!("{spinner} asking {}", pending.provider))
+}
+
+/// Takes the suggestion dialog's state, closing it and focusing its hunk.
+fn take(app: &mut App) -> Option<SuggestionState> {
+    let Some(Dialog::Suggestion(state)) = app.active_dialog.take() else {
+        return None;
+    };
+    dialog::close_dialog(app);
+    let index = app
+        .session
+        .as_ref()?
+        .hunks()
+        .iter()
+        .position(|h| h.id == state.hunk_id)?;
+    app.go_to_hunk(index);
+    Some(state)
+}
+
+/// Resolves the hunk with the suggestion.
+pub fn accept(app: &mut App) {
+    if let Some(state) = take(app) {
+        resolution::apply_resolution(app, "Accept AI suggestion", |_hunk| state.resolution);
+    }
+}
+
+/// Opens the suggestion in the external editor; saving resolves the hunk
+/// with the edited content. Returns true if the editor should be launched.
+pub fn edit(app: &mut App) -> bool {
+    let Some(state) = take(app) else {
+        return false;
+    };
+    app.editor_pending = Some(state.resolution.content);
+    true
+}
+
+/// Dismisses the suggestion.
+pub fn reject(app: &mut App) {
+    if take(app).is_some() {
+        app.set_status_message("Suggestion rejected");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use weavr_ai::SuggestionProvider;
+    use weavr_core::{MergeSession, ResolutionSource};
+
+    use super::*;
+
+    const CONTENT: &str =
+        "<<<<<<< HEAD\nx = 1\n=======\nx = 2\n>>>>>>> b\nmid\n<<<<<<< HEAD\ny\n=======\nz\n>>>>>>> b\n";
+
+    #[derive(Debug)]
+    struct Fixed(Result<&'static str, &'static str>);
+
+    impl SuggestionProvider for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn suggest(&self, request: &SuggestionRequest) -> Result<Suggestion, AiError> {
+            assert_eq!(request.path, PathBuf::from("t.py"));
+            self.0
+                .map(|content| Suggestion {
+                    content: content.to_string(),
+                    confidence: Some(0.9),
+                    explanation: None,
+                })
+                .map_err(|e| AiError::Http(e.to_string()))
+        }
+    }
+
+    fn app(provider: Fixed) -> App {
+        let session = MergeSession::from_conflicted(CONTENT, PathBuf::from("t.py")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_suggestion_provider(Arc::new(provider));
+        app
+    }
+
+    /// Polls until the provider's answer has been handled.
+    fn wait(app: &mut App) {
+        for _ in 0..200 {
+            poll(app);
+            if app.pending_suggestion.is_none() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("no answer from the provider");
+    }
+
+    #[test]
+    fn suggestion_requires_a_provider() {
+        let session = MergeSession::from_conflicted(CONTENT, PathBuf::from("t.py")).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        start(&mut app);
+        assert!(app.pending_suggestion.is_none());
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("No AI provider configured")
+        );
+    }
+
+    #[test]
+    fn accepted_suggestion_resolves_its_hunk() {
+        let mut app = app(Fixed(Ok("x = 3")));
+        start(&mut app);
+        assert!(progress(&app).unwrap().ends_with("asking fixed"));
+        // Moving on while the provider works still resolves the asked hunk
+        app.next_hunk();
+        wait(&mut app);
+        assert!(matches!(app.active_dialog(), Some(Dialog::Suggestion(_))));
+        assert_eq!(app.current_hunk_index(), 0);
+
+        accept(&mut app);
+        let resolution = &app.session().unwrap().resolutions()[&HunkId(0)];
+        assert_eq!(resolution.content, "x = 3");
+        assert_eq!(resolution.metadata.source, ResolutionSource::Ai);
+        assert_eq!(resolution.metadata.notes.as_deref(), Some("90% confident"));
+        assert!(app.active_dialog().is_none());
+    }
+
+    #[test]
+    fn rejected_or_edited_suggestion_leaves_the_hunk_to_the_user() {
+        let mut app = app(Fixed(Ok("x = 3")));
+        start(&mut app);
+        wait(&mut app);
+        reject(&mut app);
+        assert!(app.session().unwrap().resolutions().is_empty());
+
+        start(&mut app);
+        wait(&mut app);
+        assert!(edit(&mut app));
+        assert_eq!(app.take_editor_pending().as_deref(), Some("x = 3"));
+    }
+
+    #[test]
+    fn failed_suggestion_is_reported() {
+        let mut app = app(Fixed(Err("connection refused")));
+        start(&mut app);
+        wait(&mut app);
+        assert!(app.active_dialog().is_none());
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Suggestion failed: could not reach AI provider: connection refused")
+        );
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! The layout consists of:
 //! - Title bar (1 line)
-//! - Top row: Left and Right panes side by side
+//! - Top row: Left and Right panes side by side, with the Base pane between
+//!   them when shown
 //! - Bottom row: Result pane
 //! - Status bar (1 line)
 
@@ -19,6 +20,8 @@ pub struct PaneAreas {
     pub left_pane: Rect,
     /// Right pane (theirs).
     pub right_pane: Rect,
+    /// Base pane (common ancestor), if shown.
+    pub base_pane: Option<Rect>,
     /// Result pane (merged output).
     pub result_pane: Rect,
     /// Status bar area at the bottom.
@@ -27,7 +30,8 @@ pub struct PaneAreas {
 
 /// Calculates the layout areas for the given terminal size and configuration.
 ///
-/// The `config` parameter controls the top/bottom split ratio (default 60/40).
+/// The `config` parameter controls the top/bottom split ratio (default 60/40),
+/// and whether the top row splits in three to show the base.
 ///
 /// ```text
 /// +------------------------------------------+
@@ -59,14 +63,21 @@ pub fn calculate_layout(area: Rect, config: &LayoutConfig) -> PaneAreas {
     ])
     .areas(main_area);
 
-    // Horizontal split for top row: left, right (always 50/50)
-    let [left_pane, right_pane] =
-        Layout::horizontal([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)]).areas(top_row);
+    // Horizontal split for top row: left, right (50/50), or thirds with base
+    let (left_pane, base_pane, right_pane) = if config.show_base {
+        let [left, base, right] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(top_row);
+        (left, Some(base), right)
+    } else {
+        let [left, right] =
+            Layout::horizontal([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)]).areas(top_row);
+        (left, None, right)
+    };
 
     PaneAreas {
         title_bar,
         left_pane,
         right_pane,
+        base_pane,
         result_pane,
         status_bar,
     }
@@ -137,6 +148,24 @@ mod tests {
         assert_eq!(areas.result_pane.width, area.width);
     }
 
+    #[test]
+    fn base_pane_sits_between_left_and_right() {
+        let area = Rect::new(0, 0, 90, 24);
+        assert!(calculate_layout(area, &default_config())
+            .base_pane
+            .is_none());
+
+        let config = LayoutConfig {
+            show_base: true,
+            ..LayoutConfig::default()
+        };
+        let areas = calculate_layout(area, &config);
+        let base = areas.base_pane.unwrap();
+        assert_eq!(base.y, areas.left_pane.y);
+        assert!(areas.left_pane.x < base.x && base.x < areas.right_pane.x);
+        assert_eq!(base.width, 30);
+    }
+
     #[test]
     fn handles_minimum_terminal_size() {
         // Very small terminal
@@ -152,6 +181,7 @@ mod tests {
         let area = Rect::new(0, 0, 80, 24);
         let config = LayoutConfig {
             top_ratio_percent: 70,
+            ..LayoutConfig::default()
         };
         let areas = calculate_layout(area, &config);
 
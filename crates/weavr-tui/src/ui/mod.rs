@@ -8,14 +8,41 @@ mod pane;
 
 pub use layout::{calculate_layout, PaneAreas};
 
+use ratatui::layout::{Constraint, Layout};
 use ratatui::Frame;
 
 use crate::input::Dialog;
 use crate::App;
 
+/// Height of the guide panel shown during scripted sessions.
+const GUIDE_HEIGHT: u16 = 4;
+
+/// Height of the test output panel.
+const TEST_PANEL_HEIGHT: u16 = 10;
+
 /// Renders the entire UI to the frame.
 pub fn draw(frame: &mut Frame, app: &App) {
-    let areas = calculate_layout(frame.area(), app.layout_config());
+    // Reserve space for the test output panel below everything else
+    let (main_area, test_area) = if app.test_run().is_some() {
+        let [main, test] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(TEST_PANEL_HEIGHT)])
+                .areas(frame.area());
+        (main, Some(test))
+    } else {
+        (frame.area(), None)
+    };
+
+    // ...and for the guide panel above it
+    let (main_area, guide_area) = if app.guide().is_some() {
+        let [main, guide] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(GUIDE_HEIGHT)])
+                .areas(main_area);
+        (main, Some(guide))
+    } else {
+        (main_area, None)
+    };
+
+    let areas = calculate_layout(main_area, app.layout_config());
 
     // Title bar with hunk counter
     pane::render_title_bar(frame, areas.title_bar, app);
@@ -23,18 +50,77 @@ pub fn draw(frame: &mut Frame, app: &App) {
     // Three panes with full document content
     pane::render_left_pane(frame, areas.left_pane, app);
     pane::render_right_pane(frame, areas.right_pane, app);
+    if let Some(area) = areas.base_pane {
+        pane::render_base_pane(frame, area, app);
+    }
     pane::render_result_pane(frame, areas.result_pane, app);
 
     // Status bar with context-sensitive help
     pane::render_status_bar(frame, areas.status_bar, app);
 
+    if let Some(area) = guide_area {
+        pane::render_guide(frame, area, app);
+    }
+
+    if let Some(area) = test_area {
+        pane::render_test_panel(frame, area, app);
+    }
+
     // Render overlay dialogs on top
     if let Some(dialog) = app.active_dialog() {
         match dialog {
-            Dialog::Help => overlay::render_help_overlay(frame, frame.area(), app.theme()),
+            Dialog::Help => {
+                overlay::render_help_overlay(frame, frame.area(), app.theme(), app.quick_actions());
+            }
             Dialog::AcceptBothOptions(state) => {
                 overlay::render_accept_both_dialog(frame, frame.area(), app.theme(), state);
             }
+            Dialog::HeadCompare(state) => {
+                if let (Some(head), Some(session)) = (app.head_version(), app.session()) {
+                    overlay::render_head_compare_dialog(
+                        frame,
+                        frame.area(),
+                        app.theme(),
+                        head,
+                        &session.preview(),
+                        state,
+                    );
+                }
+            }
+            Dialog::ConfirmWrite(state) => {
+                if let Some(session) = app.session() {
+                    overlay::render_confirm_write_dialog(
+                        frame,
+                        frame.area(),
+                        app.theme(),
+                        session.conflicted(),
+                        &session.preview(),
+                        state,
+                    );
+                }
+            }
+            Dialog::Overview(state) => {
+                if let Some(tree) = app.overview() {
+                    let current = app.session().map(|s| s.input().left.path.as_path());
+                    overlay::render_overview_dialog(
+                        frame,
+                        frame.area(),
+                        app.theme(),
+                        tree,
+                        current,
+                        state,
+                    );
+                }
+            }
+            Dialog::RevisionPicker(picker) => {
+                overlay::render_revision_picker(frame, frame.area(), app.theme(), picker);
+            }
+            Dialog::Suggestion(state) => {
+                overlay::render_suggestion_dialog(frame, frame.area(), app.theme(), state);
+            }
+            Dialog::Substitute(preview) => {
+                overlay::render_substitute_dialog(frame, frame.area(), app.theme(), preview);
+            }
         }
     }
 }
@@ -83,6 +169,24 @@ mod tests {
         assert!(last_line.contains("pane"));
     }
 
+    #[test]
+    fn draw_shows_guide_instruction() {
+        use crate::guide::{Guide, GuideStep};
+
+        let mut terminal = create_test_terminal();
+        let mut app = App::new();
+        app.set_guide(Guide::new(vec![GuideStep::new("Press j", |_| false)]));
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = (20..24)
+            .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+            .map(|pos| buffer.cell(pos).unwrap().symbol().to_string())
+            .collect();
+        assert!(text.contains("Tutorial 1/1"));
+        assert!(text.contains("Press j"));
+    }
+
     #[test]
     fn draw_with_different_themes() {
         let mut terminal = create_test_terminal();
@@ -99,4 +203,43 @@ mod tests {
         let app_mocha = App::with_theme(ThemeName::CatppuccinMocha);
         terminal.draw(|frame| draw(frame, &app_mocha)).unwrap();
     }
+
+    #[test]
+    fn draw_overview_marks_current_file() {
+        use std::path::{Path, PathBuf};
+        use weavr_core::{ConflictStats, ConflictTree, MergeSession};
+
+        let stats = ConflictStats {
+            files: 1,
+            hunks: 2,
+            lines: 8,
+        };
+        let tree = ConflictTree::build([
+            (Path::new("src/lib.rs"), stats),
+            (Path::new("src/main.rs"), stats),
+        ]);
+        let content = "<<<<<<< HEAD\na\n=======\nb\n>>>>>>> x";
+        let session =
+            MergeSession::from_conflicted(content, PathBuf::from("./src/main.rs")).unwrap();
+
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_overview(tree);
+        app.show_overview();
+
+        let mut terminal = create_test_terminal();
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.cell((x, y)).unwrap().symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(rows.iter().any(|r| r.contains("▶   main.rs")));
+        assert!(rows
+            .iter()
+            .any(|r| r.contains("    lib.rs") && !r.contains('▶')));
+    }
 }
@@ -11,29 +11,62 @@ use ratatui::{
     Frame,
 };
 
-use crate::input::AcceptBothOptionsState;
+use crate::diff::compute_unified_lines;
+use crate::input::{
+    AcceptBothOptionsState, ConfirmWriteState, HeadCompareState, OverviewState, RevisionPicker,
+    SubstitutePreview, SuggestionState,
+};
+use crate::quick::QuickAction;
 use crate::theme::Theme;
-use weavr_core::BothOrder;
+use similar::ChangeTag;
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+use weavr_core::{BothOrder, ConflictTree, ResolutionStrategyKind};
 
-/// Renders a centered help overlay showing keybindings.
-pub fn render_help_overlay(frame: &mut Frame, area: Rect, theme: &Theme) {
+/// Renders a centered help overlay showing keybindings, including the
+/// configured quick actions.
+pub fn render_help_overlay(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    quick_actions: &BTreeMap<char, QuickAction>,
+) {
     let dialog_area = centered_rect(60, 70, area);
 
     // Clear the background
     frame.render_widget(Clear, dialog_area);
 
-    let help_lines = vec![
+    let mut help_lines = vec![
         Line::from(Span::styled(
             "=== Resolution ===",
             Style::default().add_modifier(Modifier::BOLD),
         )),
         Line::from("  o       Accept ours (left)"),
         Line::from("  t       Accept theirs (right)"),
+        Line::from("  c       Accept base (common ancestor)"),
         Line::from("  b       Accept both (default)"),
         Line::from("  B       Accept both (options)"),
+        Line::from("  a       Accept suggestion"),
+        Line::from("  A       Apply to similar hunks"),
+        Line::from("  H       Compare with pre-merge HEAD"),
+        Line::from("  O       Conflict overview"),
+        Line::from("  R       Theirs from another revision"),
+        Line::from("  s       Ask AI for a suggestion"),
+        Line::from("  T       Run test command"),
+        Line::from("  C       Run check command"),
         Line::from("  e       Edit in $EDITOR"),
+        Line::from("  Ctrl+o  Accept ours, then edit"),
+        Line::from("  Ctrl+t  Accept theirs, then edit"),
+        Line::from("  E       Open in diff tool"),
         Line::from("  x       Clear resolution"),
         Line::from("  u       Undo last action"),
+    ];
+    help_lines.extend(
+        quick_actions
+            .iter()
+            .map(|(key, action)| Line::from(format!("  {key}       {action}"))),
+    );
+    help_lines.extend([
         Line::from(""),
         Line::from(Span::styled(
             "=== Navigation ===",
@@ -53,6 +86,10 @@ pub fn render_help_overlay(frame: &mut Frame, area: Rect, theme: &Theme) {
         Line::from("  Ctrl+u  Scroll up"),
         Line::from("  PgDn    Page down"),
         Line::from("  PgUp    Page up"),
+        Line::from("  h/l     Scroll left/right"),
+        Line::from("  p       Pretty-print sides (view only)"),
+        Line::from("  m       Show/hide base (merge base)"),
+        Line::from("  +/-     More/less context around hunks"),
         Line::from(""),
         Line::from(Span::styled(
             "=== Commands ===",
@@ -62,12 +99,15 @@ pub fn render_help_overlay(frame: &mut Frame, area: Rect, theme: &Theme) {
         Line::from("  :q      Quit"),
         Line::from("  :wq     Save and quit"),
         Line::from("  :q!     Force quit"),
+        Line::from("  :note   Note on resolved hunk"),
+        Line::from("  :s/a/b/ Replace in hunk (%s: all)"),
+        Line::from("  :snippet Insert a resolution template"),
         Line::from(""),
         Line::from(Span::styled(
             "Press ?, q, or Esc to close",
             Style::default().fg(theme.base.muted),
         )),
-    ];
+    ]);
 
     let block = Block::default()
         .title(" Help ")
@@ -172,6 +212,399 @@ pub fn render_accept_both_dialog(
     frame.render_widget(paragraph, dialog_area);
 }
 
+/// Renders the diff from the pre-merge version to the current result.
+pub fn render_head_compare_dialog(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    head: &str,
+    merged: &str,
+    state: &HeadCompareState,
+) {
+    let dialog_area = centered_rect(80, 80, area);
+
+    // Clear the background
+    frame.render_widget(Clear, dialog_area);
+
+    let mut lines = diff_lines(
+        theme,
+        head,
+        merged,
+        "  No changes from the pre-merge version",
+    );
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  [j/k] scroll   [Esc/H] close",
+        Style::default().fg(theme.base.muted),
+    )));
+
+    let block = Block::default()
+        .title(" Pre-merge HEAD → current result ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.ui.border_focused))
+        .style(Style::default().bg(theme.base.background));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((state.scroll, 0))
+        .style(Style::default().fg(theme.base.foreground));
+
+    frame.render_widget(paragraph, dialog_area);
+}
+
+/// Renders the diff from the conflicted file to the result, confirmed before
+/// the result is written.
+pub fn render_confirm_write_dialog(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    conflicted: &str,
+    merged: &str,
+    state: &ConfirmWriteState,
+) {
+    let dialog_area = centered_rect(80, 80, area);
+
+    // Clear the background
+    frame.render_widget(Clear, dialog_area);
+
+    let mut lines = diff_lines(theme, conflicted, merged, "  No changes");
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  [y/Enter] write   [n/Esc] keep editing   [j/k] scroll",
+        Style::default().fg(theme.base.muted),
+    )));
+
+    let block = Block::default()
+        .title(" Write result? Conflicted file → result ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.ui.border_focused))
+        .style(Style::default().bg(theme.base.background));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((state.scroll, 0))
+        .style(Style::default().fg(theme.base.foreground));
+
+    frame.render_widget(paragraph, dialog_area);
+}
+
+/// Builds the lines of a unified diff from `old` to `new`, or a single
+/// `empty` line when they are the same.
+fn diff_lines(theme: &Theme, old: &str, new: &str, empty: &str) -> Vec<Line<'static>> {
+    let diff = compute_unified_lines(old, new, 3);
+    if diff.is_empty() {
+        return vec![Line::from(Span::styled(
+            empty.to_string(),
+            Style::default().fg(theme.base.muted),
+        ))];
+    }
+    diff.into_iter()
+        .map(|line| match line.tag {
+            ChangeTag::Delete => {
+                Line::from(Span::styled(format!("- {}", line.text), theme.diff.removed))
+            }
+            ChangeTag::Insert => {
+                Line::from(Span::styled(format!("+ {}", line.text), theme.diff.added))
+            }
+            ChangeTag::Equal => Line::from(format!("  {}", line.text)),
+        })
+        .collect()
+}
+
+/// Number of lines of the selected region previewed in the revision picker.
+const REVISION_PREVIEW_LINES: usize = 12;
+
+/// Renders the picker for taking a hunk's right side from another revision.
+pub fn render_revision_picker(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    picker: &RevisionPicker,
+) {
+    let dialog_area = centered_rect(70, 70, area);
+
+    // Clear the background
+    frame.render_widget(Clear, dialog_area);
+
+    let mut lines = vec![Line::from("")];
+    for (index, choice) in picker.choices.iter().enumerate() {
+        let selected = index == picker.selected;
+        let marker = if selected { "> " } else { "  " };
+        let style = match (&choice.region, selected) {
+            (None, _) => Style::default().fg(theme.base.muted),
+            (Some(_), true) => theme.ui.selection,
+            (Some(_), false) => Style::default(),
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{marker}{}", choice.label),
+            style,
+        )));
+    }
+
+    lines.push(Line::from(""));
+    match picker
+        .choices
+        .get(picker.selected)
+        .and_then(|c| c.region.as_ref())
+    {
+        Some(region) => {
+            for text in region.lines().take(REVISION_PREVIEW_LINES) {
+                lines.push(Line::from(Span::styled(
+                    format!("    {text}"),
+                    theme.conflict.right,
+                )));
+            }
+            if region.lines().count() > REVISION_PREVIEW_LINES {
+                lines.push(Line::from(Span::styled(
+                    "    ⋯",
+                    Style::default().fg(theme.base.muted),
+                )));
+            }
+        }
+        None => lines.push(Line::from(Span::styled(
+            "    Hunk not found in this revision",
+            Style::default().fg(theme.base.muted),
+        ))),
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  [j/k] select   [Enter] use as theirs   [Esc] cancel",
+        Style::default().fg(theme.base.muted),
+    )));
+
+    let block = Block::default()
+        .title(format!(
+            " Theirs for hunk {} from... ",
+            picker.hunk_id.0 + 1
+        ))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.ui.border_focused))
+        .style(Style::default().bg(theme.base.background));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.base.foreground));
+
+    frame.render_widget(paragraph, dialog_area);
+}
+
+/// Renders a resolution suggested by the AI provider.
+pub fn render_suggestion_dialog(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    state: &SuggestionState,
+) {
+    let dialog_area = centered_rect(70, 70, area);
+
+    // Clear the background
+    frame.render_widget(Clear, dialog_area);
+
+    let mut lines = vec![Line::from("")];
+    if let Some(notes) = &state.resolution.metadata.notes {
+        lines.push(Line::from(Span::styled(
+            format!("  {notes}"),
+            Style::default().fg(theme.base.accent),
+        )));
+        lines.push(Line::from(""));
+    }
+    for text in state.resolution.content.lines() {
+        lines.push(Line::from(Span::styled(
+            format!("    {text}"),
+            theme.conflict.resolved,
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  [y/Enter] accept   [e] edit first   [n/Esc] reject",
+        Style::default().fg(theme.base.muted),
+    )));
+
+    let provider = match &state.resolution.kind {
+        ResolutionStrategyKind::AiSuggested { provider } => provider.as_str(),
+        _ => "AI",
+    };
+    let block = Block::default()
+        .title(format!(
+            " Suggestion from {provider} for hunk {} ",
+            state.hunk_id.0 + 1
+        ))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.ui.border_focused))
+        .style(Style::default().bg(theme.base.background));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.base.foreground));
+
+    frame.render_widget(paragraph, dialog_area);
+}
+
+/// Width of the heat bar in the overview.
+const HEAT_BAR_WIDTH: usize = 16;
+
+/// Renders the conflict heatmap of the whole merge.
+///
+/// Rows are coloured by their share of the busiest row's conflicting lines,
+/// and the file being resolved is marked.
+pub fn render_overview_dialog(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    tree: &ConflictTree,
+    current: Option<&Path>,
+    state: &OverviewState,
+) {
+    let dialog_area = centered_rect(70, 80, area);
+
+    // Clear the background
+    frame.render_widget(Clear, dialog_area);
+
+    let current = current.map(normalized);
+    let rows = tree.rows();
+    let max_lines = rows.iter().map(|r| r.stats.lines).max().unwrap_or(0).max(1);
+    let width = rows
+        .iter()
+        .map(|r| r.depth * 2 + r.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    // Directory prefix at each depth, to recognise the current file
+    let mut prefixes: Vec<String> = Vec::new();
+    let mut lines = Vec::with_capacity(rows.len() + 3);
+    for row in &rows {
+        prefixes.truncate(row.depth);
+        let parent = prefixes.last().cloned().unwrap_or_default();
+        let path = format!("{parent}{}", row.name);
+        let is_current = !row.is_dir && current.as_deref() == Some(path.as_str());
+        if row.is_dir {
+            prefixes.push(path);
+        }
+
+        let heat = row.stats.lines * 3 / max_lines;
+        let style = match heat {
+            0 => theme.diff.context,
+            1 => theme.diff.modified,
+            _ => theme.conflict.unresolved,
+        };
+        let cells = (row.stats.lines * HEAT_BAR_WIDTH).div_ceil(max_lines);
+        let label = format!("{}{}", "  ".repeat(row.depth), row.name);
+        let marker = if is_current { "▶ " } else { "  " };
+        let text = format!(
+            "{marker}{label:<width$}  {:>4} hunks  {:>6} lines  {}",
+            row.stats.hunks,
+            row.stats.lines,
+            "█".repeat(cells)
+        );
+        let style = if is_current {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        };
+        lines.push(Line::from(Span::styled(text, style)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!(
+            "  {} files, {} hunks, {} conflicting lines   [j/k] scroll   [Esc/O] close",
+            tree.stats.files, tree.stats.hunks, tree.stats.lines
+        ),
+        Style::default().fg(theme.base.muted),
+    )));
+
+    let block = Block::default()
+        .title(" Conflict overview ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.ui.border_focused))
+        .style(Style::default().bg(theme.base.background));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((state.scroll, 0))
+        .style(Style::default().fg(theme.base.foreground));
+
+    frame.render_widget(paragraph, dialog_area);
+}
+
+/// Returns a path as `/`-separated normal components, matching tree rows.
+fn normalized(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Renders the preview of a pending `:s` substitution.
+pub fn render_substitute_dialog(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    preview: &SubstitutePreview,
+) {
+    let dialog_area = centered_rect(70, 60, area);
+
+    // Clear the background
+    frame.render_widget(Clear, dialog_area);
+
+    let mut lines = vec![Line::from("")];
+    for change in &preview.changes {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  Hunk {} ({} replaced)",
+                change.hunk_id.0 + 1,
+                change.count
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for (before, after) in &change.lines {
+            lines.push(Line::from(Span::styled(
+                format!("  - {before}"),
+                theme.diff.removed,
+            )));
+            lines.push(Line::from(Span::styled(
+                format!("  + {after}"),
+                theme.diff.added,
+            )));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  [Enter/y] apply        [Esc/n] cancel",
+        Style::default().fg(theme.base.muted),
+    )));
+
+    let sub = &preview.substitution;
+    let title = format!(
+        " {}s/{}/{}/{} ",
+        if sub.all_hunks { "%" } else { "" },
+        sub.pattern,
+        sub.replacement,
+        if sub.global { "g" } else { "" }
+    );
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.ui.border_focused))
+        .style(Style::default().bg(theme.base.background));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.base.foreground));
+
+    frame.render_widget(paragraph, dialog_area);
+}
+
 /// Creates a centered rectangle with the given percentage of the parent area.
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let vertical = Layout::vertical([
@@ -3,20 +3,25 @@
 //! This module handles rendering the full document with conflicts highlighted
 //! in the left, right, and result panes.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::time::Duration;
 
 use ratatui::{
     layout::Rect,
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Paragraph},
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
     Frame,
 };
 use similar::ChangeTag;
-use weavr_core::{HunkState, Segment};
+use weavr_core::{HunkState, MergeSession, Resolution, ResolutionSource, Segment};
 
-use crate::diff::{compute_line_diffs, DiffConfig};
+use crate::diagnostics::Diagnostic;
+use crate::diff::{changed_line_pairs, compute_line_diffs, compute_word_diffs};
 use crate::input::InputMode;
+use crate::pretty::pretty_print;
+use crate::verify::{RunKind, TestStatus};
 use crate::{App, FocusedPane};
 
 /// Which side of the conflict to render.
@@ -69,12 +74,11 @@ fn render_side_pane(frame: &mut Frame, area: Rect, app: &App, side: PaneSide) {
 
     let content = match app.session() {
         Some(session) => build_side_document(
-            session.segments(),
-            session.hunks(),
+            session,
             side,
-            app.current_hunk_index(),
-            theme,
-            *app.diff_config(),
+            app,
+            Viewport::new(area, app),
+            Rows::new(area, app.left_right_scroll()),
         ),
         None => vec![Line::from(Span::styled(
             "No file loaded",
@@ -82,15 +86,52 @@ fn render_side_pane(frame: &mut Frame, area: Rect, app: &App, side: PaneSide) {
         ))],
     };
 
+    // Name the revision the current hunk's right side was taken from
+    let revision = app
+        .session()
+        .and_then(|s| s.hunks().get(app.current_hunk_index()))
+        .and_then(|hunk| app.replaced_right(hunk.id))
+        .filter(|_| side == PaneSide::Right);
+    let title = match revision {
+        Some(label) => format!(" {} @ {label} ", side.title()),
+        None => format!(" {} ", side.title()),
+    };
+
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(border_style)
-        .title(format!(" {} ", side.title()));
+        .title(title);
 
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .scroll((app.left_right_scroll(), 0));
+    let paragraph = Paragraph::new(content).block(block);
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Renders the base pane showing the common ancestor of each hunk.
+pub fn render_base_pane(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme();
+
+    let content = match app.session() {
+        Some(session) => build_base_document(
+            session,
+            app,
+            Viewport::new(area, app),
+            Rows::new(area, app.left_right_scroll()),
+        ),
+        None => vec![Line::from(Span::styled(
+            "No file loaded",
+            Style::default().fg(theme.base.muted),
+        ))],
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.ui.border_unfocused))
+        .title(" Base ");
+
+    let paragraph = Paragraph::new(content).block(block);
 
     frame.render_widget(paragraph, area);
 }
@@ -108,10 +149,12 @@ pub fn render_result_pane(frame: &mut Frame, area: Rect, app: &App) {
 
     let content = match app.session() {
         Some(session) => build_result_document(
-            session.segments(),
-            session.hunks(),
+            session,
             app.current_hunk_index(),
             theme,
+            app,
+            Viewport::new(area, app),
+            Rows::new(area, app.result_scroll()),
         ),
         None => vec![Line::from(Span::styled(
             "No file loaded",
@@ -125,9 +168,7 @@ pub fn render_result_pane(frame: &mut Frame, area: Rect, app: &App) {
         .border_style(border_style)
         .title(" Result ");
 
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .scroll((app.result_scroll(), 0));
+    let paragraph = Paragraph::new(content).block(block);
 
     frame.render_widget(paragraph, area);
 }
@@ -144,8 +185,23 @@ pub fn render_title_bar(frame: &mut Frame, area: Rect, app: &App) {
                 .count()
         });
 
+        let remembered = app.session().map_or(0, |s| {
+            s.hunks()
+                .iter()
+                .filter(|h| {
+                    matches!(&h.state, HunkState::Resolved(r)
+                        if r.metadata.source == ResolutionSource::Rerere)
+                })
+                .count()
+        });
+        let remembered = if remembered > 0 {
+            format!(", {remembered} remembered")
+        } else {
+            String::new()
+        };
+
         format!(
-            "[{}/{}] ({} resolved)",
+            "[{}/{}] ({} resolved{remembered})",
             app.current_hunk_index() + 1,
             app.total_hunks(),
             resolved_count
@@ -154,11 +210,43 @@ pub fn render_title_bar(frame: &mut Frame, area: Rect, app: &App) {
         "No conflicts".to_string()
     };
 
-    let title = Line::from(vec![
+    let mut spans = vec![
         Span::styled(" weavr ", theme.ui.title),
         Span::raw("| "),
         Span::styled(hunk_info, Style::default().fg(theme.base.accent)),
-    ]);
+    ];
+    if app.session().is_some() {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            app.conflict_style().name(),
+            Style::default().fg(theme.base.muted),
+        ));
+    }
+    if let Some(progress) = app.suggestion_progress() {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            progress,
+            Style::default().fg(theme.base.accent),
+        ));
+    }
+    if app.is_verified() {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled("✓ verified", theme.conflict.resolved));
+    }
+    let failing = app.session().map_or(0, |s| {
+        s.hunks()
+            .iter()
+            .filter(|h| !app.hunk_diagnostics(h.id).is_empty())
+            .count()
+    });
+    if failing > 0 {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("✗ {failing} hunks with problems"),
+            theme.conflict.unresolved,
+        ));
+    }
+    let title = Line::from(spans);
 
     let paragraph = Paragraph::new(title).style(theme.ui.title.bg(theme.base.background));
     frame.render_widget(paragraph, area);
@@ -200,7 +288,7 @@ pub fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
     let unresolved_count = app.session().map_or(0, |s| {
         s.hunks()
             .iter()
-            .filter(|h| matches!(h.state, HunkState::Unresolved))
+            .filter(|h| !matches!(h.state, HunkState::Resolved(_)))
             .count()
     });
 
@@ -211,54 +299,109 @@ pub fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
         FocusedPane::Result => "Result",
     };
 
-    // Format: "Hunk 2/5 | Left pane | 3 unresolved"
-    let status_text = if app.total_hunks() > 0 {
+    // Format: "Hunk 2/5 | Left pane | 3 unresolved | 2 similar"
+    let status_text = if app.total_hunks() == 0 {
+        format!(" {pane_name} pane | No conflicts")
+    } else if app.is_read_only() {
+        // Reviewing: how the current hunk was resolved
+        let resolution = app
+            .session()
+            .and_then(|s| s.hunks().get(app.current_hunk_index()))
+            .map_or("unresolved", |h| match &h.state {
+                HunkState::Resolved(resolution) => resolution.kind.name(),
+                _ => "unresolved",
+            });
         format!(
-            " Hunk {}/{} | {} pane | {} unresolved",
+            " Review (read-only) | Hunk {}/{} | {} pane | {resolution}",
+            app.current_hunk_index() + 1,
+            app.total_hunks(),
+            pane_name
+        )
+    } else {
+        let similar = match app.similar_hunk_count() {
+            0 => String::new(),
+            n => format!(" | {n} similar (A applies)"),
+        };
+        let problems = app
+            .session()
+            .and_then(|s| s.hunks().get(app.current_hunk_index()))
+            .map_or(0, |h| app.hunk_diagnostics(h.id).len());
+        let problems = match problems {
+            0 => String::new(),
+            n => format!(" | {n} problems here"),
+        };
+        format!(
+            " Hunk {}/{} | {} pane | {} unresolved{similar}{problems}",
             app.current_hunk_index() + 1,
             app.total_hunks(),
             pane_name,
             unresolved_count
         )
-    } else {
-        format!(" {pane_name} pane | No conflicts")
     };
 
     let status = Paragraph::new(status_text).style(theme.ui.status.bg(theme.base.background));
     frame.render_widget(status, area);
 }
 
-/// Builds the full document content for a side pane (left or right).
+/// Builds the rows of a side pane (left or right) that `rows` shows.
+///
+/// The current hunk highlights the words that changed in each changed line,
+/// and is pretty-printed when that view is on; its lines are then unnumbered
+/// as they do not match the file's.
 fn build_side_document<'a>(
-    segments: &[Segment],
-    hunks: &[weavr_core::ConflictHunk],
+    session: &MergeSession,
     side: PaneSide,
-    current_hunk_idx: usize,
-    theme: &'a crate::theme::Theme,
-    _diff_config: DiffConfig,
+    app: &'a App,
+    view: Viewport,
+    mut rows: Rows<'a>,
 ) -> Vec<Line<'a>> {
-    let mut lines = Vec::new();
+    let theme = app.theme();
     let mut line_number = 1;
 
-    for segment in segments {
+    let segments = session.segments();
+    let hunks = session.hunks();
+    for (k, segment) in segments.iter().enumerate() {
+        if rows.is_full() {
+            break;
+        }
         match segment {
-            Segment::Clean(text) => {
-                for line_text in text.lines() {
-                    lines.push(build_line(
-                        line_number,
-                        line_text,
-                        Style::default().fg(theme.base.foreground),
-                        false,
-                    ));
-                    line_number += 1;
-                }
+            Segment::Clean(range) => {
+                let fold = Fold::around(app, k, segments.len());
+                let count = app.segment_lines()[k];
+                let text = &session.conflicted()[range.clone()];
+                push_clean_segment(&mut rows, &mut line_number, text, count, fold, theme, view);
             }
             Segment::Conflict(hunk_idx) => {
                 let hunk = &hunks[*hunk_idx];
-                let is_current = *hunk_idx == current_hunk_idx;
+                let is_current = *hunk_idx == app.current_hunk_index();
+                let side_text = match side {
+                    PaneSide::Left => &hunk.left.text,
+                    PaneSide::Right => &hunk.right.text,
+                };
+                let count = side_text.lines().count();
+
+                // A hunk out of view has a row per line; step over it undiffed
+                if !is_current && !rows.shows(count) {
+                    rows.skip(count);
+                    line_number += count;
+                    continue;
+                }
+                let pretty = is_current && app.pretty_sides();
+
+                let (left, right) = if pretty {
+                    (
+                        Cow::Owned(pretty_print(&hunk.left.text)),
+                        Cow::Owned(pretty_print(&hunk.right.text)),
+                    )
+                } else {
+                    (
+                        Cow::Borrowed(hunk.left.text.as_str()),
+                        Cow::Borrowed(hunk.right.text.as_str()),
+                    )
+                };
 
                 // Compute diff between left and right sides
-                let diffs = compute_line_diffs(&hunk.left.text, &hunk.right.text);
+                let diffs = compute_line_diffs(&left, &right);
 
                 // Select the appropriate diff lines for this side
                 let diff_lines = match side {
@@ -266,6 +409,13 @@ fn build_side_document<'a>(
                     PaneSide::Right => &diffs.right_lines,
                 };
 
+                // Only the current hunk highlights words, to keep redraws cheap
+                let partners = if is_current && app.diff_config().word_diff {
+                    line_partners(side, &left, &right)
+                } else {
+                    HashMap::new()
+                };
+
                 // Base style for the side (used for conflict markers)
                 let side_style = match side {
                     PaneSide::Left => theme.conflict.left,
@@ -274,13 +424,17 @@ fn build_side_document<'a>(
 
                 // Add marker for conflict start
                 if is_current {
-                    lines.push(Line::from(Span::styled(
-                        format!("──── Conflict {} ────", hunk_idx + 1),
-                        side_style.add_modifier(Modifier::BOLD),
-                    )));
+                    let title = if pretty {
+                        format!("──── Conflict {} (pretty-printed) ────", hunk_idx + 1)
+                    } else {
+                        format!("──── Conflict {} ────", hunk_idx + 1)
+                    };
+                    rows.push(|| {
+                        Line::from(Span::styled(title, side_style.add_modifier(Modifier::BOLD)))
+                    });
                 }
 
-                for diff_line in diff_lines {
+                for (i, diff_line) in diff_lines.iter().enumerate() {
                     // Apply style based on diff tag
                     let style = match diff_line.tag {
                         ChangeTag::Equal => theme.diff.context,
@@ -288,76 +442,296 @@ fn build_side_document<'a>(
                         ChangeTag::Insert => theme.diff.added,
                     };
 
-                    lines.push(build_line(line_number, &diff_line.text, style, is_current));
-                    line_number += 1;
+                    let number = (!pretty).then_some(line_number + i);
+                    rows.push(|| match partners.get(&i) {
+                        Some(&partner) => {
+                            let (old, new) = match side {
+                                PaneSide::Left => {
+                                    (&diff_line.text, &diffs.right_lines[partner].text)
+                                }
+                                PaneSide::Right => {
+                                    (&diffs.left_lines[partner].text, &diff_line.text)
+                                }
+                            };
+                            word_diff_line(side, old, new, style, number, is_current, view)
+                        }
+                        None => build_line(number, &[(&diff_line.text, style)], is_current, view),
+                    });
                 }
+                line_number += count;
 
                 if is_current {
-                    lines.push(Line::from(Span::styled(
-                        "────────────────────",
-                        side_style.add_modifier(Modifier::BOLD),
-                    )));
+                    rows.push(|| {
+                        Line::from(Span::styled(
+                            "────────────────────",
+                            side_style.add_modifier(Modifier::BOLD),
+                        ))
+                    });
                 }
             }
         }
     }
 
-    if lines.is_empty() {
-        lines.push(Line::from(Span::styled(
-            "(empty file)",
-            Style::default().fg(theme.base.muted),
-        )));
+    rows.finish(theme)
+}
+
+/// Builds the rows of the base pane that `rows` shows.
+///
+/// Hunks without a base show a placeholder line in its place.
+fn build_base_document<'a>(
+    session: &MergeSession,
+    app: &'a App,
+    view: Viewport,
+    mut rows: Rows<'a>,
+) -> Vec<Line<'a>> {
+    let theme = app.theme();
+    let marker_style = Style::default()
+        .fg(theme.base.muted)
+        .add_modifier(Modifier::BOLD);
+    let mut line_number = 1;
+
+    let segments = session.segments();
+    let hunks = session.hunks();
+    for (k, segment) in segments.iter().enumerate() {
+        if rows.is_full() {
+            break;
+        }
+        match segment {
+            Segment::Clean(range) => {
+                let fold = Fold::around(app, k, segments.len());
+                let count = app.segment_lines()[k];
+                let text = &session.conflicted()[range.clone()];
+                push_clean_segment(&mut rows, &mut line_number, text, count, fold, theme, view);
+            }
+            Segment::Conflict(hunk_idx) => {
+                let is_current = *hunk_idx == app.current_hunk_index();
+                if is_current {
+                    rows.push(|| {
+                        Line::from(Span::styled(
+                            format!("──── Conflict {} ────", hunk_idx + 1),
+                            marker_style,
+                        ))
+                    });
+                }
+                match &hunks[*hunk_idx].base {
+                    Some(base) => {
+                        for text in base.text.lines() {
+                            let style = theme.diff.context;
+                            rows.push(|| {
+                                build_line(Some(line_number), &[(text, style)], is_current, view)
+                            });
+                            line_number += 1;
+                        }
+                    }
+                    None => rows.push(|| {
+                        Line::from(Span::styled(
+                            "(no base)",
+                            Style::default().fg(theme.base.muted),
+                        ))
+                    }),
+                }
+                if is_current {
+                    rows.push(|| Line::from(Span::styled("────────────────────", marker_style)));
+                }
+            }
+        }
+    }
+
+    rows.finish(theme)
+}
+
+/// Maps each changed line of `side` to the line it replaces on the other.
+fn line_partners(side: PaneSide, left: &str, right: &str) -> HashMap<usize, usize> {
+    changed_line_pairs(left, right)
+        .into_iter()
+        .map(|(l, r)| match side {
+            PaneSide::Left => (l, r),
+            PaneSide::Right => (r, l),
+        })
+        .collect()
+}
+
+/// Builds a changed line of a side with the words that changed emphasized,
+/// given the line as `old` on the left and `new` on the right.
+fn word_diff_line(
+    side: PaneSide,
+    old: &str,
+    new: &str,
+    style: Style,
+    number: Option<usize>,
+    highlight: bool,
+    view: Viewport,
+) -> Line<'static> {
+    let words = compute_word_diffs(old, new);
+    let emphasis = style.add_modifier(Modifier::REVERSED);
+    let segments: Vec<(&str, Style)> = words
+        .iter()
+        .filter_map(|word| match (word.tag, side) {
+            (ChangeTag::Equal, _) => Some((word.text.as_str(), style)),
+            (ChangeTag::Delete, PaneSide::Left) | (ChangeTag::Insert, PaneSide::Right) => {
+                Some((word.text.as_str(), emphasis))
+            }
+            _ => None,
+        })
+        .collect();
+    build_line(number, &segments, highlight, view)
+}
+
+/// Which lines of a clean segment are shown when context is folded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fold {
+    /// Lines kept at the start, after a hunk.
+    head: usize,
+    /// Lines kept at the end, before a hunk.
+    tail: usize,
+}
+
+impl Fold {
+    /// Returns the fold of the clean segment at `index` of `count` segments,
+    /// or `None` if whole files are shown.
+    fn around(app: &App, index: usize, count: usize) -> Option<Self> {
+        app.context_lines().map(|lines| Self {
+            head: if index > 0 { lines } else { 0 },
+            tail: if index + 1 < count { lines } else { 0 },
+        })
+    }
+}
+
+/// Pushes the `count` lines of a clean segment, replacing those beyond the
+/// context kept by `fold` with a single line saying how many were left out.
+///
+/// `text` is the segment's range of the conflicted file, line endings
+/// included.
+fn push_clean_segment(
+    rows: &mut Rows<'_>,
+    line_number: &mut usize,
+    text: &str,
+    count: usize,
+    fold: Option<Fold>,
+    theme: &crate::theme::Theme,
+    view: Viewport,
+) {
+    let style = Style::default().fg(theme.base.foreground);
+    let first = *line_number;
+    *line_number += count;
+
+    // Folding a single line would hide nothing
+    let fold = fold.filter(|fold| count.saturating_sub(fold.head + fold.tail) > 1);
+    let hidden = fold.map_or(0, |fold| count - fold.head - fold.tail);
+    let shown = count - hidden + usize::from(fold.is_some());
+    if !rows.shows(shown) {
+        rows.skip(shown);
+        return;
     }
 
-    lines
+    let Some(fold) = fold else {
+        for (i, line_text) in text.lines().enumerate() {
+            if rows.is_full() {
+                return;
+            }
+            rows.push(|| build_line(Some(first + i), &[(line_text, style)], false, view));
+        }
+        return;
+    };
+    for (i, line_text) in text.lines().take(fold.head).enumerate() {
+        rows.push(|| build_line(Some(first + i), &[(line_text, style)], false, view));
+    }
+    rows.push(|| {
+        let summary = format!("⋯ {hidden} unchanged lines ⋯");
+        build_line(
+            None,
+            &[(&summary, Style::default().fg(theme.base.muted))],
+            false,
+            view,
+        )
+    });
+    // Read the tail from the end, leaving the hidden lines unread
+    let tail: Vec<&str> = text.lines().rev().take(fold.tail).collect();
+    let tail_start = first + fold.head + hidden;
+    for (i, line_text) in tail.into_iter().rev().enumerate() {
+        rows.push(|| build_line(Some(tail_start + i), &[(line_text, style)], false, view));
+    }
 }
 
-/// Builds the full document content for the result pane.
+/// Builds the rows of the result pane that `rows` shows.
 fn build_result_document<'a>(
-    segments: &[Segment],
-    hunks: &[weavr_core::ConflictHunk],
+    session: &MergeSession,
     current_hunk_idx: usize,
     theme: &'a crate::theme::Theme,
+    app: &App,
+    view: Viewport,
+    mut rows: Rows<'a>,
 ) -> Vec<Line<'a>> {
-    let mut lines = Vec::new();
     let mut line_number = 1;
 
-    for segment in segments {
+    let segments = session.segments();
+    let hunks = session.hunks();
+    for (k, segment) in segments.iter().enumerate() {
+        if rows.is_full() {
+            break;
+        }
         match segment {
-            Segment::Clean(text) => {
-                for line_text in text.lines() {
-                    lines.push(build_line(
-                        line_number,
-                        line_text,
-                        Style::default().fg(theme.base.foreground),
-                        false,
-                    ));
-                    line_number += 1;
-                }
+            Segment::Clean(range) => {
+                let fold = Fold::around(app, k, segments.len());
+                let count = app.segment_lines()[k];
+                let text = &session.conflicted()[range.clone()];
+                push_clean_segment(&mut rows, &mut line_number, text, count, fold, theme, view);
             }
             Segment::Conflict(hunk_idx) => {
                 let hunk = &hunks[*hunk_idx];
                 let is_current = *hunk_idx == current_hunk_idx;
 
                 if let HunkState::Resolved(resolution) = &hunk.state {
-                    // Show resolved content
-                    let style = theme.conflict.resolved;
+                    let problems = app.hunk_diagnostics(hunk.id);
+                    push_resolved_hunk(
+                        &mut rows,
+                        &mut line_number,
+                        resolution,
+                        hunk_idx + 1,
+                        is_current,
+                        problems,
+                        theme,
+                        view,
+                    );
+                } else if let HunkState::Proposed(proposals) = &hunk.state {
+                    // Suggested: preview the first proposal without applying it
+                    let style = theme.conflict.unresolved;
                     let hunk_num = hunk_idx + 1;
-                    if is_current {
-                        lines.push(Line::from(Span::styled(
-                            format!("──── Resolved {hunk_num} ────"),
-                            style.add_modifier(Modifier::BOLD),
-                        )));
-                    }
-                    for line_text in resolution.content.lines() {
-                        lines.push(build_line(line_number, line_text, style, is_current));
-                        line_number += 1;
+                    let marker = if is_current {
+                        format!("──── SUGGESTED {hunk_num} [?] ────")
+                    } else {
+                        format!("──── suggested {hunk_num} ────")
+                    };
+                    rows.push(|| {
+                        Line::from(Span::styled(marker, style.add_modifier(Modifier::BOLD)))
+                    });
+                    if let Some(proposal) = proposals.first() {
+                        for line_text in proposal.content.lines() {
+                            rows.push(|| {
+                                let mut spans = vec![Span::raw(" ".repeat(GUTTER_WIDTH.into()))];
+                                spans.extend(
+                                    view.clip(&[(
+                                        line_text,
+                                        Style::default().fg(theme.base.muted),
+                                    )]),
+                                );
+                                Line::from(spans)
+                            });
+                        }
                     }
+                    rows.push(|| {
+                        Line::from(Span::styled(
+                            "  Select: [a]ccept suggestion  [o]urs  [t]heirs  [b]oth",
+                            Style::default().fg(theme.base.muted),
+                        ))
+                    });
                     if is_current {
-                        lines.push(Line::from(Span::styled(
-                            "────────────────────",
-                            style.add_modifier(Modifier::BOLD),
-                        )));
+                        rows.push(|| {
+                            Line::from(Span::styled(
+                                "────────────────────",
+                                style.add_modifier(Modifier::BOLD),
+                            ))
+                        });
                     }
                 } else {
                     // Unresolved: show placeholder
@@ -368,37 +742,323 @@ fn build_result_document<'a>(
                     } else {
                         format!("──── unresolved {hunk_num} ────")
                     };
-                    lines.push(Line::from(Span::styled(
-                        marker,
-                        style.add_modifier(Modifier::BOLD),
-                    )));
-                    lines.push(Line::from(Span::styled(
-                        "  Select: [o]urs  [t]heirs  [b]oth",
-                        Style::default().fg(theme.base.muted),
-                    )));
+                    rows.push(|| {
+                        Line::from(Span::styled(marker, style.add_modifier(Modifier::BOLD)))
+                    });
+                    rows.push(|| {
+                        Line::from(Span::styled(
+                            "  Select: [o]urs  [t]heirs  [b]oth",
+                            Style::default().fg(theme.base.muted),
+                        ))
+                    });
                     if is_current {
-                        lines.push(Line::from(Span::styled(
-                            "────────────────────",
-                            style.add_modifier(Modifier::BOLD),
-                        )));
+                        rows.push(|| {
+                            Line::from(Span::styled(
+                                "────────────────────",
+                                style.add_modifier(Modifier::BOLD),
+                            ))
+                        });
                     }
                 }
             }
         }
     }
 
-    if lines.is_empty() {
-        lines.push(Line::from(Span::styled(
-            "(empty file)",
-            Style::default().fg(theme.base.muted),
-        )));
+    rows.finish(theme)
+}
+
+/// Renders the guide panel with the current instruction.
+pub fn render_guide(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(guide) = app.guide() else {
+        return;
+    };
+    let theme = app.theme();
+
+    let (done, total) = guide.progress();
+    let (title, instruction) = match guide.current_step() {
+        Some(step) => (
+            format!(" Tutorial {}/{total} ", done + 1),
+            step.instruction.as_str(),
+        ),
+        None => (
+            " Tutorial complete ".to_string(),
+            "Well done! Type :q and press Enter to leave.",
+        ),
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.base.accent))
+        .title(title);
+
+    let paragraph = Paragraph::new(instruction)
+        .block(block)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(theme.base.foreground));
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Renders the output of the current or most recent test run.
+pub fn render_test_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(run) = app.test_run() else {
+        return;
+    };
+    let theme = app.theme();
+
+    let name = match run.kind {
+        RunKind::Test => "Tests",
+        RunKind::Check => "Check",
+    };
+    let (title, border) = match run.status {
+        TestStatus::Running => (format!(" Running: {} ", run.command), theme.base.accent),
+        TestStatus::Passed => (
+            match run.kind {
+                RunKind::Test => " Tests passed - verified (Esc hides) ".to_string(),
+                RunKind::Check => " Check passed (Esc hides) ".to_string(),
+            },
+            theme.conflict.resolved.fg.unwrap_or(theme.base.accent),
+        ),
+        TestStatus::Failed(code) => (
+            match code {
+                Some(code) => format!(" {name} failed (exit code {code}) - Esc hides "),
+                None => format!(" {name} failed - Esc hides "),
+            },
+            theme.conflict.unresolved.fg.unwrap_or(theme.base.accent),
+        ),
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(border))
+        .title(title);
+
+    // Follow the tail of the output
+    let visible = usize::from(area.height.saturating_sub(2));
+    let start = run.output.len().saturating_sub(visible);
+    let lines: Vec<Line> = run.output[start..]
+        .iter()
+        .map(|line| Line::raw(line.as_str()))
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.base.foreground));
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Appends a resolved hunk to the result document.
+///
+/// The current hunk is framed by a header and footer; a hunk with problems
+/// from the last check is always framed, and lists them when current.
+#[allow(clippy::too_many_arguments)]
+fn push_resolved_hunk(
+    rows: &mut Rows<'_>,
+    line_number: &mut usize,
+    resolution: &Resolution,
+    hunk_num: usize,
+    is_current: bool,
+    problems: &[Diagnostic],
+    theme: &crate::theme::Theme,
+    view: Viewport,
+) {
+    let style = if problems.is_empty() {
+        theme.conflict.resolved
+    } else {
+        theme.conflict.unresolved
+    };
+    // Remembered resolutions stay framed until reviewed, so they stand out
+    let remembered = resolution.metadata.source == ResolutionSource::Rerere;
+    let framed = is_current || remembered || !problems.is_empty();
+
+    if framed {
+        let header = match problems.len() {
+            0 if remembered => format!("──── Resolved {hunk_num} · ↺ remembered ────"),
+            0 => resolved_header(hunk_num, resolution.metadata.notes.as_deref()),
+            n => format!("──── Resolved {hunk_num} · ✗ {n} problems ────"),
+        };
+        rows.push(|| Line::from(Span::styled(header, style.add_modifier(Modifier::BOLD))));
+    }
+    if is_current {
+        for problem in problems {
+            rows.push(|| {
+                Line::from(Span::styled(
+                    format!(
+                        "  ✗ line {}: {}: {}",
+                        problem.line, problem.level, problem.message
+                    ),
+                    theme.conflict.unresolved,
+                ))
+            });
+        }
+    }
+    for line_text in resolution.content.lines() {
+        rows.push(|| build_line(Some(*line_number), &[(line_text, style)], is_current, view));
+        *line_number += 1;
+    }
+    if framed {
+        rows.push(|| {
+            Line::from(Span::styled(
+                "────────────────────",
+                style.add_modifier(Modifier::BOLD),
+            ))
+        });
+    }
+}
+
+/// Returns the header shown above the current resolved hunk, with its note.
+fn resolved_header(hunk_num: usize, note: Option<&str>) -> String {
+    match note {
+        Some(note) => format!("──── Resolved {hunk_num} · {note} ────"),
+        None => format!("──── Resolved {hunk_num} ────"),
+    }
+}
+
+/// The rows of a document a pane shows: from its scroll offset down to its
+/// height.
+///
+/// Rows above and below are counted but never built, so a pane draws in time
+/// bounded by its scroll offset and height rather than the file's length.
+struct Rows<'a> {
+    /// First row shown.
+    start: usize,
+    /// Row after the last shown.
+    end: usize,
+    /// Rows pushed or skipped so far.
+    count: usize,
+    /// The rows shown.
+    lines: Vec<Line<'a>>,
+}
+
+impl<'a> Rows<'a> {
+    /// Returns the rows shown in a bordered pane covering `area`, scrolled
+    /// down `scroll` rows.
+    fn new(area: Rect, scroll: u16) -> Self {
+        let start = usize::from(scroll);
+        Self {
+            start,
+            end: start + usize::from(area.height.saturating_sub(2)),
+            count: 0,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Returns true if any of the next `rows` rows are shown.
+    fn shows(&self, rows: usize) -> bool {
+        self.count + rows > self.start && self.count < self.end
+    }
+
+    /// Returns true once every row shown has been pushed.
+    fn is_full(&self) -> bool {
+        self.count >= self.end
+    }
+
+    /// Pushes a row, building it only if it is shown.
+    fn push(&mut self, build: impl FnOnce() -> Line<'a>) {
+        if self.shows(1) {
+            self.lines.push(build());
+        }
+        self.count += 1;
+    }
+
+    /// Steps over `rows` rows that are not shown.
+    fn skip(&mut self, rows: usize) {
+        self.count += rows;
+    }
+
+    /// Returns the rows shown, or a note if the document has none.
+    fn finish(self, theme: &crate::theme::Theme) -> Vec<Line<'a>> {
+        if self.count == 0 {
+            return vec![Line::from(Span::styled(
+                "(empty file)",
+                Style::default().fg(theme.base.muted),
+            ))];
+        }
+        self.lines
+    }
+}
+
+/// Width of the line number gutter.
+const GUTTER_WIDTH: u16 = 5;
+
+/// The columns of text visible in a pane after the line number gutter.
+///
+/// Lines are cut to these columns before they are laid out, so multi-kilobyte
+/// lines cost no more to draw than the part that is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Viewport {
+    /// Columns scrolled past on the left.
+    offset: usize,
+    /// Columns shown.
+    width: usize,
+}
+
+impl Viewport {
+    /// Returns the viewport of a bordered pane covering `area`.
+    fn new(area: Rect, app: &App) -> Self {
+        Self {
+            offset: app.horizontal_scroll(),
+            width: usize::from(area.width.saturating_sub(2 + GUTTER_WIDTH)),
+        }
     }
 
-    lines
+    /// Cuts styled text down to the visible columns, marking where text
+    /// continues beyond either edge.
+    fn clip(self, segments: &[(&str, Style)]) -> Vec<Span<'static>> {
+        let mut visible: Vec<(String, Style)> = Vec::new();
+        let mut skipped = 0;
+        let mut taken = 0;
+        let mut more = false;
+        'segments: for (text, style) in segments {
+            let mut part = String::new();
+            for c in text.chars() {
+                if skipped < self.offset {
+                    skipped += 1;
+                } else if taken == self.width {
+                    more = true;
+                    visible.push((part, *style));
+                    break 'segments;
+                } else {
+                    part.push(c);
+                    taken += 1;
+                }
+            }
+            visible.push((part, *style));
+        }
+        visible.retain(|(text, _)| !text.is_empty());
+
+        let marker = Style::default().add_modifier(Modifier::DIM);
+        if more {
+            if let Some((text, _)) = visible.last_mut() {
+                text.pop();
+                visible.push(("›".to_string(), marker));
+            }
+        }
+        if skipped > 0 {
+            if let Some((text, _)) = visible.first_mut() {
+                text.remove(0);
+                visible.insert(0, ("‹".to_string(), marker));
+            }
+        }
+        visible
+            .into_iter()
+            .filter(|(text, _)| !text.is_empty())
+            .map(|(text, style)| Span::styled(text, style))
+            .collect()
+    }
 }
 
-/// Builds a single line with line number and content.
-fn build_line(line_number: usize, text: &str, style: Style, highlight: bool) -> Line<'static> {
+/// Builds a single line with line number and the visible part of its
+/// content; lines without a number get a `~` in the gutter.
+fn build_line(
+    line_number: Option<usize>,
+    segments: &[(&str, Style)],
+    highlight: bool,
+    view: Viewport,
+) -> Line<'static> {
     let line_num_style = if highlight {
         Style::default()
             .fg(ratatui::style::Color::Yellow)
@@ -407,10 +1067,13 @@ fn build_line(line_number: usize, text: &str, style: Style, highlight: bool) ->
         Style::default().add_modifier(Modifier::DIM)
     };
 
-    Line::from(vec![
-        Span::styled(format!("{line_number:4} "), line_num_style),
-        Span::styled(text.to_string(), style),
-    ])
+    let gutter = match line_number {
+        Some(number) => format!("{number:4} "),
+        None => "   ~ ".to_string(),
+    };
+    let mut spans = vec![Span::styled(gutter, line_num_style)];
+    spans.extend(view.clip(segments));
+    Line::from(spans)
 }
 
 #[cfg(test)]
@@ -479,6 +1142,55 @@ mod tests {
         assert!(title_line.contains("No conflicts"));
     }
 
+    #[test]
+    fn render_title_bar_shows_conflict_style() {
+        let content = "<<<<<<< HEAD\nours\n||||||| base\n=======\ntheirs\n>>>>>>> f\n";
+        let mut app = App::new();
+        app.set_session(weavr_core::MergeSession::from_conflicted(content, "a.rs".into()).unwrap());
+        app.set_conflict_style(weavr_core::ConflictStyle::Zdiff3);
+
+        let mut terminal = create_test_terminal();
+        terminal
+            .draw(|frame| render_title_bar(frame, Rect::new(0, 0, 80, 1), &app))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let title_line: String = (0..buffer.area.width)
+            .map(|x| buffer.cell((x, 0)).unwrap().symbol().to_string())
+            .collect();
+        assert!(title_line.contains("| zdiff3"));
+    }
+
+    #[test]
+    fn remembered_resolutions_are_marked() {
+        let content = "<<<<<<< HEAD\nx = 1\n=======\nx = 2\n>>>>>>> b\nmid\n\
+                       <<<<<<< HEAD\ny = 1\n=======\ny = 2\n>>>>>>> b\n";
+        let mut session =
+            weavr_core::MergeSession::from_conflicted(content, "a.rs".into()).unwrap();
+        let mut resolution = Resolution::manual("y = 3".to_string());
+        resolution.metadata.source = ResolutionSource::Rerere;
+        session
+            .set_resolution(weavr_core::HunkId(1), resolution)
+            .unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+
+        let mut terminal = create_test_terminal();
+        terminal
+            .draw(|frame| {
+                render_title_bar(frame, Rect::new(0, 0, 80, 1), &app);
+                render_result_pane(frame, Rect::new(0, 1, 80, 20), &app);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let text: String = (0..buffer.area.height)
+            .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+            .map(|pos| buffer.cell(pos).unwrap().symbol().to_string())
+            .collect();
+        assert!(text.contains("(1 resolved, 1 remembered)"));
+        // Not the current hunk, yet framed
+        assert!(text.contains("Resolved 2 · ↺ remembered"));
+    }
+
     #[test]
     fn render_status_bar_shows_pane_and_conflicts() {
         let mut terminal = create_test_terminal();
@@ -525,4 +1237,137 @@ mod tests {
                 .unwrap();
         }
     }
+
+    #[test]
+    fn viewport_clips_text_and_marks_cut_edges() {
+        let style = Style::default();
+        let text = |spans: Vec<Span>| -> String {
+            spans.iter().map(|span| span.content.as_ref()).collect()
+        };
+        let view = Viewport {
+            offset: 2,
+            width: 5,
+        };
+        assert_eq!(text(view.clip(&[("abcdefghij", style)])), "‹def›");
+        assert_eq!(text(view.clip(&[("abc", style)])), "‹");
+
+        let view = Viewport {
+            offset: 0,
+            width: 3,
+        };
+        assert_eq!(text(view.clip(&[("ab", style), ("cdef", style)])), "ab›");
+        assert_eq!(text(view.clip(&[("abc", style)])), "abc");
+    }
+
+    #[test]
+    fn pretty_sides_break_up_long_lines() {
+        let left = format!("{{{}}}", vec!["\"k\":1"; 400].join(","));
+        let content = format!("<<<<<<< HEAD\n{left}\n=======\n{{}}\n>>>>>>> x\n");
+        let mut app = App::new();
+        app.set_session(
+            weavr_core::MergeSession::from_conflicted(&content, "a.json".into()).unwrap(),
+        );
+        app.toggle_pretty_sides();
+
+        let mut terminal = create_test_terminal();
+        terminal
+            .draw(|frame| render_left_pane(frame, Rect::new(0, 0, 40, 10), &app))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..10)
+            .map(|y| {
+                (0..40)
+                    .map(|x| buffer.cell((x, y)).unwrap().symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(rows[1].contains("(pretty-printed)"));
+        assert!(rows[2].contains("~ {"));
+        assert!(rows[3].contains("~   \"k\":1,"));
+    }
+
+    #[test]
+    fn folded_context_hides_lines_away_from_hunks() {
+        let clean = (1..=20)
+            .map(|i| format!("line {i}\n"))
+            .collect::<Vec<_>>()
+            .concat();
+        let content = format!("{clean}<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> x\nend\n");
+        let mut app = App::new();
+        app.set_session(
+            weavr_core::MergeSession::from_conflicted(&content, "a.rs".into()).unwrap(),
+        );
+        app.set_context_lines(Some(2));
+
+        let mut terminal = create_test_terminal();
+        terminal
+            .draw(|frame| render_left_pane(frame, Rect::new(0, 0, 40, 10), &app))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..10)
+            .map(|y| {
+                (0..40)
+                    .map(|x| buffer.cell((x, y)).unwrap().symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(rows[1].contains("~ ⋯ 18 unchanged lines ⋯"));
+        assert!(rows[2].contains("19 line 19"));
+        assert!(rows[3].contains("20 line 20"));
+    }
+
+    #[test]
+    fn scrolled_pane_shows_rows_from_the_offset() {
+        let clean = (1..=500)
+            .map(|i| format!("line {i}\n"))
+            .collect::<Vec<_>>()
+            .concat();
+        let content = format!("{clean}<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> x\n{clean}");
+        let mut app = App::new();
+        app.set_session(
+            weavr_core::MergeSession::from_conflicted(&content, "a.rs".into()).unwrap(),
+        );
+        app.scroll_down(499);
+
+        let mut terminal = create_test_terminal();
+        terminal
+            .draw(|frame| render_left_pane(frame, Rect::new(0, 0, 40, 10), &app))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..10)
+            .map(|y| {
+                (0..40)
+                    .map(|x| buffer.cell((x, y)).unwrap().symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(rows[1].contains("500 line 500"));
+        assert!(rows[2].contains("Conflict 1"));
+        assert!(rows[3].contains("501 ours"));
+        assert!(rows[5].contains("502 line 1"));
+        assert!(rows[8].contains("505 line 4"));
+    }
+
+    #[test]
+    fn base_pane_shows_the_common_ancestor() {
+        let content = "a\n<<<<<<< HEAD\nours\n||||||| base\noriginal\n=======\ntheirs\n>>>>>>> x\n";
+        let mut app = App::new();
+        app.set_session(weavr_core::MergeSession::from_conflicted(content, "a.rs".into()).unwrap());
+
+        let mut terminal = create_test_terminal();
+        terminal
+            .draw(|frame| render_base_pane(frame, Rect::new(0, 0, 40, 10), &app))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..10)
+            .map(|y| {
+                (0..40)
+                    .map(|x| buffer.cell((x, y)).unwrap().symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(rows[0].contains("Base"));
+        assert!(rows[2].contains("──── Conflict 1 ────"));
+        assert!(rows[3].contains("original"));
+    }
 }
@@ -5,7 +5,10 @@
 
 use weavr_core::{HunkId, Resolution};
 
-/// Maximum depth of the undo stack.
+/// Maximum depth of the undo stack, in entries.
+///
+/// Whole steps are dropped to stay under it, but never the most recent
+/// one, however many entries it has.
 const MAX_DEPTH: usize = 100;
 
 /// An entry in the undo stack representing a previous resolution state.
@@ -15,8 +18,13 @@ pub struct UndoEntry {
     pub hunk_id: HunkId,
     /// The previous resolution (None if it was unresolved).
     pub previous_resolution: Option<Resolution>,
+    /// The previous proposals, for changes that only offered the hunk a
+    /// resolution (None for changes to its resolution).
+    pub previous_proposals: Option<Vec<Resolution>>,
     /// Description of the action for status messages.
     pub action: String,
+    /// Whether this entry is undone together with the entry below it.
+    pub grouped: bool,
 }
 
 /// A stack of undo entries with automatic depth limiting.
@@ -36,17 +44,62 @@ impl UndoStack {
 
     /// Pushes an undo entry onto the stack.
     ///
-    /// If the stack exceeds the maximum depth, the oldest entry is removed.
+    /// If the stack exceeds the maximum depth, the oldest steps are dropped.
     pub fn push(&mut self, hunk_id: HunkId, previous: Option<Resolution>, action: &str) {
         self.entries.push(UndoEntry {
             hunk_id,
             previous_resolution: previous,
+            previous_proposals: None,
             action: action.to_string(),
+            grouped: false,
         });
+        self.trim();
+    }
+
+    /// Pushes a set of changes that are undone in a single step.
+    ///
+    /// Does nothing if `changes` is empty.
+    pub fn push_group(&mut self, changes: Vec<(HunkId, Option<Resolution>)>, action: &str) {
+        for (i, (hunk_id, previous)) in changes.into_iter().enumerate() {
+            self.entries.push(UndoEntry {
+                hunk_id,
+                previous_resolution: previous,
+                previous_proposals: None,
+                action: action.to_string(),
+                grouped: i > 0,
+            });
+        }
+        self.trim();
+    }
 
-        // Trim to max depth: only exceeds by at most 1 per push
-        if self.entries.len() > MAX_DEPTH {
-            self.entries.remove(0);
+    /// Pushes a set of changes to unresolved hunks' proposals that are undone
+    /// in a single step, restoring the proposals each hunk had before.
+    ///
+    /// Does nothing if `changes` is empty.
+    pub fn push_proposals_group(&mut self, changes: Vec<(HunkId, Vec<Resolution>)>, action: &str) {
+        for (i, (hunk_id, previous)) in changes.into_iter().enumerate() {
+            self.entries.push(UndoEntry {
+                hunk_id,
+                previous_resolution: None,
+                previous_proposals: Some(previous),
+                action: action.to_string(),
+                grouped: i > 0,
+            });
+        }
+        self.trim();
+    }
+
+    /// Drops the oldest steps beyond the maximum depth.
+    ///
+    /// A step is only ever dropped whole, so undoing never half-reverts one.
+    fn trim(&mut self) {
+        while self.entries.len() > MAX_DEPTH {
+            // The oldest step runs up to the next entry that starts a step
+            let Some(end) = self.entries[1..].iter().position(|entry| !entry.grouped) else {
+                // Only the most recent step is left
+                break;
+            };
+            self.entries.drain(..=end);
         }
     }
 
@@ -55,6 +108,21 @@ impl UndoStack {
         self.entries.pop()
     }
 
+    /// Pops the most recent undo step, which may span several entries.
+    ///
+    /// Entries are returned most recent first.
+    pub fn pop_step(&mut self) -> Vec<UndoEntry> {
+        let mut step = Vec::new();
+        while let Some(entry) = self.entries.pop() {
+            let grouped = entry.grouped;
+            step.push(entry);
+            if !grouped {
+                break;
+            }
+        }
+        step
+    }
+
     /// Returns true if the stack is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
@@ -108,6 +176,50 @@ mod tests {
         assert!(stack.is_empty());
     }
 
+    #[test]
+    fn group_is_popped_as_one_step() {
+        let mut stack = UndoStack::new();
+        stack.push(HunkId(0), None, "Before");
+        stack.push_group(
+            vec![(HunkId(1), None), (HunkId(2), None), (HunkId(3), None)],
+            "Substitute",
+        );
+
+        let step = stack.pop_step();
+        let hunks: Vec<HunkId> = step.iter().map(|e| e.hunk_id).collect();
+        assert_eq!(hunks, vec![HunkId(3), HunkId(2), HunkId(1)]);
+
+        assert_eq!(stack.pop_step().len(), 1);
+        assert!(stack.pop_step().is_empty());
+    }
+
+    #[test]
+    fn group_larger_than_max_depth_is_kept_whole() {
+        let mut stack = UndoStack::new();
+        stack.push(HunkId(0), None, "Before");
+        let group: Vec<_> = (1..=150).map(|i| (HunkId(i), None)).collect();
+        stack.push_group(group, "Substitute");
+
+        let step = stack.pop_step();
+        assert_eq!(step.len(), 150);
+        assert_eq!(step.last().unwrap().hunk_id, HunkId(1));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn trim_drops_whole_steps() {
+        let mut stack = UndoStack::new();
+        let group: Vec<_> = (0..60).map(|i| (HunkId(i), None)).collect();
+        stack.push_group(group, "Apply to similar");
+        for i in 60..101 {
+            stack.push(HunkId(i), None, "Accept");
+        }
+
+        // The group went at once rather than entry by entry
+        assert_eq!(stack.entries.len(), 41);
+        assert!(stack.entries.iter().all(|entry| !entry.grouped));
+    }
+
     #[test]
     fn lifo_order() {
         let mut stack = UndoStack::new();
@@ -0,0 +1,446 @@
+//! Running the project's test and check commands from the TUI.
+//!
+//! The current result is written to the file, the configured command runs
+//! in the background with its output streamed into a panel, and the file's
+//! previous content is put back once the command exits. A passing test run
+//! marks the session as verified for exactly the resolutions that were
+//! tested; a check run attaches its diagnostics to the hunks they fall in.
+//! A run that takes long alerts the user through the app's notifier when it
+//! finishes.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Instant;
+
+use weavr_core::{HunkId, HunkState};
+
+use crate::diagnostics::{self, Diagnostic};
+use crate::App;
+
+/// Maximum number of output lines kept for the panel.
+const MAX_OUTPUT_LINES: usize = 1000;
+
+/// Which configured command a run executes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunKind {
+    /// The test command; success marks the result verified.
+    Test,
+    /// The check command; diagnostics are mapped back to hunks.
+    Check,
+}
+
+/// Progress of a test run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    /// The command is still running.
+    Running,
+    /// The command exited successfully.
+    Passed,
+    /// The command failed, with its exit code if it had one.
+    Failed(Option<i32>),
+}
+
+/// A test or check command started from the TUI.
+#[derive(Debug)]
+pub struct TestRun {
+    /// Which command is being run.
+    pub kind: RunKind,
+    /// The command line being run.
+    pub command: String,
+    /// Output lines received so far, stdout and stderr interleaved.
+    pub output: Vec<String>,
+    /// Current status.
+    pub status: TestStatus,
+    /// The merged content under test.
+    pub(crate) tested: String,
+    /// When the command started.
+    started: Instant,
+    child: Option<Child>,
+    lines: Receiver<String>,
+    /// File to put back, with its content before the run.
+    restore: Option<(PathBuf, String)>,
+    /// The file under test, as diagnostics are matched against it.
+    target: PathBuf,
+    /// Lines each hunk occupies in the content under test.
+    ranges: Vec<(HunkId, Range<usize>)>,
+    /// Each hunk's resolved content at the start of the run.
+    contents: HashMap<HunkId, Option<String>>,
+    /// Diagnostics reported for the file under test.
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Starts the configured test or check command on the current result.
+///
+/// Tests need every hunk resolved; a check also runs on a partial result so
+/// problems show up while resolving.
+pub fn start(app: &mut App, kind: RunKind) {
+    let (command, name) = match kind {
+        RunKind::Test => (app.test_command.clone(), "test"),
+        RunKind::Check => (app.check_command.clone(), "check"),
+    };
+    let Some(command) = command else {
+        app.set_status_message(&format!("No {name} command configured"));
+        return;
+    };
+    if app
+        .test_run
+        .as_ref()
+        .is_some_and(|run| run.status == TestStatus::Running)
+    {
+        app.set_status_message("A command is already running");
+        return;
+    }
+    let Some(session) = app.session.as_ref() else {
+        return;
+    };
+    if kind == RunKind::Test && !session.is_fully_resolved() {
+        app.set_status_message("Resolve all hunks before running tests");
+        return;
+    }
+
+    let path = session.input().left.path.clone();
+    let tested = session.preview();
+    let ranges = session.preview_line_ranges();
+    let contents = session
+        .hunks()
+        .iter()
+        .map(|hunk| (hunk.id, resolved_content(&hunk.state)))
+        .collect();
+    match spawn(&command, path, &tested) {
+        Ok(mut run) => {
+            run.kind = kind;
+            run.ranges = ranges;
+            run.contents = contents;
+            app.test_run = Some(run);
+            app.set_status_message(&format!("Running: {command}"));
+        }
+        Err(e) => app.set_status_message(&format!("Failed to run {name}: {e}")),
+    }
+}
+
+/// Collects new output and finishes the run once the command has exited.
+///
+/// Called on every pass of the event loop.
+pub fn poll(app: &mut App) {
+    let Some(run) = app.test_run.as_mut() else {
+        return;
+    };
+    if run.status != TestStatus::Running {
+        return;
+    }
+
+    let disconnected = loop {
+        match run.lines.try_recv() {
+            Ok(line) if run.kind == RunKind::Check => {
+                let parsed = diagnostics::parse_line(&line);
+                run.output.extend(parsed.display);
+                run.diagnostics.extend(
+                    parsed
+                        .diagnostics
+                        .into_iter()
+                        .filter(|(file, _)| diagnostics::refers_to(file, &run.target))
+                        .map(|(_, diagnostic)| diagnostic),
+                );
+            }
+            Ok(line) => run.output.push(line),
+            Err(TryRecvError::Empty) => break false,
+            Err(TryRecvError::Disconnected) => break true,
+        }
+    };
+    if run.output.len() > MAX_OUTPUT_LINES {
+        let excess = run.output.len() - MAX_OUTPUT_LINES;
+        run.output.drain(..excess);
+    }
+
+    // Wait for both output streams to close so no trailing lines are lost
+    if !disconnected {
+        return;
+    }
+    let Some(status) = run.child.as_mut().and_then(|c| c.try_wait().ok().flatten()) else {
+        return;
+    };
+
+    run.child = None;
+    run.status = if status.success() {
+        TestStatus::Passed
+    } else {
+        TestStatus::Failed(status.code())
+    };
+    let restored = restore(run);
+    let Some(run) = app.test_run.take() else {
+        return;
+    };
+
+    let message = match run.kind {
+        RunKind::Test => {
+            if run.status == TestStatus::Passed {
+                app.verified_content = Some(run.tested.clone());
+            }
+            test_message(run.status)
+        }
+        RunKind::Check => {
+            let (by_hunk, outside) = diagnostics::map_to_hunks(&run.diagnostics, &run.ranges);
+            let message = check_message(run.status, &by_hunk, outside);
+            // Pair each hunk's diagnostics with the content they were reported
+            // for, so they disappear once the hunk is resolved differently
+            app.diagnostics = by_hunk
+                .into_iter()
+                .map(|(hunk_id, found)| {
+                    let content = run.contents.get(&hunk_id).cloned().flatten();
+                    (hunk_id, (content, found))
+                })
+                .collect();
+            message
+        }
+    };
+    app.notifier.finished(run.started.elapsed(), &message);
+    app.test_run = Some(run);
+    match restored {
+        Ok(()) => app.set_status_message(&message),
+        Err(e) => app.set_status_message(&format!("{message}; could not restore file: {e}")),
+    }
+}
+
+/// Describes a finished test run.
+fn test_message(status: TestStatus) -> String {
+    match status {
+        TestStatus::Passed => "Tests passed - resolutions verified".to_string(),
+        TestStatus::Failed(Some(code)) => format!("Tests failed (exit code {code})"),
+        _ => "Tests failed".to_string(),
+    }
+}
+
+/// Describes a finished check run.
+fn check_message(
+    status: TestStatus,
+    by_hunk: &HashMap<HunkId, Vec<Diagnostic>>,
+    outside: usize,
+) -> String {
+    let in_hunks: usize = by_hunk.values().map(Vec::len).sum();
+    match (status, in_hunks + outside) {
+        (TestStatus::Passed, 0) => "Check passed".to_string(),
+        (_, 0) => "Check failed with no diagnostics for this file".to_string(),
+        (_, total) => format!(
+            "Check found {total} problems: {in_hunks} in {} hunks, {outside} elsewhere",
+            by_hunk.len()
+        ),
+    }
+}
+
+/// Returns the resolved content of a hunk, if it is resolved.
+pub(crate) fn resolved_content(state: &HunkState) -> Option<String> {
+    match state {
+        HunkState::Resolved(resolution) => Some(resolution.content.clone()),
+        _ => None,
+    }
+}
+
+/// Stops a running command and puts the file back.
+///
+/// Called when the TUI exits.
+pub fn stop(app: &mut App) {
+    if let Some(run) = app.test_run.as_mut() {
+        if let Some(mut child) = run.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        let _ = restore(run);
+    }
+}
+
+/// Hides the output panel of a finished run.
+pub fn close_panel(app: &mut App) {
+    if app
+        .test_run
+        .as_ref()
+        .is_some_and(|run| run.status != TestStatus::Running)
+    {
+        app.test_run = None;
+    }
+}
+
+/// Writes the content under test and starts the command.
+fn spawn(command: &str, path: PathBuf, tested: &str) -> std::io::Result<TestRun> {
+    let original = std::fs::read_to_string(&path)?;
+    std::fs::write(&path, tested)?;
+
+    let mut child = match shell(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            std::fs::write(&path, original)?;
+            return Err(e);
+        }
+    };
+
+    let (sender, lines) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward(stdout, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward(stderr, sender);
+    }
+
+    let target = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+    Ok(TestRun {
+        kind: RunKind::Test,
+        command: command.to_string(),
+        output: Vec::new(),
+        status: TestStatus::Running,
+        tested: tested.to_string(),
+        started: Instant::now(),
+        child: Some(child),
+        lines,
+        restore: Some((path, original)),
+        target,
+        ranges: Vec::new(),
+        contents: HashMap::new(),
+        diagnostics: Vec::new(),
+    })
+}
+
+/// Builds a command that runs `command` through the platform shell.
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// Sends each line read from `stream` to `sender` on a background thread.
+fn forward(stream: impl Read + Send + 'static, sender: mpsc::Sender<String>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Puts the file back as it was before the run, once.
+fn restore(run: &mut TestRun) -> std::io::Result<()> {
+    match run.restore.take() {
+        Some((path, original)) => std::fs::write(path, original),
+        None => Ok(()),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use tempfile::TempDir;
+    use weavr_core::MergeSession;
+
+    use super::*;
+
+    const CONFLICT: &str = "<<<<<<< HEAD\nleft\n=======\nright\n>>>>>>> f\n";
+
+    fn app_for(path: &std::path::Path, command: &str) -> App {
+        std::fs::write(path, CONFLICT).unwrap();
+        let session = MergeSession::from_conflicted(CONFLICT, path.to_path_buf()).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_test_command(command.to_string());
+        app
+    }
+
+    fn wait(app: &mut App) -> TestStatus {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            poll(app);
+            let status = app.test_run().unwrap().status;
+            if status != TestStatus::Running || Instant::now() > deadline {
+                return status;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn passing_run_tests_result_and_restores_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("pass.rs");
+        let mut app = app_for(&path, &format!("cat {}", path.display()));
+
+        start(&mut app, RunKind::Test);
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Resolve all hunks before running tests")
+        );
+
+        app.resolve_right();
+        start(&mut app, RunKind::Test);
+        assert_eq!(wait(&mut app), TestStatus::Passed);
+        assert_eq!(app.test_run().unwrap().output, vec!["right".to_string()]);
+        assert!(app.is_verified());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), CONFLICT);
+
+        // Changing a resolution invalidates the verification
+        app.resolve_left();
+        assert!(!app.is_verified());
+    }
+
+    #[test]
+    fn failing_run_reports_exit_code() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fail.rs");
+        let mut app = app_for(&path, "echo broken >&2; exit 3");
+        app.resolve_left();
+
+        start(&mut app, RunKind::Test);
+        assert_eq!(wait(&mut app), TestStatus::Failed(Some(3)));
+        assert_eq!(app.test_run().unwrap().output, vec!["broken".to_string()]);
+        assert!(!app.is_verified());
+
+        close_panel(&mut app);
+        assert!(app.test_run().is_none());
+    }
+
+    #[test]
+    fn check_attaches_diagnostics_to_hunks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("check.rs");
+        let content = "a\n<<<<<<< HEAD\nl1\n=======\nr1\n>>>>>>> f\nb\n<<<<<<< HEAD\nl2\n=======\nr2\n>>>>>>> f\n";
+        std::fs::write(&path, content).unwrap();
+        let session = MergeSession::from_conflicted(content, path.clone()).unwrap();
+        let mut app = App::new();
+        app.set_session(session);
+        app.set_check_command(format!(
+            "echo '{0}:2:1: error: bad'; echo '{0}:3: warning: meh'; echo 'other.rs:2: error: x'; exit 1",
+            path.display()
+        ));
+        app.resolve_left();
+
+        // Unlike tests, a check runs with hunks still unresolved
+        start(&mut app, RunKind::Check);
+        assert_eq!(wait(&mut app), TestStatus::Failed(Some(1)));
+        assert_eq!(
+            app.status_message().map(|(msg, _)| msg.as_str()),
+            Some("Check found 2 problems: 1 in 1 hunks, 1 elsewhere")
+        );
+        let found = app.hunk_diagnostics(HunkId(0));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].message, "bad");
+        assert!(app.hunk_diagnostics(HunkId(1)).is_empty());
+
+        // Resolving the hunk differently clears its stale diagnostics
+        app.resolve_right();
+        assert!(app.hunk_diagnostics(HunkId(0)).is_empty());
+    }
+}